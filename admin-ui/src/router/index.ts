@@ -4,6 +4,8 @@ import BanSetView from '@/views/BanSetView.vue'
 import BanSimilarView from '@/views/BanSimilarView.vue'
 import BannedStickersView from '@/views/BannedStickersView.vue'
 import CreatorView from '@/views/CreatorView.vue'
+import UsersView from '@/views/UsersView.vue'
+import ModerationLogView from '@/views/ModerationLogView.vue'
 
 const router = createRouter({
   history: createWebHistory(import.meta.env.BASE_URL),
@@ -18,6 +20,16 @@ const router = createRouter({
       name: 'creator',
       component: CreatorView,
     },
+    {
+      path: '/users',
+      name: 'users',
+      component: UsersView,
+    },
+    {
+      path: '/moderation-log',
+      name: 'moderationLog',
+      component: ModerationLogView,
+    },
     {
       path: '/banned-stickers',
       name: 'bannedStickers',
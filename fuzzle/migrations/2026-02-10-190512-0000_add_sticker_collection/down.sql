@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS sticker_collection_sticker;
+DROP TABLE IF EXISTS sticker_collection;
@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS sticker_collection (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(user_id, name),
+    FOREIGN KEY(user_id) REFERENCES user(id) ON UPDATE RESTRICT ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS sticker_collection_sticker (
+    collection_id INTEGER NOT NULL,
+    sticker_id TEXT NOT NULL,
+    added_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(collection_id, sticker_id),
+    FOREIGN KEY(collection_id) REFERENCES sticker_collection(id) ON UPDATE RESTRICT ON DELETE CASCADE,
+    FOREIGN KEY(sticker_id) REFERENCES sticker(id) ON UPDATE RESTRICT ON DELETE CASCADE
+);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS sticker_collection_follower;
+ALTER TABLE sticker_collection DROP COLUMN is_public;
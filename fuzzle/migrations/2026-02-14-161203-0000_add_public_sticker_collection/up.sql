@@ -0,0 +1,10 @@
+ALTER TABLE sticker_collection ADD COLUMN is_public BOOLEAN NOT NULL CHECK (is_public IN (0, 1)) DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS sticker_collection_follower (
+    collection_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(collection_id, user_id),
+    FOREIGN KEY(collection_id) REFERENCES sticker_collection(id) ON UPDATE RESTRICT ON DELETE CASCADE,
+    FOREIGN KEY(user_id) REFERENCES user(id) ON UPDATE RESTRICT ON DELETE CASCADE
+);
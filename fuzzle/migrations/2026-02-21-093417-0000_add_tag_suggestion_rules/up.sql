@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS tag_suggestion_rules (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    rules TEXT NOT NULL,
+    created_by_user_id INTEGER,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(created_by_user_id) REFERENCES user(id) ON UPDATE RESTRICT ON DELETE SET NULL
+);
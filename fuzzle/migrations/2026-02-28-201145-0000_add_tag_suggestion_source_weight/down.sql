@@ -0,0 +1 @@
+DROP TABLE IF EXISTS tag_suggestion_source_weight;
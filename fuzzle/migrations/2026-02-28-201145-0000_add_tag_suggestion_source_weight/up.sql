@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS tag_suggestion_source_weight (
+    source TEXT NOT NULL PRIMARY KEY,
+    weight DOUBLE NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,3 @@
+ALTER TABLE sticker_file_tag DROP COLUMN suggestion_rank;
+ALTER TABLE sticker_file_tag DROP COLUMN suggestion_source;
+ALTER TABLE sticker_file_tag DROP COLUMN origin;
@@ -0,0 +1,4 @@
+-- origin: 0 = manual, 1 = suggestion, 2 = implication; NULL for tags added before origins were tracked
+ALTER TABLE sticker_file_tag ADD COLUMN origin INTEGER NULL;
+ALTER TABLE sticker_file_tag ADD COLUMN suggestion_source TEXT NULL;
+ALTER TABLE sticker_file_tag ADD COLUMN suggestion_rank INTEGER NULL;
@@ -0,0 +1 @@
+ALTER TABLE sticker_file_tag DROP COLUMN confirmed_by_user_id;
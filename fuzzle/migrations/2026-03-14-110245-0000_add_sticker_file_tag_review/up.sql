@@ -0,0 +1 @@
+ALTER TABLE sticker_file_tag ADD COLUMN confirmed_by_user_id INTEGER NULL REFERENCES user(id) ON UPDATE RESTRICT ON DELETE SET NULL;
@@ -0,0 +1,12 @@
+ALTER TABLE user ADD COLUMN can_tag_stickers BOOLEAN NOT NULL CHECK (can_tag_stickers IN (0, 1)) DEFAULT 1;
+ALTER TABLE user ADD COLUMN can_tag_sets BOOLEAN NOT NULL CHECK (can_tag_sets IN (0, 1)) DEFAULT 1;
+
+UPDATE user SET can_tag_stickers = 0 WHERE trust_level = 0 OR json_extract(capability_grants, '$.tag_stickers') = 0;
+-- new users can only tag sets with a grant
+UPDATE user SET can_tag_sets = 0 WHERE trust_level = 0
+    OR json_extract(capability_grants, '$.tag_sets') = 0
+    OR (trust_level = 1 AND json_extract(capability_grants, '$.tag_sets') IS NOT 1);
+
+ALTER TABLE user DROP COLUMN capability_grants;
+ALTER TABLE user DROP COLUMN trust_level_updated_by_user_id;
+ALTER TABLE user DROP COLUMN trust_level;
@@ -0,0 +1,13 @@
+ALTER TABLE user ADD COLUMN trust_level INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE user ADD COLUMN trust_level_updated_by_user_id INTEGER NULL REFERENCES user(id) ON UPDATE RESTRICT ON DELETE SET NULL;
+ALTER TABLE user ADD COLUMN capability_grants TEXT NULL;
+
+-- existing users were allowed to tag sets unless they were restricted, which is what trusted
+-- users can do; only new users start out without it
+UPDATE user SET trust_level = 2 WHERE can_tag_sets = 1;
+
+-- users that were not allowed to tag stickers keep their restriction as a capability grant
+UPDATE user SET capability_grants = json_object('tag_stickers', json('false')) WHERE can_tag_stickers = 0;
+
+ALTER TABLE user DROP COLUMN can_tag_stickers;
+ALTER TABLE user DROP COLUMN can_tag_sets;
@@ -0,0 +1,4 @@
+ALTER TABLE moderation_task DROP COLUMN decided_at;
+ALTER TABLE moderation_task DROP COLUMN decided_by_user_id;
+ALTER TABLE moderation_task DROP COLUMN assigned_at;
+ALTER TABLE moderation_task DROP COLUMN assigned_to_user_id;
@@ -0,0 +1,4 @@
+ALTER TABLE moderation_task ADD COLUMN assigned_to_user_id INTEGER NULL REFERENCES user(id) ON UPDATE RESTRICT ON DELETE SET NULL;
+ALTER TABLE moderation_task ADD COLUMN assigned_at TIMESTAMP NULL;
+ALTER TABLE moderation_task ADD COLUMN decided_by_user_id INTEGER NULL REFERENCES user(id) ON UPDATE RESTRICT ON DELETE SET NULL;
+ALTER TABLE moderation_task ADD COLUMN decided_at TIMESTAMP NULL;
@@ -0,0 +1,24 @@
+-- no foreign keys: entries must stay as they are even if the actor deletes their account
+CREATE TABLE moderation_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- NULL for automatic actions
+    actor_user_id INTEGER NULL,
+    action INTEGER NOT NULL,
+    target TEXT NOT NULL,
+    details TEXT NULL
+);
+
+CREATE INDEX moderation_log_actor_user_id_index ON moderation_log(actor_user_id);
+CREATE INDEX moderation_log_action_index ON moderation_log(action);
+CREATE INDEX moderation_log_target_index ON moderation_log(target);
+
+CREATE TRIGGER moderation_log_no_update BEFORE UPDATE ON moderation_log
+BEGIN
+    SELECT RAISE(ABORT, 'moderation log entries can not be changed');
+END;
+
+CREATE TRIGGER moderation_log_no_delete BEFORE DELETE ON moderation_log
+BEGIN
+    SELECT RAISE(ABORT, 'moderation log entries can not be deleted');
+END;
@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS api_key (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    -- only the hash is stored, the key itself is shown once when it is created
+    key_hash TEXT NOT NULL UNIQUE,
+    requests_per_minute INTEGER NOT NULL DEFAULT 60,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP NULL,
+    revoked_at TIMESTAMP NULL,
+    FOREIGN KEY(user_id) REFERENCES user(id) ON UPDATE RESTRICT ON DELETE CASCADE
+);
+
+CREATE INDEX api_key_user_id_index ON api_key(user_id);
@@ -0,0 +1,2 @@
+DROP INDEX sticker_file_tag_tag_created_at_index;
+DROP TABLE tag_description;
@@ -0,0 +1,11 @@
+-- wiki text of a tag; tags from e621 have no row in the tag table, so this is keyed by name
+CREATE TABLE IF NOT EXISTS tag_description (
+    tag_id TEXT NOT NULL PRIMARY KEY,
+    description TEXT NOT NULL,
+    updated_by_user_id INTEGER NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(updated_by_user_id) REFERENCES user(id) ON UPDATE RESTRICT ON DELETE SET NULL
+);
+
+-- usage of a tag over time
+CREATE INDEX IF NOT EXISTS sticker_file_tag_tag_created_at_index ON sticker_file_tag(tag, created_at);
@@ -0,0 +1,2 @@
+DROP INDEX sticker_set_created_by_user_id_index;
+DROP TABLE creator_profile;
@@ -0,0 +1,12 @@
+-- bio and links of a sticker set creator; keyed by `sticker_set.created_by_user_id`, and creators
+-- do not need to have started the bot, so there is no reference to the user table
+CREATE TABLE IF NOT EXISTS creator_profile (
+    user_id INTEGER NOT NULL PRIMARY KEY,
+    bio TEXT NOT NULL DEFAULT '',
+    links TEXT NOT NULL DEFAULT '[]',
+    -- set when the creator logged in and claimed the page; only then bio and links are shown
+    claimed_at TIMESTAMP NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS sticker_set_created_by_user_id_index ON sticker_set(created_by_user_id);
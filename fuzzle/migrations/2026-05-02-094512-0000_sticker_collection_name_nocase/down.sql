@@ -0,0 +1 @@
+DROP INDEX sticker_collection_user_id_name_nocase_index;
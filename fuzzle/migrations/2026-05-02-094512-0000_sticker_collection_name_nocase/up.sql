@@ -0,0 +1,13 @@
+-- collections are looked up case-insensitively (`collection:cute` finds "Cute"), so names that
+-- only differ in case would make one of them unreachable; existing ones get their id appended
+UPDATE sticker_collection
+SET name = name || ' ' || id
+WHERE EXISTS (
+    SELECT 1 FROM sticker_collection AS other
+    WHERE other.user_id = sticker_collection.user_id
+        AND other.name = sticker_collection.name COLLATE NOCASE
+        AND other.id < sticker_collection.id
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS sticker_collection_user_id_name_nocase_index
+    ON sticker_collection(user_id, name COLLATE NOCASE);
@@ -1,57 +1,95 @@
 use itertools::Itertools;
 use teloxide::{
     payloads::SendMessageSetters,
-    types::{InlineKeyboardMarkup, UserId},
+    prelude::Requester,
+    types::{ChatId, InlineKeyboardMarkup, InputFile},
 };
 
 use crate::{
     bot::{Bot, BotExt, InternalError},
-    database::{Database, ModerationTask},
+    database::{Database, ModerationTask, ModerationTaskDetails},
     message::Keyboard,
+    services::ModerationService,
     text::{Markdown, Text},
 };
 
+/// sends the report to the moderator chat or every moderator, and distributes the pending tasks
 pub async fn send_daily_report(
     database: Database,
     bot: Bot,
-    admin_id: UserId,
+    moderation: ModerationService,
+) -> Result<(), InternalError> {
+    for chat_id in moderation.report_chat_ids().await? {
+        // one moderator that blocked the bot should not stop the others from getting the report
+        if let Err(err) = send_report(database.clone(), bot.clone(), chat_id).await {
+            tracing::warn!("could not send daily report to {chat_id}: {err:?}");
+        }
+    }
+
+    for (chat_id, moderation_task) in moderation.distribute_tasks().await? {
+        if let Err(err) = send_moderation_task(moderation_task, &database, &bot, chat_id).await {
+            tracing::warn!("could not send moderation task to {chat_id}: {err:?}");
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn send_report(
+    database: Database,
+    bot: Bot,
+    chat_id: ChatId,
 ) -> Result<(), InternalError> {
     let counts = database.get_stats().await?;
     let stats = database.get_admin_stats().await?;
     let taggings = database.get_user_tagging_stats_24_hours().await?;
 
     bot.send_markdown(
-        admin_id,
+        chat_id,
         Text::daily_report(counts, stats, taggings.clone()),
     )
     .reply_markup(Keyboard::daily_report(taggings)?)
     .await?;
 
-    send_moderation_tasks(database, bot, admin_id).await?;
-
     Ok(())
 }
 
-pub async fn send_moderation_tasks(
-    database: Database,
-    bot: Bot,
-    admin_id: UserId,
+pub async fn send_moderation_task(
+    moderation_task: ModerationTask,
+    database: &Database,
+    bot: &Bot,
+    chat_id: ChatId,
 ) -> Result<(), InternalError> {
-    let moderation_tasks = database.get_open_moderation_tasks().await?;
-
-    for moderation_task in moderation_tasks {
-        let (text, keyboard) = get_moderation_task_data(moderation_task, &database).await?;
-        bot.send_markdown(admin_id, text)
-            .reply_markup(keyboard)
+    // the reported sticker is shown above the task
+    let reported_sticker = match &moderation_task.details {
+        ModerationTaskDetails::ReportSticker { sticker_id, .. } => {
+            database.get_sticker_by_id(sticker_id).await?
+        }
+        _ => None,
+    };
+    if let Some(sticker) = reported_sticker {
+        bot.send_sticker(chat_id, InputFile::file_id(sticker.telegram_file_identifier))
             .await?;
     }
-
+    let (text, keyboard) = get_moderation_task_data(moderation_task, database).await?;
+    bot.send_markdown(chat_id, text)
+        .reply_markup(keyboard)
+        .await?;
     Ok(())
 }
 
 pub async fn get_moderation_task_data(
     moderation_task: ModerationTask,
     database: &Database,
+) -> Result<(Markdown, InlineKeyboardMarkup), InternalError> {
+    let task = moderation_task.clone();
+    let (text, keyboard) = get_moderation_task_details(moderation_task, database).await?;
+    Ok((Text::with_moderation_task_moderator(text, &task), keyboard))
+}
+
+async fn get_moderation_task_details(
+    moderation_task: ModerationTask,
+    database: &Database,
 ) -> Result<(Markdown, InlineKeyboardMarkup), InternalError> {
     match moderation_task.details {
         crate::database::ModerationTaskDetails::CreateTag {
@@ -90,8 +128,13 @@ pub async fn get_moderation_task_data(
                 ),
             ))
         }
-        crate::database::ModerationTaskDetails::ReportStickerSet { set_id, reason } => Ok((
-            Text::report_sticker_set_task(),
+        crate::database::ModerationTaskDetails::ReportStickerSet {
+            set_id,
+            reason,
+            note,
+            additional_reports,
+        } => Ok((
+            Text::report_sticker_set_task(&set_id, reason, note.as_deref(), &additional_reports),
             Keyboard::report_sticker_set_task(
                 moderation_task.completion_status,
                 moderation_task.created_by_user_id,
@@ -103,6 +146,23 @@ pub async fn get_moderation_task_data(
                     .is_empty(),
             )?,
         )),
+        crate::database::ModerationTaskDetails::ReportSticker {
+            sticker_id,
+            reason,
+            note,
+            additional_reports,
+        } => {
+            let sticker = database.get_sticker_by_id(&sticker_id).await?;
+            Ok((
+                Text::report_sticker_task(&sticker_id, reason, note.as_deref(), &additional_reports),
+                Keyboard::report_sticker_task(
+                    moderation_task.completion_status,
+                    moderation_task.created_by_user_id,
+                    moderation_task.id,
+                    sticker.as_ref().map(|sticker| &sticker.sticker_set_id),
+                )?,
+            ))
+        }
         crate::database::ModerationTaskDetails::ReviewNewSets { set_ids } => Ok((
             Text::review_new_sets_task(),
             Keyboard::review_new_sets_task(
@@ -10,7 +10,7 @@ use crate::bot::report_periodic_task_error;
 
 use crate::Config;
 use crate::bot::InternalError;
-use crate::database::Database;
+use crate::database::{Database, TagOrigin};
 use crate::inference::text_to_clip_embedding;
 use crate::message::send_database_export_to_chat;
 use crate::qdrant::VectorDatabase;
@@ -104,14 +104,16 @@ pub fn start_periodic_tasks(
 
     let bot = bot_clone.clone();
     let database = database_clone.clone();
+    let moderation = services_clone.moderation.clone();
     tokio::spawn(async move {
         loop {
             let span = tracing::info_span!("periodic_daily_report");
             let bot = bot.clone();
             let database = database.clone();
+            let moderation = moderation.clone();
             async move {
                 let result =
-                    send_daily_report(database.clone(), bot.clone(), admin_id)
+                    send_daily_report(database.clone(), bot.clone(), moderation)
                         .await;
                 report_periodic_task_error(result);
             }
@@ -180,6 +182,25 @@ pub fn start_periodic_tasks(
         }
     });
 
+    let services = services_clone.clone();
+    tokio::spawn(async move {
+        loop {
+            sleep(Duration::from_mins(30)).await;
+            let span = tracing::info_span!("periodic_trust_promotion");
+            let services = services.clone();
+            async move {
+                let result = services.trust.promote_users().await;
+                if let Ok(promoted) = &result {
+                    tracing::info!(count = promoted.len(), "promoted users to trusted");
+                }
+                report_periodic_task_error(result);
+            }
+            .instrument(span)
+            .await;
+            sleep(Duration::from_hours(24)).await;
+        }
+    });
+
     let database = database_clone.clone();
     let vector_db = vector_db_clone.clone();
     tokio::spawn(async move {
@@ -291,6 +312,8 @@ async fn fix_missing_tag_implications(
                     vec![tag.clone()],
                     vec![implication.clone()],
                     vec![],
+                    vec![],
+                    vec![],
                     1000,
                     0,
                     crate::database::Order::LatestFirst,
@@ -303,7 +326,12 @@ async fn fix_missing_tag_implications(
                         continue;
                     };
                     database
-                        .tag_file(&file.id, &vec![implication.clone()], None)
+                        .tag_file(
+                            &file.id,
+                            &vec![implication.clone()],
+                            None,
+                            TagOrigin::Implication,
+                        )
                         .await?;
                     sleep(Duration::from_secs(1)).await;
                 }
@@ -112,6 +112,11 @@ impl TagManagerService {
             .get_inverse_implications(tag)
     }
 
+    #[must_use]
+    pub fn get_aliases_of(&self, tag: &str) -> Vec<String> {
+        self.tag_manager.read().unwrap().get_aliases_of(tag)
+    }
+
     #[must_use]
     #[tracing::instrument(skip(self))]
     pub async fn closest_matching_tags(&self, query: &[String]) -> Vec<(String, Option<String>)> {
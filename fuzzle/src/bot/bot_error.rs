@@ -1,7 +1,7 @@
 use teloxide::utils::command::ParseError;
 use thiserror::Error;
 
-use crate::{background_tasks, database::DatabaseError, qdrant::VectorDatabaseError};
+use crate::{background_tasks, database::DatabaseError, qdrant::VectorDatabaseError, text::Language};
 
 // BotError inludes internal (like database errors) and user-facing errors (like invalid syntax for input); InternalError only has internal errros
 
@@ -119,42 +119,42 @@ pub enum UserError {
 }
 
 impl InternalError {
-    pub fn end_user_error(&self) -> (String, UserErrorSeverity) {
-        ("Aw, something's wrong. ".to_string(), UserErrorSeverity::Error)
+    pub fn end_user_error(&self, language: Language) -> (String, UserErrorSeverity) {
+        (language.text("error.internal", &[]), UserErrorSeverity::Error)
     }
 }
 
 impl UserError {
-    pub fn end_user_error(&self) -> (String, UserErrorSeverity) {
+    pub fn end_user_error(&self, language: Language) -> (String, UserErrorSeverity) {
         match self {
-            UserError::NoPermissionForAction(action) => (format!("Oh no, I can't let you do this ({action}) yet."), UserErrorSeverity::Error),
-            UserError::InvalidMode => ("Can't do that here!".to_string(), UserErrorSeverity::Info),
-            UserError::UnsupportedStickerType => ("Those don't look like regular stickers.".to_string(), UserErrorSeverity::Error),
-            UserError::StickerNotPartOfSet =>( "Stickers must be part of a set!".to_string(), UserErrorSeverity::Error),
-            UserError::UnhandledMessageType =>( "I have no idea what to do with this. Send me text messages containing commands, stickers, or t.me/addsticker links!".to_string(),UserErrorSeverity::Error),
-            UserError::CommandError(ParseError::UnknownCommand(input)) => (format!("What the heck is a \"{input}\"?"),UserErrorSeverity::Error),
-            UserError::CommandError(error) => ("Invalid arguments!".to_string(),UserErrorSeverity::Error),
-            UserError::ParseError(position, rest) => (format!("Invalid input at position {position}: {}", rest.chars().take(10).collect::<String>()),UserErrorSeverity::Error),
-            UserError::TagsNotFound(tags) => (format!("Could not find tags: {}", tags.join(", ")),UserErrorSeverity::Error),
-            UserError::ListHasZeroResults(name) => (format!("No {name} here :("),UserErrorSeverity::Info),
-            UserError::ChannelWithoutUsername => ("The channel needs to have a public name.".to_string(), UserErrorSeverity::Error),
-            UserError::UserWithoutUsername => ("This user doesn't have a username or their privacy settings don't allow me to see it.".to_string(), UserErrorSeverity::Error),
-            UserError::InvalidStartParameter => ("No idea where to start.".to_string(), UserErrorSeverity::Error),
-            UserError::ValidationError(description) => (format!("Invalid data: {description}"), UserErrorSeverity::Error),
-            UserError::VectorNotFound => (format!("Come back later, looks like I'm not done processing this one"), UserErrorSeverity::Error),
-            UserError::AlreadyExists(name) => (format!("This {name} already exists"),UserErrorSeverity::Error),
-            UserError::NoSuitableStickerFound => (format!("Couldn't find a suitable sticker."), UserErrorSeverity::Info),
-            UserError::StickerBanned => (format!("I can't or don't want to handle that sticker."), UserErrorSeverity::Error),
-            UserError::StickerSetBanned => (format!("I can't or dont want to handle that sticker set."), UserErrorSeverity::Error),
+            UserError::NoPermissionForAction(action) => (language.text("error.no_permission", &[("action", action.as_str().into())]), UserErrorSeverity::Error),
+            UserError::InvalidMode => (language.text("error.invalid_mode", &[]), UserErrorSeverity::Info),
+            UserError::UnsupportedStickerType => (language.text("error.unsupported_sticker_type", &[]), UserErrorSeverity::Error),
+            UserError::StickerNotPartOfSet => (language.text("error.sticker_not_part_of_set", &[]), UserErrorSeverity::Error),
+            UserError::UnhandledMessageType => (language.text("error.unhandled_message_type", &[]), UserErrorSeverity::Error),
+            UserError::CommandError(ParseError::UnknownCommand(input)) => (language.text("error.unknown_command", &[("command", input.as_str().into())]), UserErrorSeverity::Error),
+            UserError::CommandError(_) => (language.text("error.invalid_arguments", &[]), UserErrorSeverity::Error),
+            UserError::ParseError(position, rest) => (language.text("error.parse", &[("position", (*position).into()), ("input", rest.chars().take(10).collect::<String>().into())]), UserErrorSeverity::Error),
+            UserError::TagsNotFound(tags) => (language.text("error.tags_not_found", &[("tags", tags.join(", ").into())]), UserErrorSeverity::Error),
+            UserError::ListHasZeroResults(name) => (language.text("error.no_results", &[("name", name.as_str().into())]), UserErrorSeverity::Info),
+            UserError::ChannelWithoutUsername => (language.text("error.channel_without_username", &[]), UserErrorSeverity::Error),
+            UserError::UserWithoutUsername => (language.text("error.user_without_username", &[]), UserErrorSeverity::Error),
+            UserError::InvalidStartParameter => (language.text("error.invalid_start_parameter", &[]), UserErrorSeverity::Error),
+            UserError::ValidationError(description) => (language.text("error.validation", &[("description", description.as_str().into())]), UserErrorSeverity::Error),
+            UserError::VectorNotFound => (language.text("error.vector_not_found", &[]), UserErrorSeverity::Error),
+            UserError::AlreadyExists(name) => (language.text("error.already_exists", &[("name", name.as_str().into())]), UserErrorSeverity::Error),
+            UserError::NoSuitableStickerFound => (language.text("error.no_suitable_sticker", &[]), UserErrorSeverity::Info),
+            UserError::StickerBanned => (language.text("error.sticker_banned", &[]), UserErrorSeverity::Error),
+            UserError::StickerSetBanned => (language.text("error.sticker_set_banned", &[]), UserErrorSeverity::Error),
         }
     }
 }
 
 impl BotError {
-    pub fn end_user_error(&self) -> (String, UserErrorSeverity) {
+    pub fn end_user_error(&self, language: Language) -> (String, UserErrorSeverity) {
         match self {
-            BotError::InternalError(err) => err.end_user_error(),
-            BotError::UserError(err) => err.end_user_error(),
+            BotError::InternalError(err) => err.end_user_error(language),
+            BotError::UserError(err) => err.end_user_error(language),
         }
     }
 }
@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
-use teloxide::types::UserId;
+use teloxide::types::{ChatId, UserId};
 
 use crate::util::StickerId;
 
@@ -26,6 +26,8 @@ pub struct Config {
     pub admin_http_listen_address: String, // TODO: set this env var
 
     pub admin_telegram_user_id: u64,
+    /// moderation tasks and daily reports go to this chat instead of every moderator
+    pub moderator_chat_id: Option<i64>,
     pub telegram_bot_token: String,
     pub telegram_bot_username: String,
 }
@@ -36,6 +38,11 @@ impl Config {
         UserId(self.admin_telegram_user_id)
     }
 
+    #[must_use]
+    pub fn get_moderator_chat_id(&self) -> Option<ChatId> {
+        self.moderator_chat_id.map(ChatId)
+    }
+
     #[must_use]
     pub fn db(&self) -> PathBuf {
         self.db_file_path.clone().into()
@@ -1,7 +1,7 @@
 use teloxide::types::UserId;
 
 use super::{Bot, BotError};
-use crate::{Config, background_tasks::{ TagManagerService, TfIdfService}, database::{Database, DialogState, User}, qdrant::VectorDatabase, services::Services, util::Required};
+use crate::{Config, background_tasks::{ TagManagerService, TfIdfService}, database::{Capability, Database, DialogState, TrustLevel, User}, qdrant::VectorDatabase, services::Services, tags::Rating, text::Language, util::Required};
 use std::sync::Arc;
 
 #[derive(Clone)]
@@ -11,6 +11,8 @@ pub struct RequestContext {
     pub tag_manager: TagManagerService,
     pub bot: Bot,
     pub user: Arc<User>,
+    /// language of the replies, see `UserSettings::language`
+    pub language: Language,
     pub tfidf: TfIdfService,
     // pub tag_worker: TagWorker,
     pub vector_db: VectorDatabase,
@@ -18,14 +20,20 @@ pub struct RequestContext {
 }
 
 impl RequestContext {
+    /// the admin can do everything
+    #[must_use]
+    pub fn has_capability(&self, capability: Capability) -> bool {
+        self.is_admin() || self.user.has_capability(capability)
+    }
+
     #[must_use]
     pub fn can_tag_stickers(&self) -> bool {
-        self.user.can_tag_stickers
+        self.has_capability(Capability::TagStickers)
     }
 
     #[must_use]
     pub fn can_tag_sets(&self) -> bool {
-        self.user.can_tag_sets
+        self.has_capability(Capability::TagSets)
     }
 
     pub fn user_id(&self) -> UserId {
@@ -34,9 +42,16 @@ impl RequestContext {
     pub fn is_admin(&self) -> bool {
         self.user_id() == self.config.get_admin_user_id()
     }
+    /// moderators handle moderation tasks; the admin is always a moderator
+    pub fn is_moderator(&self) -> bool {
+        self.is_admin() || self.user.trust_level == TrustLevel::Moderator
+    }
     pub fn dialog_state(&self) -> DialogState {
         self.user.dialog_state.clone().unwrap_or_default()
     }
+    pub fn rating_profile(&self) -> Rating {
+        self.user.settings.clone().unwrap_or_default().rating_profile()
+    }
     pub fn is_continuous_tag_state(&self) -> bool {
             matches!(self.dialog_state(), DialogState::ContinuousTag { .. })
     }
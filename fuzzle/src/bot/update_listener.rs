@@ -2,11 +2,15 @@ use crate::bot::config::Config;
 use crate::callback::callback_handler_wrapper;
 use crate::database::Database;
 use crate::inline::{inline_query_handler_wrapper, inline_result_handler_wrapper};
-use crate::message::{list_visible_admin_commands, list_visible_user_commands, message_handler_wrapper};
+use crate::message::{
+    list_visible_admin_commands, list_visible_moderator_commands, list_visible_user_commands,
+    message_handler_wrapper,
+};
 use crate::qdrant::VectorDatabase;
 
 use crate::background_tasks::{start_periodic_tasks, TagManagerService, TfIdfService};
 use crate::services::{ExternalTelegramService, Services};
+use crate::text::Language;
 
 use std::sync::Arc;
 use std::time::Duration;
@@ -48,6 +52,8 @@ impl UpdateListener {
         let vector_db = VectorDatabase::new(&config.vector_db_url).await?;
         let config = Arc::new(config);
         let services = Services::new(config.clone(), database.clone(), vector_db.clone(), bot.clone());
+        services.tag_suggestion_rules.reload().await?;
+        services.suggestion_combiner.reload().await?;
         tracing::info!("set up services");
         let tag_manager = TagManagerService::new(database.clone(), config.clone()).await?;
         tracing::info!("set up tag manager");
@@ -68,15 +74,36 @@ impl UpdateListener {
 
     pub async fn setup_buttons(&self) -> anyhow::Result<()> {
         self.bot
-            .set_my_commands(list_visible_user_commands())
-            .await?;
-        // convert admin_telegram_user_id to i64
-        self.bot
-            .set_my_commands(list_visible_admin_commands())
-            .scope(teloxide::types::BotCommandScope::Chat {
-                chat_id: self.config.get_admin_user_id().into(),
-            })
+            .set_my_commands(list_visible_user_commands(Language::default()))
             .await?;
+        // telegram picks the menu by the language of the app, the default covers all others
+        for language in Language::ALL {
+            self.bot
+                .set_my_commands(list_visible_user_commands(language))
+                .language_code(language.code())
+                .await?;
+        }
+        for moderator_id in self.services.moderation.moderator_ids().await? {
+            let commands = if moderator_id == self.config.get_admin_user_id() {
+                list_visible_admin_commands()
+            } else {
+                list_visible_moderator_commands()
+            };
+            self.bot
+                .set_my_commands(commands)
+                .scope(teloxide::types::BotCommandScope::Chat {
+                    chat_id: moderator_id.into(),
+                })
+                .await?;
+        }
+        if let Some(chat_id) = self.config.get_moderator_chat_id() {
+            self.bot
+                .set_my_commands(list_visible_moderator_commands())
+                .scope(teloxide::types::BotCommandScope::Chat {
+                    chat_id: chat_id.into(),
+                })
+                .await?;
+        }
         self.bot
             .set_chat_menu_button()
             .menu_button(teloxide::types::MenuButton::Commands)
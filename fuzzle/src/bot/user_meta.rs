@@ -40,6 +40,11 @@ pub async fn handle_incoming_telegram_update(
         {
             Ok(user) => Some(TracedMessage {
                 message: RequestContext {
+                    language: user
+                        .settings
+                        .clone()
+                        .unwrap_or_default()
+                        .language(update.from().and_then(|from| from.language_code.as_deref())),
                     bot,
                     config,
                     database,
@@ -95,6 +100,6 @@ pub async fn get_or_create_user(
         }
     };
 
-    // TODO: check if user is banned?
+    // banned users can still search, they just lack all capabilities (see `User::has_capability`)
     Ok(user)
 }
@@ -1,7 +1,7 @@
 use nom::bytes::complete::tag;
 
-use nom::character::complete::{i64, u8};
-use nom::combinator::{eof, fail, map};
+use nom::character::complete::{alpha1, i64, u8};
+use nom::combinator::{eof, fail, map, map_opt};
 
 use nom::sequence::{preceded, terminated, tuple};
 
@@ -12,14 +12,22 @@ use num_traits::{FromPrimitive, ToPrimitive};
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
-use crate::database::{ModerationTaskStatus, StickerOrder};
+use crate::database::{BlacklistMode, ModerationTaskStatus, StickerOrder};
 
 use crate::message::PrivacyPolicy;
-use crate::tags::Category;
+use crate::tags::{Category, Rating, SuggestionSource};
 use crate::util::{StickerId, StickerSetId, sticker_id_literal, tag_literal};
 
 fn parse_tag_operation(input: &str) -> IResult<&str, TagOperation> {
     alt((
+        map(
+            tuple((
+                preceded(tag("ts;"), u8),
+                preceded(tag(";"), opt(map_opt(alpha1, SuggestionSource::from_short_name))),
+                parse_tag,
+            )),
+            |(rank, source, tag)| TagOperation::TagSuggestion { tag, rank, source },
+        ),
         map(preceded(tag("t"), parse_tag), TagOperation::Tag),
         map(preceded(tag("u"), parse_tag), TagOperation::Untag),
     )).parse(input)
@@ -34,12 +42,23 @@ fn parse_tag(input: &str) -> IResult<&str, String> {
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum TagOperation {
     Tag(String),
+    /// a click on a suggested tag; the rank starts at 1
+    TagSuggestion {
+        tag: String,
+        rank: u8,
+        source: Option<SuggestionSource>,
+    },
     Untag(String),
 }
 impl Display for TagOperation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Tag(tag) => write!(f, "t;{tag}"),
+            Self::TagSuggestion { tag, rank, source } => write!(
+                f,
+                "ts;{rank};{};{tag}",
+                source.map_or("", SuggestionSource::short_name)
+            ),
             Self::Untag(tag) => write!(f, "u;{tag}"),
         }
     }
@@ -57,6 +76,9 @@ pub enum FavoriteAction {
     Unfavorite,
 }
 
+/// telegram rejects the whole keyboard if the data of one button is longer
+pub const MAX_CALLBACK_DATA_LENGTH: usize = 64;
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum CallbackData {
     NoAction,
@@ -77,14 +99,29 @@ pub enum CallbackData {
     UserStats,
     LatestStickers,
     Info,
+    /// the sticker that was shown last, so that skipped stickers are not shown again
+    NextTagReview {
+        after: Option<StickerId>,
+    },
+    ApplySuggestionWeights,
 
     RemoveBlacklistedTag(String),
+    /// index into the whitelisted sets of the user, since set names can be longer than the
+    /// callback data allows; the keyboard is redrawn after every change
+    RemoveWhitelistedSet(usize),
     RemoveContinuousTag(String),
     RemoveAlias(String),
+    /// description, examples and relationships of a tag
+    TagWiki(String),
     UserInfo(u64),
     SetOrder(StickerOrder),
     SetCategory(Option<Category>),
+    SetRatingProfile(Rating),
     Privacy(Option<PrivacyPolicy>),
+    SetBlacklistMode {
+        tag: String,
+        mode: BlacklistMode,
+    },
 
     OwnerPage {
         sticker_id: StickerId,
@@ -104,6 +141,9 @@ pub enum CallbackData {
     ApplyTags {
         sticker_id: StickerId,
     },
+    CollectionsPage {
+        sticker_id: StickerId,
+    },
 
     Sticker {
         sticker_id: StickerId,
@@ -121,6 +161,30 @@ pub enum CallbackData {
         sticker_id: StickerId,
         positive: bool,
     },
+    ToggleCollectionSticker {
+        sticker_id: StickerId,
+        collection_id: i64,
+        add: bool,
+    },
+    ToggleSetWhitelist {
+        sticker_id: StickerId,
+        whitelist: bool,
+    },
+    /// confirms or rejects a tag of a low trust user
+    ReviewTag {
+        sticker_id: StickerId,
+        tag: String,
+        accept: bool,
+    },
+    SetCollectionPublic {
+        sticker_id: StickerId,
+        collection_id: i64,
+        public: bool,
+    },
+    FollowCollection {
+        collection_id: i64,
+        follow: bool,
+    },
 
     ChangeSetBannedStatus {
         set_name: StickerSetId,
@@ -144,15 +208,41 @@ pub enum CallbackData {
         sticker_id_b: StickerId,
         merge: bool,
     },
+    /// comparison of the sets of the two stickers
+    CompareSets {
+        sticker_id_a: StickerId,
+        sticker_id_b: StickerId,
+    },
 }
 
 impl CallbackData {
+    /// whether telegram accepts the data; tags are part of some callbacks and may be too long
+    #[must_use]
+    pub fn fits(&self) -> bool {
+        self.to_string().len() <= MAX_CALLBACK_DATA_LENGTH
+    }
+
     pub fn tag_sticker(sticker_id: StickerId, tag: impl Into<String>) -> Self {
         Self::Sticker {
             sticker_id,
             operation: Some(TagOperation::Tag(tag.into())),
         }
     }
+    pub fn tag_sticker_from_suggestion(
+        sticker_id: StickerId,
+        tag: impl Into<String>,
+        rank: u8,
+        source: Option<SuggestionSource>,
+    ) -> Self {
+        Self::Sticker {
+            sticker_id,
+            operation: Some(TagOperation::TagSuggestion {
+                tag: tag.into(),
+                rank,
+                source,
+            }),
+        }
+    }
     pub fn untag_sticker(sticker_id: StickerId, tag: impl Into<String>) -> Self {
         Self::Sticker {
             sticker_id,
@@ -196,6 +286,10 @@ fn parse_callback_data(input: &str) -> IResult<&str, CallbackData> {
                 parse_privacy_policy,
                 parse_remove_alias,
                 parse_lock_data,
+                parse_blacklist_mode,
+                parse_remove_whitelisted_set,
+                parse_rating_profile,
+                parse_tag_wiki,
             )),
             alt((
                 parse_recommend_sticker,
@@ -209,6 +303,14 @@ fn parse_callback_data(input: &str) -> IResult<&str, CallbackData> {
                 parse_tag_list_action,
                 parse_merge_data,
                 parse_create_tag_for_user,
+                parse_collections_page,
+                parse_toggle_collection_sticker,
+                parse_set_collection_public,
+                parse_follow_collection,
+                parse_toggle_set_whitelist,
+                parse_review_tag,
+                parse_next_tag_review,
+                parse_compare_sets,
             )),
         )),
         eof,
@@ -235,6 +337,7 @@ fn parse_simple(input: &str) -> IResult<&str, CallbackData> {
         map(tag("linkself"), |_| CallbackData::LinkSelf),
         map(tag("createtag"), |_| CallbackData::CreateTag),
         map(tag("removechannel"), |_| CallbackData::RemoveLinkedChannel),
+        map(tag("applysw"), |_| CallbackData::ApplySuggestionWeights),
     )).parse(input)
 }
 
@@ -256,6 +359,16 @@ fn parse_merge_data(input: &str) -> IResult<&str, CallbackData> {
     ).parse(input)
 }
 
+fn parse_compare_sets(input: &str) -> IResult<&str, CallbackData> {
+    map(
+        tuple((tag("cmp;"), sticker_id_literal, tag(";"), sticker_id_literal)),
+        |(_, sticker_id_a, _, sticker_id_b)| CallbackData::CompareSets {
+            sticker_id_a: StickerId::from(sticker_id_a),
+            sticker_id_b: StickerId::from(sticker_id_b),
+        },
+    ).parse(input)
+}
+
 fn parse_create_tag_for_user(input: &str) -> IResult<&str, CallbackData> {
     map( tuple(( tag("ctfu;"), i64,)), |(_, user_id)| CallbackData::CreateTagForUser { user_id },).parse(input)
 }
@@ -362,6 +475,68 @@ fn parse_apply_tags(input: &str) -> IResult<&str, CallbackData> {
     ))
 }
 
+fn parse_collections_page(input: &str) -> IResult<&str, CallbackData> {
+    let (input, _) = tag("colp;")(input)?;
+    let (input, sticker_id) = sticker_id_literal(input)?;
+    Ok((
+        input,
+        CallbackData::CollectionsPage {
+            sticker_id: StickerId::from(sticker_id),
+        },
+    ))
+}
+
+fn parse_toggle_collection_sticker(input: &str) -> IResult<&str, CallbackData> {
+    map(
+        tuple((
+            tag("colt;"),
+            sticker_id_literal,
+            tag(";"),
+            i64,
+            tag(";"),
+            alt((map(tag("add"), |_| true), map(tag("remove"), |_| false))),
+        )),
+        |(_, sticker_id, _, collection_id, _, add)| CallbackData::ToggleCollectionSticker {
+            sticker_id: StickerId::from(sticker_id),
+            collection_id,
+            add,
+        },
+    ).parse(input)
+}
+
+fn parse_set_collection_public(input: &str) -> IResult<&str, CallbackData> {
+    map(
+        tuple((
+            tag("colpub;"),
+            sticker_id_literal,
+            tag(";"),
+            i64,
+            tag(";"),
+            alt((map(tag("public"), |_| true), map(tag("private"), |_| false))),
+        )),
+        |(_, sticker_id, _, collection_id, _, public)| CallbackData::SetCollectionPublic {
+            sticker_id: StickerId::from(sticker_id),
+            collection_id,
+            public,
+        },
+    ).parse(input)
+}
+
+fn parse_follow_collection(input: &str) -> IResult<&str, CallbackData> {
+    map(
+        tuple((
+            tag("colf;"),
+            i64,
+            tag(";"),
+            alt((map(tag("follow"), |_| true), map(tag("unfollow"), |_| false))),
+        )),
+        |(_, collection_id, _, follow)| CallbackData::FollowCollection {
+            collection_id,
+            follow,
+        },
+    ).parse(input)
+}
+
 fn parse_recommend_sticker(input: &str) -> IResult<&str, CallbackData> {
     let (input, _) = tag("rec;")(input)?;
     let (input, sticker_id) = sticker_id_literal(input)?;
@@ -412,6 +587,13 @@ fn parse_set_category(input: &str) -> IResult<&str, CallbackData> {
     )).parse(input)
 }
 
+fn parse_rating_profile(input: &str) -> IResult<&str, CallbackData> {
+    map(
+        preceded(tag("rating;"), map_opt(u8, Rating::from_u8)),
+        CallbackData::SetRatingProfile,
+    ).parse(input)
+}
+
 fn parse_privacy_policy(input: &str) -> IResult<&str, CallbackData> {
     
         map(preceded(tag("ppolicy;"), u8), |p| {
@@ -425,6 +607,12 @@ fn parse_remove_alias(input: &str) -> IResult<&str, CallbackData> {
     }).parse(input)
 }
 
+fn parse_tag_wiki(input: &str) -> IResult<&str, CallbackData> {
+    map(preceded(tag("wiki;"), tag_literal), |tag| {
+        CallbackData::TagWiki(tag.to_string())
+    }).parse(input)
+}
+
 fn parse_user_info_data(input: &str) -> IResult<&str, CallbackData> {
     let (input, _) = tag("userinfo")(input)?;
     let (input, _) = tag(";")(input)?;
@@ -469,6 +657,68 @@ fn parse_remove_blacklist_data(input: &str) -> IResult<&str, CallbackData> {
     }).parse(input)
 }
 
+fn parse_blacklist_mode(input: &str) -> IResult<&str, CallbackData> {
+    map(
+        tuple((
+            tag("blmode;"),
+            map_opt(u8, BlacklistMode::from_u8),
+            tag(";"),
+            tag_literal,
+        )),
+        |(_, mode, _, tag)| CallbackData::SetBlacklistMode {
+            tag: tag.to_string(),
+            mode,
+        },
+    ).parse(input)
+}
+
+fn parse_remove_whitelisted_set(input: &str) -> IResult<&str, CallbackData> {
+    map(preceded(tag("removewl;"), u64), |index| {
+        CallbackData::RemoveWhitelistedSet(index as usize)
+    }).parse(input)
+}
+
+fn parse_toggle_set_whitelist(input: &str) -> IResult<&str, CallbackData> {
+    map(
+        tuple((
+            tag("wl;"),
+            sticker_id_literal,
+            tag(";"),
+            alt((map(tag("add"), |_| true), map(tag("remove"), |_| false))),
+        )),
+        |(_, sticker_id, _, whitelist)| CallbackData::ToggleSetWhitelist {
+            sticker_id: StickerId::from(sticker_id),
+            whitelist,
+        },
+    ).parse(input)
+}
+
+fn parse_review_tag(input: &str) -> IResult<&str, CallbackData> {
+    map(
+        tuple((
+            tag("rvt;"),
+            sticker_id_literal,
+            tag(";"),
+            alt((map(tag("1"), |_| true), map(tag("0"), |_| false))),
+            parse_tag,
+        )),
+        |(_, sticker_id, _, accept, tag)| CallbackData::ReviewTag {
+            sticker_id: StickerId::from(sticker_id),
+            tag,
+            accept,
+        },
+    ).parse(input)
+}
+
+fn parse_next_tag_review(input: &str) -> IResult<&str, CallbackData> {
+    map(
+        preceded(tag("rvn"), opt(preceded(tag(";"), sticker_id_literal))),
+        |after| CallbackData::NextTagReview {
+            after: after.map(StickerId::from),
+        },
+    ).parse(input)
+}
+
 fn parse_remove_continuous_tag(input: &str) -> IResult<&str, CallbackData> {
     map(preceded(tag("removec;"), tag_literal), |tag| {
         CallbackData::RemoveContinuousTag(tag.to_string())
@@ -548,15 +798,62 @@ impl Display for CallbackData {
             Self::LinkSelf => write!(f, "linkself"),
             Self::CreateTag => write!(f, "createtag"),
             Self::RemoveLinkedChannel => write!(f, "removechannel"),
+            Self::NextTagReview { after: None } => write!(f, "rvn"),
+            Self::NextTagReview { after: Some(sticker_id) } => write!(f, "rvn;{sticker_id}"),
+            Self::ApplySuggestionWeights => write!(f, "applysw"),
             Self::StickerSetPage { sticker_id } => write!(f, "ssp;{sticker_id}"),
             Self::OwnerPage { sticker_id } => write!(f, "owner;{sticker_id}"),
             Self::DownloadSticker { sticker_id } => write!(f, "dls;{sticker_id}"),
             Self::StickerExplorePage { sticker_id } => write!(f, "sep;{sticker_id}"),
             Self::ToggleExampleSticker { sticker_id } => write!(f, "tex;{sticker_id}"),
             Self::ApplyTags { sticker_id } => write!(f, "apptags;{sticker_id}"),
+            Self::CollectionsPage { sticker_id } => write!(f, "colp;{sticker_id}"),
+            Self::ToggleCollectionSticker {
+                sticker_id,
+                collection_id,
+                add,
+            } => {
+                let add = if *add { "add" } else { "remove" };
+                write!(f, "colt;{sticker_id};{collection_id};{add}")
+            }
+            Self::SetCollectionPublic {
+                sticker_id,
+                collection_id,
+                public,
+            } => {
+                let public = if *public { "public" } else { "private" };
+                write!(f, "colpub;{sticker_id};{collection_id};{public}")
+            }
+            Self::FollowCollection {
+                collection_id,
+                follow,
+            } => {
+                let follow = if *follow { "follow" } else { "unfollow" };
+                write!(f, "colf;{collection_id};{follow}")
+            }
             Self::RemoveBlacklistedTag(tag) => write!(f, "removebl;{tag}"),
+            Self::RemoveWhitelistedSet(index) => write!(f, "removewl;{index}"),
+            Self::SetBlacklistMode { tag, mode } => {
+                write!(f, "blmode;{};{tag}", mode.to_u8().unwrap_or_default())
+            }
+            Self::ToggleSetWhitelist {
+                sticker_id,
+                whitelist,
+            } => {
+                let whitelist = if *whitelist { "add" } else { "remove" };
+                write!(f, "wl;{sticker_id};{whitelist}")
+            }
+            Self::ReviewTag {
+                sticker_id,
+                tag,
+                accept,
+            } => {
+                let accept = if *accept { "1" } else { "0" };
+                write!(f, "rvt;{sticker_id};{accept};{tag}")
+            }
             Self::RemoveContinuousTag(tag) => write!(f, "removec;{tag}"),
             Self::RemoveAlias(tag) => write!(f, "ras;{tag}"),
+            Self::TagWiki(tag) => write!(f, "wiki;{tag}"),
             Self::ChangeSetBannedStatus { set_name, banned, moderation_task_id } => {
                 let action = if *banned { "b" } else { "u" };
                 write!(f, "b{moderation_task_id}{action}{set_name}")
@@ -577,6 +874,9 @@ impl Display for CallbackData {
                 };
                 write!(f, "cat;{category}")
             }
+            Self::SetRatingProfile(rating) => {
+                write!(f, "rating;{}", rating.to_u8().unwrap_or_default())
+            }
             Self::Privacy(pp) => {
                 let pp = match pp {
                     Some(pp) => pp.to_u8().unwrap_or_default().to_string(),
@@ -603,6 +903,10 @@ impl Display for CallbackData {
                 let merge = if *merge { "true" } else { "false" };
                 write!(f, "merge;{sticker_id_a};{sticker_id_b};{merge}")
             }
+            Self::CompareSets {
+                sticker_id_a,
+                sticker_id_b,
+            } => write!(f, "cmp;{sticker_id_a};{sticker_id_b}"),
         }
     }
 }
@@ -637,6 +941,79 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn parse_stringify_sticker_tag_suggestion() -> Result<()> {
+        let data = CallbackData::try_from("s;5uh33fj84;ts;3;si;male".to_string())?;
+        assert_eq!(
+            CallbackData::tag_sticker_from_suggestion(
+                StickerId::from("5uh33fj84"),
+                "male",
+                3,
+                Some(SuggestionSource::SimilarStickers)
+            ),
+            data
+        );
+        assert_eq!(data.to_string(), "s;5uh33fj84;ts;3;si;male");
+
+        let data = CallbackData::try_from("s;5uh33fj84;ts;12;;safe".to_string())?;
+        assert_eq!(
+            CallbackData::tag_sticker_from_suggestion(StickerId::from("5uh33fj84"), "safe", 12, None),
+            data
+        );
+        assert_eq!(data.to_string(), "s;5uh33fj84;ts;12;;safe");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_stringify_tag_review() -> Result<()> {
+        let data = CallbackData::try_from("rvt;AgADGQADmmz0;0;solo".to_string())?;
+        assert_eq!(
+            CallbackData::ReviewTag {
+                sticker_id: StickerId::from("AgADGQADmmz0"),
+                tag: "solo".to_string(),
+                accept: false,
+            },
+            data
+        );
+        assert_eq!(data.to_string(), "rvt;AgADGQADmmz0;0;solo");
+
+        let data = CallbackData::try_from("rvn".to_string())?;
+        assert_eq!(CallbackData::NextTagReview { after: None }, data);
+        assert_eq!(data.to_string(), "rvn");
+
+        let data = CallbackData::try_from("rvn;AgADGQADmmz0".to_string())?;
+        assert_eq!(
+            CallbackData::NextTagReview {
+                after: Some(StickerId::from("AgADGQADmmz0")),
+            },
+            data
+        );
+        assert_eq!(data.to_string(), "rvn;AgADGQADmmz0");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_stringify_apply_suggestion_weights() -> Result<()> {
+        let data = CallbackData::try_from("applysw".to_string())?;
+        assert_eq!(CallbackData::ApplySuggestionWeights, data);
+        assert_eq!(data.to_string(), "applysw");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_stringify_compare_sets() -> Result<()> {
+        let data = CallbackData::try_from("cmp;AgADGQADmmz0;AgADbQAD1Q4c".to_string())?;
+        assert_eq!(
+            CallbackData::CompareSets {
+                sticker_id_a: StickerId::from("AgADGQADmmz0"),
+                sticker_id_b: StickerId::from("AgADbQAD1Q4c"),
+            },
+            data
+        );
+        assert_eq!(data.to_string(), "cmp;AgADGQADmmz0;AgADbQAD1Q4c");
+        Ok(())
+    }
+
     #[test]
     fn parse_sticker_untag() -> Result<()> {
         let data = CallbackData::try_from("s;5uh33fj84;u;male".to_string())?;
@@ -665,4 +1042,99 @@ mod tests {
         assert_eq!(data.to_string(), "start");
         Ok(())
     }
+
+    #[test]
+    fn parse_stringify_toggle_collection_sticker() -> Result<()> {
+        let data = CallbackData::try_from("colt;5uh33fj84xy;12;remove".to_string())?;
+        assert_eq!(
+            data,
+            CallbackData::ToggleCollectionSticker {
+                sticker_id: StickerId::from("5uh33fj84xy"),
+                collection_id: 12,
+                add: false,
+            }
+        );
+        assert_eq!(data.to_string(), "colt;5uh33fj84xy;12;remove");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_stringify_follow_collection() -> Result<()> {
+        let data = CallbackData::try_from("colf;7;unfollow".to_string())?;
+        assert_eq!(
+            data,
+            CallbackData::FollowCollection {
+                collection_id: 7,
+                follow: false,
+            }
+        );
+        assert_eq!(data.to_string(), "colf;7;unfollow");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_stringify_blacklist_mode() -> Result<()> {
+        let data = CallbackData::try_from("blmode;2;meta_sticker".to_string())?;
+        assert_eq!(
+            data,
+            CallbackData::SetBlacklistMode {
+                tag: "meta_sticker".to_string(),
+                mode: BlacklistMode::Warn,
+            }
+        );
+        assert_eq!(data.to_string(), "blmode;2;meta_sticker");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_stringify_remove_whitelisted_set() -> Result<()> {
+        let data = CallbackData::try_from("removewl;3".to_string())?;
+        assert_eq!(data, CallbackData::RemoveWhitelistedSet(3));
+        assert_eq!(data.to_string(), "removewl;3");
+        Ok(())
+    }
+
+    #[test]
+    fn blacklist_keyboard_fits_long_names() {
+        use crate::database::UserSettings;
+        use crate::message::Keyboard;
+        use teloxide::types::InlineKeyboardButtonKind;
+
+        let settings = UserSettings {
+            whitelisted_sets: vec![StickerSetId::from("a".repeat(64))],
+            ..Default::default()
+        };
+        let blacklist = ["meta_sticker".to_string(), "a".repeat(60)];
+        let keyboard = Keyboard::blacklist(&blacklist, &settings);
+        let data = keyboard
+            .inline_keyboard
+            .iter()
+            .flatten()
+            .filter_map(|button| match &button.kind {
+                InlineKeyboardButtonKind::CallbackData(data) => Some(data.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(data.iter().all(|data| data.len() <= MAX_CALLBACK_DATA_LENGTH));
+        assert!(data.contains(&"removewl;0".to_string()));
+        assert!(data.contains(&"removebl;meta_sticker".to_string()));
+        assert!(!data.iter().any(|data| data.contains(&"a".repeat(60))));
+    }
+
+    #[test]
+    fn parse_stringify_tag_wiki() -> Result<()> {
+        let data = CallbackData::try_from("wiki;red_fox".to_string())?;
+        assert_eq!(data, CallbackData::TagWiki("red_fox".to_string()));
+        assert_eq!(data.to_string(), "wiki;red_fox");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_stringify_rating_profile() -> Result<()> {
+        let data = CallbackData::try_from("rating;1".to_string())?;
+        assert_eq!(data, CallbackData::SetRatingProfile(Rating::Questionable));
+        assert_eq!(data.to_string(), "rating;1");
+        assert!(CallbackData::try_from("rating;3".to_string()).is_err());
+        Ok(())
+    }
 }
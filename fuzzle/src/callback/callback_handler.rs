@@ -22,15 +22,18 @@ use crate::bot::{
 };
 use crate::callback::TagOperation;
 
-use crate::database::{ContinuousTag, Database, MergeStatus};
+use crate::database::{BlacklistMode, Capability, ContinuousTag, Database, ModerationAction, ModerationTaskStatus, TagOrigin};
 use crate::database::{DialogState, TagCreator};
 use crate::fmetrics::TracedMessage;
-use crate::message::{send_merge_queue, send_readonly_message, set_tag_id, Keyboard};
-use crate::services::{ Services};
-use crate::sticker::{determine_canonical_sticker_and_merge, fetch_sticker_file, FileKind};
-use crate::tags::{suggest_tags, Category};
+use crate::message::{
+    check_can_review_tags, send_merge_queue, send_next_tag_review, send_readonly_message,
+    set_tag_id, Keyboard,
+};
+use crate::services::{Services, TaggedFile, tags_that_should_be_removed};
+use crate::sticker::{fetch_sticker_file, FileKind};
+use crate::tags::{suggest_tags, Category, SuggestionSource};
 use crate::text::{Markdown, Text};
-use crate::util::{Emoji, Required, StickerId, create_tag_id, teloxide_error_can_safely_be_ignored};
+use crate::util::{Emoji, Required, StickerFileId, StickerId, create_tag_id, teloxide_error_can_safely_be_ignored};
 
 use crate::callback::CallbackData;
 
@@ -86,42 +89,26 @@ async fn handle_sticker_tag_action(
                 return Ok(());
             }
 
-            if let Some(implications) = request_context.tag_manager.get_implications(&tag) {
-                let tags = implications
-                    .clone()
-                    .into_iter()
-                    .chain(std::iter::once(tag.clone()))
-                    .collect_vec();
-                request_context
-                    .database
-                    .tag_file(&file.id, &tags, Some(request_context.user.id))
-                    .await?;
-                request_context.tfidf.request_recompute().await;
-                notification = Some(if implications.is_empty() {
-                    "Saved!".to_string()
-                } else {
-                    format!("Saved! ({tag} implies {})", implications.join(", "))
-                });
-            } else {
-                notification = Some("Invalid tag!".to_string());
+            notification = Some(tag_sticker_file(&file.id, tag, None, &request_context).await?);
+        }
+        Some(TagOperation::TagSuggestion { tag, rank, source }) => {
+            if handle_readonly(&request_context, &q).await? {
+                return Ok(());
             }
+
+            notification = Some(
+                tag_sticker_file(&file.id, tag, Some((rank, source)), &request_context).await?,
+            );
         }
         Some(TagOperation::Untag(tag)) => {
             if handle_readonly(&request_context, &q).await? {
                 return Ok(());
             }
 
-            let tags = tags_that_should_be_removed(
-                tag.clone(),
-                request_context
-                    .database
-                    .get_sticker_tags(&unique_id)
-                    .await?,
-                request_context.tag_manager.clone(),
-            )?;
-            request_context
-                .database
-                .untag_file(&file.id, &tags, request_context.user.id)
+            let tags = request_context
+                .services
+                .tagging
+                .untag_file(&file.id, &tag, request_context.user.id, &request_context.tag_manager)
                 .await?;
             let implications = request_context.tag_manager.get_implications(&tag);
             let tags = tags.join(", ");
@@ -145,6 +132,46 @@ async fn handle_sticker_tag_action(
     send_tagging_keyboard(request_context, notification, &unique_id, q).await
 }
 
+/// returns the notification for the user
+async fn tag_sticker_file(
+    file_id: &StickerFileId,
+    tag: String,
+    suggestion: Option<(u8, Option<SuggestionSource>)>,
+    request_context: &RequestContext,
+) -> Result<String, BotError> {
+    let Some(TaggedFile {
+        implications,
+        conflicts,
+    }) = request_context
+        .services
+        .tagging
+        .tag_file(
+            file_id,
+            &tag,
+            suggestion,
+            request_context.user.id,
+            &request_context.tag_manager,
+            &request_context.tfidf,
+        )
+        .await?
+    else {
+        return Ok("Invalid tag!".to_string());
+    };
+    let notification = if implications.is_empty() {
+        "Saved!".to_string()
+    } else {
+        format!("Saved! ({tag} implies {})", implications.join(", "))
+    };
+    Ok(if conflicts.is_empty() {
+        notification
+    } else {
+        format!(
+            "{notification} ⚠️ Conflicting tags: {}",
+            conflicts.iter().join(", ")
+        )
+    })
+}
+
 #[tracing::instrument(skip(request_context, q))]
 async fn send_tagging_keyboard(
     request_context: RequestContext,
@@ -163,6 +190,8 @@ async fn send_tagging_keyboard(
         request_context.database.clone(),
         request_context.tfidf.clone(),
         request_context.vector_db.clone(),
+        request_context.services.tag_suggestion_rules.current(),
+        request_context.services.suggestion_combiner.current(),
         // request_context.tag_worker.clone(),
     )
     .await?;
@@ -210,7 +239,7 @@ pub async fn show_error(
     request_context: RequestContext,
     error: BotError,
 ) -> Result<(), BotError> {
-    let error = error.end_user_error();
+    let error = error.end_user_error(request_context.language);
     request_context
         .bot
         .answer_callback_query(&q.id)
@@ -243,27 +272,57 @@ pub async fn callback_handler(
     let data: CallbackData = q.data.clone().unwrap_or_default().try_into()?;
     match data {
         CallbackData::ChangeModerationTaskStatus { status, task_id } => {
-            if !request_context.is_admin() {
+            if !request_context.is_moderator() {
                 return Err(UserError::NoPermissionForAction(
                     "changing moderation task status".to_string(),
                 )
                 .into());
             }
 
+            request_context
+                .services
+                .moderation
+                .claim_task(task_id, request_context.user.id)
+                .await?;
+            let previous_status = request_context
+                .database
+                .get_moderation_task_by_id(task_id)
+                .await?
+                .map(|task| task.completion_status);
             let task = request_context
                 .database
-                .change_moderation_task_status(task_id, status)
+                .change_moderation_task_status(task_id, status, request_context.user.id)
                 .await?;
+            request_context
+                .database
+                .add_moderation_log_entry(
+                    Some(request_context.user.id),
+                    ModerationAction::ChangeModerationTaskStatus,
+                    &task_id.to_string(),
+                    Some(format!("{status:?}")),
+                )
+                .await?;
+            // reporters are only told once, not every time a moderator changes their mind
+            if previous_status == Some(ModerationTaskStatus::Pending)
+                && status != ModerationTaskStatus::Pending
+            {
+                request_context
+                    .services
+                    .report
+                    .notify_reporters(&task)
+                    .await?;
+            }
 
             let (text, keyboard) =
                 get_moderation_task_data(task, &request_context.database).await?;
             answer_callback_query(request_context, q, Some(text), Some(keyboard), None).await
         }
         CallbackData::Privacy(privacy) => {
+            let language = request_context.language;
             answer_callback_query(
                 request_context,
                 q,
-                Some(Text::privacy(privacy.unwrap_or_default())),
+                Some(Text::privacy(privacy.unwrap_or_default(), language)),
                 Some(Keyboard::privacy(privacy.unwrap_or_default())),
                 None,
             )
@@ -273,6 +332,9 @@ pub async fn callback_handler(
             if handle_readonly(&request_context, &q).await? {
                 return Ok(());
             }
+            if !request_context.has_capability(Capability::CreateTags) {
+                return Err(UserError::NoPermissionForAction("create tag".to_string()).into());
+            }
 
             let mut state = match request_context.dialog_state() {
                 DialogState::TagCreator(state) => state,
@@ -405,6 +467,9 @@ pub async fn callback_handler(
             if handle_readonly(&request_context, &q).await? {
                 return Ok(());
             }
+            if !request_context.can_tag_stickers() {
+                return Err(UserError::NoPermissionForAction("tag sticker".to_string()).into());
+            }
 
             let continuous_tag = match request_context.dialog_state() {
                 DialogState::ContinuousTag(ct) => ct,
@@ -425,6 +490,7 @@ pub async fn callback_handler(
                     &file.id,
                     &continuous_tag.add_tags,
                     Some(request_context.user.id),
+                    TagOrigin::Manual,
                 )
                 .await?;
 
@@ -529,21 +595,66 @@ pub async fn callback_handler(
             banned,
             moderation_task_id,
         } => {
-            if !request_context.is_admin() {
+            if !request_context.is_moderator() {
                 return Err(
                     UserError::NoPermissionForAction("set banned status".to_string()).into(),
                 );
             }
-            let task = request_context
+            request_context
+                .services
+                .moderation
+                .claim_task(moderation_task_id, request_context.user.id)
+                .await?;
+            let moderator_id = Some(request_context.user.id);
+            if banned {
+                request_context
+                    .services
+                    .import
+                    .ban_sticker_set(&set_name, moderator_id)
+                    .await?;
+            } else {
+                request_context
+                    .services
+                    .import
+                    .unban_sticker_set(&set_name, moderator_id)
+                    .await?;
+            }
+            let previous_status = request_context
                 .database
                 .get_moderation_task_by_id(moderation_task_id)
                 .await?
-                .required()?;
-            if banned {
-                request_context.services.import.ban_sticker_set(&set_name).await?;
+                .map(|task| task.completion_status);
+            // banning resolves a report of the set, unbanning only records the decision
+            let task = if banned && previous_status == Some(ModerationTaskStatus::Pending) {
+                let task = request_context
+                    .database
+                    .change_moderation_task_status(
+                        moderation_task_id,
+                        ModerationTaskStatus::Completed,
+                        request_context.user.id,
+                    )
+                    .await?;
+                request_context
+                    .database
+                    .add_moderation_log_entry(
+                        Some(request_context.user.id),
+                        ModerationAction::ChangeModerationTaskStatus,
+                        &moderation_task_id.to_string(),
+                        Some(format!("{:?}", ModerationTaskStatus::Completed)),
+                    )
+                    .await?;
+                request_context
+                    .services
+                    .report
+                    .notify_reporters(&task)
+                    .await?;
+                task
             } else {
-                request_context.services.import.unban_sticker_set(&set_name).await?;
-            }
+                request_context
+                    .database
+                    .record_moderation_task_decision(moderation_task_id, request_context.user.id)
+                    .await?
+            };
 
             let (text, keyboard) =
                 get_moderation_task_data(task, &request_context.database).await?;
@@ -563,7 +674,11 @@ pub async fn callback_handler(
             answer_callback_query(
                 request_context.clone(),
                 q,
-                Some(Text::get_help_text(request_context.is_admin())),
+                Some(Text::get_help_text(
+                    request_context.is_moderator(),
+                    request_context.is_admin(),
+                    request_context.language,
+                )),
                 Some(Keyboard::make_help_keyboard()),
                 None,
             )
@@ -575,6 +690,7 @@ pub async fn callback_handler(
                 q,
                 Some(Text::get_settings_text(
                     &request_context.user.settings.clone().unwrap_or_default(),
+                    request_context.language,
                 )),
                 Some(Keyboard::make_settings_keyboard(
                     &request_context.user.settings.clone().unwrap_or_default(),
@@ -588,7 +704,7 @@ pub async fn callback_handler(
             answer_callback_query(
                 request_context.clone(),
                 q,
-                Some(Text::latest_sets(sets)),
+                Some(Text::latest_sets(sets, request_context.language)),
                 Some(Keyboard::latest_sets()),
                 None,
             )
@@ -602,7 +718,7 @@ pub async fn callback_handler(
             answer_callback_query(
                 request_context.clone(),
                 q,
-                Some(Text::latest_stickers(changes.clone())),
+                Some(Text::latest_stickers(changes.clone(), request_context.language)),
                 Some(Keyboard::latest_stickers(changes)),
                 None,
             )
@@ -635,7 +751,7 @@ pub async fn callback_handler(
             answer_callback_query(
                 request_context.clone(),
                 q,
-                Some(Text::personal_stats(stats, set_count)),
+                Some(Text::personal_stats(stats, set_count, request_context.language)),
                 Some(Keyboard::personal_stats(request_context.user.id)),
                 None,
             )
@@ -646,7 +762,7 @@ pub async fn callback_handler(
             answer_callback_query(
                 request_context.clone(),
                 q,
-                Some(Text::general_stats(stats)),
+                Some(Text::general_stats(stats, request_context.language)),
                 Some(Keyboard::general_stats()),
                 None,
             )
@@ -667,12 +783,28 @@ pub async fn callback_handler(
             answer_callback_query(
                 request_context.clone(),
                 q,
-                Some(Text::popular_tags(tags)),
+                Some(Text::popular_tags(tags, request_context.language)),
                 Some(Keyboard::popular_tags()),
                 None,
             )
             .await
         }
+        CallbackData::TagWiki(tag) => {
+            let wiki = request_context
+                .services
+                .tag_wiki
+                .wiki(&tag, &request_context.tag_manager, request_context.rating_profile())
+                .await?
+                .ok_or_else(|| UserError::TagsNotFound(vec![tag.clone()]))?;
+            answer_callback_query(
+                request_context.clone(),
+                q,
+                Some(Text::tag_wiki(&wiki, request_context.language)),
+                Some(Keyboard::tag_wiki(&wiki, &request_context.config.domain_name)?),
+                None,
+            )
+            .await
+        }
         CallbackData::ExitDialog => {
             exit_mode(request_context.clone(), true).await?;
 
@@ -792,7 +924,8 @@ pub async fn callback_handler(
                     owner_username,
                     owner_tags,
                     channel_usernames,
-                )),
+                    &request_context.config.domain_name,
+                )?),
                 None,
             )
             .await
@@ -811,7 +944,13 @@ pub async fn callback_handler(
                     &sticker_id,
                     &set.id,
                     set.created_at,
-                )),
+                    request_context
+                        .user
+                        .settings
+                        .as_ref()
+                        .is_some_and(|settings| settings.is_set_whitelisted(&set.id)),
+                    &request_context.config.domain_name,
+                )?),
                 None,
             )
             .await
@@ -854,39 +993,271 @@ pub async fn callback_handler(
         CallbackData::StickerExplorePage { sticker_id } => {
             sticker_explore_page(sticker_id, request_context, q).await
         }
+        CallbackData::CollectionsPage { sticker_id } => {
+            answer_callback_query(
+                request_context.clone(),
+                q,
+                None,
+                Some(sticker_collections_keyboard(&sticker_id, request_context).await?),
+                None,
+            )
+            .await
+        }
+        CallbackData::ToggleCollectionSticker {
+            sticker_id,
+            collection_id,
+            add,
+        } => {
+            let collection = request_context
+                .database
+                .get_sticker_collection_by_id(request_context.user.id, collection_id)
+                .await?
+                .required()?;
+            if add {
+                request_context
+                    .database
+                    .add_sticker_to_collection(collection.id, &sticker_id)
+                    .await?;
+            } else {
+                request_context
+                    .database
+                    .remove_sticker_from_collection(collection.id, &sticker_id)
+                    .await?;
+            }
+            answer_callback_query(
+                request_context.clone(),
+                q,
+                None,
+                Some(sticker_collections_keyboard(&sticker_id, request_context).await?),
+                None,
+            )
+            .await
+        }
+        CallbackData::SetCollectionPublic {
+            sticker_id,
+            collection_id,
+            public,
+        } => {
+            let collection = request_context
+                .database
+                .get_sticker_collection_by_id(request_context.user.id, collection_id)
+                .await?
+                .required()?;
+            request_context
+                .database
+                .set_sticker_collection_public(request_context.user.id, collection.id, public)
+                .await?;
+            answer_callback_query(
+                request_context.clone(),
+                q,
+                None,
+                Some(sticker_collections_keyboard(&sticker_id, request_context).await?),
+                None,
+            )
+            .await
+        }
+        CallbackData::FollowCollection {
+            collection_id,
+            follow,
+        } => {
+            if follow {
+                let collection = request_context
+                    .database
+                    .get_public_sticker_collection(collection_id)
+                    .await?
+                    .ok_or_else(|| UserError::ListHasZeroResults("public collection".to_string()))?;
+                if collection.user_id == request_context.user.id {
+                    return Err(UserError::ValidationError(
+                        "you can't follow your own collection".to_string(),
+                    )
+                    .into());
+                }
+                request_context
+                    .database
+                    .follow_sticker_collection(request_context.user.id, collection.id)
+                    .await?;
+            } else {
+                request_context
+                    .database
+                    .unfollow_sticker_collection(request_context.user.id, collection_id)
+                    .await?;
+            }
+            let (text, keyboard) =
+                public_collection_overview(collection_id, request_context.clone()).await?;
+            answer_callback_query(request_context, q, Some(text), Some(keyboard), None).await
+        }
         CallbackData::Blacklist => {
+            let settings = request_context.user.settings.clone().unwrap_or_default();
+            answer_callback_query(
+                request_context.clone(),
+                q,
+                Some(Text::blacklist(&settings.whitelisted_sets, request_context.language)),
+                Some(Keyboard::blacklist(&request_context.user.blacklist, &settings)),
+                None,
+            )
+            .await
+        }
+        CallbackData::SetBlacklistMode { tag, mode } => {
+            let mut settings = request_context.user.settings.clone().unwrap_or_default();
+            if mode == BlacklistMode::default() {
+                settings.blacklist_modes.remove(&tag);
+            } else {
+                settings.blacklist_modes.insert(tag, mode);
+            }
+            request_context
+                .database
+                .update_settings(request_context.user.id, &settings)
+                .await?;
+            answer_callback_query(
+                request_context.clone(),
+                q,
+                Some(Text::blacklist(&settings.whitelisted_sets, request_context.language)),
+                Some(Keyboard::blacklist(&request_context.user.blacklist, &settings)),
+                None,
+            )
+            .await
+        }
+        CallbackData::RemoveWhitelistedSet(index) => {
+            let mut settings = request_context.user.settings.clone().unwrap_or_default();
+            if index < settings.whitelisted_sets.len() {
+                settings.whitelisted_sets.remove(index);
+            }
+            request_context
+                .database
+                .update_settings(request_context.user.id, &settings)
+                .await?;
+            answer_callback_query(
+                request_context.clone(),
+                q,
+                Some(Text::blacklist(&settings.whitelisted_sets, request_context.language)),
+                Some(Keyboard::blacklist(&request_context.user.blacklist, &settings)),
+                None,
+            )
+            .await
+        }
+        CallbackData::ToggleSetWhitelist {
+            sticker_id,
+            whitelist,
+        } => {
+            let set = request_context
+                .database
+                .get_sticker_set_by_sticker_id(&sticker_id)
+                .await?
+                .required()?;
+            let mut settings = request_context.user.settings.clone().unwrap_or_default();
+            settings.whitelisted_sets.retain(|whitelisted| whitelisted != &set.id);
+            if whitelist {
+                settings.whitelisted_sets.push(set.id.clone());
+            }
+            request_context
+                .database
+                .update_settings(request_context.user.id, &settings)
+                .await?;
+            answer_callback_query(
+                request_context.clone(),
+                q,
+                None,
+                Some(Keyboard::sticker_set_page(
+                    &sticker_id,
+                    &set.id,
+                    set.created_at,
+                    whitelist,
+                    &request_context.config.domain_name,
+                )?),
+                None,
+            )
+            .await
+        }
+        CallbackData::ReviewTag {
+            sticker_id,
+            tag,
+            accept,
+        } => {
+            if handle_readonly(&request_context, &q).await? {
+                return Ok(());
+            }
+            check_can_review_tags(&request_context)?;
+            let file = request_context
+                .database
+                .get_sticker_file_by_sticker_id(&sticker_id)
+                .await?
+                .required()?;
+            let tag_review = &request_context.services.tag_review;
+            let notification = if accept {
+                tag_review
+                    .confirm(&file.id, &tag, request_context.user.id)
+                    .await?;
+                "Confirmed!"
+            } else {
+                tag_review
+                    .reject(&file.id, &tag, request_context.user.id)
+                    .await?;
+                "Removed!"
+            };
+            let pending_tags = tag_review
+                .pending_tags(&file.id, request_context.user.id)
+                .await?;
             answer_callback_query(
                 request_context.clone(),
                 q,
-                Some(Text::blacklist()),
-                Some(Keyboard::blacklist(&request_context.user.blacklist)),
                 None,
+                Some(Keyboard::tag_review(&sticker_id, &pending_tags)),
+                Some(notification.to_string()),
             )
             .await
         }
+        CallbackData::NextTagReview { after } => {
+            check_can_review_tags(&request_context)?;
+            if let Some(chat_id) = q.chat_id() {
+                send_next_tag_review(chat_id, after.as_ref(), request_context.clone()).await?;
+            }
+            answer_callback_query(request_context, q, None, None, None).await
+        }
+        CallbackData::ApplySuggestionWeights => {
+            if !request_context.is_admin() {
+                return Err(UserError::NoPermissionForAction(
+                    "apply suggestion weights".to_string(),
+                )
+                .into());
+            }
+            let applied = request_context
+                .services
+                .suggestion_combiner
+                .apply_proposed()
+                .await?;
+            let notification = if applied {
+                "The trained weights are now in use"
+            } else {
+                "There are no trained weights to apply, evaluate the suggestions again"
+            };
+            answer_callback_query(request_context, q, None, None, Some(notification.to_string()))
+                .await
+        }
         // show main menu: show main menu, edit message, add keyboard
         CallbackData::Start => {
+            let language = request_context.language;
             answer_callback_query(
                 request_context,
                 q,
-                Some(Text::get_main_text()),
+                Some(Text::get_main_text(language)),
                 Some(Keyboard::make_main_keyboard()),
                 None,
             )
             .await
         }
         CallbackData::Info => {
+            let language = request_context.language;
             answer_callback_query(
                 request_context,
                 q,
-                Some(Text::infos()),
+                Some(Text::infos(language)),
                 Some(Keyboard::info()),
                 None,
             )
             .await
         }
         CallbackData::UserInfo(user_id) => {
-            if !request_context.is_admin() {
+            if !request_context.is_moderator() {
                 return Ok(());
             }
             request_context.bot.answer_callback_query(&q.id).await?;
@@ -910,6 +1281,24 @@ pub async fn callback_handler(
         CallbackData::SetOrder(order) => {
             let mut settings = request_context.user.settings.clone().unwrap_or_default();
             settings.order = Some(order);
+            let language = request_context.language;
+            request_context
+                .database
+                .update_settings(request_context.user.id, &settings)
+                .await?;
+            answer_callback_query(
+                request_context,
+                q,
+                Some(Text::get_settings_text(&settings, language)),
+                Some(Keyboard::make_settings_keyboard(&settings)),
+                None,
+            )
+            .await
+        }
+        CallbackData::SetRatingProfile(rating) => {
+            let mut settings = request_context.user.settings.clone().unwrap_or_default();
+            settings.rating_profile = Some(rating);
+            let language = request_context.language;
             request_context
                 .database
                 .update_settings(request_context.user.id, &settings)
@@ -917,7 +1306,7 @@ pub async fn callback_handler(
             answer_callback_query(
                 request_context,
                 q,
-                Some(Text::get_settings_text(&settings)),
+                Some(Text::get_settings_text(&settings, language)),
                 Some(Keyboard::make_settings_keyboard(&settings)),
                 None,
             )
@@ -928,6 +1317,43 @@ pub async fn callback_handler(
             sticker_id_b,
             merge,
         } => handle_sticker_merge(sticker_id_a.into(), sticker_id_b.into(), merge, q, request_context).await,
+        CallbackData::CompareSets {
+            sticker_id_a,
+            sticker_id_b,
+        } => {
+            // comparing takes a while for big sets
+            request_context.bot.answer_callback_query(&q.id).await?;
+            request_context
+                .bot
+                .send_chat_action(request_context.user_id(), teloxide::types::ChatAction::Typing)
+                .await?;
+            let set_a = request_context
+                .database
+                .get_sticker_set_by_sticker_id(&sticker_id_a)
+                .await?
+                .required()?;
+            let set_b = request_context
+                .database
+                .get_sticker_set_by_sticker_id(&sticker_id_b)
+                .await?
+                .required()?;
+            let comparison = request_context
+                .services
+                .set_comparison
+                .compare(&set_a.id, &set_b.id, true)
+                .await?
+                .required()?;
+            request_context
+                .bot
+                .send_markdown(
+                    request_context.user_id(),
+                    Text::set_comparison(&comparison, request_context.is_moderator(), request_context.language),
+                )
+                .reply_markup(Keyboard::set_comparison(&comparison, &request_context.config.domain_name)?)
+                .link_preview_options(LinkPreviewOptions::new().is_disabled(true))
+                .await?;
+            Ok(())
+        }
         CallbackData::RemoveLinkedUser => {
             if handle_readonly(&request_context, &q).await? {
                 return Ok(());
@@ -1056,10 +1482,15 @@ pub async fn callback_handler(
             moderation_task_id,
             action,
         } => {
-            if !request_context.is_admin() {
+            if !request_context.is_moderator() {
                 return Err(UserError::NoPermissionForAction("changing tags".to_string()).into());
             }
 
+            request_context
+                .services
+                .moderation
+                .claim_task(moderation_task_id, request_context.user.id)
+                .await?;
             let task = request_context
                 .database
                 .get_moderation_task_by_id(moderation_task_id)
@@ -1090,19 +1521,42 @@ pub async fn callback_handler(
                                     implications,
                                 )
                                 .await?;
+                            request_context
+                                .database
+                                .add_moderation_log_entry(
+                                    Some(request_context.user.id),
+                                    ModerationAction::CreateTag,
+                                    &tag_id,
+                                    Some(format!("task {moderation_task_id}")),
+                                )
+                                .await?;
                         }
                         super::TagListAction::Remove => {
                             request_context.database.delete_tag(&tag_id).await?;
+                            request_context
+                                .database
+                                .add_moderation_log_entry(
+                                    Some(request_context.user.id),
+                                    ModerationAction::DeleteTag,
+                                    &tag_id,
+                                    Some(format!("task {moderation_task_id}")),
+                                )
+                                .await?;
                         }
                     }
 
                     request_context.tag_manager.recompute().await; // might take a while
                 }
                 crate::database::ModerationTaskDetails::ReportStickerSet { .. }
+                | crate::database::ModerationTaskDetails::ReportSticker { .. }
                 | crate::database::ModerationTaskDetails::ReviewNewSets { .. } => {
                     return Err(anyhow::anyhow!("invalid task type").into());
                 }
             }
+            let task = request_context
+                .database
+                .record_moderation_task_decision(moderation_task_id, request_context.user.id)
+                .await?;
 
             let (text, keyboard) =
                 get_moderation_task_data(task, &request_context.database).await?;
@@ -1119,37 +1573,14 @@ async fn handle_sticker_merge(
     q: CallbackQuery,
     request_context: RequestContext,
 ) -> Result<(), BotError> {
-    if !request_context.is_admin() {
+    if !request_context.is_moderator() {
         return Err(UserError::NoPermissionForAction("merge stickers".to_string()).into());
     }
-    let done_text = if merge { "merged" } else { "not merged" };
-    let file_a = request_context
-        .database
-        .get_sticker_file_by_sticker_id(&sticker_id_a)
-        .await?
-        .required()?;
-    let file_b = request_context
-        .database
-        .get_sticker_file_by_sticker_id(&sticker_id_b)
-        .await?
-        .required()?;
-    if merge {
-        determine_canonical_sticker_and_merge(
-            sticker_id_a.clone(),
-            sticker_id_b.clone(),
-            request_context.database.clone(),
-        )
+    request_context
+        .services
+        .set_comparison
+        .decide_merge(request_context.user.id, &sticker_id_a, &sticker_id_b, merge)
         .await?;
-        request_context
-            .database
-            .add_or_modify_potential_merge(&file_a.id, &file_b.id, MergeStatus::Merged)
-            .await?;
-    } else {
-        request_context
-            .database
-            .add_or_modify_potential_merge(&file_a.id, &file_b.id, MergeStatus::NotMerged)
-            .await?;
-    }
     let set_a = request_context
         .database
         .get_sticker_set_by_sticker_id(&sticker_id_a)
@@ -1263,6 +1694,60 @@ pub async fn sticker_explore_keyboard(
     ))
 }
 
+#[tracing::instrument(skip(request_context), err(Debug))]
+pub async fn sticker_collections_keyboard(
+    sticker_id: &StickerId,
+    request_context: RequestContext,
+) -> Result<InlineKeyboardMarkup, InternalError> {
+    let collections = request_context
+        .database
+        .get_sticker_collections(request_context.user.id)
+        .await?;
+    let containing_collection_ids = request_context
+        .database
+        .get_sticker_collection_ids_containing_sticker(request_context.user.id, sticker_id)
+        .await?;
+    Keyboard::sticker_collections(
+        sticker_id,
+        &collections,
+        &containing_collection_ids,
+        &request_context.config.telegram_bot_username,
+    )
+}
+
+pub async fn public_collection_overview(
+    collection_id: i64,
+    request_context: RequestContext,
+) -> Result<(Markdown, InlineKeyboardMarkup), BotError> {
+    let collection = request_context
+        .database
+        .get_public_sticker_collection(collection_id)
+        .await?
+        .ok_or_else(|| UserError::ListHasZeroResults("public collection".to_string()))?;
+    let sticker_count = request_context
+        .database
+        .get_collection_sticker_count(collection.id)
+        .await?;
+    let follower_count = request_context
+        .database
+        .get_sticker_collection_follower_count(collection.id)
+        .await?;
+    let is_following = request_context
+        .database
+        .is_following_sticker_collection(request_context.user.id, collection.id)
+        .await?;
+    Ok((
+        Text::public_collection(&collection, sticker_count, follower_count, request_context.language),
+        Keyboard::public_collection(
+            collection.id,
+            collection.user_id == request_context.user.id,
+            is_following,
+            &request_context.config.telegram_bot_username,
+            &request_context.config.domain_name,
+        )?,
+    ))
+}
+
 fn create_sticker_name(path: String) -> String {
     path.rsplit_once(".")
         .map_or("sticker.bin".to_string(), |(_, ext)| {
@@ -1270,20 +1755,6 @@ fn create_sticker_name(path: String) -> String {
         })
 }
 
-fn tags_that_should_be_removed(
-    tag: String,
-    current: Vec<String>,
-    tag_manager: TagManagerService,
-) -> Result<Vec<String>, InternalError> {
-    Ok(current
-        .into_iter()
-        .map(|t| (t.clone(), tag_manager.get_implications(&t)))
-        .filter(|(t, implications)| implications.clone().unwrap_or_default().contains(&tag))
-        .map(|(t, _)| t)
-        .chain(std::iter::once(tag.clone()))
-        .collect_vec())
-}
-
 #[tracing::instrument(skip(request_context, q), err(Debug))]
 async fn remove_continuous_tag(
     q: CallbackQuery,
@@ -1331,12 +1802,14 @@ async fn remove_continuous_tag(
         .update_dialog_state(request_context.user.id, &new_dialog_state)
         .await?;
 
+    let language = request_context.language;
     answer_callback_query(
         request_context,
         q,
         Some(Text::get_continuous_tag_mode_text(
             add_tags.as_slice(),
             remove_tags.as_slice(),
+            language,
         )),
         Some(Keyboard::make_continuous_tag_keyboard(
             true,
@@ -1367,11 +1840,19 @@ async fn remove_blacklist_tag(
         .database
         .update_user_blacklist(request_context.user.id, blacklist.clone().into())
         .await?;
-    let keyboard = Keyboard::blacklist(&blacklist);
+    let mut settings = request_context.user.settings.clone().unwrap_or_default();
+    if settings.blacklist_modes.remove(&tag).is_some() {
+        request_context
+            .database
+            .update_settings(request_context.user.id, &settings)
+            .await?;
+    }
+    let keyboard = Keyboard::blacklist(&blacklist, &settings);
+    let language = request_context.language;
     answer_callback_query(
         request_context,
         q,
-        Some(Text::blacklist()),
+        Some(Text::blacklist(&settings.whitelisted_sets, language)),
         Some(keyboard),
         None,
     )
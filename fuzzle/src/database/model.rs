@@ -21,7 +21,7 @@ use teloxide::types::{ChatId, UserId};
 use teloxide::{requests::Requester, types::InputSticker};
 
 use crate::util::{StickerFileId, StickerId, StickerSetId};
-use crate::{bot::Bot, tags::Category, util::Emoji};
+use crate::{bot::Bot, tags::{Category, Rating}, text::Language, util::Emoji};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PopularTag {
@@ -43,16 +43,48 @@ pub struct Stats {
     pub tagged_stickers: i64,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct SitemapStats {
+    pub sets: i64,
+    pub tags: i64,
+    pub emojis: i64,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct PersonalStats {
     pub favorites: i64,
+    pub public_collections: i64,
+    /// summed over all of the user's public collections
+    pub collection_followers: i64,
+    pub followed_collections: i64,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct AdminStats {
     pub number_of_sets_fetched_in_24_hours: i64,
     pub least_recently_fetched_set_age: Option<Duration>,
     pub pending_set_count: i64,
+    pub suggestion_acceptance: SuggestionAcceptanceStats,
+}
+
+/// tags added by users, grouped by how they were added
+#[derive(Debug, Clone, Default)]
+pub struct SuggestionAcceptanceStats {
+    pub manual: i64,
+    pub suggested: i64,
+    pub implied: i64,
+    /// accepted suggestions and their average rank by suggestion source, most accepted first
+    pub sources: Vec<(String, i64, f64)>,
+}
+
+impl SuggestionAcceptanceStats {
+    /// share of the directly chosen tags that were suggested; this is not the share of shown
+    /// suggestions that were accepted, since impressions are not stored
+    #[must_use]
+    pub fn suggested_share(&self) -> Option<f64> {
+        let chosen = self.suggested + self.manual;
+        (chosen > 0).then(|| self.suggested as f64 / chosen as f64)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -73,12 +105,116 @@ pub struct AddedRemoved {
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct UserSettings {
     pub order: Option<StickerOrder>,
+    /// blacklisted tags without an entry use the default mode
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub blacklist_modes: HashMap<String, BlacklistMode>,
+    /// sets that are shown even if their stickers contain blacklisted tags
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub whitelisted_sets: Vec<StickerSetId>,
+    pub rating_profile: Option<Rating>,
+    /// what /tagme serves; everything if not set
+    pub tagging_focus: Option<TaggingFocus>,
+    /// tags whose new stickers show up in the personal feed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub followed_tags: Vec<String>,
+    /// overrides the language of the telegram app
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<Language>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum TaggingFocus {
+    /// untagged stickers in sets that already contain the tag, eg a species
+    Tag(String),
+    Set(StickerSetId),
 }
 
 impl UserSettings {
     pub fn order(&self) -> StickerOrder {
         self.order.unwrap_or_default()
     }
+
+    pub fn blacklist_mode(&self, tag: &str) -> BlacklistMode {
+        self.blacklist_modes.get(tag).copied().unwrap_or_default()
+    }
+
+    /// the chosen language, or the one of the telegram app if there is a catalog for it
+    pub fn language(&self, language_code: Option<&str>) -> Language {
+        self.language
+            .or_else(|| language_code.and_then(Language::from_language_code))
+            .unwrap_or_default()
+    }
+
+    /// users that never chose a profile see everything that is not blacklisted
+    pub fn rating_profile(&self) -> Rating {
+        self.rating_profile.unwrap_or(Rating::Explicit)
+    }
+
+    /// blacklisted tags that exclude stickers from the results
+    pub fn hidden_tags(&self, blacklist: &[String], emoji_search: bool) -> Vec<String> {
+        blacklist
+            .iter()
+            .filter(|tag| match self.blacklist_mode(tag) {
+                BlacklistMode::TagSearch => !emoji_search,
+                BlacklistMode::Everywhere => true,
+                BlacklistMode::Warn => false,
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// tags that cause stickers to be shown as spoilers instead of being excluded
+    pub fn warned_tags(&self, blacklist: &[String]) -> Vec<String> {
+        blacklist
+            .iter()
+            .filter(|tag| self.blacklist_mode(tag) == BlacklistMode::Warn)
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_set_whitelisted(&self, set_id: &StickerSetId) -> bool {
+        self.whitelisted_sets.contains(set_id)
+    }
+}
+
+#[derive(Debug, Serialize_repr, Deserialize_repr, Clone, Default, PartialEq, Eq, Copy, Primitive)]
+#[repr(u8)]
+pub enum BlacklistMode {
+    /// hidden when searching by tags, but not when searching by a single emoji
+    #[default]
+    TagSearch = 0,
+    Everywhere = 1,
+    /// shown as a spoiler
+    Warn = 2,
+}
+
+impl BlacklistMode {
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::TagSearch => Self::Everywhere,
+            Self::Everywhere => Self::Warn,
+            Self::Warn => Self::TagSearch,
+        }
+    }
+
+    #[must_use]
+    pub const fn to_emoji(self) -> &'static str {
+        match self {
+            Self::TagSearch => "🏷️",
+            Self::Everywhere => "🚫",
+            Self::Warn => "⚠️",
+        }
+    }
+
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::TagSearch => "hidden in tag searches",
+            Self::Everywhere => "hidden everywhere",
+            Self::Warn => "shown as spoiler",
+        }
+    }
 }
 
 #[derive(Debug, Serialize_repr, Deserialize_repr, Clone, Default, PartialEq, Eq, Copy)]
@@ -132,6 +268,8 @@ macro_rules! json_wrapper {
 }
 
 json_wrapper!(StringVec, Vec<String>);
+/// overrides the capabilities of the trust level: `true` grants a capability, `false` revokes it
+json_wrapper!(CapabilityGrants, HashMap<Capability, bool>);
 
 macro_rules! impl_json {
     ($type_name:ty) => {
@@ -177,31 +315,133 @@ pub enum ModerationTaskDetails {
     ReportStickerSet {
         set_id: StickerSetId,
         reason: ReportReason,
+        #[serde(default)]
+        note: Option<String>,
+        /// reports of other users while this one was pending
+        #[serde(default)]
+        additional_reports: Vec<AdditionalReport>,
     },
     #[deprecated]
     ReviewNewSets {
         set_ids: Vec<StickerSetId>,
     },
+    ReportSticker {
+        sticker_id: StickerId,
+        reason: ReportReason,
+        note: Option<String>,
+        additional_reports: Vec<AdditionalReport>,
+    },
+}
+
+impl ModerationTaskDetails {
+    #[must_use]
+    pub fn report_target(&self) -> Option<ReportTarget> {
+        match self {
+            Self::ReportStickerSet { set_id, .. } => Some(ReportTarget::Set(set_id.clone())),
+            Self::ReportSticker { sticker_id, .. } => Some(ReportTarget::Sticker(sticker_id.clone())),
+            Self::CreateTag { .. } | Self::ReviewNewSets { .. } => None,
+        }
+    }
+
+    /// users that reported the same target after the creator of the task
+    #[must_use]
+    pub fn additional_reports(&self) -> &[AdditionalReport] {
+        match self {
+            Self::ReportStickerSet {
+                additional_reports, ..
+            }
+            | Self::ReportSticker {
+                additional_reports, ..
+            } => additional_reports,
+            Self::CreateTag { .. } | Self::ReviewNewSets { .. } => &[],
+        }
+    }
+
+    pub fn add_report(&mut self, report: AdditionalReport) {
+        if let Self::ReportStickerSet {
+            additional_reports, ..
+        }
+        | Self::ReportSticker {
+            additional_reports, ..
+        } = self
+        {
+            additional_reports.push(report);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ReportTarget {
+    Set(StickerSetId),
+    Sticker(StickerId),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdditionalReport {
+    pub user_id: i64,
+    pub reason: ReportReason,
+    pub note: Option<String>,
+    /// missing for reports that were added before it was recorded
+    #[serde(default)]
+    pub created_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportInsertion {
+    Created,
+    AddedToPendingTask,
+    AlreadyReported,
+    /// a report of the same target was decided within the cooldown, nothing was inserted
+    RecentlyDecided,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, Primitive)]
 pub enum ReportReason {
     Other = 0,
     NotFurry = 1,
+    WrongTags = 2,
+    NsfwNotRated = 3,
+    Spam = 4,
+    DuplicateSet = 5,
 }
 
 impl ReportReason {
+    /// in the order they are offered to the user
+    pub const SET_REASONS: [Self; 6] = [
+        Self::NotFurry,
+        Self::NsfwNotRated,
+        Self::WrongTags,
+        Self::Spam,
+        Self::DuplicateSet,
+        Self::Other,
+    ];
+    pub const STICKER_REASONS: [Self; 5] = [
+        Self::NotFurry,
+        Self::NsfwNotRated,
+        Self::WrongTags,
+        Self::Spam,
+        Self::Other,
+    ];
+
     pub fn get_title(&self) -> &'static str {
         match self {
             Self::Other => "Other Reason",
             Self::NotFurry => "Not Furry",
+            Self::WrongTags => "Wrong Tags",
+            Self::NsfwNotRated => "NSFW Not Rated",
+            Self::Spam => "Spam or Ads",
+            Self::DuplicateSet => "Duplicate Set",
         }
     }
     
     pub fn get_description(&self) -> &'static str {
         match self {
             Self::Other => "My concern isn't listed",
-            Self::NotFurry => "Set does not (mostly) contain furry stickers",
+            Self::NotFurry => "Does not (mostly) contain furry stickers",
+            Self::WrongTags => "Tags are missing or do not match the stickers",
+            Self::NsfwNotRated => "NSFW content that is not tagged as questionable or explicit",
+            Self::Spam => "Advertisements, scams or spam",
+            Self::DuplicateSet => "Copy of another set",
         }
     }
 }
@@ -210,6 +450,7 @@ impl_json!(UserSettings);
 impl_json!(ModerationTaskDetails);
 impl_json!(StringVec);
 impl_json!(DialogState);
+impl_json!(CapabilityGrants);
 
 #[derive(PartialEq, Debug, Copy, Clone, Primitive, AsExpression)]
 #[diesel(sql_type = diesel::sql_types::BigInt)]
@@ -234,6 +475,211 @@ pub enum BanReason {
     Automatic = 1,
 }
 
+/// how a tag ended up on a sticker file
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Primitive, AsExpression, FromSqlRow)]
+#[diesel(sql_type = diesel::sql_types::BigInt)]
+pub enum TagOrigin {
+    /// typed by the user or clicked on a button that was not a suggestion
+    Manual = 0,
+    /// clicked on a suggested tag
+    Suggestion = 1,
+    /// added because another tag implies it
+    Implication = 2,
+}
+
+impl TagOrigin {
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Suggestion => "suggestion",
+            Self::Implication => "implication",
+        }
+    }
+}
+
+/// how much the bot trusts a user
+///
+/// New users become trusted automatically once enough of their tags were accepted; all other
+/// changes are made by admins.
+#[derive(
+    PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone, Primitive, AsExpression, FromSqlRow,
+)]
+#[diesel(sql_type = diesel::sql_types::BigInt)]
+pub enum TrustLevel {
+    Banned = 0,
+    New = 1,
+    Trusted = 2,
+    Moderator = 3,
+}
+
+impl TrustLevel {
+    pub const ALL: [Self; 4] = [Self::Banned, Self::New, Self::Trusted, Self::Moderator];
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Banned => "banned",
+            Self::New => "new",
+            Self::Trusted => "trusted",
+            Self::Moderator => "moderator",
+        }
+    }
+
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|level| level.name() == name)
+    }
+
+    /// capabilities of users that have no grants
+    #[must_use]
+    pub const fn default_capabilities(self) -> &'static [Capability] {
+        match self {
+            Self::Banned => &[],
+            Self::New => &[
+                Capability::TagStickers,
+                Capability::CreateTags,
+                Capability::Report,
+                Capability::TagMe,
+            ],
+            Self::Trusted | Self::Moderator => &Capability::ALL,
+        }
+    }
+}
+
+/// kind of a moderation log entry; the target depends on the action
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Primitive, AsExpression, FromSqlRow)]
+#[diesel(sql_type = diesel::sql_types::BigInt)]
+pub enum ModerationAction {
+    /// target: set id
+    BanSet = 0,
+    UnbanSet = 1,
+    ApproveSet = 2,
+    /// target: sticker id
+    BanSticker = 3,
+    UnbanSticker = 4,
+    /// target: sticker id; details: the other sticker id
+    MergeStickers = 5,
+    KeepStickersSeparate = 6,
+    /// target: tag id
+    CreateTag = 7,
+    DeleteTag = 8,
+    /// target: moderation task id; details: the new status
+    ChangeModerationTaskStatus = 9,
+    /// target: user id; details: the new trust level
+    ChangeTrustLevel = 10,
+    /// target: user id; details: capability and grant
+    ChangeCapabilityGrant = 11,
+    /// target: revision id
+    UpdateTagSuggestionRules = 12,
+    /// target: tag id; details: the new description, none if it was removed
+    EditTagDescription = 13,
+    /// target: user id; details: the removed bio and links
+    ResetCreatorProfile = 14,
+}
+
+impl ModerationAction {
+    pub const ALL: [Self; 15] = [
+        Self::BanSet,
+        Self::UnbanSet,
+        Self::ApproveSet,
+        Self::BanSticker,
+        Self::UnbanSticker,
+        Self::MergeStickers,
+        Self::KeepStickersSeparate,
+        Self::CreateTag,
+        Self::DeleteTag,
+        Self::ChangeModerationTaskStatus,
+        Self::ChangeTrustLevel,
+        Self::ChangeCapabilityGrant,
+        Self::UpdateTagSuggestionRules,
+        Self::EditTagDescription,
+        Self::ResetCreatorProfile,
+    ];
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::BanSet => "ban_set",
+            Self::UnbanSet => "unban_set",
+            Self::ApproveSet => "approve_set",
+            Self::BanSticker => "ban_sticker",
+            Self::UnbanSticker => "unban_sticker",
+            Self::MergeStickers => "merge_stickers",
+            Self::KeepStickersSeparate => "keep_stickers_separate",
+            Self::CreateTag => "create_tag",
+            Self::DeleteTag => "delete_tag",
+            Self::ChangeModerationTaskStatus => "change_moderation_task_status",
+            Self::ChangeTrustLevel => "change_trust_level",
+            Self::ChangeCapabilityGrant => "change_capability_grant",
+            Self::UpdateTagSuggestionRules => "update_tag_suggestion_rules",
+            Self::EditTagDescription => "edit_tag_description",
+            Self::ResetCreatorProfile => "reset_creator_profile",
+        }
+    }
+
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+/// an action that requires a certain trust level or an explicit grant
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// also allows untagging
+    TagStickers,
+    /// tagging and untagging all stickers of a set at once
+    TagSets,
+    CreateTags,
+    Report,
+    TagMe,
+    ReviewTags,
+    /// the description on the tag wiki page
+    EditTagDescriptions,
+}
+
+impl Capability {
+    pub const ALL: [Self; 7] = [
+        Self::TagStickers,
+        Self::TagSets,
+        Self::CreateTags,
+        Self::Report,
+        Self::TagMe,
+        Self::ReviewTags,
+        Self::EditTagDescriptions,
+    ];
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::TagStickers => "tag_stickers",
+            Self::TagSets => "tag_sets",
+            Self::CreateTags => "create_tags",
+            Self::Report => "report",
+            Self::TagMe => "tagme",
+            Self::ReviewTags => "review_tags",
+            Self::EditTagDescriptions => "edit_tag_descriptions",
+        }
+    }
+
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|capability| capability.name() == name)
+    }
+}
+
+/// tags of a user that are still present and tags that other users removed again
+#[derive(QueryableByName, Debug, Clone)]
+pub struct TaggingHistory {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub user_id: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub accepted_count: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub rejected_count: i64,
+}
 
 #[derive(PartialEq, Debug, Copy, Clone, Primitive, AsExpression, FromSqlRow)]
 #[diesel(sql_type = diesel::sql_types::BigInt)]
@@ -278,6 +724,9 @@ impl_enum!(Category);
 impl_enum!(ModerationTaskStatus);
 impl_enum!(UsernameKind);
 impl_enum!(BanReason);
+impl_enum!(TagOrigin);
+impl_enum!(TrustLevel);
+impl_enum!(ModerationAction);
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub enum DialogState {
@@ -329,6 +778,16 @@ pub struct TagCreator {
 // - database null -> Normal
 // - database ContinuousTag {} -> defaults for add_tag and remove_tag
 
+/// number of taggings that were added in a month
+#[derive(QueryableByName, Debug, Clone)]
+pub struct TagUsage {
+    /// `YYYY-MM`
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub month: String,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub count: i64,
+}
+
 #[derive(QueryableByName, Debug, Clone)]
 pub struct StickerChange {
     #[diesel(sql_type = diesel::sql_types::Text)]
@@ -342,6 +801,19 @@ pub struct StickerChange {
 }
 
 
+/// an untagged sticker together with how popular it is
+#[derive(QueryableByName, Debug, Clone)]
+pub struct TaggingCandidate {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub sticker_id: StickerId,
+    /// number of users that used the sticker
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub usage_count: i64,
+    /// number of users that used any sticker of the set
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub set_user_count: i64,
+}
+
 #[derive(QueryableByName, Debug, Clone)]
 pub struct UserStickerStat {
     #[diesel(sql_type = diesel::sql_types::BigInt)]
@@ -0,0 +1,98 @@
+use diesel::prelude::*;
+use diesel::{insert_into, update};
+
+use crate::database::ApiKey;
+
+use super::DatabaseError;
+
+use super::Database;
+
+use super::super::schema::*;
+
+impl Database {
+    #[tracing::instrument(skip(self, key_hash), err(Debug))]
+    pub async fn create_api_key(
+        &self,
+        user_id: i64,
+        name: &str,
+        key_hash: &str,
+        requests_per_minute: i64,
+    ) -> Result<ApiKey, DatabaseError> {
+        let name = name.to_string();
+        let key_hash = key_hash.to_string();
+        self
+            .exec(move |conn| {
+                Ok(insert_into(api_key::table)
+                    .values((
+                        api_key::user_id.eq(user_id),
+                        api_key::name.eq(name),
+                        api_key::key_hash.eq(key_hash),
+                        api_key::requests_per_minute.eq(requests_per_minute),
+                    ))
+                    .get_result(conn)?)
+            })
+            .await
+    }
+
+    /// keys that were not revoked
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_api_keys_by_user_id(&self, user_id: i64) -> Result<Vec<ApiKey>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(api_key::table
+                    .filter(api_key::user_id.eq(user_id))
+                    .filter(api_key::revoked_at.is_null())
+                    .order_by(api_key::created_at)
+                    .select(ApiKey::as_select())
+                    .load(conn)?)
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self, key_hash), err(Debug))]
+    pub async fn get_active_api_key_by_hash(
+        &self,
+        key_hash: &str,
+    ) -> Result<Option<ApiKey>, DatabaseError> {
+        let key_hash = key_hash.to_string();
+        self
+            .exec(move |conn| {
+                Ok(api_key::table
+                    .filter(api_key::key_hash.eq(key_hash))
+                    .filter(api_key::revoked_at.is_null())
+                    .select(ApiKey::as_select())
+                    .first(conn)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// returns false if the user has no such key
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn revoke_api_key(&self, user_id: i64, api_key_id: i64) -> Result<bool, DatabaseError> {
+        self
+            .exec(move |conn| {
+                let updated = update(api_key::table)
+                    .filter(api_key::id.eq(api_key_id))
+                    .filter(api_key::user_id.eq(user_id))
+                    .filter(api_key::revoked_at.is_null())
+                    .set(api_key::revoked_at.eq(chrono::Utc::now().naive_utc()))
+                    .execute(conn)?;
+                Ok(updated > 0)
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn update_api_key_last_used(&self, api_key_id: i64) -> Result<(), DatabaseError> {
+        self
+            .exec(move |conn| {
+                update(api_key::table)
+                    .filter(api_key::id.eq(api_key_id))
+                    .set(api_key::last_used_at.eq(chrono::Utc::now().naive_utc()))
+                    .execute(conn)?;
+                Ok(())
+            })
+            .await
+    }
+}
@@ -0,0 +1,144 @@
+use diesel::dsl::count;
+use diesel::insert_into;
+use diesel::prelude::*;
+use itertools::Itertools;
+
+use crate::database::model::PopularTag;
+use crate::database::{CreatorProfile, ModerationAction, StringVec};
+
+use super::DatabaseError;
+
+use super::Database;
+
+use super::super::schema::*;
+
+impl Database {
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_creator_profile(
+        &self,
+        user_id: i64,
+    ) -> Result<Option<CreatorProfile>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(creator_profile::table
+                    .filter(creator_profile::user_id.eq(user_id))
+                    .select(CreatorProfile::as_select())
+                    .first(conn)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// keeps the bio and links if the page was claimed before
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn claim_creator_profile(&self, user_id: i64) -> Result<(), DatabaseError> {
+        self
+            .exec(move |conn| {
+                let now = chrono::Utc::now().naive_utc();
+                insert_into(creator_profile::table)
+                    .values((
+                        creator_profile::user_id.eq(user_id),
+                        creator_profile::claimed_at.eq(now),
+                        creator_profile::updated_at.eq(now),
+                    ))
+                    .on_conflict(creator_profile::user_id)
+                    .do_update()
+                    .set(creator_profile::claimed_at.eq(now))
+                    .execute(conn)?;
+                Ok(())
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self, bio), err(Debug))]
+    pub async fn update_creator_profile(
+        &self,
+        user_id: i64,
+        bio: &str,
+        links: Vec<String>,
+    ) -> Result<(), DatabaseError> {
+        let bio = bio.to_string();
+        let links = StringVec::from(links);
+        self
+            .exec(move |conn| {
+                diesel::update(creator_profile::table)
+                    .filter(creator_profile::user_id.eq(user_id))
+                    .set((
+                        creator_profile::bio.eq(bio),
+                        creator_profile::links.eq(links),
+                        creator_profile::updated_at.eq(chrono::Utc::now().naive_utc()),
+                    ))
+                    .execute(conn)?;
+                Ok(())
+            })
+            .await
+    }
+
+    /// removes the bio and links, eg because they were abusive; the page stays claimed
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn reset_creator_profile(
+        &self,
+        user_id: i64,
+        moderator_id: i64,
+    ) -> Result<(), DatabaseError> {
+        self
+            .exec(move |conn| {
+                conn.immediate_transaction(|conn| {
+                    let profile: CreatorProfile = creator_profile::table
+                        .filter(creator_profile::user_id.eq(user_id))
+                        .select(CreatorProfile::as_select())
+                        .first(conn)?;
+                    diesel::update(creator_profile::table)
+                        .filter(creator_profile::user_id.eq(user_id))
+                        .set((
+                            creator_profile::bio.eq(""),
+                            creator_profile::links.eq(StringVec::from(Vec::<String>::new())),
+                            creator_profile::updated_at.eq(chrono::Utc::now().naive_utc()),
+                        ))
+                        .execute(conn)?;
+                    Self::insert_moderation_log_entry(
+                        Some(moderator_id),
+                        ModerationAction::ResetCreatorProfile,
+                        &user_id.to_string(),
+                        Some(format!("bio: {}; links: {}", profile.bio, profile.links.join(" "))),
+                        conn,
+                    )
+                })
+            })
+            .await
+    }
+
+    /// tags of the stickers in all sets of the creator, counted once per file
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_creator_tag_distribution(
+        &self,
+        user_id: i64,
+        limit: i64,
+    ) -> Result<Vec<PopularTag>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                let tags = sticker_file_tag::table
+                    .inner_join(
+                        sticker::table
+                            .on(sticker::sticker_file_id.eq(sticker_file_tag::sticker_file_id)),
+                    )
+                    .inner_join(sticker_set::table.on(sticker_set::id.eq(sticker::sticker_set_id)))
+                    .filter(sticker_set::created_by_user_id.eq(user_id))
+                    .filter(sticker_set::is_pending.eq(false))
+                    .group_by(sticker_file_tag::tag)
+                    .select((
+                        sticker_file_tag::tag,
+                        count(sticker_file_tag::sticker_file_id).aggregate_distinct(),
+                    ))
+                    .order(count(sticker_file_tag::sticker_file_id).aggregate_distinct().desc())
+                    .limit(limit)
+                    .load(conn)?;
+
+                Ok(tags
+                    .into_iter()
+                    .map(|(name, count)| PopularTag { name, count })
+                    .collect_vec())
+            })
+            .await
+    }
+}
@@ -1,10 +1,15 @@
+mod api_key;
+mod creator_profile;
 mod export;
+mod moderation_log;
 mod moderation_task;
 mod stats;
 mod sticker;
 mod sticker_set;
 mod sticker_tagging;
 mod tag;
+mod tag_review;
+mod tag_suggestion_rules;
 mod user;
 mod username;
 
@@ -0,0 +1,80 @@
+use diesel::insert_into;
+use diesel::prelude::*;
+
+use crate::database::{ModerationAction, ModerationLogEntry};
+
+use super::DatabaseError;
+
+use super::Database;
+
+use super::super::schema::*;
+
+impl Database {
+    /// `actor_user_id` is `None` for automatic actions
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn add_moderation_log_entry(
+        &self,
+        actor_user_id: Option<i64>,
+        action: ModerationAction,
+        target: &str,
+        details: Option<String>,
+    ) -> Result<(), DatabaseError> {
+        let target = target.to_string();
+        self
+            .exec(move |conn| {
+                Self::insert_moderation_log_entry(actor_user_id, action, &target, details, conn)
+            })
+            .await
+    }
+
+    /// for queries that log their change in the same transaction
+    pub(super) fn insert_moderation_log_entry(
+        actor_user_id: Option<i64>,
+        action: ModerationAction,
+        target: &str,
+        details: Option<String>,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), DatabaseError> {
+        insert_into(moderation_log::table)
+            .values((
+                moderation_log::actor_user_id.eq(actor_user_id),
+                moderation_log::action.eq(action),
+                moderation_log::target.eq(target),
+                moderation_log::details.eq(details),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// newest entries first; filters that are `None` match everything
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_moderation_log(
+        &self,
+        actor_user_id: Option<i64>,
+        action: Option<ModerationAction>,
+        target: Option<String>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ModerationLogEntry>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                let mut query = moderation_log::table
+                    .select(ModerationLogEntry::as_select())
+                    .order_by(moderation_log::id.desc())
+                    .limit(limit)
+                    .offset(offset)
+                    .into_boxed();
+                if let Some(actor_user_id) = actor_user_id {
+                    query = query.filter(moderation_log::actor_user_id.eq(actor_user_id));
+                }
+                if let Some(action) = action {
+                    query = query.filter(moderation_log::action.eq(action));
+                }
+                if let Some(target) = target {
+                    query = query.filter(moderation_log::target.eq(target));
+                }
+                Ok(query.load(conn)?)
+            })
+            .await
+    }
+}
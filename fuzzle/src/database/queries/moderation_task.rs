@@ -15,9 +15,11 @@ use teloxide::types::ChatId;
 use teloxide::types::UserId;
 
 use crate::database::model::PopularTag;
+use crate::database::AdditionalReport;
 use crate::database::ModerationTask;
 use crate::database::ModerationTaskDetails;
 use crate::database::ModerationTaskStatus;
+use crate::database::ReportInsertion;
 use crate::database::Tag;
 use crate::database::UserStats;
 use crate::tags::Category;
@@ -52,23 +54,199 @@ impl Database {
             .await
     }
 
+    /// appends the report to the pending task of the same target or creates a new task;
+    /// both happen in one transaction so that concurrent reports end up in the same task
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn insert_report(
+        &self,
+        details: &ModerationTaskDetails,
+        report: AdditionalReport,
+        cooldown_start: chrono::NaiveDateTime,
+    ) -> Result<ReportInsertion, DatabaseError> {
+        let details = details.clone();
+        self
+            .exec(move |conn| {
+                conn.immediate_transaction(|conn| {
+                    let target = details.report_target();
+                    let pending = moderation_task::table
+                        .select(ModerationTask::as_select())
+                        .filter(moderation_task::completion_status.eq(ModerationTaskStatus::Pending))
+                        .load(conn)?
+                        .into_iter()
+                        .find(|task| task.details.report_target() == target);
+                    if let Some(mut task) = pending {
+                        let already_reported = task.created_by_user_id == report.user_id
+                            || task
+                                .details
+                                .additional_reports()
+                                .iter()
+                                .any(|other| other.user_id == report.user_id);
+                        if already_reported {
+                            return Ok(ReportInsertion::AlreadyReported);
+                        }
+                        task.details.add_report(report);
+                        update(moderation_task::table)
+                            .filter(moderation_task::id.eq(task.id))
+                            .set(moderation_task::details.eq(task.details))
+                            .execute(conn)?;
+                        return Ok(ReportInsertion::AddedToPendingTask);
+                    }
+
+                    let recently_decided = moderation_task::table
+                        .select(ModerationTask::as_select())
+                        .filter(moderation_task::completion_status.ne(ModerationTaskStatus::Pending))
+                        .filter(moderation_task::decided_at.ge(cooldown_start))
+                        .load(conn)?
+                        .into_iter()
+                        .any(|task| task.details.report_target() == target);
+                    if recently_decided {
+                        return Ok(ReportInsertion::RecentlyDecided);
+                    }
+
+                    insert_into(moderation_task::table)
+                        .values((
+                            moderation_task::details.eq(details),
+                            moderation_task::created_by_user_id.eq(report.user_id),
+                            moderation_task::completion_status.eq(ModerationTaskStatus::Pending),
+                        ))
+                        .execute(conn)?;
+                    Ok(ReportInsertion::Created)
+                })
+            })
+            .await
+    }
+
     #[tracing::instrument(skip(self), err(Debug))]
     pub async fn change_moderation_task_status(
         &self,
         task_id: i64,
         status: ModerationTaskStatus,
+        moderator_id: i64,
+    ) -> Result<ModerationTask, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(update(moderation_task::table)
+                    .filter(moderation_task::id.eq(task_id))
+                    .set((
+                        moderation_task::completion_status.eq(status),
+                        moderation_task::decided_by_user_id.eq(moderator_id),
+                        moderation_task::decided_at.eq(diesel::dsl::now),
+                    ))
+                    .returning(ModerationTask::as_select())
+                    .get_result(conn)?)
+            })
+            .await
+    }
+
+    /// for decisions that do not change the status, eg banning the reported set
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn record_moderation_task_decision(
+        &self,
+        task_id: i64,
+        moderator_id: i64,
     ) -> Result<ModerationTask, DatabaseError> {
         self
             .exec(move |conn| {
                 Ok(update(moderation_task::table)
                     .filter(moderation_task::id.eq(task_id))
-                    .set(moderation_task::completion_status.eq(status))
+                    .set((
+                        moderation_task::decided_by_user_id.eq(moderator_id),
+                        moderation_task::decided_at.eq(diesel::dsl::now),
+                    ))
                     .returning(ModerationTask::as_select())
                     .get_result(conn)?)
             })
             .await
     }
 
+    /// assigns up to `limit` pending tasks to the moderator; tasks that were assigned to another
+    /// moderator before `expired_before` are taken over
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn claim_moderation_tasks(
+        &self,
+        moderator_id: i64,
+        limit: i64,
+        expired_before: chrono::NaiveDateTime,
+    ) -> Result<Vec<ModerationTask>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                let tasks = conn.immediate_transaction(|conn| {
+                    let task_ids: Vec<i64> = moderation_task::table
+                        .filter(moderation_task::completion_status.eq(ModerationTaskStatus::Pending))
+                        .filter(
+                            moderation_task::assigned_to_user_id
+                                .is_null()
+                                .or(moderation_task::assigned_to_user_id.eq(moderator_id))
+                                .or(moderation_task::assigned_at.lt(expired_before)),
+                        )
+                        .order_by(moderation_task::created_at)
+                        .limit(limit)
+                        .select(moderation_task::id)
+                        .load(conn)?;
+                    update(moderation_task::table)
+                        .filter(moderation_task::id.eq_any(task_ids))
+                        .set((
+                            moderation_task::assigned_to_user_id.eq(moderator_id),
+                            moderation_task::assigned_at.eq(diesel::dsl::now),
+                        ))
+                        .returning(ModerationTask::as_select())
+                        .get_results(conn)
+                })?;
+                Ok(tasks)
+            })
+            .await
+    }
+
+    /// returns `false` if another moderator is handling the task
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn claim_moderation_task(
+        &self,
+        task_id: i64,
+        moderator_id: i64,
+        expired_before: chrono::NaiveDateTime,
+    ) -> Result<bool, DatabaseError> {
+        self
+            .exec(move |conn| {
+                let updated_rows = update(moderation_task::table)
+                    .filter(moderation_task::id.eq(task_id))
+                    .filter(
+                        moderation_task::assigned_to_user_id
+                            .is_null()
+                            .or(moderation_task::assigned_to_user_id.eq(moderator_id))
+                            .or(moderation_task::assigned_at.lt(expired_before)),
+                    )
+                    .set((
+                        moderation_task::assigned_to_user_id.eq(moderator_id),
+                        moderation_task::assigned_at.eq(diesel::dsl::now),
+                    ))
+                    .execute(conn)?;
+                Ok(updated_rows == 1)
+            })
+            .await
+    }
+
+    /// pending tasks that no moderator is handling
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_unassigned_moderation_tasks(
+        &self,
+        expired_before: chrono::NaiveDateTime,
+    ) -> Result<Vec<ModerationTask>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(moderation_task::table
+                    .select(ModerationTask::as_select())
+                    .filter(moderation_task::completion_status.eq(ModerationTaskStatus::Pending))
+                    .filter(
+                        moderation_task::assigned_to_user_id
+                            .is_null()
+                            .or(moderation_task::assigned_at.lt(expired_before)),
+                    )
+                    .order_by(moderation_task::created_at)
+                    .load(conn)?)
+            })
+            .await
+    }
+
     #[tracing::instrument(skip(self), err(Debug))]
     pub async fn get_open_moderation_tasks(&self) -> Result<Vec<ModerationTask>, DatabaseError> {
         self
@@ -96,4 +274,64 @@ impl Database {
             })
             .await
     }
+
+    /// tasks that were completed or cancelled since the given time
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_moderation_tasks_decided_since(
+        &self,
+        since: chrono::NaiveDateTime,
+    ) -> Result<Vec<ModerationTask>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(moderation_task::table
+                    .select(ModerationTask::as_select())
+                    .filter(moderation_task::completion_status.ne(ModerationTaskStatus::Pending))
+                    .filter(moderation_task::decided_at.ge(since))
+                    .load(conn)?)
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_moderation_tasks_created_by_user_since(
+        &self,
+        user_id: i64,
+        since: chrono::NaiveDateTime,
+    ) -> Result<Vec<ModerationTask>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(moderation_task::table
+                    .select(ModerationTask::as_select())
+                    .filter(moderation_task::created_by_user_id.eq(user_id))
+                    .filter(moderation_task::created_at.ge(since))
+                    .load(conn)?)
+            })
+            .await
+    }
+
+    /// the completed task that created the tag; tags from e621 or tags that were added directly
+    /// have none
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_create_tag_task(
+        &self,
+        tag_id: &str,
+    ) -> Result<Option<ModerationTask>, DatabaseError> {
+        let tag_id = tag_id.to_string();
+        self
+            .exec(move |conn| {
+                Ok(moderation_task::table
+                    .select(ModerationTask::as_select())
+                    .filter(moderation_task::completion_status.eq(ModerationTaskStatus::Completed))
+                    .filter(
+                        sql::<diesel::sql_types::Bool>(
+                            "json_extract(details, '$.CreateTag.tag_id') = ",
+                        )
+                        .bind::<Text, _>(tag_id),
+                    )
+                    .order_by(moderation_task::decided_at.desc())
+                    .first(conn)
+                    .optional()?)
+            })
+            .await
+    }
 }
@@ -3,6 +3,7 @@ use diesel::dsl::count_star;
 use diesel::prelude::*;
 use diesel::sql_query;
 use diesel::sql_types::BigInt;
+use itertools::Itertools;
 use std::collections::HashMap;
 
 use crate::database::model::Stats;
@@ -11,6 +12,9 @@ use crate::database::AdminStats;
 use crate::database::AggregatedUserStats;
 use crate::database::FullUserStats;
 use crate::database::PersonalStats;
+use crate::database::SitemapStats;
+use crate::database::SuggestionAcceptanceStats;
+use crate::database::TagOrigin;
 use crate::database::UserStats;
 use crate::database::UserStickerStat;
 
@@ -30,7 +34,29 @@ impl Database {
                     .filter(sticker_user::user_id.eq(user_id))
                     .select(count_star())
                     .first(conn)?;
-                Ok(PersonalStats { favorites })
+                let public_collections: i64 = sticker_collection::table
+                    .filter(sticker_collection::user_id.eq(user_id))
+                    .filter(sticker_collection::is_public.eq(true))
+                    .select(count_star())
+                    .first(conn)?;
+                let collection_followers: i64 = sticker_collection_follower::table
+                    .inner_join(sticker_collection::table)
+                    .filter(sticker_collection::user_id.eq(user_id))
+                    .filter(sticker_collection::is_public.eq(true))
+                    .select(count_star())
+                    .first(conn)?;
+                let followed_collections: i64 = sticker_collection_follower::table
+                    .inner_join(sticker_collection::table)
+                    .filter(sticker_collection_follower::user_id.eq(user_id))
+                    .filter(sticker_collection::is_public.eq(true))
+                    .select(count_star())
+                    .first(conn)?;
+                Ok(PersonalStats {
+                    favorites,
+                    public_collections,
+                    collection_followers,
+                    followed_collections,
+                })
             })
             .await
     }
@@ -59,6 +85,27 @@ impl Database {
             .await
     }
 
+    /// number of public set, tag and emoji pages
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_sitemap_stats(&self) -> Result<SitemapStats, DatabaseError> {
+        self
+            .exec(move |conn| {
+                let sets: i64 = sticker_set::table
+                    .filter(sticker_set::is_pending.eq(false))
+                    .filter(sticker_set::last_fetched.is_not_null())
+                    .select(count_star())
+                    .first(conn)?;
+                let tags: i64 = sticker_file_tag::table
+                    .select(count(sticker_file_tag::tag).aggregate_distinct())
+                    .first(conn)?;
+                let emojis: i64 = sticker::table
+                    .select(count(sticker::emoji).aggregate_distinct())
+                    .first(conn)?;
+                Ok(SitemapStats { sets, tags, emojis })
+            })
+            .await
+    }
+
     #[tracing::instrument(skip(self), err(Debug))]
     pub async fn get_admin_stats(&self) -> Result<AdminStats, DatabaseError> {
         let now = chrono::Utc::now().naive_utc(); // TODO: pass time as parameter?
@@ -79,16 +126,64 @@ impl Database {
                     .select(count_star())
                     .filter(sticker_set::is_pending.eq(true))
                     .get_result(conn)?;
+                let suggestion_acceptance =
+                    Self::get_suggestion_acceptance_stats(now - chrono::Duration::hours(24), conn)?;
                 Ok(AdminStats {
                     least_recently_fetched_set_age: least_recently_fetched_set_time
                         .map(|time| now - time),
                     number_of_sets_fetched_in_24_hours,
                     pending_set_count,
+                    suggestion_acceptance,
                 })
             })
             .await
     }
 
+    /// only counts tags that are still present and were added by users
+    fn get_suggestion_acceptance_stats(
+        since: chrono::NaiveDateTime,
+        conn: &mut SqliteConnection,
+    ) -> QueryResult<SuggestionAcceptanceStats> {
+        let origin_counts: Vec<(Option<TagOrigin>, i64)> = sticker_file_tag::table
+            .filter(sticker_file_tag::created_at.ge(since))
+            .filter(sticker_file_tag::added_by_user_id.is_not_null())
+            .group_by(sticker_file_tag::origin)
+            .select((sticker_file_tag::origin, count_star()))
+            .load(conn)?;
+        let origin_count = |origin| {
+            origin_counts
+                .iter()
+                .filter(|(o, _)| *o == Some(origin))
+                .map(|(_, count)| count)
+                .sum()
+        };
+        let suggestions: Vec<(Option<String>, Option<i64>)> = sticker_file_tag::table
+            .filter(sticker_file_tag::created_at.ge(since))
+            .filter(sticker_file_tag::origin.eq(TagOrigin::Suggestion))
+            .select((
+                sticker_file_tag::suggestion_source,
+                sticker_file_tag::suggestion_rank,
+            ))
+            .load(conn)?;
+        let sources = suggestions
+            .into_iter()
+            .into_group_map_by(|(source, _)| source.clone().unwrap_or_else(|| "default".to_string()))
+            .into_iter()
+            .map(|(source, suggestions)| {
+                let accepted = suggestions.len() as i64;
+                let rank_sum: i64 = suggestions.iter().filter_map(|(_, rank)| *rank).sum();
+                (source, accepted, rank_sum as f64 / accepted as f64)
+            })
+            .sorted_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)))
+            .collect();
+        Ok(SuggestionAcceptanceStats {
+            manual: origin_count(TagOrigin::Manual),
+            suggested: origin_count(TagOrigin::Suggestion),
+            implied: origin_count(TagOrigin::Implication),
+            sources,
+        })
+    }
+
     #[tracing::instrument(skip(self), err(Debug))]
     pub async fn get_user_tagging_stats_24_hours(
         &self,
@@ -7,7 +7,7 @@ use tracing::warn;
 
 use crate::{
     database::{
-        BanReason, BannedSticker, MergeStatus, Order, Sticker, StickerFile, StickerIdStickerFileId, StickerSet, StickerType, StickerUser, min_max, query_builder::StickerTagQuery
+        BanReason, BannedSticker, MergeStatus, Order, Sticker, StickerFile, StickerIdStickerFileId, StickerSet, StickerType, StickerUser, TaggingCandidate, min_max, query_builder::{StickerTagQuery, generate_emoji_query}
     },
     util::{Emoji, StickerFileId, StickerId, StickerSetId},
 };
@@ -254,6 +254,24 @@ impl Database {
             .await
     }
 
+    /// returns (sticker file, tag) pairs, but only for the given tags
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_matching_sticker_file_tags(
+        &self,
+        sticker_file_ids: Vec<StickerFileId>,
+        tags: Vec<String>,
+    ) -> Result<Vec<(StickerFileId, String)>, DatabaseError> {
+        self
+            .exec(move |conn| {
+        Ok(sticker_file_tag::table
+            .filter(sticker_file_tag::sticker_file_id.eq_any(sticker_file_ids))
+            .filter(sticker_file_tag::tag.eq_any(tags))
+            .select((sticker_file_tag::sticker_file_id, sticker_file_tag::tag))
+            .load(conn)?)
+            })
+            .await
+    }
+
     #[tracing::instrument(skip(self), err(Debug))]
     pub async fn get_multiple_sticker_tags(
         &self,
@@ -281,14 +299,19 @@ impl Database {
         &self,
         tags: Vec<String>, // tags are anded (solo AND mammal)
         blacklist: Vec<String>,
+        whitelisted_sets: Vec<StickerSetId>, // blacklist does not apply to those sets
+        always_hidden: Vec<String>, // like the blacklist, but also applies to whitelisted sets (rating, negated query tags)
         emoji: Vec<String>, // emojis are ored (<smile emoji> OR <paw emoji>)
         limit: i64,
         offset: i64,
         order: Order,
     ) -> Result<Vec<Sticker>, DatabaseError> {
+        let whitelisted_sets = whitelisted_sets.into_iter().map(StickerSetId::into_inner).collect_vec();
         self
             .exec(move |conn| {
         let query = StickerTagQuery::new(tags, blacklist)
+            .whitelisted_sets(whitelisted_sets)
+            .always_hidden(always_hidden)
             .emoji(emoji)
             .limit(limit)
             .offset(offset)
@@ -325,18 +348,61 @@ impl Database {
             .await
     }
 
+    /// untagged stickers (one per file) of approved sets, popular ones are more likely to be returned
     #[tracing::instrument(skip(self), err(Debug))]
-    pub async fn get_random_sticker_to_tag(&self) -> Result<Option<Sticker>, DatabaseError> {
+    pub async fn get_tagging_candidates(
+        &self,
+        set_id: Option<StickerSetId>,
+        set_contains_tag: Option<String>,
+        limit: i64,
+    ) -> Result<Vec<TaggingCandidate>, DatabaseError> {
+        self
+            .exec(move |conn| {
+        Ok(sql_query("WITH sticker_usage AS (
+                            SELECT sticker_id, count(*) AS usage_count FROM sticker_user GROUP BY sticker_id
+                        ), set_usage AS (
+                            SELECT sticker.sticker_set_id, count(DISTINCT sticker_user.user_id) AS set_user_count
+                                FROM sticker_user INNER JOIN sticker ON sticker.id = sticker_user.sticker_id
+                                GROUP BY sticker.sticker_set_id
+                        )
+                        SELECT sticker.id AS sticker_id,
+                               coalesce(max(sticker_usage.usage_count), 0) AS usage_count,
+                               coalesce(max(set_usage.set_user_count), 0) AS set_user_count
+                            FROM sticker
+                            INNER JOIN sticker_set ON sticker_set.id = sticker.sticker_set_id
+                            LEFT JOIN sticker_usage ON sticker_usage.sticker_id = sticker.id
+                            LEFT JOIN set_usage ON set_usage.sticker_set_id = sticker.sticker_set_id
+                            WHERE sticker_set.is_pending = 0
+                                AND NOT EXISTS (SELECT * FROM sticker_file_tag WHERE sticker_file_tag.sticker_file_id = sticker.sticker_file_id)
+                                AND (?1 IS NULL OR sticker.sticker_set_id = ?1)
+                                AND (?2 IS NULL OR EXISTS (
+                                    SELECT * FROM sticker AS set_sticker
+                                        INNER JOIN sticker_file_tag ON sticker_file_tag.sticker_file_id = set_sticker.sticker_file_id
+                                        WHERE set_sticker.sticker_set_id = sticker.sticker_set_id AND sticker_file_tag.tag = ?2
+                                ))
+                            GROUP BY sticker.sticker_file_id
+                            ORDER BY (1 + usage_count + set_user_count) * (abs(random()) % 1000) DESC
+                            LIMIT ?3")
+                                .bind::<Nullable<Text>, _>(set_id.map(|set_id| set_id.to_string()))
+                                .bind::<Nullable<Text>, _>(set_contains_tag)
+                                .bind::<BigInt, _>(limit)
+            .load(conn)?)
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_random_tagged_stickers(&self, limit: i64) -> Result<Vec<Sticker>, DatabaseError> {
         self
             .exec(move |conn| {
         Ok(sticker::table
             .filter(
-                sticker::sticker_file_id.ne_all(sticker_file_tag::table.select((sticker_file_tag::sticker_file_id))),
+                sticker::sticker_file_id.eq_any(sticker_file_tag::table.select(sticker_file_tag::sticker_file_id)),
             )
             .select(Sticker::as_select())
             .order(random())
-            .first(conn)
-            .optional()?)
+            .limit(limit)
+            .load(conn)?)
             })
             .await
     }
@@ -726,17 +792,18 @@ impl Database {
     pub async fn get_stickers_by_emoji(
         &self,
         emoji: &str,
+        blacklist: Vec<String>,
+        whitelisted_sets: Vec<StickerSetId>,
+        always_hidden: Vec<String>,
         limit: i64,
         offset: i64,
     ) -> Result<Vec<Sticker>, DatabaseError> {
         let emoji = emoji.to_string();
+        let whitelisted_sets = whitelisted_sets.into_iter().map(StickerSetId::into_inner).collect_vec();
         self
             .exec(move |conn| {
         // TODO: does not support random sort
-        Ok(sql_query("SELECT * FROM sticker WHERE emoji = ?1 GROUP BY sticker.sticker_file_id LIMIT ?2 OFFSET ?3")
-                                .bind::<Text, _>(emoji)
-                                .bind::<BigInt, _>(limit)
-                                .bind::<BigInt, _>(offset)
+        Ok(generate_emoji_query(emoji, blacklist, whitelisted_sets, always_hidden, limit, offset)
             .load(conn)?)
             })
             .await
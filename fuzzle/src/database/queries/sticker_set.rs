@@ -314,6 +314,7 @@ impl Database {
             .await
     }
 
+    /// pending sets are left out until they are approved
     #[tracing::instrument(skip(self), err(Debug))]
     pub async fn get_owned_sticker_sets(
         &self,
@@ -325,6 +326,7 @@ impl Database {
             .exec(move |conn| {
                 Ok(sticker_set::table
                     .filter(sticker_set::created_by_user_id.eq(user_id))
+                    .filter(sticker_set::is_pending.eq(false))
                     .select(StickerSet::as_select())
                     .order_by(sticker_set::created_at.desc())
                     .limit(limit)
@@ -352,6 +354,46 @@ impl Database {
             .await
     }
 
+    /// sets that have a public page (fetched and not pending) with the time they were last fetched
+    /// none if the set is unknown or still pending
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_public_sticker_set_by_id(
+        &self,
+        set_id: &StickerSetId,
+    ) -> Result<Option<StickerSet>, DatabaseError> {
+        let set_id = set_id.to_string();
+        self
+            .exec(move |conn| {
+                Ok(sticker_set::table
+                    .filter(sticker_set::id.eq(set_id))
+                    .filter(sticker_set::is_pending.eq(false))
+                    .select(StickerSet::as_select())
+                    .first(conn)
+                    .optional()?)
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_public_sticker_set_ids(
+        &self,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<(StickerSetId, Option<NaiveDateTime>)>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(sticker_set::table
+                    .filter(sticker_set::is_pending.eq(false))
+                    .filter(sticker_set::last_fetched.is_not_null())
+                    .select((sticker_set::id, sticker_set::last_fetched))
+                    .order_by(sticker_set::id)
+                    .limit(limit)
+                    .offset(offset)
+                    .load(conn)?)
+            })
+            .await
+    }
+
     #[tracing::instrument(skip(self), err(Debug))]
     pub async fn get_pending_sticker_sets_by_creator(
         &self,
@@ -406,6 +448,7 @@ impl Database {
             .exec(move |conn| {
                 Ok(sticker_set::table
                     .filter(sticker_set::created_by_user_id.eq(user_id))
+                    .filter(sticker_set::is_pending.eq(false))
                     .select(count_star())
                     .first(conn)?)
             })
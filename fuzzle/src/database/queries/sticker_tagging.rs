@@ -12,7 +12,9 @@ use itertools::Itertools;
 use std::collections::HashMap;
 
 use crate::database::model::PopularTag;
+use crate::database::TagOrigin;
 use crate::database::UserStats;
+use crate::tags::SuggestionSource;
 use crate::util::Emoji;
 use crate::util::StickerFileId;
 use crate::util::StickerSetId;
@@ -31,27 +33,123 @@ impl Database {
         file_id: &StickerFileId,
         tag_names: &[String],
         user: Option<i64>,
+        origin: TagOrigin,
     ) -> Result<(), DatabaseError> {
         let file_id = file_id.to_string();
         let tag_names = tag_names.to_vec();
-        self
+        let inserted = self
             .exec(move |conn| {
-                conn.immediate_transaction(|conn| {
+                let inserted = conn.immediate_transaction(|conn| {
+                    let mut inserted = 0;
                     for tag in tag_names {
-                        let inserted = insert_into(sticker_file_tag::table)
+                        inserted += insert_into(sticker_file_tag::table)
                             .values((
                                 sticker_file_tag::sticker_file_id.eq(&file_id),
                                 sticker_file_tag::tag.eq(tag),
                                 sticker_file_tag::added_by_user_id.eq(user),
+                                sticker_file_tag::origin.eq(origin),
                             ))
                             .on_conflict_do_nothing()
                             .execute(conn)?;
                     }
-                    QueryResult::Ok(())
+                    QueryResult::Ok(inserted)
                 })?;
-                Ok(())
+                Ok(inserted)
             })
-            .await
+            .await?;
+        count_taggings(origin, None, inserted);
+        Ok(())
+    }
+
+    /// tags all files in one transaction; returns the number of new taggings
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn tag_files(
+        &self,
+        file_ids: &[StickerFileId],
+        tags: &[(String, TagOrigin)],
+        user: i64,
+    ) -> Result<usize, DatabaseError> {
+        let file_ids = file_ids.to_vec();
+        let origins = tags.iter().map(|(_, origin)| *origin).collect_vec();
+        let tags = tags.to_vec();
+        let inserted = self
+            .exec(move |conn| {
+                let inserted = conn.immediate_transaction(|conn| {
+                    // new taggings per tag, to count them by origin
+                    let mut inserted = vec![0; tags.len()];
+                    for file_id in &file_ids {
+                        for ((tag, origin), inserted) in tags.iter().zip(&mut inserted) {
+                            *inserted += insert_into(sticker_file_tag::table)
+                                .values((
+                                    sticker_file_tag::sticker_file_id.eq(file_id),
+                                    sticker_file_tag::tag.eq(tag),
+                                    sticker_file_tag::added_by_user_id.eq(user),
+                                    sticker_file_tag::origin.eq(*origin),
+                                ))
+                                .on_conflict_do_nothing()
+                                .execute(conn)?;
+                        }
+                    }
+                    QueryResult::Ok(inserted)
+                })?;
+                Ok(inserted)
+            })
+            .await?;
+        for (origin, inserted) in origins.into_iter().zip(&inserted) {
+            count_taggings(origin, None, *inserted);
+        }
+        Ok(inserted.iter().sum())
+    }
+
+    /// the suggested tag is stored together with where it was suggested; its implications are added as well
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn tag_file_from_suggestion(
+        &self,
+        file_id: &StickerFileId,
+        tag: &str,
+        implications: &[String],
+        user: i64,
+        suggestion_source: Option<SuggestionSource>,
+        suggestion_rank: i64,
+    ) -> Result<(), DatabaseError> {
+        let file_id = file_id.to_string();
+        let tag = tag.to_string();
+        let implications = implications.to_vec();
+        let (inserted, implied) = self
+            .exec(move |conn| {
+                let counts = conn.immediate_transaction(|conn| {
+                    let inserted = insert_into(sticker_file_tag::table)
+                        .values((
+                            sticker_file_tag::sticker_file_id.eq(&file_id),
+                            sticker_file_tag::tag.eq(&tag),
+                            sticker_file_tag::added_by_user_id.eq(user),
+                            sticker_file_tag::origin.eq(TagOrigin::Suggestion),
+                            sticker_file_tag::suggestion_source
+                                .eq(suggestion_source.map(SuggestionSource::name)),
+                            sticker_file_tag::suggestion_rank.eq(suggestion_rank),
+                        ))
+                        .on_conflict_do_nothing()
+                        .execute(conn)?;
+                    let mut implied = 0;
+                    for implication in implications.iter().filter(|implication| **implication != tag) {
+                        implied += insert_into(sticker_file_tag::table)
+                            .values((
+                                sticker_file_tag::sticker_file_id.eq(&file_id),
+                                sticker_file_tag::tag.eq(implication),
+                                sticker_file_tag::added_by_user_id.eq(user),
+                                sticker_file_tag::origin.eq(TagOrigin::Implication),
+                            ))
+                            .on_conflict_do_nothing()
+                            .execute(conn)?;
+                    }
+                    QueryResult::Ok((inserted, implied))
+                })?;
+                Ok(counts)
+            })
+            .await?;
+        count_taggings(TagOrigin::Suggestion, suggestion_source, inserted);
+        count_taggings(TagOrigin::Implication, None, implied);
+        Ok(())
     }
 
     #[tracing::instrument(skip(self), err(Debug))]
@@ -109,26 +207,29 @@ impl Database {
     ) -> Result<usize, DatabaseError> {
         let set_name = set_name.to_string();
         let tags = tags.to_vec();
-        self
+        let affected = self
             .exec(move |conn| {
                   let affected =   conn.immediate_transaction(|conn| {
             let mut tags_affected = 0;
         for tag in tags {
             // TODO: translate to proper diesel query?
-            tags_affected += sql_query("INSERT INTO sticker_file_tag (sticker_file_id, tag, added_by_user_id)
-                                           SELECT DISTINCT sticker_file_id, ?1, ?2 FROM sticker
+            tags_affected += sql_query("INSERT INTO sticker_file_tag (sticker_file_id, tag, added_by_user_id, origin)
+                                           SELECT DISTINCT sticker_file_id, ?1, ?2, ?4 FROM sticker
                                                 WHERE sticker.sticker_set_id = ?3 AND NOT EXISTS (SELECT * FROM sticker_file WHERE sticker.sticker_file_id = sticker_file.id AND sticker_file.tags_locked_by_user_id IS NOT NULL)
                                            ON CONFLICT (sticker_file_id, tag) DO NOTHING")
                                 .bind::<Text, _>(tag)
                                 .bind::<BigInt, _>(user)
                                 .bind::<Text, _>(&set_name)
+                                .bind::<BigInt, _>(TagOrigin::Manual)
                                 .execute(conn)?;
         }
             QueryResult::Ok(tags_affected)
         })?;
         Ok(affected)
             })
-            .await
+            .await?;
+        count_taggings(TagOrigin::Manual, None, affected);
+        Ok(affected)
     }
 
     /// except locked stickers
@@ -192,6 +293,31 @@ impl Database {
             .await
     }
 
+    /// files that have more than one tag of any of the groups
+    #[tracing::instrument(skip(self, tag_groups), err(Debug))]
+    pub async fn get_sticker_file_ids_with_multiple_tags_of_group(
+        &self,
+        tag_groups: Vec<Vec<String>>,
+        limit: i64,
+    ) -> Result<Vec<StickerFileId>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                let mut file_ids = Vec::new();
+                for group in tag_groups {
+                    let ids: Vec<StickerFileId> = sticker_file_tag::table
+                        .filter(sticker_file_tag::tag.eq_any(group))
+                        .group_by(sticker_file_tag::sticker_file_id)
+                        .having(count_star().gt(1))
+                        .select(sticker_file_tag::sticker_file_id)
+                        .limit(limit)
+                        .load(conn)?;
+                    file_ids.extend(ids);
+                }
+                Ok(file_ids.into_iter().unique().collect_vec())
+            })
+            .await
+    }
+
     fn delete_sticker_file_tag(
         sticker_file_id: &StickerFileId,
         tag: &str,
@@ -230,12 +356,18 @@ impl Database {
             .await
     }
 
+    /// tags of the sets that contain the file; the excluded set and file are not counted, which
+    /// is used to hide a sticker's own tags during the suggestion evaluation
     #[tracing::instrument(skip(self), err(Debug))]
     pub async fn get_all_sticker_set_tag_counts_by_sticker_file_id(
         &self,
         sticker_file_id: &StickerFileId,
+        excluded_set_id: Option<&StickerSetId>,
+        excluded_file_id: Option<&StickerFileId>,
     ) -> Result<Vec<(String, i64)>, DatabaseError> {
         let sticker_file_id = sticker_file_id.to_string();
+        let excluded_set_ids = excluded_set_id.map(ToString::to_string).into_iter().collect_vec();
+        let excluded_file_ids = excluded_file_id.map(ToString::to_string).into_iter().collect_vec();
         self
             .exec(move |conn| {
                 let (sticker1, sticker2) = diesel::alias!(sticker as sticker1, sticker as sticker2);
@@ -257,6 +389,8 @@ impl Database {
                                             .select(sticker2.field(sticker::sticker_set_id)),
                                     ),
                                 )
+                                .filter(sticker1.field(sticker::sticker_set_id).ne_all(excluded_set_ids))
+                                .filter(sticker1.field(sticker::sticker_file_id).ne_all(excluded_file_ids))
                                 .select((sticker1.field(sticker::sticker_file_id))),
                         ),
                     )
@@ -266,11 +400,17 @@ impl Database {
             .await
     }
 
+    /// tags of all sets of the owner, see `get_all_sticker_set_tag_counts_by_sticker_file_id` for
+    /// the exclusions
     #[tracing::instrument(skip(self), err(Debug))]
     pub async fn get_all_sticker_set_tag_counts_by_owner_id(
         &self,
         owner_id: i64,
+        excluded_set_id: Option<&StickerSetId>,
+        excluded_file_id: Option<&StickerFileId>,
     ) -> Result<Vec<(String, i64)>, DatabaseError> {
+        let excluded_set_ids = excluded_set_id.map(ToString::to_string).into_iter().collect_vec();
+        let excluded_file_ids = excluded_file_id.map(ToString::to_string).into_iter().collect_vec();
         self
             .exec(move |conn| {
                 Ok(sticker_file_tag::table
@@ -286,6 +426,8 @@ impl Database {
                                             .select(sticker_set::id),
                                     ),
                                 )
+                                .filter(sticker::sticker_set_id.ne_all(excluded_set_ids))
+                                .filter(sticker::sticker_file_id.ne_all(excluded_file_ids))
                                 .select((sticker::sticker_file_id)),
                         ),
                     )
@@ -358,3 +500,15 @@ impl Database {
             .await
     }
 }
+
+fn count_taggings(origin: TagOrigin, suggestion_source: Option<SuggestionSource>, inserted: usize) {
+    if inserted == 0 {
+        return;
+    }
+    metrics::counter!(
+        "fuzzle_taggings_total",
+        "origin" => origin.name(),
+        "suggestion_source" => suggestion_source.map_or("none", SuggestionSource::name)
+    )
+    .increment(inserted as u64);
+}
@@ -14,8 +14,11 @@ use teloxide::types::ChatId;
 use teloxide::types::UserId;
 
 use crate::database::model::PopularTag;
+use crate::database::ModerationAction;
 use crate::database::StringVec;
 use crate::database::Tag;
+use crate::database::TagDescription;
+use crate::database::TagUsage;
 use crate::database::UserStats;
 use crate::tags::Category;
 use crate::util::Emoji;
@@ -118,4 +121,83 @@ impl Database {
             })
             .await
     }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_tag_description(
+        &self,
+        tag_id: &str,
+    ) -> Result<Option<TagDescription>, DatabaseError> {
+        let tag_id = tag_id.to_string();
+        self
+            .exec(move |conn| {
+                Ok(tag_description::table
+                    .filter(tag_description::tag_id.eq(tag_id))
+                    .select(TagDescription::as_select())
+                    .first(conn)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// an empty description removes it; every change is logged to the moderation log
+    #[tracing::instrument(skip(self, description), err(Debug))]
+    pub async fn set_tag_description(
+        &self,
+        tag_id: &str,
+        description: &str,
+        user_id: i64,
+    ) -> Result<(), DatabaseError> {
+        let tag_id = tag_id.to_string();
+        let description = description.to_string();
+        self
+            .exec(move |conn| {
+                conn.immediate_transaction(|conn| {
+                    if description.is_empty() {
+                        delete(tag_description::table.filter(tag_description::tag_id.eq(&tag_id)))
+                            .execute(conn)?;
+                    } else {
+                        let now = chrono::Utc::now().naive_utc();
+                        insert_into(tag_description::table)
+                            .values((
+                                tag_description::tag_id.eq(&tag_id),
+                                tag_description::description.eq(&description),
+                                tag_description::updated_by_user_id.eq(user_id),
+                                tag_description::updated_at.eq(now),
+                            ))
+                            .on_conflict(tag_description::tag_id)
+                            .do_update()
+                            .set((
+                                tag_description::description.eq(&description),
+                                tag_description::updated_by_user_id.eq(user_id),
+                                tag_description::updated_at.eq(now),
+                            ))
+                            .execute(conn)?;
+                    }
+                    Self::insert_moderation_log_entry(
+                        Some(user_id),
+                        ModerationAction::EditTagDescription,
+                        &tag_id,
+                        Some(description).filter(|description| !description.is_empty()),
+                        conn,
+                    )
+                })
+            })
+            .await
+    }
+
+    /// how many stickers were tagged with the tag per month, oldest month first
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_tag_usage_by_month(&self, tag_id: &str) -> Result<Vec<TagUsage>, DatabaseError> {
+        let tag_id = tag_id.to_string();
+        self
+            .exec(move |conn| {
+                Ok(sql_query(
+                    "SELECT strftime('%Y-%m', created_at) AS month, count(*) AS count \
+                     FROM sticker_file_tag WHERE tag = ?1 GROUP BY month ORDER BY month",
+                )
+                .bind::<Text, _>(tag_id)
+                .load(conn)?)
+            })
+            .await
+    }
 }
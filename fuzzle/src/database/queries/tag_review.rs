@@ -0,0 +1,124 @@
+use diesel::prelude::*;
+use diesel::update;
+use itertools::Itertools;
+
+use crate::database::{StickerFileTag, TrustLevel};
+use crate::util::StickerFileId;
+
+use super::DatabaseError;
+
+use super::Database;
+
+use super::super::schema::*;
+
+impl Database {
+    /// a file with unconfirmed tags that were added by users with the trust level
+    ///
+    /// Files are ordered by the oldest of these tags, confirmed or not, so that the position of a
+    /// file does not change while it is reviewed. The result is the first file after `after`, or
+    /// the first file at all if `after` is not in the list (anymore).
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_next_sticker_file_to_review(
+        &self,
+        author_trust_level: TrustLevel,
+        reviewer_id: i64,
+        since: chrono::NaiveDateTime,
+        after: Option<StickerFileId>,
+    ) -> Result<Option<StickerFileId>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                let tags: Vec<(StickerFileId, chrono::NaiveDateTime, Option<i64>)> =
+                    sticker_file_tag::table
+                        .filter(
+                            sticker_file_tag::added_by_user_id.eq_any(
+                                user::table
+                                    .filter(user::trust_level.eq(author_trust_level))
+                                    .select(user::id.nullable()),
+                            ),
+                        )
+                        .filter(sticker_file_tag::added_by_user_id.ne(reviewer_id))
+                        .filter(sticker_file_tag::created_at.ge(since))
+                        .select((
+                            sticker_file_tag::sticker_file_id,
+                            sticker_file_tag::created_at,
+                            sticker_file_tag::confirmed_by_user_id,
+                        ))
+                        .load(conn)?;
+                let files = tags
+                    .into_iter()
+                    .into_group_map_by(|(sticker_file_id, _, _)| sticker_file_id.clone())
+                    .into_iter()
+                    .map(|(sticker_file_id, tags)| {
+                        let oldest = tags.iter().map(|(_, created_at, _)| *created_at).min();
+                        let pending = tags.iter().any(|(_, _, confirmed_by)| confirmed_by.is_none());
+                        ((oldest, sticker_file_id), pending)
+                    })
+                    .collect_vec();
+                let after = after.and_then(|after| {
+                    files
+                        .iter()
+                        .map(|(position, _)| position)
+                        .find(|(_, sticker_file_id)| *sticker_file_id == after)
+                        .cloned()
+                });
+                Ok(files
+                    .into_iter()
+                    .filter(|(_, pending)| *pending)
+                    .map(|(position, _)| position)
+                    .filter(|position| after.as_ref().is_none_or(|after| position > after))
+                    .min()
+                    .map(|(_, sticker_file_id)| sticker_file_id))
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_unconfirmed_sticker_file_tags(
+        &self,
+        sticker_file_id: &StickerFileId,
+        author_trust_level: TrustLevel,
+        reviewer_id: i64,
+    ) -> Result<Vec<StickerFileTag>, DatabaseError> {
+        let sticker_file_id = sticker_file_id.clone();
+        self
+            .exec(move |conn| {
+                Ok(sticker_file_tag::table
+                    .filter(sticker_file_tag::sticker_file_id.eq(sticker_file_id))
+                    .filter(sticker_file_tag::confirmed_by_user_id.is_null())
+                    .filter(
+                        sticker_file_tag::added_by_user_id.eq_any(
+                            user::table
+                                .filter(user::trust_level.eq(author_trust_level))
+                                .select(user::id.nullable()),
+                        ),
+                    )
+                    .filter(sticker_file_tag::added_by_user_id.ne(reviewer_id))
+                    .order_by(sticker_file_tag::tag)
+                    .select(StickerFileTag::as_select())
+                    .load(conn)?)
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn confirm_sticker_file_tag(
+        &self,
+        sticker_file_id: &StickerFileId,
+        tag: &str,
+        reviewer_id: i64,
+    ) -> Result<(), DatabaseError> {
+        let sticker_file_id = sticker_file_id.clone();
+        let tag = tag.to_string();
+        self
+            .exec(move |conn| {
+                update(sticker_file_tag::table)
+                    .filter(sticker_file_tag::sticker_file_id.eq(sticker_file_id))
+                    .filter(sticker_file_tag::tag.eq(tag))
+                    .filter(sticker_file_tag::added_by_user_id.ne(reviewer_id))
+                    .set(sticker_file_tag::confirmed_by_user_id.eq(reviewer_id))
+                    .execute(conn)?;
+                Ok(())
+            })
+            .await
+    }
+}
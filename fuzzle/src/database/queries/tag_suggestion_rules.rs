@@ -0,0 +1,93 @@
+use diesel::delete;
+use diesel::insert_into;
+use diesel::prelude::*;
+
+use crate::database::TagSuggestionRulesRevision;
+
+use super::DatabaseError;
+
+use super::Database;
+
+use super::super::schema::*;
+
+impl Database {
+    /// the latest revision is the active one
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_latest_tag_suggestion_rules(
+        &self,
+    ) -> Result<Option<TagSuggestionRulesRevision>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(tag_suggestion_rules::table
+                    .order_by(tag_suggestion_rules::id.desc())
+                    .select(TagSuggestionRulesRevision::as_select())
+                    .first(conn)
+                    .optional()?)
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self, rules), err(Debug))]
+    pub async fn insert_tag_suggestion_rules(
+        &self,
+        rules: String,
+        created_by_user_id: Option<i64>,
+    ) -> Result<i64, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(insert_into(tag_suggestion_rules::table)
+                    .values((
+                        tag_suggestion_rules::rules.eq(rules),
+                        tag_suggestion_rules::created_by_user_id.eq(created_by_user_id),
+                    ))
+                    .returning(tag_suggestion_rules::id)
+                    .get_result(conn)?)
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_tag_suggestion_source_weights(
+        &self,
+    ) -> Result<Vec<(String, f64)>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(tag_suggestion_source_weight::table
+                    .select((
+                        tag_suggestion_source_weight::source,
+                        tag_suggestion_source_weight::weight,
+                    ))
+                    .load(conn)?)
+            })
+            .await
+    }
+
+    /// replaces all weights from a previous training run
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn replace_tag_suggestion_source_weights(
+        &self,
+        weights: Vec<(String, f64)>,
+    ) -> Result<(), DatabaseError> {
+        self
+            .exec(move |conn| {
+                conn.immediate_transaction(|conn| {
+                    delete(tag_suggestion_source_weight::table).execute(conn)?;
+                    insert_into(tag_suggestion_source_weight::table)
+                        .values(
+                            weights
+                                .into_iter()
+                                .map(|(source, weight)| {
+                                    (
+                                        tag_suggestion_source_weight::source.eq(source),
+                                        tag_suggestion_source_weight::weight.eq(weight),
+                                    )
+                                })
+                                .collect::<Vec<_>>(),
+                        )
+                        .execute(conn)?;
+                    Ok(())
+                })
+            })
+            .await
+    }
+}
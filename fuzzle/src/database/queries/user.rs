@@ -1,7 +1,11 @@
 use diesel::dsl::now;
-use diesel::{delete, insert_into, prelude::*, update};
+use diesel::sql_types::BigInt;
+use diesel::{delete, insert_into, prelude::*, sql_query, update};
 
-use crate::database::{DialogState, StringVec, User, UserSettings, UserStats};
+use crate::database::{
+    CapabilityGrants, DialogState, ModerationAction, Sticker, StickerCollection, StringVec, TaggingHistory,
+    TrustLevel, User, UserSettings, UserStats,
+};
 use crate::util::StickerId;
 
 use super::DatabaseError;
@@ -10,6 +14,11 @@ use super::Database;
 
 use super::super::schema::*;
 
+define_sql_function! {
+    /// Represents the `lower` function; like `COLLATE NOCASE`, it only folds ascii letters
+    fn lower(expr: diesel::sql_types::Text) -> diesel::sql_types::Text;
+}
+
 impl Database {
     #[tracing::instrument(skip(self), err(Debug))]
     pub async fn update_dialog_state(
@@ -95,6 +104,141 @@ impl Database {
             .await
     }
 
+    /// `updated_by_user_id` is `None` for automatic changes
+    #[tracing::instrument(skip(self), err(Debug))]
+    /// also logs the change; `updated_by_user_id` is `None` for automatic promotions
+    pub async fn update_trust_level(
+        &self,
+        user_id: i64,
+        trust_level: TrustLevel,
+        updated_by_user_id: Option<i64>,
+    ) -> Result<(), DatabaseError> {
+        self
+            .exec(move |conn| {
+                conn.immediate_transaction(|conn| {
+                    let updated_rows = update(user::table.find(user_id))
+                        .set((
+                            user::trust_level.eq(trust_level),
+                            user::trust_level_updated_by_user_id.eq(updated_by_user_id),
+                        ))
+                        .execute(conn)?;
+                    #[cfg(debug_assertions)]
+                    assert_eq!(updated_rows, 1);
+                    Self::insert_moderation_log_entry(
+                        updated_by_user_id,
+                        ModerationAction::ChangeTrustLevel,
+                        &user_id.to_string(),
+                        Some(trust_level.name().to_string()),
+                        conn,
+                    )
+                })
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    /// also logs the change with the given details
+    pub async fn update_capability_grants(
+        &self,
+        user_id: i64,
+        capability_grants: Option<CapabilityGrants>,
+        updated_by_user_id: i64,
+        details: String,
+    ) -> Result<(), DatabaseError> {
+        self
+            .exec(move |conn| {
+                conn.immediate_transaction(|conn| {
+                    let updated_rows = update(user::table.find(user_id))
+                        .set(user::capability_grants.eq(capability_grants))
+                        .execute(conn)?;
+                    #[cfg(debug_assertions)]
+                    assert_eq!(updated_rows, 1);
+                    Self::insert_moderation_log_entry(
+                        Some(updated_by_user_id),
+                        ModerationAction::ChangeCapabilityGrant,
+                        &user_id.to_string(),
+                        Some(details),
+                        conn,
+                    )
+                })
+            })
+            .await
+    }
+
+    /// users with a trust level or grants that differ from the defaults are listed first
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_users_by_trust_level(
+        &self,
+        trust_level: TrustLevel,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<User>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(user::table
+                    .filter(user::trust_level.eq(trust_level))
+                    .order_by((
+                        user::trust_level_updated_by_user_id.is_null(),
+                        user::capability_grants.is_null(),
+                        user::created_at.desc(),
+                    ))
+                    .limit(limit)
+                    .offset(offset)
+                    .select(User::as_select())
+                    .load(conn)?)
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_user_ids_by_trust_level(
+        &self,
+        trust_level: TrustLevel,
+    ) -> Result<Vec<i64>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(user::table
+                    .filter(user::trust_level.eq(trust_level))
+                    .order_by(user::id)
+                    .select(user::id)
+                    .load(conn)?)
+            })
+            .await
+    }
+
+    /// tagging history of the users with the trust level whose level was never changed manually
+    ///
+    /// `untag_file` stores the user that added the tag in `removed_by_user_id` and the user that
+    /// removed it in `added_by_user_id`, so rejected tags are those that someone else removed.
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_tagging_histories(
+        &self,
+        trust_level: TrustLevel,
+    ) -> Result<Vec<TaggingHistory>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(sql_query("WITH accepted AS (
+                            SELECT added_by_user_id AS user_id, count(*) AS count FROM sticker_file_tag
+                                WHERE added_by_user_id IS NOT NULL
+                                GROUP BY added_by_user_id
+                        ), rejected AS (
+                            SELECT removed_by_user_id AS user_id, count(*) AS count FROM sticker_file_tag_history
+                                WHERE removed_by_user_id IS NOT NULL AND added_by_user_id IS NOT removed_by_user_id
+                                GROUP BY removed_by_user_id
+                        )
+                        SELECT user.id AS user_id,
+                               accepted.count AS accepted_count,
+                               coalesce(rejected.count, 0) AS rejected_count
+                            FROM user
+                            INNER JOIN accepted ON accepted.user_id = user.id
+                            LEFT JOIN rejected ON rejected.user_id = user.id
+                            WHERE user.trust_level = ?1 AND user.trust_level_updated_by_user_id IS NULL")
+                    .bind::<BigInt, _>(trust_level)
+                    .load(conn)?)
+            })
+            .await
+    }
+
     #[tracing::instrument(skip(self), err(Debug))]
     pub async fn set_recently_used_sticker_favorite(
         &self,
@@ -160,4 +304,342 @@ impl Database {
             })
             .await
     }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_sticker_collections(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<StickerCollection>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(sticker_collection::table
+                    .filter(sticker_collection::user_id.eq(user_id))
+                    .select(StickerCollection::as_select())
+                    .order_by(sticker_collection::name.asc())
+                    .load(conn)?)
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_sticker_collection_by_id(
+        &self,
+        user_id: i64,
+        collection_id: i64,
+    ) -> Result<Option<StickerCollection>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(sticker_collection::table
+                    .filter(sticker_collection::id.eq(collection_id))
+                    .filter(sticker_collection::user_id.eq(user_id))
+                    .select(StickerCollection::as_select())
+                    .first(conn)
+                    .optional()?)
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_sticker_collection_by_name(
+        &self,
+        user_id: i64,
+        name: &str,
+    ) -> Result<Option<StickerCollection>, DatabaseError> {
+        let name = name.to_string();
+        self
+            .exec(move |conn| {
+                Ok(sticker_collection::table
+                    .filter(sticker_collection::user_id.eq(user_id))
+                    .filter(lower(sticker_collection::name).eq(lower(name)))
+                    .select(StickerCollection::as_select())
+                    .first(conn)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// returns the ids of the user's collections that contain the sticker
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_sticker_collection_ids_containing_sticker(
+        &self,
+        user_id: i64,
+        sticker_id: &StickerId,
+    ) -> Result<Vec<i64>, DatabaseError> {
+        let sticker_id = sticker_id.to_string();
+        self
+            .exec(move |conn| {
+                Ok(sticker_collection_sticker::table
+                    .inner_join(sticker_collection::table)
+                    .filter(sticker_collection::user_id.eq(user_id))
+                    .filter(sticker_collection_sticker::sticker_id.eq(sticker_id))
+                    .select(sticker_collection::id)
+                    .load(conn)?)
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn create_sticker_collection(
+        &self,
+        user_id: i64,
+        name: &str,
+    ) -> Result<StickerCollection, DatabaseError> {
+        let name = name.to_string();
+        self
+            .exec(move |conn| {
+                Ok(insert_into(sticker_collection::table)
+                    .values((
+                        sticker_collection::user_id.eq(user_id),
+                        sticker_collection::name.eq(name),
+                    ))
+                    .get_result(conn)?)
+            })
+            .await
+    }
+
+    /// names are unique per user, ignoring case
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn rename_sticker_collection(
+        &self,
+        user_id: i64,
+        collection_id: i64,
+        name: &str,
+    ) -> Result<(), DatabaseError> {
+        let name = name.to_string();
+        self
+            .exec(move |conn| {
+                update(sticker_collection::table)
+                    .filter(sticker_collection::id.eq(collection_id))
+                    .filter(sticker_collection::user_id.eq(user_id))
+                    .set(sticker_collection::name.eq(name))
+                    .execute(conn)?;
+                Ok(())
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn delete_sticker_collection(
+        &self,
+        user_id: i64,
+        collection_id: i64,
+    ) -> Result<(), DatabaseError> {
+        self
+            .exec(move |conn| {
+                delete(
+                    sticker_collection::table
+                        .filter(sticker_collection::id.eq(collection_id))
+                        .filter(sticker_collection::user_id.eq(user_id)),
+                )
+                .execute(conn)?;
+                Ok(())
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn add_sticker_to_collection(
+        &self,
+        collection_id: i64,
+        sticker_id: &StickerId,
+    ) -> Result<(), DatabaseError> {
+        let sticker_id = sticker_id.to_string();
+        self
+            .exec(move |conn| {
+                insert_into(sticker_collection_sticker::table)
+                    .values((
+                        sticker_collection_sticker::collection_id.eq(collection_id),
+                        sticker_collection_sticker::sticker_id.eq(sticker_id),
+                    ))
+                    .on_conflict_do_nothing()
+                    .execute(conn)?;
+                Ok(())
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn remove_sticker_from_collection(
+        &self,
+        collection_id: i64,
+        sticker_id: &StickerId,
+    ) -> Result<(), DatabaseError> {
+        let sticker_id = sticker_id.to_string();
+        self
+            .exec(move |conn| {
+                delete(
+                    sticker_collection_sticker::table
+                        .filter(sticker_collection_sticker::collection_id.eq(collection_id))
+                        .filter(sticker_collection_sticker::sticker_id.eq(sticker_id)),
+                )
+                .execute(conn)?;
+                Ok(())
+            })
+            .await
+    }
+
+    /// most recently added first
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_collection_stickers(
+        &self,
+        collection_id: i64,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Sticker>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(sticker::table
+                    .inner_join(sticker_collection_sticker::table)
+                    .filter(sticker_collection_sticker::collection_id.eq(collection_id))
+                    .select(Sticker::as_select())
+                    .order_by(sticker_collection_sticker::added_at.desc())
+                    .limit(limit)
+                    .offset(offset)
+                    .load(conn)?)
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn set_sticker_collection_public(
+        &self,
+        user_id: i64,
+        collection_id: i64,
+        is_public: bool,
+    ) -> Result<(), DatabaseError> {
+        self
+            .exec(move |conn| {
+                update(sticker_collection::table)
+                    .filter(sticker_collection::id.eq(collection_id))
+                    .filter(sticker_collection::user_id.eq(user_id))
+                    .set(sticker_collection::is_public.eq(is_public))
+                    .execute(conn)?;
+                Ok(())
+            })
+            .await
+    }
+
+    /// returns the collection regardless of its owner, but only if it is public
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_public_sticker_collection(
+        &self,
+        collection_id: i64,
+    ) -> Result<Option<StickerCollection>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(sticker_collection::table
+                    .filter(sticker_collection::id.eq(collection_id))
+                    .filter(sticker_collection::is_public.eq(true))
+                    .select(StickerCollection::as_select())
+                    .first(conn)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// public collections the user follows; collections that were made private again are omitted
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_followed_sticker_collections(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<StickerCollection>, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(sticker_collection::table
+                    .inner_join(sticker_collection_follower::table)
+                    .filter(sticker_collection_follower::user_id.eq(user_id))
+                    .filter(sticker_collection::is_public.eq(true))
+                    .select(StickerCollection::as_select())
+                    .order_by(sticker_collection::name.asc())
+                    .load(conn)?)
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn is_following_sticker_collection(
+        &self,
+        user_id: i64,
+        collection_id: i64,
+    ) -> Result<bool, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(diesel::select(diesel::dsl::exists(
+                    sticker_collection_follower::table
+                        .filter(sticker_collection_follower::user_id.eq(user_id))
+                        .filter(sticker_collection_follower::collection_id.eq(collection_id)),
+                ))
+                .get_result(conn)?)
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn follow_sticker_collection(
+        &self,
+        user_id: i64,
+        collection_id: i64,
+    ) -> Result<(), DatabaseError> {
+        self
+            .exec(move |conn| {
+                insert_into(sticker_collection_follower::table)
+                    .values((
+                        sticker_collection_follower::collection_id.eq(collection_id),
+                        sticker_collection_follower::user_id.eq(user_id),
+                    ))
+                    .on_conflict_do_nothing()
+                    .execute(conn)?;
+                Ok(())
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn unfollow_sticker_collection(
+        &self,
+        user_id: i64,
+        collection_id: i64,
+    ) -> Result<(), DatabaseError> {
+        self
+            .exec(move |conn| {
+                delete(
+                    sticker_collection_follower::table
+                        .filter(sticker_collection_follower::collection_id.eq(collection_id))
+                        .filter(sticker_collection_follower::user_id.eq(user_id)),
+                )
+                .execute(conn)?;
+                Ok(())
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_sticker_collection_follower_count(
+        &self,
+        collection_id: i64,
+    ) -> Result<i64, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(sticker_collection_follower::table
+                    .filter(sticker_collection_follower::collection_id.eq(collection_id))
+                    .select(diesel::dsl::count_star())
+                    .first(conn)?)
+            })
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn get_collection_sticker_count(
+        &self,
+        collection_id: i64,
+    ) -> Result<i64, DatabaseError> {
+        self
+            .exec(move |conn| {
+                Ok(sticker_collection_sticker::table
+                    .filter(sticker_collection_sticker::collection_id.eq(collection_id))
+                    .select(diesel::dsl::count_star())
+                    .first(conn)?)
+            })
+            .await
+    }
 }
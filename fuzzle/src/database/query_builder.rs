@@ -19,6 +19,8 @@ pub(super) struct StickerTagQuery {
     offset: Option<i64>,
     order: Option<Order>,
     emoji: Vec<String>,
+    whitelisted_sets: Vec<String>,
+    always_hidden: Vec<String>,
     sets: bool,
 }
 
@@ -32,6 +34,8 @@ impl StickerTagQuery {
             offset: None,
             order: None,
             emoji: vec![],
+            whitelisted_sets: vec![],
+            always_hidden: vec![],
             sets: false,
         }
     }
@@ -66,6 +70,21 @@ impl StickerTagQuery {
         self
     }
 
+    /// stickers in these sets are not affected by `must_not`
+    #[must_use]
+    pub(super) fn whitelisted_sets(mut self, whitelisted_sets: Vec<String>) -> Self {
+        self.whitelisted_sets = whitelisted_sets;
+        self
+    }
+
+    /// like `must_not`, but also applies to whitelisted sets (eg for rating profiles or tags
+    /// excluded in the query itself)
+    #[must_use]
+    pub(super) fn always_hidden(mut self, always_hidden: Vec<String>) -> Self {
+        self.always_hidden = always_hidden;
+        self
+    }
+
     #[must_use]
     pub(super) fn generate(&self) -> BoxedSqlQuery<Sqlite, SqlQuery> {
         // TODO: test if exists or in is faster:
@@ -97,9 +116,16 @@ impl StickerTagQuery {
             .sql(main_select)
             .sql("WHERE sticker.sticker_file_id IN (SELECT sticker_file_id FROM sticker_file_tag GROUP BY sticker_file_id ");
 
+        // the set is not known inside the subquery, so whitelisted sets need a separate condition
+        let must_not = if self.whitelisted_sets.is_empty() {
+            self.must_not.iter().chain(&self.always_hidden).cloned().unique().collect_vec()
+        } else {
+            self.always_hidden.clone()
+        };
+
         // https://stackoverflow.com/a/69911488
         // TODO: benchmark if HAVING is faster than the old IN or EXISTS variants
-        if self.must.len() > 0 || self.must_not.len() > 0 {
+        if self.must.len() > 0 || must_not.len() > 0 {
             q = q.sql("HAVING ");
         }
 
@@ -111,18 +137,22 @@ impl StickerTagQuery {
                 .bind::<Integer, _>(self.must.len() as i32);
         }
 
-        if self.must.len() > 0 && self.must_not.len() > 0 {
+        if self.must.len() > 0 && must_not.len() > 0 {
             q = q.sql(" AND ")
         }
 
-        if self.must_not.len() > 0 {
+        if must_not.len() > 0 {
             q = q.sql("count(CASE WHEN tag IN ");
-            q = generate_sql_list(q, self.must_not.clone());
+            q = generate_sql_list(q, must_not);
             q = q.sql(" THEN 1 END) = 0")
         }
 
         q = q .sql(") ");
 
+        if !self.whitelisted_sets.is_empty() {
+            q = generate_blacklist_condition(q, self.must_not.clone(), self.whitelisted_sets.clone());
+        }
+
         if self.emoji.len() > 0 {
             q = q.sql("AND sticker.emoji IN ");
             q = generate_sql_list(q, self.emoji.clone());
@@ -149,6 +179,45 @@ impl StickerTagQuery {
     }
 }
 
+/// unlike `StickerTagQuery`, this also finds stickers that are not tagged
+#[must_use]
+pub(super) fn generate_emoji_query(
+    emoji: String,
+    blacklist: Vec<String>,
+    whitelisted_sets: Vec<String>,
+    always_hidden: Vec<String>,
+    limit: i64,
+    offset: i64,
+) -> BoxedSqlQuery<'static, Sqlite, SqlQuery> {
+    let mut q = sql_query("").into_boxed::<Sqlite>()
+        .sql("SELECT * FROM sticker WHERE emoji = ? ")
+        .bind::<Text, _>(emoji);
+    q = generate_blacklist_condition(q, blacklist, whitelisted_sets);
+    q = generate_blacklist_condition(q, always_hidden, vec![]);
+    q.sql("GROUP BY sticker.sticker_file_id LIMIT ? ")
+        .bind::<BigInt, _>(limit)
+        .sql("OFFSET ?")
+        .bind::<BigInt, _>(offset)
+}
+
+fn generate_blacklist_condition(
+    mut q: BoxedSqlQuery<Sqlite, SqlQuery>,
+    blacklist: Vec<String>,
+    whitelisted_sets: Vec<String>,
+) -> BoxedSqlQuery<Sqlite, SqlQuery> {
+    if blacklist.is_empty() {
+        return q;
+    }
+    q = q.sql("AND (sticker.sticker_file_id NOT IN (SELECT sticker_file_id FROM sticker_file_tag WHERE tag IN ");
+    q = generate_sql_list(q, blacklist);
+    q = q.sql(")");
+    if !whitelisted_sets.is_empty() {
+        q = q.sql(" OR sticker.sticker_set_id IN ");
+        q = generate_sql_list(q, whitelisted_sets);
+    }
+    q.sql(") ")
+}
+
 fn generate_sql_list(
     mut q: BoxedSqlQuery<Sqlite, SqlQuery>,
     list: Vec<String>,
@@ -229,4 +298,43 @@ mod tests {
         .order(Order::Random { seed: 42 });
         assert_eq!(&debug_query(&query.generate()).to_string(), "SELECT * FROM sticker WHERE sticker.sticker_file_id IN (SELECT sticker_file_id FROM sticker_file_tag GROUP BY sticker_file_id HAVING count(CASE WHEN tag IN (?) THEN 1 END) = 0) GROUP BY sticker.sticker_file_id ORDER BY sin(rowid + ?) LIMIT ? OFFSET ? -- binds: [\"meta_sticker\", 42, 100, 200]");
     }
+
+    #[test]
+    fn test_query_builder_whitelisted_sets() {
+        let query = StickerTagQuery::new(vec!["solo".into()], vec!["meta_sticker".into()])
+            .whitelisted_sets(vec!["HugSet".into()])
+            .limit(100)
+            .offset(200)
+            .order(Order::LatestFirst);
+        assert_eq!(&debug_query(&query.generate()).to_string(), "SELECT * FROM sticker WHERE sticker.sticker_file_id IN (SELECT sticker_file_id FROM sticker_file_tag GROUP BY sticker_file_id HAVING count(CASE WHEN tag IN (?) THEN 1 END) = ?) AND (sticker.sticker_file_id NOT IN (SELECT sticker_file_id FROM sticker_file_tag WHERE tag IN (?)) OR sticker.sticker_set_id IN (?)) GROUP BY sticker.sticker_file_id ORDER BY rowid DESC LIMIT ? OFFSET ? -- binds: [\"solo\", 1, \"meta_sticker\", \"HugSet\", 100, 200]");
+    }
+
+    #[test]
+    fn test_query_builder_excluded_tag_with_whitelisted_sets() {
+        // "fox -gore" with "meta_sticker" blacklisted: gore stays hidden in the whitelisted set
+        let query = StickerTagQuery::new(vec!["fox".into()], vec!["meta_sticker".into()])
+            .whitelisted_sets(vec!["HugSet".into()])
+            .always_hidden(vec!["gore".into()])
+            .limit(100)
+            .offset(200)
+            .order(Order::LatestFirst);
+        assert_eq!(&debug_query(&query.generate()).to_string(), "SELECT * FROM sticker WHERE sticker.sticker_file_id IN (SELECT sticker_file_id FROM sticker_file_tag GROUP BY sticker_file_id HAVING count(CASE WHEN tag IN (?) THEN 1 END) = ? AND count(CASE WHEN tag IN (?) THEN 1 END) = 0) AND (sticker.sticker_file_id NOT IN (SELECT sticker_file_id FROM sticker_file_tag WHERE tag IN (?)) OR sticker.sticker_set_id IN (?)) GROUP BY sticker.sticker_file_id ORDER BY rowid DESC LIMIT ? OFFSET ? -- binds: [\"fox\", 1, \"gore\", \"meta_sticker\", \"HugSet\", 100, 200]");
+    }
+
+    #[test]
+    fn test_emoji_query() {
+        let query = generate_emoji_query("🤍".to_string(), vec!["meta_sticker".into()], vec![], vec![], 100, 200);
+        assert_eq!(&debug_query(&query).to_string(), "SELECT * FROM sticker WHERE emoji = ? AND (sticker.sticker_file_id NOT IN (SELECT sticker_file_id FROM sticker_file_tag WHERE tag IN (?))) GROUP BY sticker.sticker_file_id LIMIT ? OFFSET ? -- binds: [\"🤍\", \"meta_sticker\", 100, 200]");
+    }
+
+    #[test]
+    fn test_query_builder_always_hidden() {
+        let query = StickerTagQuery::new(vec!["solo".into()], vec!["meta_sticker".into()])
+            .whitelisted_sets(vec!["HugSet".into()])
+            .always_hidden(vec!["explicit".into()])
+            .limit(100)
+            .offset(200)
+            .order(Order::LatestFirst);
+        assert_eq!(&debug_query(&query.generate()).to_string(), "SELECT * FROM sticker WHERE sticker.sticker_file_id IN (SELECT sticker_file_id FROM sticker_file_tag GROUP BY sticker_file_id HAVING count(CASE WHEN tag IN (?) THEN 1 END) = ? AND count(CASE WHEN tag IN (?) THEN 1 END) = 0) AND (sticker.sticker_file_id NOT IN (SELECT sticker_file_id FROM sticker_file_tag WHERE tag IN (?)) OR sticker.sticker_set_id IN (?)) GROUP BY sticker.sticker_file_id ORDER BY rowid DESC LIMIT ? OFFSET ? -- binds: [\"solo\", 1, \"explicit\", \"meta_sticker\", \"HugSet\", 100, 200]");
+    }
 }
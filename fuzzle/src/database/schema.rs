@@ -1,5 +1,20 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    use crate::database::sqlite_mapping::*;
+
+    api_key (id) {
+        id -> BigInt,
+        user_id -> BigInt,
+        name -> Text,
+        key_hash -> Text,
+        requests_per_minute -> BigInt,
+        created_at -> Timestamp,
+        last_used_at -> Nullable<Timestamp>,
+        revoked_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     use crate::database::sqlite_mapping::*;
 
@@ -16,6 +31,18 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use crate::database::sqlite_mapping::*;
+
+    creator_profile (user_id) {
+        user_id -> BigInt,
+        bio -> Text,
+        links -> Text,
+        claimed_at -> Nullable<Timestamp>,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     use crate::database::sqlite_mapping::*;
 
@@ -29,6 +56,19 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use crate::database::sqlite_mapping::*;
+
+    moderation_log (id) {
+        id -> BigInt,
+        created_at -> Timestamp,
+        actor_user_id -> Nullable<BigInt>,
+        action -> Integer,
+        target -> Text,
+        details -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     use crate::database::sqlite_mapping::*;
 
@@ -38,6 +78,10 @@ diesel::table! {
         created_by_user_id -> BigInt,
         details -> Text,
         completion_status -> Integer,
+        assigned_to_user_id -> Nullable<BigInt>,
+        assigned_at -> Nullable<Timestamp>,
+        decided_by_user_id -> Nullable<BigInt>,
+        decided_at -> Nullable<Timestamp>,
     }
 }
 
@@ -73,6 +117,38 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use crate::database::sqlite_mapping::*;
+
+    sticker_collection (id) {
+        id -> BigInt,
+        user_id -> BigInt,
+        name -> Text,
+        created_at -> Timestamp,
+        is_public -> Bool,
+    }
+}
+
+diesel::table! {
+    use crate::database::sqlite_mapping::*;
+
+    sticker_collection_follower (collection_id, user_id) {
+        collection_id -> BigInt,
+        user_id -> BigInt,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use crate::database::sqlite_mapping::*;
+
+    sticker_collection_sticker (collection_id, sticker_id) {
+        collection_id -> BigInt,
+        sticker_id -> Text,
+        added_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     use crate::database::sqlite_mapping::*;
 
@@ -93,6 +169,10 @@ diesel::table! {
         tag -> Text,
         added_by_user_id -> Nullable<BigInt>,
         created_at -> Timestamp,
+        origin -> Nullable<BigInt>,
+        suggestion_source -> Nullable<Text>,
+        suggestion_rank -> Nullable<BigInt>,
+        confirmed_by_user_id -> Nullable<BigInt>,
     }
 }
 
@@ -149,17 +229,50 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use crate::database::sqlite_mapping::*;
+
+    tag_description (tag_id) {
+        tag_id -> Text,
+        description -> Text,
+        updated_by_user_id -> Nullable<BigInt>,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use crate::database::sqlite_mapping::*;
+
+    tag_suggestion_rules (id) {
+        id -> BigInt,
+        rules -> Text,
+        created_by_user_id -> Nullable<BigInt>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use crate::database::sqlite_mapping::*;
+
+    tag_suggestion_source_weight (source) {
+        source -> Text,
+        weight -> Double,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     use crate::database::sqlite_mapping::*;
 
     user (id) {
         id -> BigInt,
         blacklist -> Text,
-        can_tag_stickers -> Bool,
-        can_tag_sets -> Bool,
         created_at -> Timestamp,
         settings -> Nullable<Text>,
         dialog_state -> Nullable<Text>,
+        trust_level -> BigInt,
+        trust_level_updated_by_user_id -> Nullable<BigInt>,
+        capability_grants -> Nullable<Text>,
     }
 }
 
@@ -174,11 +287,17 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(api_key -> user (user_id));
 diesel::joinable!(merged_sticker -> user (created_by_user_id));
 diesel::joinable!(moderation_task -> user (created_by_user_id));
 diesel::joinable!(removed_set -> user (added_by_user_id));
 diesel::joinable!(sticker -> sticker_file (sticker_file_id));
 diesel::joinable!(sticker -> sticker_set (sticker_set_id));
+diesel::joinable!(sticker_collection -> user (user_id));
+diesel::joinable!(sticker_collection_follower -> sticker_collection (collection_id));
+diesel::joinable!(sticker_collection_follower -> user (user_id));
+diesel::joinable!(sticker_collection_sticker -> sticker (sticker_id));
+diesel::joinable!(sticker_collection_sticker -> sticker_collection (collection_id));
 diesel::joinable!(sticker_file -> user (tags_locked_by_user_id));
 diesel::joinable!(sticker_file_tag -> sticker_file (sticker_file_id));
 diesel::joinable!(sticker_file_tag -> user (added_by_user_id));
@@ -187,20 +306,31 @@ diesel::joinable!(sticker_set -> user (added_by_user_id));
 diesel::joinable!(sticker_user -> sticker (sticker_id));
 diesel::joinable!(sticker_user -> user (user_id));
 diesel::joinable!(tag -> user (created_by_user_id));
+diesel::joinable!(tag_description -> user (updated_by_user_id));
+diesel::joinable!(tag_suggestion_rules -> user (created_by_user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    api_key,
     banned_sticker,
+    creator_profile,
     merged_sticker,
+    moderation_log,
     moderation_task,
     potentially_similar_file,
     removed_set,
     sticker,
+    sticker_collection,
+    sticker_collection_follower,
+    sticker_collection_sticker,
     sticker_file,
     sticker_file_tag,
     sticker_file_tag_history,
     sticker_set,
     sticker_user,
     tag,
+    tag_description,
+    tag_suggestion_rules,
+    tag_suggestion_source_weight,
     user,
     username,
 );
@@ -11,7 +11,7 @@ use std::{
 
 use crate::{database::BanReason, tags::Category, util::{StickerFileId, StickerId, StickerSetId}};
 
-use super::{schema, DatabaseError, DialogState, ModerationTaskDetails, ModerationTaskStatus, StickerType, StringVec, UserSettings};
+use super::{schema, Capability, CapabilityGrants, DatabaseError, DialogState, ModerationAction, ModerationTaskDetails, ModerationTaskStatus, StickerType, StringVec, TagOrigin, TrustLevel, UserSettings};
 
 #[derive(Queryable, Selectable)]
 #[diesel(table_name = schema::sticker_file)]
@@ -63,11 +63,27 @@ impl StickerSet {
 pub struct User {
     pub id: i64,
     pub blacklist: StringVec,
-    pub can_tag_stickers: bool,
-    pub can_tag_sets: bool,
     pub created_at: chrono::NaiveDateTime,
     pub settings: Option<UserSettings>,
     pub dialog_state: Option<DialogState>,
+    pub trust_level: TrustLevel,
+    /// `None` if the trust level was only changed automatically
+    pub trust_level_updated_by_user_id: Option<i64>,
+    pub capability_grants: Option<CapabilityGrants>,
+}
+
+impl User {
+    /// banned users have no capabilities, even if they were granted some
+    #[must_use]
+    pub fn has_capability(&self, capability: Capability) -> bool {
+        if self.trust_level == TrustLevel::Banned {
+            return false;
+        }
+        self.capability_grants
+            .as_ref()
+            .and_then(|grants| grants.get(&capability).copied())
+            .unwrap_or_else(|| self.trust_level.default_capabilities().contains(&capability))
+    }
 }
 
 #[derive(Queryable, QueryableByName, Selectable, Debug, Clone)]
@@ -91,6 +107,11 @@ pub struct StickerFileTag {
     pub tag: String,
     pub added_by_user_id: Option<i64>,
     pub created_at: chrono::NaiveDateTime,
+    pub origin: Option<TagOrigin>,
+    pub suggestion_source: Option<String>,
+    pub suggestion_rank: Option<i64>,
+    /// set once another user reviewed the tag
+    pub confirmed_by_user_id: Option<i64>,
 }
 
 #[derive(Queryable, Selectable, Debug, Clone)]
@@ -104,6 +125,44 @@ pub struct StickerUser {
     pub last_used: chrono::NaiveDateTime,
 }
 
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = schema::sticker_collection)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct StickerCollection {
+    pub id: i64,
+    pub user_id: i64,
+    pub name: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub is_public: bool,
+}
+
+/// key for the public api; the key itself is not stored
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = schema::api_key)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct ApiKey {
+    pub id: i64,
+    pub user_id: i64,
+    pub name: String,
+    pub key_hash: String,
+    pub requests_per_minute: i64,
+    pub created_at: chrono::NaiveDateTime,
+    pub last_used_at: Option<chrono::NaiveDateTime>,
+    pub revoked_at: Option<chrono::NaiveDateTime>,
+}
+
+/// what a sticker set creator wrote about themselves
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = schema::creator_profile)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct CreatorProfile {
+    pub user_id: i64,
+    pub bio: String,
+    pub links: StringVec,
+    pub claimed_at: Option<chrono::NaiveDateTime>,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
 #[derive(Queryable, Selectable, Debug, Clone)]
 #[diesel(table_name = schema::tag)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
@@ -118,6 +177,41 @@ pub struct Tag {
     pub implications: Option<StringVec>,
 }
 
+/// wiki text of a tag
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = schema::tag_description)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct TagDescription {
+    pub tag_id: String,
+    pub description: String,
+    pub updated_by_user_id: Option<i64>,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = schema::tag_suggestion_rules)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct TagSuggestionRulesRevision {
+    pub id: i64,
+    pub rules: String,
+    pub created_by_user_id: Option<i64>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// append-only; see `ModerationAction` for what the target is
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = schema::moderation_log)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct ModerationLogEntry {
+    pub id: i64,
+    pub created_at: chrono::NaiveDateTime,
+    /// `None` for automatic actions
+    pub actor_user_id: Option<i64>,
+    pub action: ModerationAction,
+    pub target: String,
+    pub details: Option<String>,
+}
+
 #[derive(Queryable, Selectable, Debug, Clone, QueryableByName)]
 #[diesel(table_name = schema::moderation_task)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
@@ -127,4 +221,10 @@ pub struct ModerationTask {
     pub created_by_user_id: i64,
     pub details: ModerationTaskDetails,
     pub completion_status: ModerationTaskStatus,
+    /// the moderator that is currently handling the task
+    pub assigned_to_user_id: Option<i64>,
+    pub assigned_at: Option<chrono::NaiveDateTime>,
+    /// the moderator that made the last decision
+    pub decided_by_user_id: Option<i64>,
+    pub decided_at: Option<chrono::NaiveDateTime>,
 }
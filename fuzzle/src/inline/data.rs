@@ -94,6 +94,25 @@ pub enum InlineQueryData {
     },
     ReportSet {
         set_id: StickerSetId,
+        note: Option<String>,
+    },
+    ReportSticker {
+        sticker_id: StickerId,
+        note: Option<String>,
+    },
+    ListCollectionStickers {
+        name: String,
+    },
+    CreateCollection {
+        sticker_id: StickerId,
+        name: Option<String>,
+    },
+    ExportCollection {
+        collection_id: i64,
+        set_title: Option<String>,
+    },
+    ListPublicCollectionStickers {
+        collection_id: i64,
     },
 }
 
@@ -226,6 +245,41 @@ impl InlineQueryData {
             set_title: None,
         }
     }
+
+    #[must_use]
+    pub fn collection(name: String) -> Self {
+        Self::ListCollectionStickers { name }
+    }
+
+    #[must_use]
+    pub fn create_collection(sticker_id: StickerId) -> Self {
+        Self::CreateCollection {
+            sticker_id,
+            name: None,
+        }
+    }
+
+    #[must_use]
+    pub fn export_collection(collection_id: i64) -> Self {
+        Self::ExportCollection {
+            collection_id,
+            set_title: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn public_collection(collection_id: i64) -> Self {
+        Self::ListPublicCollectionStickers { collection_id }
+    }
+}
+
+fn optional_trimmed(input: &str) -> Option<String> {
+    let input = input.trim();
+    if input.is_empty() {
+        None
+    } else {
+        Some(input.to_string())
+    }
 }
 
 fn parse_tags(input: &str) -> IResult<&str, Vec<String>> {
@@ -446,10 +500,69 @@ fn parse_inline_query_data(input: &str) -> IResult<&str, InlineQueryData> {
                         unique_id: StickerId::from(unique_id),
                     },
                 ),
+                alt((
+                    map(
+                        tuple((
+                            tag("(reportset:"),
+                            set_name_literal,
+                            tag(")"),
+                            take_while(|c| true),
+                        )),
+                        |(_, set_id, _, note)| InlineQueryData::ReportSet {
+                            set_id: StickerSetId::from(set_id),
+                            note: optional_trimmed(note),
+                        },
+                    ),
+                    map(
+                        tuple((
+                            tag("(reportsticker:"),
+                            sticker_id_literal,
+                            tag(")"),
+                            take_while(|c| true),
+                        )),
+                        |(_, sticker_id, _, note)| InlineQueryData::ReportSticker {
+                            sticker_id: StickerId::from(sticker_id),
+                            note: optional_trimmed(note),
+                        },
+                    ),
+                )),
                 map(
-                    terminated(preceded(tag("(reportset:"), set_name_literal), tag(")")),
-                    |set_id| InlineQueryData::ReportSet {
-                        set_id: StickerSetId::from(set_id),
+                    tuple((
+                        tag("(newcol:"),
+                        sticker_id_literal,
+                        tag(")"),
+                        take_while(|c| true),
+                    )),
+                    |(_, sticker_id, _, name)| InlineQueryData::CreateCollection {
+                        sticker_id: StickerId::from(sticker_id),
+                        name: optional_trimmed(name),
+                    },
+                ),
+                map(
+                    tuple((
+                        tag("(exportcol:"),
+                        map_res(digit1, str::parse),
+                        tag(")"),
+                        take_while(|c| true),
+                    )),
+                    |(_, collection_id, _, set_title)| InlineQueryData::ExportCollection {
+                        collection_id,
+                        set_title: optional_trimmed(set_title),
+                    },
+                ),
+                map(
+                    terminated(
+                        preceded(tag("(pubcol:"), map_res(digit1, str::parse)),
+                        tag(")"),
+                    ),
+                    |collection_id| InlineQueryData::ListPublicCollectionStickers {
+                        collection_id,
+                    },
+                ),
+                map(
+                    preceded(tag("collection:"), take_while(|c| true)),
+                    |name: &str| InlineQueryData::ListCollectionStickers {
+                        name: name.trim().to_string(),
                     },
                 ),
                 map(parse_tags_and_emojis, |(tags, emoji)| {
@@ -574,7 +687,29 @@ impl Display for InlineQueryData {
             InlineQueryData::TopOwners => write!(f, "(owners) "),
             InlineQueryData::ListRecommendationModeRecommendations => write!(f, "(rec) "),
             InlineQueryData::SetsByUserId { user_id } => write!(f, "(usersets:{user_id}) "),
-            InlineQueryData::ReportSet { set_id } => write!(f, "(reportset:{set_id}) "),
+            InlineQueryData::ReportSet { set_id, note } => {
+                write!(f, "(reportset:{set_id}) {}", note.as_deref().unwrap_or_default())
+            }
+            InlineQueryData::ReportSticker { sticker_id, note } => {
+                write!(f, "(reportsticker:{sticker_id}) {}", note.as_deref().unwrap_or_default())
+            }
+            InlineQueryData::ListCollectionStickers { name } => write!(f, "collection:{name}"),
+            InlineQueryData::CreateCollection { sticker_id, name } => {
+                write!(f, "(newcol:{sticker_id}) {}", name.as_deref().unwrap_or_default())
+            }
+            InlineQueryData::ExportCollection {
+                collection_id,
+                set_title,
+            } => {
+                write!(
+                    f,
+                    "(exportcol:{collection_id}) {}",
+                    set_title.as_deref().unwrap_or_default()
+                )
+            }
+            InlineQueryData::ListPublicCollectionStickers { collection_id } => {
+                write!(f, "(pubcol:{collection_id}) ")
+            }
         }
     }
 }
@@ -648,6 +783,61 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn parse_collection_query() -> Result<(), UserError> {
+        let query = InlineQueryData::try_from("collection:reaction faces ".to_string())?;
+        assert_eq!(
+            query,
+            InlineQueryData::collection("reaction faces".to_string())
+        );
+        assert_eq!(query.to_string(), "collection:reaction faces");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_export_collection_query() -> Result<(), UserError> {
+        let query = InlineQueryData::try_from("(exportcol:42) My Set".to_string())?;
+        assert_eq!(
+            query,
+            InlineQueryData::ExportCollection {
+                collection_id: 42,
+                set_title: Some("My Set".to_string()),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_report_queries() -> Result<(), UserError> {
+        let query = InlineQueryData::try_from("(reportset:some_set) ".to_string())?;
+        assert_eq!(
+            query,
+            InlineQueryData::ReportSet {
+                set_id: StickerSetId::from("some_set"),
+                note: None,
+            }
+        );
+        let query =
+            InlineQueryData::try_from("(reportsticker:AgADAQADxyz1) wrong artist".to_string())?;
+        assert_eq!(
+            query,
+            InlineQueryData::ReportSticker {
+                sticker_id: StickerId::from("AgADAQADxyz1"),
+                note: Some("wrong artist".to_string()),
+            }
+        );
+        assert_eq!(query.to_string(), "(reportsticker:AgADAQADxyz1) wrong artist");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_public_collection_query() -> Result<(), UserError> {
+        let query = InlineQueryData::try_from("(pubcol:7) ".to_string())?;
+        assert_eq!(query, InlineQueryData::public_collection(7));
+        assert_eq!(query.to_string(), "(pubcol:7) ");
+        Ok(())
+    }
+
     #[test]
     fn parse_blacklist_query() -> Result<(), UserError> {
         let query = InlineQueryData::try_from("(blacklist) attribution".to_string())?;
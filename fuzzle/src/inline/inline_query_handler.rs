@@ -4,7 +4,7 @@ use crate::bot::{
     UserError,
 };
 use crate::bot::{BotExt, RequestContext};
-use crate::database::{self, min_max, DialogState, ReportReason, User};
+use crate::database::{self, min_max, DialogState, ReportReason, ReportTarget, User, UserSettings};
 use crate::database::{Database, Sticker, StickerSet};
 use crate::fmetrics::TracedMessage;
 use crate::inline::{InlineQueryData, SetOperation};
@@ -15,11 +15,12 @@ use crate::sticker::{
     resolve_file_hashes_to_sticker_ids_and_clean_up_unreferenced_files, Match,
 };
 use crate::text::{Markdown, Text};
-use crate::util::{Emoji, Required, StickerId, StickerSetId, create_sticker_set_id, create_tag_id, format_relative_time};
+use crate::util::{Emoji, Required, StickerFileId, StickerId, StickerSetId, create_sticker_set_id, create_tag_id, format_relative_time};
 use chrono::DateTime;
 use itertools::Itertools;
 use num_traits::ToPrimitive;
 use rand::Rng;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::future::IntoFuture;
 use std::sync::Arc;
@@ -28,8 +29,8 @@ use teloxide::utils::markdown::escape;
 use teloxide::{
     prelude::*,
     types::{
-        InlineQueryResult, InlineQueryResultArticle, InlineQueryResultCachedSticker,
-        InputMessageContent, InputMessageContentText,
+        InlineKeyboardMarkup, InlineQueryResult, InlineQueryResultArticle,
+        InlineQueryResultCachedSticker, InputMessageContent, InputMessageContentText,
     },
 };
 use tracing::{warn, Instrument};
@@ -49,6 +50,7 @@ fn create_query_set(
     set: &database::StickerSet,
     info: Option<String>,
     thumb_url: String,
+    reply_markup: Option<InlineKeyboardMarkup>,
 ) -> Result<InlineQueryResult, BotError> {
     // // TODO: do not rely on this service for images (base64 does not work)
     let set_title = set.title_or_id();
@@ -72,6 +74,9 @@ fn create_query_set(
         .thumb_width(THUMBNAIL_SIZE)
         .thumb_height(THUMBNAIL_SIZE)
         .hide_url(true);
+    if let Some(reply_markup) = reply_markup {
+        article = article.reply_markup(reply_markup);
+    }
 
     Ok(article.into())
 }
@@ -277,9 +282,47 @@ pub async fn query_stickers(
     // TODO: fall back to default blacklist if blacklist is not set
     let query_empty = tags.is_empty() && emoji.is_empty();
 
+    let settings = user.settings.clone().unwrap_or_default();
+
+    if query_empty {
+        let stickers = database
+            .get_recently_used_stickers(user.id, limit as i64, offset as i64)
+            .await?;
+        if !stickers.is_empty() {
+            return Ok(stickers);
+        }
+    }
+    search_stickers(
+        tags,
+        emoji,
+        &settings,
+        &user.blacklist,
+        database,
+        tag_manager,
+        limit,
+        offset,
+        seed,
+    )
+    .await
+}
+
+/// stickers matching the tags (`-tag` excludes a tag) and emojis, filtered by the blacklist and
+/// rating profile of the settings; used by inline queries and the website
+#[tracing::instrument(skip(database, settings, blacklist, tag_manager))]
+pub async fn search_stickers(
+    tags: Vec<String>,
+    emoji: Vec<Emoji>,
+    settings: &UserSettings,
+    blacklist: &[String],
+    database: Database,
+    tag_manager: TagManagerService,
+    limit: usize,
+    offset: usize,
+    seed: i32,
+) -> Result<Vec<Sticker>, BotError> {
     // TODO: give warning: querying by emoji is very limited (no blacklist, only single emoji)
 
-    let order = user.settings.clone().unwrap_or_default().order();
+    let order = settings.order();
     let order = match order {
         crate::database::StickerOrder::LatestFirst => crate::database::Order::LatestFirst,
         crate::database::StickerOrder::Random => crate::database::Order::Random { seed },
@@ -290,75 +333,54 @@ pub async fn query_stickers(
         .map(|emoji| emoji.to_string_without_variant())
         .collect_vec();
 
-    let stickers = if query_empty {
-        let stickers = database
-            .get_recently_used_stickers(user.id, limit as i64, offset as i64)
-            .await?;
-        if stickers.is_empty() {
-            database
-                .get_stickers_for_tag_query(
-                    vec![],
-                    user.blacklist.clone().into_inner(),
-                    vec![],
-                    limit as i64,
-                    offset as i64,
-                    order,
-                )
-                .await?
-        } else {
-            stickers
-        }
-    } else {
-        let (tags, query_blacklist): (Vec<String>, Vec<String>) =
-            tags.into_iter().partition(|tag| !tag.starts_with('-'));
-        if query_blacklist.is_empty() && tags.is_empty() && emoji.len() == 1 {
-            // TODO: warn the user that this is not blacklisted
-            return Ok(database
-                .get_stickers_by_emoji(&emoji[0].to_string(), limit as i64, offset as i64)
-                .await?);
-        }
-        let query_blacklist: Vec<String> = query_blacklist
-            .into_iter()
-            .map(|tag| tag.strip_prefix('-').unwrap_or(&tag).to_string())
-            .collect(); // TODO: this should probably be done during parsing
-        let (tags, query_blacklist) = (
-            treat_missing_tags_as_errors(tag_manager.closest_matching_tags(&tags).await)?,
-            treat_missing_tags_as_errors(
-                tag_manager.closest_matching_tags(&query_blacklist).await,
-            )?,
-        );
-
-        let tags_empty = tags.is_empty();
-
-        let blacklist = user
-            .blacklist
-            .iter()
-            .cloned()
-            .chain(query_blacklist)
-            .collect_vec();
-
-        // let empty_result_message = if query_empty {
-        //     "Tip: try \"hug\"".to_string()
-        // } else if tags_empty {
-        //     "No matching tags found".to_string()
-        // } else {
-        //     format!("No results for \"{}\"", tags.join(" "))
-        // };
-
-        // TODO: if tags are empty -> show the user's recently used or favorited (if implemented alread) stickers
-        database
-            .get_stickers_for_tag_query(
-                tags.clone(),
-                blacklist,
-                emoji,
+    let (tags, query_blacklist): (Vec<String>, Vec<String>) =
+        tags.into_iter().partition(|tag| !tag.starts_with('-'));
+    if query_blacklist.is_empty() && tags.is_empty() && emoji.len() == 1 {
+        // only tags that are blacklisted everywhere apply to emoji searches
+        return Ok(database
+            .get_stickers_by_emoji(
+                &emoji[0].to_string(),
+                settings.hidden_tags(blacklist, true),
+                settings.whitelisted_sets.clone(),
+                settings.rating_profile().hidden_tags(),
                 limit as i64,
                 offset as i64,
-                order,
             )
-            .await?
-    };
+            .await?);
+    }
+    let query_blacklist: Vec<String> = query_blacklist
+        .into_iter()
+        .map(|tag| tag.strip_prefix('-').unwrap_or(&tag).to_string())
+        .collect(); // TODO: this should probably be done during parsing
+    let (tags, query_blacklist) = (
+        treat_missing_tags_as_errors(tag_manager.closest_matching_tags(&tags).await)?,
+        treat_missing_tags_as_errors(
+            tag_manager.closest_matching_tags(&query_blacklist).await,
+        )?,
+    );
 
-    Ok(stickers)
+    // whitelisted sets only bypass the stored blacklist, tags excluded in the query always apply
+    let always_hidden = settings
+        .rating_profile()
+        .hidden_tags()
+        .into_iter()
+        .chain(query_blacklist)
+        .unique()
+        .collect_vec();
+
+    // TODO: if tags are empty -> show the user's recently used or favorited (if implemented alread) stickers
+    Ok(database
+        .get_stickers_for_tag_query(
+            tags,
+            settings.hidden_tags(blacklist, false),
+            settings.whitelisted_sets.clone(),
+            always_hidden,
+            emoji,
+            limit as i64,
+            offset as i64,
+            order,
+        )
+        .await?)
 }
 
 #[tracing::instrument(skip(query, q, request_context))]
@@ -380,6 +402,13 @@ async fn handle_similar_sticker_query(
     )
     .await?;
     let stickers = request_context.services.similarity.matches_to_stickers(result).await?;
+    let stickers = request_context
+        .services
+        .sticker
+        .filter_by_rating_profile(stickers, request_context.rating_profile(), |(sticker, _)| {
+            &sticker.sticker_file_id
+        })
+        .await?;
 
     let sticker_result = stickers
         .into_iter()
@@ -427,16 +456,18 @@ async fn search_stickers(
 
     let result_empty = stickers.is_empty();
 
+    let warnings = find_blacklist_warnings(&stickers, &request_context).await?;
     let sticker_result = stickers
         .into_iter()
-        .map(|sticker| {
-            InlineQueryResultCachedSticker::new(
+        .map(|sticker| match warnings.get(&sticker.sticker_file_id) {
+            Some(warned_tags) => spoiler_sticker_result(&sticker.id, warned_tags, &request_context),
+            None => Ok(InlineQueryResultCachedSticker::new(
                 InlineQueryResultId::Sticker(sticker.id).to_string(),
                 sticker.telegram_file_identifier,
             )
-            .into()
+            .into()),
         })
-        .collect_vec();
+        .collect::<Result<Vec<InlineQueryResult>, BotError>>()?;
 
     require_some_results("stickers", current_offset, sticker_result.len())?;
     request_context
@@ -455,6 +486,55 @@ async fn search_stickers(
     Ok(())
 }
 
+/// maps sticker files to the blacklisted tags (in warn mode) they are tagged with
+async fn find_blacklist_warnings(
+    stickers: &[Sticker],
+    request_context: &RequestContext,
+) -> Result<HashMap<StickerFileId, Vec<String>>, BotError> {
+    let settings = request_context.user.settings.clone().unwrap_or_default();
+    let warned_tags = settings.warned_tags(&request_context.user.blacklist);
+    let sticker_file_ids = stickers
+        .iter()
+        .filter(|sticker| !settings.is_set_whitelisted(&sticker.sticker_set_id))
+        .map(|sticker| sticker.sticker_file_id.clone())
+        .collect_vec();
+    if warned_tags.is_empty() || sticker_file_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    Ok(request_context
+        .database
+        .get_matching_sticker_file_tags(sticker_file_ids, warned_tags)
+        .await?
+        .into_iter()
+        .into_group_map())
+}
+
+fn spoiler_sticker_result(
+    sticker_id: &StickerId,
+    warned_tags: &[String],
+    request_context: &RequestContext,
+) -> Result<InlineQueryResult, BotError> {
+    let sticker_url = Url::parse(&format!(
+        "https://{}/sticker/{sticker_id}",
+        request_context.config.domain_name
+    ))?;
+    let thumbnail_url = Url::parse("https://fuzzle-bot.avoonix.com/assets/fuzzle_happy.png")?;
+    let content = InputMessageContent::Text(InputMessageContentText::new(
+        Text::spoiler_sticker(warned_tags, &sticker_url, request_context.language),
+    ));
+    Ok(InlineQueryResultArticle::new(
+        InlineQueryResultId::Sticker(sticker_id.clone()).to_string(),
+        format!("⚠️ {}", warned_tags.join(", ")),
+        content,
+    )
+    .description("Contains blacklisted tags, sent as a spoiler")
+    .thumb_url(thumbnail_url)
+    .thumb_width(THUMBNAIL_SIZE)
+    .thumb_height(THUMBNAIL_SIZE)
+    .hide_url(true)
+    .into())
+}
+
 #[tracing::instrument(skip(q, request_context))]
 async fn search_tags_for_blacklist(
     current_offset: QueryPage,
@@ -568,7 +648,7 @@ pub async fn show_error(
     request_context: RequestContext,
     error: BotError,
 ) -> Result<(), BotError> {
-    let error = error.end_user_error();
+    let error = error.end_user_error(request_context.language);
     let thumbnail_url = match error.1 {
         crate::bot::UserErrorSeverity::Error => Url::parse(&format!(
             "https://fuzzle-bot.avoonix.com/assets/fuzzle_error.png"
@@ -689,11 +769,241 @@ pub async fn inline_query_handler(
             sticker_id,
             set_title,
         } => handle_user_sets(current_offset, sticker_id, set_title, q, request_context).await,
-        InlineQueryData::ReportSet { set_id } => {
-            handle_report(current_offset, set_id, q, request_context).await
+        InlineQueryData::ReportSet { set_id, note } => {
+            handle_report(ReportTarget::Set(set_id), note, q, request_context).await
+        }
+        InlineQueryData::ReportSticker { sticker_id, note } => {
+            handle_report(ReportTarget::Sticker(sticker_id), note, q, request_context).await
         }
         InlineQueryData::ListAllSets => list_all_sets(current_offset, q, request_context).await,
+        InlineQueryData::ListCollectionStickers { name } => {
+            handle_collection_stickers(current_offset, name, q, request_context).await
+        }
+        InlineQueryData::CreateCollection { sticker_id, name } => {
+            handle_create_collection(sticker_id, name, q, request_context).await
+        }
+        InlineQueryData::ExportCollection {
+            collection_id,
+            set_title,
+        } => handle_export_collection(collection_id, set_title, q, request_context).await,
+        InlineQueryData::ListPublicCollectionStickers { collection_id } => {
+            handle_public_collection_stickers(current_offset, collection_id, q, request_context)
+                .await
+        }
+    }
+}
+
+#[tracing::instrument(skip(q, request_context))]
+async fn handle_collection_stickers(
+    current_offset: QueryPage,
+    name: String,
+    q: InlineQuery,
+    request_context: RequestContext,
+) -> Result<(), BotError> {
+    if let Some(collection) = request_context
+        .database
+        .get_sticker_collection_by_name(request_context.user.id, &name)
+        .await?
+    {
+        return answer_collection_stickers(current_offset, collection.id, q, request_context).await;
+    }
+    // followed collections of different owners can share a name; those have to be searched by id
+    let followed_collections = request_context
+        .database
+        .get_followed_sticker_collections(request_context.user.id)
+        .await?
+        .into_iter()
+        .filter(|collection| collection.name.to_lowercase() == name.to_lowercase())
+        .collect_vec();
+    let [collection] = followed_collections.as_slice() else {
+        return Err(UserError::ListHasZeroResults("unique collection with this name".to_string()).into());
+    };
+    answer_collection_stickers(current_offset, collection.id, q, request_context).await
+}
+
+#[tracing::instrument(skip(q, request_context))]
+async fn handle_public_collection_stickers(
+    current_offset: QueryPage,
+    collection_id: i64,
+    q: InlineQuery,
+    request_context: RequestContext,
+) -> Result<(), BotError> {
+    let Some(collection) = request_context
+        .database
+        .get_public_sticker_collection(collection_id)
+        .await?
+    else {
+        return Err(UserError::ListHasZeroResults("public collection".to_string()).into());
+    };
+    answer_collection_stickers(current_offset, collection.id, q, request_context).await
+}
+
+async fn answer_collection_stickers(
+    current_offset: QueryPage,
+    collection_id: i64,
+    q: InlineQuery,
+    request_context: RequestContext,
+) -> Result<(), BotError> {
+    let stickers = request_context
+        .database
+        .get_collection_stickers(
+            collection_id,
+            current_offset.page_size() as i64,
+            current_offset.skip() as i64,
+        )
+        .await?;
+
+    require_some_results("stickers", current_offset, stickers.len())?;
+    let r = stickers
+        .into_iter()
+        .map(|sticker| {
+            InlineQueryResultCachedSticker::new(
+                InlineQueryResultId::Sticker(sticker.id).to_string(),
+                sticker.telegram_file_identifier,
+            )
+            .into()
+        })
+        .collect_vec();
+    request_context
+        .bot
+        .answer_inline_query(q.id, r.clone())
+        .next_offset(current_offset.next_query_offset(r.len()))
+        .cache_time(0)
+        .is_personal(true)
+        .await?;
+    Ok(())
+}
+
+#[tracing::instrument(skip(q, request_context))]
+async fn handle_create_collection(
+    sticker_id: StickerId,
+    name: Option<String>,
+    q: InlineQuery,
+    request_context: RequestContext,
+) -> Result<(), BotError> {
+    let url = Url::parse(&format!(
+        "https://fuzzle-bot.avoonix.com/assets/fuzzle_happy.png"
+    ))?;
+    let article = match name {
+        Some(name) => {
+            let content = InputMessageContent::Text(InputMessageContentText::new(
+                Markdown::escaped(format!("/newcollection {sticker_id} {name}")),
+            ));
+            InlineQueryResultArticle::new(
+                InlineQueryResultId::Other("newcollection".to_string()).to_string(),
+                format!("Create: {name}"),
+                content,
+            )
+            .description("Create a new collection containing this sticker")
+        }
+        None => {
+            let content = InputMessageContent::Text(InputMessageContentText::new(
+                Markdown::escaped("Type a name for the collection"),
+            ));
+            InlineQueryResultArticle::new(
+                InlineQueryResultId::Other("newcollection".to_string()).to_string(),
+                "Type a name for the new collection",
+                content,
+            )
+        }
     }
+    .thumb_url(url)
+    .thumb_width(THUMBNAIL_SIZE)
+    .thumb_height(THUMBNAIL_SIZE)
+    .hide_url(true);
+
+    request_context
+        .bot
+        .answer_inline_query(q.id, vec![article.into()])
+        .next_offset("")
+        .cache_time(0)
+        .is_personal(true)
+        .await?;
+    Ok(())
+}
+
+#[tracing::instrument(skip(q, request_context))]
+async fn handle_export_collection(
+    collection_id: i64,
+    set_title: Option<String>,
+    q: InlineQuery,
+    request_context: RequestContext,
+) -> Result<(), BotError> {
+    let collection = request_context
+        .database
+        .get_sticker_collection_by_id(request_context.user.id, collection_id)
+        .await?
+        .required()?;
+    let sets = request_context
+        .database
+        .get_owned_sticker_sets_by_bot(
+            &request_context.config.telegram_bot_username,
+            request_context.user.id,
+        )
+        .await?;
+
+    let filter_term = &set_title.clone().unwrap_or_default().to_lowercase();
+    let mut articles: Vec<InlineQueryResult> = sets
+        .into_iter()
+        .filter(|set| {
+            set.id.to_lowercase().contains(filter_term)
+                || set
+                    .title
+                    .as_ref()
+                    .map_or(false, |title| title.to_lowercase().contains(filter_term))
+        })
+        .take(49)
+        .map(|set| {
+            let content = InputMessageContent::Text(InputMessageContentText::new(
+                Markdown::escaped(format!("/exportcollection {collection_id} {}", &set.id)),
+            ));
+            let thumbnail_url = Url::parse(&format!(
+                "https://fuzzle-bot.avoonix.com/thumbnails/sticker-set/{}/image.png",
+                &set.id
+            ))?;
+            Ok(InlineQueryResultArticle::new(
+                InlineQueryResultId::Other(rand::rng().random::<u32>().to_string()).to_string(),
+                set.title_or_id(),
+                content,
+            )
+            .description(format!("➕ Add all stickers from \"{}\" to this set", collection.name))
+            .thumb_url(thumbnail_url)
+            .thumb_width(THUMBNAIL_SIZE)
+            .thumb_height(THUMBNAIL_SIZE)
+            .hide_url(true)
+            .into())
+        })
+        .collect::<Result<_, BotError>>()?;
+
+    let set_title_with_fallback = set_title.unwrap_or_else(|| collection.name.clone());
+    let url = Url::parse(&format!(
+        "https://fuzzle-bot.avoonix.com/assets/fuzzle_happy.png"
+    ))?;
+    let content = InputMessageContent::Text(InputMessageContentText::new(Markdown::escaped(
+        format!("/exportcollection {collection_id} {set_title_with_fallback}"),
+    )));
+    articles.push(
+        InlineQueryResultArticle::new(
+            InlineQueryResultId::Other("exportcollection".to_string()).to_string(),
+            format!("Create: {set_title_with_fallback}"),
+            content,
+        )
+        .description("Create a new set from this collection (add text to change the title)")
+        .thumb_url(url)
+        .thumb_width(THUMBNAIL_SIZE)
+        .thumb_height(THUMBNAIL_SIZE)
+        .hide_url(true)
+        .into(),
+    );
+
+    request_context
+        .bot
+        .answer_inline_query(q.id, articles)
+        .next_offset("")
+        .cache_time(0)
+        .is_personal(true)
+        .await?;
+    Ok(())
 }
 
 #[tracing::instrument(skip(q, request_context))]
@@ -725,6 +1035,7 @@ async fn handle_sticker_contained_query(
                     "https://fuzzle-bot.avoonix.com/thumbnails/sticker-set/{}/image.png",
                     &set.id
                 ),
+                None,
             )
         })
         .collect::<Result<Vec<_>, _>>()?;
@@ -834,7 +1145,13 @@ async fn handle_overlapping_sets(
             {
             let (smaller, bigger) = min_max(&set_id, &main_set.id);
             format!( "https://fuzzle-bot.avoonix.com/thumbnails/compare-sticker-sets/{smaller}/{bigger}/image.png")
-            }
+            },
+            // the set itself is part of the list
+            (set_id != main_set.id)
+                .then(|| {
+                    Keyboard::compare_sets_link(&main_set.id, &set_id, &request_context.config.domain_name)
+                })
+                .transpose()?,
         )?);
     }
 
@@ -1047,6 +1364,11 @@ async fn get_recommended_stickers_in_recommender_mode(
             stickers.push(sticker); // TODO: single query?
         }
     }
+    let stickers = request_context
+        .services
+        .sticker
+        .filter_stickers_by_rating_profile(stickers, request_context.rating_profile())
+        .await?;
 
     let sticker_result = stickers
         .into_iter()
@@ -1285,7 +1607,7 @@ async fn handle_stickers_by_date(
         .await?
         .required()?;
 
-    let groups = request_context.services.sticker.get_sticker_set_timeline(&set.id).await?;
+    let groups = request_context.services.sticker.get_sticker_set_timeline(&set.id, request_context.rating_profile().hidden_tags()).await?;
 
     let r = groups
         .into_iter()
@@ -1349,6 +1671,7 @@ async fn get_all_sticker_sets_owned_by_a_user(
                 "https://fuzzle-bot.avoonix.com/thumbnails/sticker-set/{}/image.png",
                 &set.id
             ),
+            None,
         )?);
     }
 
@@ -1423,18 +1746,30 @@ async fn handle_top_owners(
 
 #[tracing::instrument(skip(q, request_context))]
 async fn handle_report(
-    current_offset: QueryPage,
-    set_id: StickerSetId,
+    target: ReportTarget,
+    note: Option<String>,
     q: InlineQuery,
     request_context: RequestContext,
 ) -> Result<(), BotError> {
-    let reasons = [ReportReason::NotFurry, ReportReason::Other];
+    let (command, target_id, reasons) = match &target {
+        ReportTarget::Set(set_id) => (
+            "reportset",
+            set_id.to_string(),
+            ReportReason::SET_REASONS.to_vec(),
+        ),
+        ReportTarget::Sticker(sticker_id) => (
+            "reportsticker",
+            sticker_id.to_string(),
+            ReportReason::STICKER_REASONS.to_vec(),
+        ),
+    };
+    let note = note.map(|note| format!(" {note}")).unwrap_or_default();
     let reasons = reasons
         .into_iter()
         .map(|reason| {
             let content =
                 InputMessageContent::Text(InputMessageContentText::new(Markdown::escaped(
-                    format!("/reportset {} {}", reason.to_i64().unwrap(), &set_id), // todo!
+                    format!("/{command} {} {target_id}{note}", reason.to_i64().unwrap()),
                 )));
 
             InlineQueryResultArticle::new(
@@ -1480,6 +1815,7 @@ async fn list_all_sets(
                 "https://fuzzle-bot.avoonix.com/thumbnails/sticker-set/{}/image.png",
                 &set.id
             ),
+            None,
         )?);
     }
 
@@ -5,5 +5,5 @@ mod result_id;
 mod pagination;
 
 pub use data::*;
-pub use inline_query_handler::{inline_query_handler_wrapper, query_stickers, get_last_input_match_list_and_other_input_closest_matches};
+pub use inline_query_handler::{inline_query_handler_wrapper, query_stickers, search_stickers, get_last_input_match_list_and_other_input_closest_matches};
 pub use inline_result_handler::inline_result_handler_wrapper;
@@ -1,20 +1,20 @@
-use crate::background_tasks::send_daily_report;
-use crate::bot::{Bot, BotError, BotExt, InternalError, RequestContext, SendDocumentExt};
-use crate::database::{export_database, Database, StickerIdStickerFileId};
+use crate::bot::{Bot, BotError, BotExt, InternalError, RequestContext, UserError};
+use crate::database::{export_database, Capability, Database, TrustLevel};
 use crate::message::Keyboard;
 use crate::services::{ Services};
-use crate::sticker::generate_merge_image;
-use crate::text::Markdown;
-use crate::util::{Required, StickerSetId};
+use crate::tags::train_and_evaluate_suggestions;
+use crate::text::{Markdown, Text};
+use crate::util::StickerSetId;
 
 use flate2::read::GzEncoder;
 use flate2::Compression;
-use tracing::info;
+use tracing::{Instrument, info};
 use std::io::prelude::*;
-use teloxide::types::{BotCommand, InputFile};
+use teloxide::types::BotCommand;
 
 use teloxide::{prelude::*, utils::command::BotCommands};
 
+use super::moderator::{ModeratorCommand, update_moderator_command_menu};
 use super::user::RegularCommand;
 
 #[derive(BotCommands, Debug)]
@@ -26,20 +26,44 @@ pub enum AdminCommand {
     #[command(description = "ADMIN unban a set (set name is case sensitive)")]
     UnbanSet { set_name: String },
 
-    #[command(description = "ADMIN get pending moderation tasks")]
-    Tasks,
-
     #[command(description = "ADMIN ui")]
     Ui,
 
-    #[command(description = "ADMIN merge queue")]
-    MergeQueue,
+    #[command(description = "ADMIN train the tag suggestion weights and report their precision at k")]
+    EvaluateSuggestions,
+
+    #[command(description = "ADMIN show the trust level and capabilities of a user")]
+    Permissions { user_id: i64 },
+
+    #[command(
+        description = "ADMIN set the trust level of a user (banned, new, trusted or moderator)",
+        parse_with = "split"
+    )]
+    Trust { user_id: i64, trust_level: String },
+
+    #[command(
+        description = "ADMIN grant (yes), revoke (no) or reset (default) a capability of a user",
+        parse_with = "split"
+    )]
+    Grant {
+        user_id: i64,
+        capability: String,
+        grant: String,
+    },
 }
 
+/// number of tagged stickers used for training and evaluation
+const SUGGESTION_EVALUATION_SAMPLE_SIZE: i64 = 400;
+
 impl AdminCommand {
     #[must_use]
     pub fn list_visible() -> Vec<BotCommand> {
-        [RegularCommand::bot_commands(), Self::bot_commands()].concat()
+        [
+            RegularCommand::bot_commands(),
+            ModeratorCommand::bot_commands(),
+            Self::bot_commands(),
+        ]
+        .concat()
     }
 
     #[tracing::instrument(skip(self, msg, request_context))]
@@ -55,7 +79,11 @@ impl AdminCommand {
                     request_context.bot.send_markdown(msg.chat.id, Markdown::escaped("missing set name"))
                         .await?;
                 } else {
-                    request_context.services.import.unban_sticker_set(&set_name).await?;
+                    request_context
+                        .services
+                        .import
+                        .unban_sticker_set(&set_name, Some(request_context.user.id))
+                        .await?;
                     request_context.bot.send_markdown(msg.chat.id, Markdown::escaped("unbanned set"))
                         .await?;
                 }
@@ -66,7 +94,11 @@ impl AdminCommand {
                     request_context.bot.send_markdown(msg.chat.id, Markdown::escaped("missing set name"))
                         .await?;
                 } else {
-                    request_context.services.import.ban_sticker_set(&set_name).await?;
+                    request_context
+                        .services
+                        .import
+                        .ban_sticker_set(&set_name, Some(request_context.user.id))
+                        .await?;
                     request_context.bot.send_markdown(msg.chat.id, Markdown::escaped("banned set"))
                         .await?;
                 }
@@ -76,13 +108,80 @@ impl AdminCommand {
                     .reply_markup(Keyboard::ui(request_context.config.domain_name.clone())?)
                     .await?;
             }
-            Self::Tasks => {
-                send_daily_report(request_context.database, request_context.bot, request_context.config.get_admin_user_id()).await?;
+            Self::EvaluateSuggestions => {
+                request_context
+                    .bot
+                    .send_markdown(
+                        msg.chat.id,
+                        Markdown::escaped("Evaluating tag suggestions, this will take a while"),
+                    )
+                    .await?;
+                // runs in the background since it runs all suggestion sources for every sampled sticker
+                tokio::spawn(
+                    async move {
+                        let result = evaluate_suggestions(msg.chat.id, request_context).await;
+                        if let Err(err) = result {
+                            tracing::error!("suggestion evaluation error: {err:?}");
+                        }
+                    }
+                    .instrument(tracing::info_span!("evaluate_suggestions_task")),
+                );
             }
-            Self::MergeQueue => {
-                for _ in 0..10 {
-                    send_merge_queue(msg.chat.id, request_context.clone()).await?;
-                }
+            Self::Permissions { user_id } => {
+                let user = request_context
+                    .database
+                    .get_user_by_id(user_id)
+                    .await?
+                    .ok_or_else(|| UserError::ValidationError(format!("user {user_id} does not exist")))?;
+                request_context
+                    .bot
+                    .send_markdown(msg.chat.id, Text::user_permissions(&user))
+                    .await?;
+            }
+            Self::Trust { user_id, trust_level } => {
+                let trust_level = TrustLevel::from_name(&trust_level).ok_or_else(|| {
+                    UserError::ValidationError(format!("unknown trust level {trust_level}"))
+                })?;
+                let user = request_context
+                    .services
+                    .trust
+                    .set_trust_level(user_id, trust_level, request_context.user.id)
+                    .await?;
+                update_moderator_command_menu(&request_context.bot, &request_context.config, &user)
+                    .await?;
+                request_context
+                    .bot
+                    .send_markdown(msg.chat.id, Text::user_permissions(&user))
+                    .await?;
+            }
+            Self::Grant {
+                user_id,
+                capability,
+                grant,
+            } => {
+                let capability = Capability::from_name(&capability).ok_or_else(|| {
+                    UserError::ValidationError(format!("unknown capability {capability}"))
+                })?;
+                let granted = match grant.as_str() {
+                    "yes" => Some(true),
+                    "no" => Some(false),
+                    "default" => None,
+                    _ => {
+                        return Err(UserError::ValidationError(
+                            "grant must be yes, no or default".to_string(),
+                        )
+                        .into());
+                    }
+                };
+                let user = request_context
+                    .services
+                    .trust
+                    .set_capability_grant(user_id, capability, granted, request_context.user.id)
+                    .await?;
+                request_context
+                    .bot
+                    .send_markdown(msg.chat.id, Text::user_permissions(&user))
+                    .await?;
             }
         }
 
@@ -90,40 +189,25 @@ impl AdminCommand {
     }
 }
 
-pub async fn send_merge_queue(chat_id: ChatId, request_context: RequestContext) -> Result<(), BotError> {
-    // TODO: not random
-    // TODO: maybe spawn without waiting since this may take a while
-    let Some((file_id_a, file_id_b)) = request_context.database.get_random_potential_merge_file_ids().await? else {
-        request_context.bot.send_markdown(chat_id, Markdown::escaped("No more potential merges :3")).await?;
-        return Ok(());
-    };
-
-                request_context
-                    .bot
-                    .send_chat_action(chat_id, teloxide::types::ChatAction::Typing)
-                    .await?;
-
-    let result = request_context.database.get_some_sticker_ids_for_sticker_file_ids(vec![file_id_a, file_id_b]).await?;
-    let mut result = result.into_iter();
-    let StickerIdStickerFileId {sticker_id: a, ..} = result.next().required()?;
-    let StickerIdStickerFileId { sticker_id: b, ..} = result.next().required()?;
-    let set_a = request_context.database.get_sticker_set_by_sticker_id(&a).await?.required()?;
-    let set_b = request_context.database.get_sticker_set_by_sticker_id(&b).await?.required()?;
-
-    let buf = generate_merge_image(
-        &a,
-        &b,
+async fn evaluate_suggestions(chat_id: ChatId, request_context: RequestContext) -> Result<(), BotError> {
+    let evaluation = train_and_evaluate_suggestions(
+        SUGGESTION_EVALUATION_SAMPLE_SIZE,
+        request_context.tag_manager.clone(),
         request_context.database.clone(),
-        request_context.bot.clone(),
-    )
-    .await?;
-
-    request_context.bot.send_document(
-        chat_id,
-        InputFile::memory(buf).file_name("comparison.png"),
+        request_context.tfidf.clone(),
+        request_context.vector_db.clone(),
+        request_context.services.tag_suggestion_rules.current(),
     )
-    .markdown_caption(Markdown::escaped("TODO: some content"))
-    .reply_markup(Keyboard::merge(&a, &b, &set_a.id, &set_b.id)?)
     .await?;
+    let current = request_context.services.suggestion_combiner.current();
+    request_context
+        .services
+        .suggestion_combiner
+        .propose(evaluation.combiner.clone());
+    request_context
+        .bot
+        .send_markdown(chat_id, Text::suggestion_evaluation(&evaluation, &current))
+        .reply_markup(Keyboard::apply_suggestion_weights())
+        .await?;
     Ok(())
 }
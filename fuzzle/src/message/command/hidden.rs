@@ -2,15 +2,19 @@ use std::str::FromStr;
 use std::sync::Arc;
 
 use crate::bot::{BotError, BotExt, InternalError, RequestContext, UserError};
-use crate::callback::{exit_mode, sticker_explore_keyboard, TagOperation};
+use crate::callback::{
+    exit_mode, sticker_collections_keyboard, sticker_explore_keyboard, TagOperation,
+};
 
 use crate::database::{
-    ContinuousTag, Database, DialogState, Order, ReportReason, Sticker, TagCreator,
+    Capability, ContinuousTag, Database, DialogState, Order, ReportReason, ReportTarget, Sticker,
+    StickerType, TagCreator, TagOrigin,
 };
 use crate::inline::{SetOperation, SimilarityAspect, TagKind};
 use crate::message::message_handler::handle_readonly;
 use crate::message::Keyboard;
 use crate::qdrant::{StickerMatch, VectorDatabase};
+use crate::services::ReportOutcome;
 use crate::simple_bot_api;
 use crate::sticker::resolve_file_hashes_to_sticker_ids_and_clean_up_unreferenced_files;
 use crate::tags::suggest_tags;
@@ -20,17 +24,18 @@ use crate::util::{
 };
 use futures::future::try_join_all;
 use itertools::Itertools;
-use nom::bytes::complete::take_while1;
+use nom::bytes::complete::{take_while, take_while1};
 use nom::character::complete::{i64, multispace1};
 use nom::combinator::{eof, map, map_res};
 use nom::sequence::tuple;
-use nom::{Finish, Parser};
+use nom::{Finish, IResult, Parser};
 use num_traits::FromPrimitive;
 use rand::Rng;
 use teloxide::dispatching::dialogue::GetChatId;
 use teloxide::types::{
     ButtonRequest, InlineKeyboardButton, InlineKeyboardMarkup, InputFile, KeyboardButton,
-    KeyboardButtonRequestChat, KeyboardButtonRequestUsers, KeyboardMarkup, ReplyMarkup,
+    KeyboardButtonRequestChat, KeyboardButtonRequestUsers, KeyboardMarkup, LinkPreviewOptions,
+    ReplyMarkup,
 };
 
 use teloxide::utils::command::ParseError;
@@ -107,7 +112,7 @@ pub enum HiddenCommand {
         parse_with = "split"
     )]
     RemoveSticker { set_id: String, sticker_id: String },
-    #[command(description = "show information about an user", parse_with = "split")]
+    #[command(description = "show the profile of a sticker set creator", parse_with = "split")]
     User { user_id: i64 },
     #[command(
         description = "report a sticker set (do not use manually)",
@@ -116,16 +121,107 @@ pub enum HiddenCommand {
     ReportSet {
         reason: ReportReason,
         set_id: String,
+        note: String,
+    },
+    #[command(
+        description = "report a sticker (do not use manually)",
+        parse_with = report_sticker_custom_parser
+    )]
+    ReportSticker {
+        reason: ReportReason,
+        sticker_id: String,
+        note: String,
     },
+    #[command(
+        description = "create a new personal collection (do not use manually)",
+        parse_with = new_sticker_set_custom_parser
+    )]
+    NewCollection { sticker_id: String, name: String },
+    #[command(
+        description = "add all stickers of a collection to a set (do not use manually)",
+        parse_with = export_collection_custom_parser
+    )]
+    ExportCollection {
+        collection_id: i64,
+        set_title: String,
+    },
+}
+
+pub(super) const MAX_COLLECTION_NAME_LENGTH: usize = 32;
+const MAX_COLLECTIONS_PER_USER: usize = 25;
+const MAX_EXPORTED_COLLECTION_STICKERS: i64 = 120;
+
+fn export_collection_custom_parser(input: String) -> Result<(i64, String), ParseError> {
+    Ok(Finish::finish(map(
+        tuple((i64, multispace1, take_while1(|_| true), eof)),
+        |(collection_id, _, set_title, _)| (collection_id, set_title.to_string()),
+    ).parse(&input))
+    .map_err(|err| {
+        ParseError::Custom(Box::new(UserError::ParseError(
+            input.len() - err.input.len(),
+            err.input.to_string(),
+        )))
+    })?
+    .1)
 }
 
-fn report_set_custom_parser(input: String) -> Result<(ReportReason, String), ParseError> {
+async fn handle_report(
+    target: ReportTarget,
+    reason: ReportReason,
+    note: String,
+    request_context: &RequestContext,
+) -> Result<(), BotError> {
+    let outcome = request_context
+        .services
+        .report
+        .report(request_context.user.id, target, reason, Some(note))
+        .await?;
+    let text = match outcome {
+        ReportOutcome::Created => "Success! A moderator should review your report soon(ish) :3",
+        ReportOutcome::AddedToPendingReport => {
+            "Someone already reported this, your report was added to theirs. Thank you!"
+        }
+        ReportOutcome::AlreadyReported => {
+            "You already reported this, a moderator will look at it soon"
+        }
+    };
+    request_context
+        .bot
+        .send_markdown(request_context.user_id(), Markdown::escaped(text))
+        .await?;
+    Ok(())
+}
+
+const fn sticker_format(sticker_type: StickerType) -> &'static str {
+    match sticker_type {
+        StickerType::Animated => "animated",
+        StickerType::Video => "video",
+        StickerType::Static => "static",
+    }
+}
+
+fn report_set_custom_parser(input: String) -> Result<(ReportReason, String, String), ParseError> {
+    report_custom_parser(input, set_name_literal)
+}
+
+fn report_sticker_custom_parser(
+    input: String,
+) -> Result<(ReportReason, String, String), ParseError> {
+    report_custom_parser(input, sticker_id_literal)
+}
+
+/// reason, target and an optional note
+fn report_custom_parser(
+    input: String,
+    target_literal: fn(&str) -> IResult<&str, &str>,
+) -> Result<(ReportReason, String, String), ParseError> {
     Ok(Finish::finish(map_res(
-        tuple((i64, multispace1, set_name_literal)),
-        |(reason, _, set_id)| {
+        tuple((i64, multispace1, target_literal, take_while(|_| true), eof)),
+        |(reason, _, target, note, _)| {
             Ok::<_, anyhow::Error>((
                 ReportReason::from_i64(reason).ok_or_else(|| anyhow::anyhow!("invalid reason"))?,
-                set_id.to_string(),
+                target.to_string(),
+                note.trim().to_string(),
             ))
         },
     ).parse(&input))
@@ -187,69 +283,53 @@ impl HiddenCommand {
         request_context: RequestContext,
     ) -> Result<(), BotError> {
         match self {
-            Self::ReportSet { reason, set_id } => {
+            Self::ReportSet {
+                reason,
+                set_id,
+                note,
+            } => {
                 if handle_readonly(&request_context, &msg).await? { return Ok(()); }
-
-                request_context
-                    .database
-                    .create_moderation_task(
-                        &crate::database::ModerationTaskDetails::ReportStickerSet {
-                            set_id: set_id.into(),
-                            reason,
-                        },
-                        request_context.user.id,
-                    )
-                    .await?;
-
-                request_context
-                    .bot
-                    .send_markdown(
-                        request_context.user_id(),
-                        Markdown::escaped(
-                            "Success! An admin should review your report soon(ish) :3",
-                        ),
-                    )
+                if !request_context.has_capability(Capability::Report) {
+                    return Err(UserError::NoPermissionForAction("report set".to_string()).into());
+                }
+                handle_report(ReportTarget::Set(set_id.into()), reason, note, &request_context)
                     .await?;
             }
-            Self::User { user_id } => {
-                let set_count = request_context
-                    .database
-                    .get_owned_sticker_set_count(user_id)
-                    .await?;
-                if set_count == 0 {
-                    return Err(anyhow::anyhow!("user is not a set owner").into());
-                    // TODO: user error?
+            Self::ReportSticker {
+                reason,
+                sticker_id,
+                note,
+            } => {
+                if handle_readonly(&request_context, &msg).await? { return Ok(()); }
+                if !request_context.has_capability(Capability::Report) {
+                    return Err(UserError::NoPermissionForAction("report sticker".to_string()).into());
                 }
-                let owner_username = request_context
-                    .database
-                    .get_username(crate::database::UsernameKind::User, user_id)
-                    .await?;
-                let owner_tags = request_context
-                    .database
-                    .get_all_tags_by_linked_user_id(user_id)
-                    .await?;
-                let channel_usernames = request_context
-                    .database
-                    .get_usernames(
-                        crate::database::UsernameKind::Channel,
-                        owner_tags
-                            .iter()
-                            .filter_map(|tag| tag.linked_channel_id)
-                            .collect_vec(),
+                handle_report(
+                    ReportTarget::Sticker(sticker_id.into()),
+                    reason,
+                    note,
+                    &request_context,
+                )
+                .await?;
+            }
+            Self::User { user_id } => {
+                let Some(creator) = request_context.services.creator.creator(user_id).await? else {
+                    return Err(UserError::ValidationError(
+                        "This user did not create any sticker sets that I know".to_string(),
                     )
-                    .await?;
-                let keyboard = Keyboard::owner_standalone(
-                    Some(user_id),
-                    set_count,
-                    owner_username,
-                    owner_tags,
-                    channel_usernames,
-                );
-
+                    .into());
+                };
                 request_context
                     .bot
-                    .send_markdown(msg.chat.id, Markdown::escaped("User Info"))
-                    .reply_markup(keyboard)
+                    .send_markdown(
+                        msg.chat.id,
+                        Text::creator_profile(&creator, request_context.language),
+                    )
+                    .reply_markup(Keyboard::creator_profile(
+                        &creator,
+                        &request_context.config.domain_name,
+                    )?)
+                    .link_preview_options(LinkPreviewOptions::new().is_disabled(true))
                     .reply_to_message_id(msg.id)
                     .allow_sending_without_reply(true)
                     .await?;
@@ -389,11 +469,7 @@ impl HiddenCommand {
                     &set_id,
                     &set_title,
                     &sticker.telegram_file_identifier,
-                    match sticker_file.sticker_type {
-                        crate::database::StickerType::Animated => "animated",
-                        crate::database::StickerType::Video => "video",
-                        crate::database::StickerType::Static => "static",
-                    },
+                    sticker_format(sticker_file.sticker_type),
                     &[sticker.emoji.unwrap_or("😊".to_string())],
                     &tags,
                 )
@@ -440,15 +516,202 @@ impl HiddenCommand {
                     .allow_sending_without_reply(true)
                     .await?;
             }
+            Self::NewCollection { sticker_id, name } => {
+                let name = name.trim().to_string();
+                if name.chars().count() > MAX_COLLECTION_NAME_LENGTH {
+                    return Err(UserError::ValidationError(format!(
+                        "collection names can have at most {MAX_COLLECTION_NAME_LENGTH} characters"
+                    ))
+                    .into());
+                }
+                let collection_count = request_context
+                    .database
+                    .get_sticker_collections(request_context.user.id)
+                    .await?
+                    .len();
+                if collection_count >= MAX_COLLECTIONS_PER_USER {
+                    return Err(UserError::ValidationError(format!(
+                        "you can have at most {MAX_COLLECTIONS_PER_USER} collections"
+                    ))
+                    .into());
+                }
+                let sticker = request_context
+                    .database
+                    .get_sticker_by_id(&sticker_id.into())
+                    .await?
+                    .required()?;
+                let collection = match request_context
+                    .database
+                    .create_sticker_collection(request_context.user.id, &name)
+                    .await
+                {
+                    Err(crate::database::DatabaseError::UniqueConstraintViolated(_)) => {
+                        return Err(UserError::AlreadyExists("collection".to_string()).into());
+                    }
+                    other => other?,
+                };
+                request_context
+                    .database
+                    .add_sticker_to_collection(collection.id, &sticker.id)
+                    .await?;
+
+                request_context
+                    .bot
+                    .send_sticker(
+                        msg.chat.id,
+                        InputFile::file_id(sticker.telegram_file_identifier.clone()),
+                    )
+                    .reply_markup(sticker_collections_keyboard(&sticker.id, request_context).await?)
+                    .reply_to_message_id(msg.id)
+                    .allow_sending_without_reply(true)
+                    .await?;
+            }
+            Self::ExportCollection {
+                collection_id,
+                set_title,
+            } => {
+                if handle_readonly(&request_context, &msg).await? { return Ok(()); }
+
+                let collection = request_context
+                    .database
+                    .get_sticker_collection_by_id(request_context.user.id, collection_id)
+                    .await?
+                    .required()?;
+                let stickers = request_context
+                    .database
+                    .get_collection_stickers(collection.id, MAX_EXPORTED_COLLECTION_STICKERS, 0)
+                    .await?;
+                let Some((first_sticker, other_stickers)) = stickers.split_first() else {
+                    return Err(UserError::ListHasZeroResults("stickers in this collection".to_string()).into());
+                };
+                let existing_set = request_context
+                    .database
+                    .get_owned_sticker_sets_by_bot(
+                        &request_context.config.telegram_bot_username,
+                        request_context.user.id,
+                    )
+                    .await?
+                    .into_iter()
+                    .find(|set| set.id.to_string() == set_title);
+
+                let (set_id, stickers_to_add) = if let Some(set) = existing_set {
+                    (set.id, stickers.as_slice())
+                } else {
+                    let set_id = create_sticker_set_id(
+                        &set_title,
+                        &request_context.config.telegram_bot_username,
+                    );
+                    let tags = request_context
+                        .database
+                        .get_sticker_tags(&first_sticker.id)
+                        .await?
+                        .into_iter()
+                        .take(20)
+                        .collect_vec();
+                    let sticker_file = request_context
+                        .database
+                        .get_sticker_file_by_sticker_id(&first_sticker.id)
+                        .await?
+                        .required()?;
+                    simple_bot_api::create_new_sticker_set(
+                        &request_context.config.telegram_bot_token,
+                        request_context.user_id(),
+                        &set_id,
+                        &set_title,
+                        &first_sticker.telegram_file_identifier,
+                        sticker_format(sticker_file.sticker_type),
+                        &[first_sticker.emoji.clone().unwrap_or("😊".to_string())],
+                        &tags,
+                    )
+                    .await?;
+                    request_context
+                        .database
+                        .upsert_sticker_set_with_title(
+                            &set_id,
+                            &set_title,
+                            Some(request_context.user.id),
+                        )
+                        .await?;
+                    request_context
+                        .database
+                        .upsert_sticker_set_with_creator(
+                            &set_id,
+                            request_context.user.id,
+                            Some(request_context.user.id),
+                        )
+                        .await?;
+                    (set_id, other_stickers)
+                };
+
+                for sticker in stickers_to_add {
+                    let sticker_file = request_context
+                        .database
+                        .get_sticker_file_by_sticker_id(&sticker.id)
+                        .await?
+                        .required()?;
+                    if request_context
+                        .database
+                        .sticker_set_contains_file(&set_id, &sticker_file.id)
+                        .await?
+                        .is_some()
+                    {
+                        continue;
+                    }
+                    let tags = request_context
+                        .database
+                        .get_sticker_tags(&sticker.id)
+                        .await?
+                        .into_iter()
+                        .take(20)
+                        .collect_vec();
+                    simple_bot_api::add_sticker_to_set(
+                        &request_context.config.telegram_bot_token,
+                        request_context.user_id(),
+                        &set_id,
+                        &sticker.telegram_file_identifier,
+                        sticker_format(sticker_file.sticker_type),
+                        &[sticker.emoji.clone().unwrap_or("😊".to_string())],
+                        &tags,
+                    )
+                    .await?;
+                }
+
+                request_context.services.import.queue_sticker_set_import(&set_id, true, Some(request_context.user_id()), None).await;
+
+                request_context
+                    .bot
+                    .send_markdown(
+                        msg.chat.id,
+                        Markdown::escaped(format!(
+                            "Exported the collection \"{}\" to the set: https://t.me/addstickers/{}\n\nThe set should update within an hour",
+                            collection.name, set_id
+                        )),
+                    )
+                    .await?;
+            }
             Self::Random => {
+                if !request_context.has_capability(Capability::TagMe) {
+                    return Err(UserError::NoPermissionForAction("tagme".to_string()).into());
+                }
                 let request_context = if request_context.is_continuous_tag_state() {
                     exit_mode(request_context.clone(), false).await?
                 } else {
                     request_context
                 };
+                let focus = request_context
+                    .user
+                    .settings
+                    .as_ref()
+                    .and_then(|settings| settings.tagging_focus.clone());
                 let sticker = request_context
-                    .database
-                    .get_random_sticker_to_tag()
+                    .services
+                    .tagging_queue
+                    .next_sticker(
+                        request_context.user.id,
+                        focus,
+                        request_context.tag_manager.clone(),
+                        request_context.tfidf.clone(),
+                    )
                     .await?
                     .required()?;
                 send_sticker_with_tag_input(sticker, request_context.clone(), msg.chat.id, msg.id)
@@ -499,6 +762,7 @@ impl HiddenCommand {
                                 &sticker.sticker_file_id,
                                 &tags,
                                 Some(request_context.user.id),
+                                TagOrigin::Manual,
                             )
                             .await?;
                         request_context.tfidf.request_recompute().await;
@@ -514,6 +778,8 @@ impl HiddenCommand {
                         request_context.database.clone(),
                         request_context.tfidf.clone(),
                         request_context.vector_db.clone(),
+                        request_context.services.tag_suggestion_rules.current(),
+                        request_context.services.suggestion_combiner.current(),
                         // request_context.tag_worker.clone(),
                     )
                     .await?;
@@ -567,10 +833,11 @@ impl HiddenCommand {
                     .database
                     .update_user_blacklist(request_context.user.id, blacklist.clone().into())
                     .await?;
+                let settings = request_context.user.settings.clone().unwrap_or_default();
                 request_context
                     .bot
-                    .send_markdown(msg.chat.id, Text::blacklist())
-                    .reply_markup(Keyboard::blacklist(&blacklist))
+                    .send_markdown(msg.chat.id, Text::blacklist(&settings.whitelisted_sets, request_context.language))
+                    .reply_markup(Keyboard::blacklist(&blacklist, &settings))
                     .await?;
             }
             Self::TagSet { set_name, tag } => {
@@ -619,6 +886,8 @@ async fn suggest_sticker(
             tag_state.add_tags.clone(),
             tag_state.remove_tags.clone(),
             vec![],
+            vec![],
+            vec![],
             50,
             0,
             Order::Random { seed },
@@ -816,7 +1085,7 @@ async fn modify_continuous_tag(
         .bot
         .send_markdown(
             msg.chat.id,
-            Text::get_continuous_tag_mode_text(add_tags.as_slice(), remove_tags.as_slice()),
+            Text::get_continuous_tag_mode_text(add_tags.as_slice(), remove_tags.as_slice(), request_context.language),
         )
         .reply_markup(Keyboard::make_continuous_tag_keyboard(
             true,
@@ -839,25 +1108,30 @@ async fn set_tag_operation(
     }
     let message = match operation {
         SetOperation::Tag => {
-            if let Some(tags) =
-                get_tag_implications_including_self(&tags, request_context.clone()).await?
+            if let Some((tags, taggings_changed)) = request_context
+                .services
+                .tagging
+                .tag_set(
+                    &set_name,
+                    &tags,
+                    request_context.user.id,
+                    &request_context.tag_manager,
+                    &request_context.tfidf,
+                )
+                .await?
             {
-                let taggings_changed = request_context
-                    .database
-                    .tag_all_files_in_set(&set_name, tags.as_ref(), request_context.user.id)
-                    .await?;
-                request_context.tfidf.request_recompute().await;
-                Text::tagged_set(&set_name, &tags, taggings_changed)
+                Text::tagged_set(&set_name, &tags, taggings_changed, request_context.language)
             } else {
                 Markdown::escaped("No tags changed".to_string())
             }
         }
         SetOperation::Untag => {
             let taggings_changed = request_context
-                .database
-                .untag_all_files_in_set(&set_name, &tags, request_context.user.id)
+                .services
+                .tagging
+                .untag_set(&set_name, &tags, request_context.user.id)
                 .await?;
-            Text::untagged_set(&set_name, &tags, taggings_changed)
+            Text::untagged_set(&set_name, &tags, taggings_changed, request_context.language)
         }
     };
     request_context
@@ -1035,6 +1309,15 @@ async fn suggest_sticker_recommender(
     let sticker_ids = recommended.into_iter().map(|m| m.sticker_id).collect_vec();
     for id in sticker_ids {
         if let Some(sticker) = request_context.database.get_sticker_by_id(&id).await? {
+            let Some(sticker) = request_context
+                .services
+                .sticker
+                .filter_stickers_by_rating_profile(vec![sticker], request_context.rating_profile())
+                .await?
+                .pop()
+            else {
+                continue;
+            };
             send_sticker_with_tag_input(sticker, request_context.clone(), msg.chat.id, msg.id)
                 .await?;
             return Ok(());
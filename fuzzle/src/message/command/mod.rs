@@ -1,5 +1,6 @@
 mod admin;
 mod hidden;
+mod moderator;
 mod start_parameter;
 mod user;
 mod util;
@@ -7,10 +8,16 @@ mod privacy;
 
 pub use start_parameter::StartParameter;
 
-pub use admin::{AdminCommand, send_merge_queue};
+pub use admin::AdminCommand;
+pub use moderator::{ModeratorCommand, send_merge_queue, update_moderator_command_menu};
 pub use hidden::{HiddenCommand, set_tag_id};
 use teloxide::{types::BotCommand, utils::command::BotCommands};
-pub use user::{RegularCommand, send_sticker_with_tag_input, send_database_export_to_chat};
+
+use crate::text::Language;
+pub use user::{
+    RegularCommand, check_can_review_tags, send_database_export_to_chat, send_next_tag_review,
+    send_sticker_with_tag_input,
+};
 pub use util::*;
 pub use privacy::*;
 
@@ -18,14 +25,18 @@ pub fn list_visible_admin_commands() -> Vec<BotCommand> {
     AdminCommand::list_visible()
 }
 
-pub fn list_visible_user_commands() -> Vec<BotCommand> {
-    RegularCommand::list_visible()
+pub fn list_visible_moderator_commands() -> Vec<BotCommand> {
+    ModeratorCommand::list_visible()
+}
+
+pub fn list_visible_user_commands(language: Language) -> Vec<BotCommand> {
+    RegularCommand::list_visible(language)
 }
 
 pub fn admin_command_description() -> String {
     AdminCommand::descriptions().to_string()
 }
 
-pub fn user_command_description() -> String {
-    RegularCommand::descriptions().to_string()
+pub fn moderator_command_description() -> String {
+    ModeratorCommand::descriptions().to_string()
 }
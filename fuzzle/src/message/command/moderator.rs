@@ -0,0 +1,172 @@
+use crate::background_tasks::{send_moderation_task, send_report};
+use crate::Config;
+use crate::bot::{Bot, BotError, BotExt, RequestContext, SendDocumentExt};
+use crate::database::{StickerIdStickerFileId, TrustLevel, User};
+use crate::message::Keyboard;
+use crate::sticker::generate_merge_image;
+use crate::text::{Markdown, Text};
+use crate::util::Required;
+
+use teloxide::types::{BotCommand, BotCommandScope, InputFile};
+
+use teloxide::{prelude::*, utils::command::BotCommands};
+
+use super::user::{RegularCommand, send_sticker_with_tag_input};
+
+#[derive(BotCommands, Debug)]
+#[command(rename_rule = "lowercase", description = "Moderator commands")]
+pub enum ModeratorCommand {
+    #[command(description = "MOD get the report and your pending moderation tasks")]
+    Tasks,
+
+    #[command(description = "MOD merge queue")]
+    MergeQueue,
+
+    #[command(description = "MOD stickers with conflicting tags (eg solo and duo)")]
+    TagConflicts,
+}
+
+/// number of moderation tasks that are assigned per command
+const MODERATION_TASK_COUNT: i64 = 10;
+
+/// number of stickers with conflicting tags that are sent per command
+const TAG_CONFLICT_COUNT: usize = 5;
+
+impl ModeratorCommand {
+    #[must_use]
+    pub fn list_visible() -> Vec<BotCommand> {
+        [RegularCommand::bot_commands(), Self::bot_commands()].concat()
+    }
+
+    #[tracing::instrument(skip(self, msg, request_context))]
+    pub async fn execute(
+        self,
+        msg: Message,
+        request_context: RequestContext,
+    ) -> Result<(), BotError> {
+        match self {
+            Self::Tasks => {
+                send_report(
+                    request_context.database.clone(),
+                    request_context.bot.clone(),
+                    msg.chat.id,
+                )
+                .await?;
+                let tasks = request_context
+                    .services
+                    .moderation
+                    .claim_tasks(request_context.user.id, MODERATION_TASK_COUNT)
+                    .await?;
+                if tasks.is_empty() {
+                    request_context
+                        .bot
+                        .send_markdown(msg.chat.id, Markdown::escaped("No pending moderation tasks"))
+                        .await?;
+                }
+                for task in tasks {
+                    send_moderation_task(
+                        task,
+                        &request_context.database,
+                        &request_context.bot,
+                        msg.chat.id,
+                    )
+                    .await?;
+                }
+            }
+            Self::MergeQueue => {
+                for _ in 0..10 {
+                    send_merge_queue(msg.chat.id, request_context.clone()).await?;
+                }
+            }
+            Self::TagConflicts => {
+                let conflicting = request_context
+                    .services
+                    .tag_review
+                    .find_conflicting_tags(TAG_CONFLICT_COUNT, &request_context.tag_manager)
+                    .await?;
+                if conflicting.is_empty() {
+                    request_context
+                        .bot
+                        .send_markdown(msg.chat.id, Markdown::escaped("No conflicting tags found"))
+                        .await?;
+                }
+                for conflicting in conflicting {
+                    request_context
+                        .bot
+                        .send_markdown(msg.chat.id, Text::tag_conflicts(&conflicting.conflicts, request_context.language))
+                        .await?;
+                    send_sticker_with_tag_input(
+                        conflicting.sticker,
+                        request_context.clone(),
+                        msg.chat.id,
+                        msg.id,
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// shows the moderator commands in the private chat with the user, or hides them again
+pub async fn update_moderator_command_menu(
+    bot: &Bot,
+    config: &Config,
+    user: &User,
+) -> Result<(), BotError> {
+    let user_id = UserId(user.id as u64);
+    if user_id == config.get_admin_user_id() {
+        return Ok(());
+    }
+    let scope = BotCommandScope::Chat {
+        chat_id: user_id.into(),
+    };
+    if user.trust_level == TrustLevel::Moderator {
+        bot.set_my_commands(ModeratorCommand::list_visible())
+            .scope(scope)
+            .await?;
+    } else {
+        bot.delete_my_commands().scope(scope).await?;
+    }
+    Ok(())
+}
+
+pub async fn send_merge_queue(chat_id: ChatId, request_context: RequestContext) -> Result<(), BotError> {
+    // TODO: not random
+    // TODO: maybe spawn without waiting since this may take a while
+    let Some((file_id_a, file_id_b)) = request_context.database.get_random_potential_merge_file_ids().await? else {
+        request_context.bot.send_markdown(chat_id, Markdown::escaped("No more potential merges :3")).await?;
+        return Ok(());
+    };
+
+                request_context
+                    .bot
+                    .send_chat_action(chat_id, teloxide::types::ChatAction::Typing)
+                    .await?;
+
+    let result = request_context.database.get_some_sticker_ids_for_sticker_file_ids(vec![file_id_a, file_id_b]).await?;
+    let mut result = result.into_iter();
+    let StickerIdStickerFileId {sticker_id: a, ..} = result.next().required()?;
+    let StickerIdStickerFileId { sticker_id: b, ..} = result.next().required()?;
+    let set_a = request_context.database.get_sticker_set_by_sticker_id(&a).await?.required()?;
+    let set_b = request_context.database.get_sticker_set_by_sticker_id(&b).await?.required()?;
+
+    let buf = generate_merge_image(
+        &a,
+        &b,
+        request_context.database.clone(),
+        request_context.bot.clone(),
+    )
+    .await?;
+
+    request_context.bot.send_document(
+        chat_id,
+        InputFile::memory(buf).file_name("comparison.png"),
+    )
+    .markdown_caption(Markdown::escaped("TODO: some content"))
+    .reply_markup(Keyboard::merge(&a, &b, &set_a.id, &set_b.id)?)
+    .await?;
+    Ok(())
+}
@@ -8,6 +8,7 @@ pub enum StartParameter {
     Greeting,
     Regular,
     Help,
+    Collection(i64),
 }
 
 impl FromStr for StartParameter {
@@ -19,7 +20,11 @@ impl FromStr for StartParameter {
             "blacklist" => Ok(Self::Blacklist),
             "help" => Ok(Self::Help),
             "beep" => Ok(Self::Greeting),
-            _ => Err(UserError::InvalidStartParameter),
+            _ => s
+                .strip_prefix("collection_")
+                .and_then(|collection_id| collection_id.parse().ok())
+                .map(Self::Collection)
+                .ok_or(UserError::InvalidStartParameter),
         }
     }
 }
@@ -31,6 +36,7 @@ impl Display for StartParameter {
             Self::Blacklist => write!(f, "blacklist"),
             Self::Help => write!(f, "help"),
             Self::Greeting => write!(f, "beep"),
+            Self::Collection(collection_id) => write!(f, "collection_{collection_id}"),
         }
     }
 }
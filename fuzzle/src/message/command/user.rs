@@ -1,11 +1,12 @@
-use crate::bot::{Bot, BotError, BotExt, InternalError, RequestContext, SendDocumentExt};
+use crate::bot::{Bot, BotError, BotExt, InternalError, RequestContext, SendDocumentExt, UserError};
 
-use crate::callback::exit_mode;
-use crate::database::{export_database, Database, DialogState, Sticker, TagCreator};
-use crate::message::message_handler::handle_readonly;
+use crate::callback::{exit_mode, public_collection_overview};
+use crate::database::{export_database, Capability, Database, DialogState, Sticker, TagCreator, TaggingFocus, TrustLevel};
+use crate::message::message_handler::{get_sticker_set_name_from_url, handle_readonly};
 use crate::message::Keyboard;
 use crate::tags::suggest_tags;
-use crate::text::{Markdown, Text};
+use crate::text::{Language, Markdown, Text};
+use crate::util::StickerId;
 
 use itertools::Itertools;
 use teloxide::types::{
@@ -13,24 +14,29 @@ use teloxide::types::{
     ReplyMarkup,
 };
 use tracing::warn;
+use url::Url;
 
 use teloxide::{prelude::*, utils::command::BotCommands};
 
+use super::hidden::MAX_COLLECTION_NAME_LENGTH;
 use super::privacy::PrivacyPolicy;
 use super::StartParameter;
 
-#[derive(BotCommands, Debug, Clone, Copy)]
+#[derive(BotCommands, Debug, Clone)]
 #[command(rename_rule = "lowercase", description = "Supported commands")]
 pub enum RegularCommand {
     #[command(description = "show settings")]
     Settings,
 
-    #[command(description = "get a random sticker that needs tagging")]
-    TagMe,
+    #[command(description = "get a sticker that needs tagging (optionally focus on a tag, a set link or all)")]
+    TagMe { focus: String },
 
     #[command(description = "tag multiple stickers with the same tag")]
     ContinuousTagMode,
 
+    #[command(description = "review tags that new taggers added recently")]
+    Review,
+
     #[command(description = "get sticker recommendations")]
     StickerRecommenderMode,
 
@@ -46,6 +52,24 @@ pub enum RegularCommand {
     #[command(description = "clear recently used stickers")]
     ClearRecentlyUsed,
 
+    #[command(description = "follow tags to get their new stickers in a personal feed")]
+    Follow { tags: String },
+
+    #[command(description = "stop following a tag")]
+    Unfollow { tag: String },
+
+    #[command(description = "explain a tag: description, examples and related tags")]
+    Wiki { tag: String },
+
+    #[command(description = "choose the language of the bot (en, de, ru or auto)")]
+    Language { language: String },
+
+    #[command(description = "manage your keys for the json api (new <name>, revoke <id>)")]
+    ApiKeys { args: String },
+
+    #[command(description = "list, rename or delete your sticker collections")]
+    Collections { args: String },
+
     #[command(description = "show welcome message")]
     Start { start_parameter: StartParameter },
 
@@ -57,9 +81,17 @@ pub enum RegularCommand {
 }
 
 impl RegularCommand {
+    /// the descriptions come from the catalog (`command.<name>`); the attributes above are the
+    /// english fallback for the `BotCommands` derive
     #[must_use]
-    pub fn list_visible() -> Vec<BotCommand> {
+    pub fn list_visible(language: Language) -> Vec<BotCommand> {
         Self::bot_commands()
+            .into_iter()
+            .map(|command| {
+                let key = format!("command.{}", command.command.trim_start_matches('/'));
+                BotCommand::new(command.command, language.text(&key, &[]))
+            })
+            .collect_vec()
     }
 
     #[tracing::instrument(skip(self, msg, request_context))]
@@ -75,14 +107,17 @@ impl RegularCommand {
             Self::Privacy => {
                 request_context
                     .bot
-                    .send_markdown(msg.chat.id, Text::privacy(PrivacyPolicy::Introduction))
+                    .send_markdown(msg.chat.id, Text::privacy(PrivacyPolicy::Introduction, request_context.language))
                     .reply_markup(Keyboard::privacy(PrivacyPolicy::Introduction))
                     .await?;
             }
             Self::Help => {
                 request_context
                     .bot
-                    .send_markdown(msg.chat.id, Text::get_help_text(request_context.is_admin()))
+                    .send_markdown(
+                        msg.chat.id,
+                        Text::get_help_text(request_context.is_moderator(), request_context.is_admin(), request_context.language),
+                    )
                     .reply_markup(Keyboard::make_help_keyboard())
                     .await?;
             }
@@ -90,10 +125,14 @@ impl RegularCommand {
                 let request_context = exit_mode(request_context.clone(), false).await?;
                 match start_parameter {
                     StartParameter::Blacklist => {
+                        let settings = request_context.user.settings.clone().unwrap_or_default();
                         request_context
                             .bot
-                            .send_markdown(msg.chat.id, Text::blacklist())
-                            .reply_markup(Keyboard::blacklist(&request_context.user.blacklist))
+                            .send_markdown(msg.chat.id, Text::blacklist(&settings.whitelisted_sets, request_context.language))
+                            .reply_markup(Keyboard::blacklist(
+                                &request_context.user.blacklist,
+                                &settings,
+                            ))
                             .await?;
                     }
                     StartParameter::Regular | StartParameter::Greeting => {
@@ -121,13 +160,13 @@ impl RegularCommand {
                         }
                         request_context
                             .bot
-                            .send_markdown(msg.chat.id, Text::get_start_text())
+                            .send_markdown(msg.chat.id, Text::get_start_text(request_context.language))
                             .reply_to_message_id(msg.id)
                             .allow_sending_without_reply(true)
                             .await?;
                         request_context
                             .bot
-                            .send_markdown(msg.chat.id, Text::get_main_text())
+                            .send_markdown(msg.chat.id, Text::get_main_text(request_context.language))
                             .reply_markup(Keyboard::make_main_keyboard())
                             .disable_notification(true)
                             .await?;
@@ -137,11 +176,25 @@ impl RegularCommand {
                             .bot
                             .send_markdown(
                                 msg.chat.id,
-                                Text::get_help_text(request_context.is_admin()),
+                                Text::get_help_text(
+                                    request_context.is_moderator(),
+                                    request_context.is_admin(),
+                                    request_context.language,
+                                ),
                             )
                             .reply_markup(Keyboard::make_help_keyboard())
                             .await?;
                     }
+                    StartParameter::Collection(collection_id) => {
+                        let (text, keyboard) =
+                            public_collection_overview(collection_id, request_context.clone())
+                                .await?;
+                        request_context
+                            .bot
+                            .send_markdown(msg.chat.id, text)
+                            .reply_markup(keyboard)
+                            .await?;
+                    }
                 }
             }
             Self::Settings => {
@@ -151,6 +204,7 @@ impl RegularCommand {
                         msg.chat.id,
                         Text::get_settings_text(
                             &request_context.user.settings.clone().unwrap_or_default(),
+                            request_context.language,
                         ),
                     )
                     .reply_markup(Keyboard::make_settings_keyboard(
@@ -162,14 +216,46 @@ impl RegularCommand {
                 let stats = request_context.database.get_stats().await?;
                 request_context
                     .bot
-                    .send_markdown(msg.chat.id, Text::general_stats(stats))
+                    .send_markdown(msg.chat.id, Text::general_stats(stats, request_context.language))
                     .link_preview_options(LinkPreviewOptions::new().is_disabled(true))
                     .reply_markup(Keyboard::general_stats())
                     .await?;
             }
-            Self::TagMe => {
+            Self::TagMe { focus } => {
+                if !request_context.has_capability(Capability::TagMe) {
+                    return Err(UserError::NoPermissionForAction("tagme".to_string()).into());
+                }
                 let request_context = exit_mode(request_context.clone(), false).await?;
-                let sticker = request_context.database.get_random_sticker_to_tag().await?;
+                let mut settings = request_context.user.settings.clone().unwrap_or_default();
+                let focus = focus.trim();
+                if !focus.is_empty() {
+                    settings.tagging_focus = if focus == "all" {
+                        None
+                    } else {
+                        Some(parse_tagging_focus(focus, &request_context).await?)
+                    };
+                    request_context
+                        .database
+                        .update_settings(request_context.user.id, &settings)
+                        .await?;
+                    request_context
+                        .bot
+                        .send_markdown(
+                            msg.chat.id,
+                            Text::tagging_focus(settings.tagging_focus.as_ref(), request_context.language),
+                        )
+                        .await?;
+                }
+                let sticker = request_context
+                    .services
+                    .tagging_queue
+                    .next_sticker(
+                        request_context.user.id,
+                        settings.tagging_focus.clone(),
+                        request_context.tag_manager.clone(),
+                        request_context.tfidf.clone(),
+                    )
+                    .await?;
                 if let Some(sticker) = sticker {
                     send_sticker_with_tag_input(
                         sticker,
@@ -181,9 +267,196 @@ impl RegularCommand {
                 } else {
                     request_context
                         .bot
-                        .send_markdown(msg.chat.id, Markdown::escaped("No stickers to tag!"))
+                        .send_markdown(
+                            msg.chat.id,
+                            Text::no_stickers_to_tag(
+                                settings.tagging_focus.as_ref(),
+                                request_context.language,
+                            ),
+                        )
+                        .await?;
+                }
+            }
+            Self::Follow { tags } => {
+                let tags = tags
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|tag| !tag.is_empty())
+                    .map(str::to_string)
+                    .collect_vec();
+                let followed_tags = if tags.is_empty() {
+                    request_context.user.settings.clone().unwrap_or_default().followed_tags
+                } else {
+                    request_context
+                        .services
+                        .feed
+                        .follow_tags(&request_context.user, &tags, &request_context.tag_manager)
+                        .await?
+                };
+                send_followed_tags(msg.chat.id, &followed_tags, &request_context).await?;
+            }
+            Self::Unfollow { tag } => {
+                let followed_tags = request_context
+                    .services
+                    .feed
+                    .unfollow_tag(&request_context.user, tag.trim())
+                    .await?;
+                send_followed_tags(msg.chat.id, &followed_tags, &request_context).await?;
+            }
+            Self::Wiki { tag } => {
+                let Some(tag) = request_context.tag_manager.closest_matching_tag(tag.trim()).await else {
+                    return Err(UserError::TagsNotFound(vec![tag.trim().to_string()]).into());
+                };
+                let wiki = request_context
+                    .services
+                    .tag_wiki
+                    .wiki(&tag, &request_context.tag_manager, request_context.rating_profile())
+                    .await?
+                    .ok_or_else(|| UserError::TagsNotFound(vec![tag.clone()]))?;
+                request_context
+                    .bot
+                    .send_markdown(msg.chat.id, Text::tag_wiki(&wiki, request_context.language))
+                    .reply_markup(Keyboard::tag_wiki(&wiki, &request_context.config.domain_name)?)
+                    .link_preview_options(LinkPreviewOptions::new().is_disabled(true))
+                    .await?;
+            }
+            Self::Language { language } => {
+                let language = language.trim();
+                if language.is_empty() {
+                    request_context
+                        .bot
+                        .send_markdown(msg.chat.id, Text::language_usage(request_context.language))
                         .await?;
+                    return Ok(());
+                }
+                let chosen = if language == "auto" {
+                    None
+                } else {
+                    Some(Language::from_language_code(language).ok_or_else(|| {
+                        UserError::ValidationError(format!("There is no translation for {language}"))
+                    })?)
+                };
+                let mut settings = request_context.user.settings.clone().unwrap_or_default();
+                settings.language = chosen;
+                request_context
+                    .database
+                    .update_settings(request_context.user.id, &settings)
+                    .await?;
+                let language = settings.language(
+                    msg.from().and_then(|from| from.language_code.as_deref()),
+                );
+                request_context
+                    .bot
+                    .send_markdown(msg.chat.id, Text::language_changed(chosen, language))
+                    .await?;
+            }
+            Self::ApiKeys { args } => {
+                if !msg.chat.is_private() {
+                    return Err(UserError::ValidationError(
+                        "API keys can only be managed in a private chat with the bot".to_string(),
+                    )
+                    .into());
+                }
+                if request_context.user.trust_level == TrustLevel::Banned {
+                    return Err(UserError::NoPermissionForAction("api keys".to_string()).into());
+                }
+                let api_keys = &request_context.services.api_key;
+                let mut words = args.split_whitespace();
+                match words.next() {
+                    None => {}
+                    Some("new") => {
+                        let name = words.join(" ");
+                        let (key, secret) = api_keys.create_key(request_context.user.id, &name).await?;
+                        request_context
+                            .bot
+                            .send_markdown(msg.chat.id, Text::api_key_created(&key, &secret, request_context.language))
+                            .await?;
+                        return Ok(());
+                    }
+                    Some("revoke") => {
+                        let Some(Ok(id)) = words.next().map(str::parse::<i64>) else {
+                            return Err(UserError::ValidationError(
+                                "Usage: /apikeys revoke <id>".to_string(),
+                            )
+                            .into());
+                        };
+                        api_keys.revoke_key(request_context.user.id, id).await?;
+                    }
+                    Some(_) => {
+                        return Err(UserError::ValidationError(
+                            "Usage: /apikeys, /apikeys new <name> or /apikeys revoke <id>".to_string(),
+                        )
+                        .into());
+                    }
                 }
+                let keys = api_keys.list_keys(request_context.user.id).await?;
+                request_context
+                    .bot
+                    .send_markdown(
+                        msg.chat.id,
+                        Text::api_keys(&keys, &request_context.config.domain_name, request_context.language),
+                    )
+                    .await?;
+            }
+            Self::Collections { args } => {
+                let user_id = request_context.user.id;
+                let mut words = args.split_whitespace();
+                match (words.next(), words.next().map(str::parse::<i64>)) {
+                    (None, _) => {}
+                    (Some("rename"), Some(Ok(collection_id))) => {
+                        if handle_readonly(&request_context, &msg).await? { return Ok(()); }
+                        let name = words.join(" ");
+                        if name.is_empty() || name.chars().count() > MAX_COLLECTION_NAME_LENGTH {
+                            return Err(UserError::ValidationError(format!(
+                                "collection names need 1 to {MAX_COLLECTION_NAME_LENGTH} characters"
+                            ))
+                            .into());
+                        }
+                        let collection = request_context
+                            .database
+                            .get_sticker_collection_by_id(user_id, collection_id)
+                            .await?
+                            .ok_or_else(|| UserError::ListHasZeroResults("collection with this id".to_string()))?;
+                        match request_context
+                            .database
+                            .rename_sticker_collection(user_id, collection.id, &name)
+                            .await
+                        {
+                            Err(crate::database::DatabaseError::UniqueConstraintViolated(_)) => {
+                                return Err(UserError::AlreadyExists("collection".to_string()).into());
+                            }
+                            other => other?,
+                        }
+                    }
+                    (Some("delete"), Some(Ok(collection_id))) => {
+                        if handle_readonly(&request_context, &msg).await? { return Ok(()); }
+                        let collection = request_context
+                            .database
+                            .get_sticker_collection_by_id(user_id, collection_id)
+                            .await?
+                            .ok_or_else(|| UserError::ListHasZeroResults("collection with this id".to_string()))?;
+                        request_context
+                            .database
+                            .delete_sticker_collection(user_id, collection.id)
+                            .await?;
+                    }
+                    _ => {
+                        return Err(UserError::ValidationError(
+                            "Usage: /collections, /collections rename <id> <name> or /collections delete <id>".to_string(),
+                        )
+                        .into());
+                    }
+                }
+                let collections = request_context.database.get_sticker_collections(user_id).await?;
+                request_context
+                    .bot
+                    .send_markdown(msg.chat.id, Text::own_collections(&collections, request_context.language))
+                    .await?;
+            }
+            Self::Review => {
+                if handle_readonly(&request_context, &msg).await? { return Ok(()); }
+                let request_context = exit_mode(request_context.clone(), false).await?;
+                check_can_review_tags(&request_context)?;
+                send_next_tag_review(msg.chat.id, None, request_context).await?;
             }
             Self::ContinuousTagMode => {
                 if handle_readonly(&request_context, &msg).await? { return Ok(()); }
@@ -209,6 +482,7 @@ impl RegularCommand {
                             Text::get_continuous_tag_mode_text(
                                 continuous_tag.add_tags.as_slice(),
                                 continuous_tag.remove_tags.as_slice(),
+                                request_context.language,
                             ),
                         )
                         .reply_markup(Keyboard::make_continuous_tag_keyboard(
@@ -221,6 +495,9 @@ impl RegularCommand {
             }},
             Self::TagCreator => {
                 if handle_readonly(&request_context, &msg).await? { return Ok(()); }
+                if !request_context.has_capability(Capability::CreateTags) {
+                    return Err(UserError::NoPermissionForAction("create tag".to_string()).into());
+                }
 
                 match request_context.dialog_state() {
                 DialogState::Normal
@@ -278,6 +555,7 @@ impl RegularCommand {
                         Text::sticker_recommender_text(
                             positive_sticker_id.len(),
                             negative_sticker_id.len(),
+                            request_context.language,
                         ),
                     )
                     .reply_markup(ReplyMarkup::Keyboard(
@@ -324,6 +602,26 @@ impl RegularCommand {
     }
 }
 
+/// a set link or a tag (eg a species)
+async fn parse_tagging_focus(
+    focus: &str,
+    request_context: &RequestContext,
+) -> Result<TaggingFocus, BotError> {
+    if let Some(set_id) = Url::parse(focus)
+        .or_else(|_| Url::parse(&format!("https://{focus}")))
+        .ok()
+        .and_then(|url| get_sticker_set_name_from_url(&url))
+    {
+        return Ok(TaggingFocus::Set(set_id));
+    }
+    let tag = request_context
+        .tag_manager
+        .closest_matching_tag(focus)
+        .await
+        .ok_or_else(|| UserError::TagsNotFound(vec![focus.to_string()]))?;
+    Ok(TaggingFocus::Tag(tag))
+}
+
 pub async fn send_sticker_with_tag_input(
     sticker: Sticker,
     request_context: RequestContext,
@@ -349,6 +647,8 @@ pub async fn send_sticker_with_tag_input(
                     request_context.database.clone(),
                     request_context.tfidf.clone(),
                     request_context.vector_db.clone(),
+                    request_context.services.tag_suggestion_rules.current(),
+                    request_context.services.suggestion_combiner.current(),
                     // request_context.tag_worker.clone(),
                 )
                 .await?;
@@ -391,6 +691,74 @@ pub async fn send_sticker_with_tag_input(
     Ok(())
 }
 
+/// the feed links are only shown in private chats since they are not protected otherwise
+async fn send_followed_tags(
+    chat_id: ChatId,
+    followed_tags: &[String],
+    request_context: &RequestContext,
+) -> Result<(), BotError> {
+    let feed_path = request_context
+        .services
+        .feed
+        .personal_feed_path(request_context.user.id);
+    let feed_url =
+        |format: &str| format!("https://{}/feeds/{format}/{feed_path}", request_context.config.domain_name);
+    let (atom_url, json_url) = (feed_url("atom"), feed_url("json"));
+    let feed_urls = chat_id.is_user().then_some((atom_url.as_str(), json_url.as_str()));
+    request_context
+        .bot
+        .send_markdown(
+            chat_id,
+            Text::followed_tags(followed_tags, feed_urls, request_context.language),
+        )
+        .link_preview_options(LinkPreviewOptions::new().is_disabled(true))
+        .await?;
+    Ok(())
+}
+
+pub fn check_can_review_tags(request_context: &RequestContext) -> Result<(), BotError> {
+    if request_context.can_tag_stickers()
+        && request_context.has_capability(Capability::ReviewTags)
+    {
+        Ok(())
+    } else {
+        Err(UserError::NoPermissionForAction("review tags".to_string()).into())
+    }
+}
+
+/// `after` is the sticker that was shown before; without it, the review starts from the oldest tag
+pub async fn send_next_tag_review(
+    chat_id: ChatId,
+    after: Option<&StickerId>,
+    request_context: RequestContext,
+) -> Result<(), BotError> {
+    let review = request_context
+        .services
+        .tag_review
+        .next_review(request_context.user.id, after, &request_context.tag_manager)
+        .await?;
+    let Some(review) = review else {
+        request_context
+            .bot
+            .send_markdown(chat_id, Text::no_tags_to_review(request_context.language))
+            .await?;
+        return Ok(());
+    };
+    request_context
+        .bot
+        .send_markdown(chat_id, Text::tag_review(&review, request_context.language))
+        .await?;
+    request_context
+        .bot
+        .send_sticker(
+            chat_id,
+            InputFile::file_id(review.sticker.telegram_file_identifier.clone()),
+        )
+        .reply_markup(Keyboard::tag_review(&review.sticker.id, &review.tags))
+        .await?;
+    Ok(())
+}
+
 pub async fn send_database_export_to_chat(
     chat_id: ChatId,
     database: Database,
@@ -5,19 +5,20 @@ use crate::{
     bot::InternalError,
     callback::CallbackData,
     database::{
-        ModerationTaskStatus, Sticker, StickerChange, Tag, TagCreator, UserSettings, UserStats,
-        UserStickerStat,
+        ModerationTaskStatus, Sticker, StickerChange, StickerCollection, StickerFileTag, StickerSet, Tag,
+        TagCreator, UserSettings, UserStats, UserStickerStat,
     },
-    inline::{InlineQueryData, SetOperation, TagKind},
-    tags::{self, Category, Characters, all_count_tags, all_rating_tags, character_count, rating},
+    inline::{InlineQueryData, SetOperation, SimilarityAspect, TagKind},
+    services::{Creator, SetComparison, TagWiki},
+    tags::{self, Category, Characters, Rating, SuggestedTag, all_count_tags, all_rating_tags, character_count, rating},
     util::{Emoji, StickerId, StickerSetId, format_relative_time},
 };
 use chrono::NaiveDateTime;
 use itertools::Itertools;
-use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, LoginUrl, UserId};
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, LoginUrl, UserId, WebAppInfo};
 use url::Url;
 
-use super::PrivacyPolicy;
+use super::{PrivacyPolicy, StartParameter};
 
 pub struct Keyboard;
 
@@ -26,7 +27,7 @@ impl Keyboard {
     pub fn tagging(
         current_tags: &[String],
         sticker_unique_id: &StickerId,
-        suggested_tags: &[String],
+        suggestions: &[SuggestedTag],
         tagging_locked: bool,
         is_continuous_tag: bool,
         tag_manager: TagManagerService,
@@ -69,6 +70,7 @@ impl Keyboard {
                     button_layout_to_keyboard_layout(
                         button_layout,
                         current_tags,
+                        suggestions,
                         sticker_unique_id,
                         tag_manager,
                     )?,
@@ -99,8 +101,9 @@ impl Keyboard {
 
         button_layout.push(vec![]);
 
-        let suggested_tags = suggested_tags
+        let suggested_tags = suggestions
             .iter()
+            .map(|suggestion| &suggestion.tag)
             .filter(|tag| {
                 // TODO: instead of filtering this, allow these suggestions + remove incompatible tags if clicked (eg safe is present, user clicks explicit, -> safe is removed)
                 character_count(&(*tag).to_string()).is_none()
@@ -129,6 +132,7 @@ impl Keyboard {
             button_layout_to_keyboard_layout(
                 button_layout,
                 current_tags,
+                suggestions,
                 sticker_unique_id,
                 tag_manager,
             )?,
@@ -180,7 +184,7 @@ impl Keyboard {
     // }
 
     #[must_use]
-    pub fn blacklist(current_blacklist: &[String]) -> InlineKeyboardMarkup {
+    pub fn blacklist(current_blacklist: &[String], settings: &UserSettings) -> InlineKeyboardMarkup {
         let mut keyboard: Vec<Vec<InlineKeyboardButton>> = vec![];
 
         keyboard.push(vec![InlineKeyboardButton::callback(
@@ -189,9 +193,29 @@ impl Keyboard {
         )]);
 
         for tag in current_blacklist {
+            let mode = settings.blacklist_mode(tag);
+            let set_mode = CallbackData::SetBlacklistMode {
+                tag: tag.to_string(),
+                mode: mode.next(),
+            };
+            let remove = CallbackData::RemoveBlacklistedTag(tag.to_string());
+            if !set_mode.fits() || !remove.fits() {
+                tracing::warn!(tag, "blacklisted tag is too long for the keyboard");
+                continue;
+            }
+            keyboard.push(vec![
+                InlineKeyboardButton::callback(
+                    format!("{} {}", mode.to_emoji(), mode.description()),
+                    set_mode,
+                ),
+                InlineKeyboardButton::callback(format!("Remove \"{tag}\""), remove),
+            ]);
+        }
+
+        for (index, set_id) in settings.whitelisted_sets.iter().enumerate() {
             keyboard.push(vec![InlineKeyboardButton::callback(
-                format!("Remove \"{tag}\""),
-                CallbackData::RemoveBlacklistedTag(tag.to_string()),
+                format!("Remove exception for \"{set_id}\""),
+                CallbackData::RemoveWhitelistedSet(index),
             )]);
         }
 
@@ -322,6 +346,20 @@ impl Keyboard {
         ]))
     }
 
+    pub fn report_sticker_task(
+        status: ModerationTaskStatus,
+        creator_id: i64,
+        task_id: i64,
+        set_id: Option<&StickerSetId>,
+    ) -> Result<InlineKeyboardMarkup, InternalError> {
+        let mut markup =
+            InlineKeyboardMarkup::new(vec![Self::moderation_task_common(status, creator_id, task_id)]);
+        if let Some(set_id) = set_id {
+            markup = markup.append_row(vec![set_button(set_id)?]);
+        }
+        Ok(markup)
+    }
+
     #[must_use]
     pub fn review_new_sets_task(
         status: ModerationTaskStatus,
@@ -626,6 +664,57 @@ impl Keyboard {
         InlineKeyboardMarkup::new([stat_tabs(StatTab::Popular)])
     }
 
+    /// related tags open their own wiki page
+    #[must_use]
+    pub fn tag_wiki(wiki: &TagWiki, domain_name: &str) -> Result<InlineKeyboardMarkup, InternalError> {
+        const MAX_RELATED_TAGS: usize = 12;
+        let mut markup = InlineKeyboardMarkup::new([[
+            InlineKeyboardButton::switch_inline_query_current_chat(
+                "🔍 Show stickers",
+                InlineQueryData::search(vec![wiki.tag.clone()]),
+            ),
+            InlineKeyboardButton::url(
+                "🌐 Website",
+                Url::parse(&format!("https://{domain_name}/wiki/{}", wiki.tag))?,
+            ),
+        ]]);
+        let related = wiki
+            .implications
+            .iter()
+            .map(|tag| (format!("⬆️ {tag}"), tag))
+            .chain(wiki.inverse_implications.iter().map(|tag| (format!("⬇️ {tag}"), tag)))
+            // callback data is limited to 64 bytes
+            .filter(|(_, tag)| CallbackData::TagWiki((*tag).clone()).to_string().len() <= 64)
+            .take(MAX_RELATED_TAGS)
+            .collect_vec();
+        for row in related.chunks(3) {
+            markup = markup.append_row(
+                row.iter()
+                    .map(|(label, tag)| {
+                        InlineKeyboardButton::callback(label.clone(), CallbackData::TagWiki((*tag).clone()))
+                    })
+                    .collect_vec(),
+            );
+        }
+        Ok(markup)
+    }
+
+    #[must_use]
+    pub fn reverse_image_search(matches: &[(Sticker, f32)]) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::new(matches.iter().map(|(sticker, score)| {
+            vec![
+                InlineKeyboardButton::switch_inline_query_current_chat(
+                    format!("{} ({:.0}%)", sticker.sticker_set_id, score * 100.0),
+                    InlineQueryData::set_stickers_by_date(sticker.id.clone()),
+                ),
+                InlineKeyboardButton::switch_inline_query_current_chat(
+                    "🖼️ Similar",
+                    InlineQueryData::similar(sticker.id.clone(), SimilarityAspect::Embedding),
+                ),
+            ]
+        }))
+    }
+
     #[must_use]
     pub fn latest_sets() -> InlineKeyboardMarkup {
         InlineKeyboardMarkup::new([stat_tabs(StatTab::LatestSets)])
@@ -718,19 +807,39 @@ impl Keyboard {
             ),
         };
 
+        let ratings = [Rating::Safe, Rating::Questionable, Rating::Explicit]
+            .into_iter()
+            .map(|rating| {
+                let selected = if rating == settings.rating_profile() { "✅ " } else { "" };
+                InlineKeyboardButton::callback(
+                    format!("{selected}{} {rating}", rating.to_emoji()),
+                    CallbackData::SetRatingProfile(rating),
+                )
+            })
+            .collect_vec();
+
         InlineKeyboardMarkup::new([
-            [InlineKeyboardButton::callback(
+            vec![InlineKeyboardButton::callback(
                 "🔙 Start",
                 CallbackData::Start,
             )],
-            [order],
-            [InlineKeyboardButton::callback(
+            vec![order],
+            ratings,
+            vec![InlineKeyboardButton::callback(
                 "Blacklist",
                 CallbackData::Blacklist,
             )],
         ])
     }
 
+    #[must_use]
+    pub fn apply_suggestion_weights() -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::new([[InlineKeyboardButton::callback(
+            "✅ Apply trained weights",
+            CallbackData::ApplySuggestionWeights,
+        )]])
+    }
+
     #[must_use]
     pub fn ui(domain_name: String) -> Result<InlineKeyboardMarkup, InternalError> {
         Ok(InlineKeyboardMarkup::new([[InlineKeyboardButton::login(
@@ -783,7 +892,7 @@ impl Keyboard {
         set_id_a: &StickerSetId,
         set_id_b: &StickerSetId,
     ) -> Result<InlineKeyboardMarkup, InternalError> {
-        Ok(InlineKeyboardMarkup::new(vec![
+        let mut markup = InlineKeyboardMarkup::new(vec![
             if set_id_a == set_id_b {
                 vec![set_button(set_id_a)?]
             } else {
@@ -799,9 +908,52 @@ impl Keyboard {
                     CallbackData::merge(sticker_id_a.clone(), sticker_id_b.clone(), false),
                 ),
             ],
+        ]);
+        if set_id_a != set_id_b {
+            markup = markup.append_row(vec![InlineKeyboardButton::callback(
+                "⚖️ Compare sets",
+                CallbackData::CompareSets {
+                    sticker_id_a: sticker_id_a.clone(),
+                    sticker_id_b: sticker_id_b.clone(),
+                },
+            )]);
+        }
+        Ok(markup)
+    }
+
+    #[must_use]
+    pub fn set_comparison(
+        comparison: &SetComparison,
+        domain_name: &str,
+    ) -> Result<InlineKeyboardMarkup, InternalError> {
+        let open_set = |set: &StickerSet| -> Result<InlineKeyboardButton, InternalError> {
+            Ok(InlineKeyboardButton::url(
+                set.title_or_id(),
+                Url::parse(&format!("https://t.me/addstickers/{}", set.id))?,
+            ))
+        };
+        Ok(InlineKeyboardMarkup::new(vec![
+            vec![open_set(&comparison.set_a)?, open_set(&comparison.set_b)?],
+            vec![InlineKeyboardButton::url(
+                "🌐 Website",
+                compare_sets_url(&comparison.set_a.id, &comparison.set_b.id, domain_name)?,
+            )],
         ]))
     }
 
+    /// attached to the results of the set overlap search
+    #[must_use]
+    pub fn compare_sets_link(
+        set_id_a: &StickerSetId,
+        set_id_b: &StickerSetId,
+        domain_name: &str,
+    ) -> Result<InlineKeyboardMarkup, InternalError> {
+        Ok(InlineKeyboardMarkup::new([[InlineKeyboardButton::url(
+            "⚖️ Compare sets",
+            compare_sets_url(set_id_a, set_id_b, domain_name)?,
+        )]]))
+    }
+
     #[must_use]
     pub fn merge_done(
         set_id_a: &StickerSetId,
@@ -819,10 +971,12 @@ impl Keyboard {
         sticker_id: &StickerId,
         set_id: &StickerSetId,
         created_at: NaiveDateTime,
-    ) -> InlineKeyboardMarkup {
+        is_whitelisted: bool,
+        domain_name: &str,
+    ) -> Result<InlineKeyboardMarkup, InternalError> {
         let now = chrono::Utc::now().naive_utc();
 
-        InlineKeyboardMarkup::new(vec![
+        Ok(InlineKeyboardMarkup::new(vec![
             sticker_tabs(StickerTab::Set, sticker_id),
             vec![
                 InlineKeyboardButton::callback(
@@ -833,6 +987,7 @@ impl Keyboard {
                     format!("🚩 Report Set"),
                     InlineQueryData::ReportSet {
                         set_id: set_id.clone(),
+                        note: None,
                     },
                 ),
             ],
@@ -858,7 +1013,27 @@ impl Keyboard {
                 format!("➖ Remove tags from all stickers in the set \"{set_id}\""),
                 InlineQueryData::set_operation(set_id.clone(), vec![], SetOperation::Untag),
             )],
-        ])
+            vec![InlineKeyboardButton::callback(
+                if is_whitelisted {
+                    "✅ Blacklist exception (tap to remove)"
+                } else {
+                    "🛡️ Ignore blacklist for this set"
+                },
+                CallbackData::ToggleSetWhitelist {
+                    sticker_id: sticker_id.clone(),
+                    whitelist: !is_whitelisted,
+                },
+            )],
+            vec![InlineKeyboardButton::web_app(
+                "🧩 Browse and tag in the app",
+                WebAppInfo {
+                    url: Url::parse_with_params(
+                        &format!("https://{domain_name}/webapp"),
+                        &[("redirect", format!("/app/set/{set_id}"))],
+                    )?,
+                },
+            )],
+        ]))
     }
 
     #[must_use]
@@ -969,8 +1144,13 @@ impl Keyboard {
         owner_username: Option<String>,
         owner_tags: Vec<Tag>,
         channel_usernames: Vec<(i64, String)>,
-    ) -> InlineKeyboardMarkup {
-        InlineKeyboardMarkup::new(
+        domain_name: &str,
+    ) -> Result<InlineKeyboardMarkup, InternalError> {
+        let profile_row = match sticker_pack_owner_user_id {
+            Some(user_id) => vec![vec![creator_profile_button(user_id, domain_name)?]],
+            None => vec![],
+        };
+        Ok(InlineKeyboardMarkup::new(
             vec![
                 vec![sticker_tabs(StickerTab::Owner, sticker_id)],
                 Self::owner(
@@ -980,28 +1160,117 @@ impl Keyboard {
                     owner_tags,
                     channel_usernames,
                 ),
+                profile_row,
             ]
             .concat(),
-        )
+        ))
     }
 
     #[must_use]
-    pub fn owner_standalone(
-        sticker_pack_owner_user_id: Option<i64>,
-        set_count: i64,
-        owner_username: Option<String>,
-        owner_tags: Vec<Tag>,
-        channel_usernames: Vec<(i64, String)>,
-    ) -> InlineKeyboardMarkup {
-        InlineKeyboardMarkup::new(Self::owner(
-            sticker_pack_owner_user_id,
-            set_count,
-            owner_username,
-            owner_tags,
-            channel_usernames,
+    pub fn creator_profile(creator: &Creator, domain_name: &str) -> Result<InlineKeyboardMarkup, InternalError> {
+        Ok(InlineKeyboardMarkup::new(
+            vec![
+                Self::owner(
+                    Some(creator.user_id),
+                    creator.set_count,
+                    creator.username.clone(),
+                    creator.linked_tags.clone(),
+                    creator.channels.clone(),
+                ),
+                vec![vec![creator_profile_button(creator.user_id, domain_name)?]],
+            ]
+            .concat(),
         ))
     }
 
+    pub fn sticker_collections(
+        sticker_id: &StickerId,
+        collections: &[StickerCollection],
+        containing_collection_ids: &[i64],
+        bot_username: &str,
+    ) -> Result<InlineKeyboardMarkup, InternalError> {
+        let mut markup = InlineKeyboardMarkup::new(vec![sticker_tabs(StickerTab::Sticker, sticker_id)]);
+        for collection in collections {
+            let contains_sticker = containing_collection_ids.contains(&collection.id);
+            let mut row = vec![
+                InlineKeyboardButton::callback(
+                    if contains_sticker {
+                        format!("✅ {}", collection.name)
+                    } else {
+                        format!("➕ {}", collection.name)
+                    },
+                    CallbackData::ToggleCollectionSticker {
+                        sticker_id: sticker_id.clone(),
+                        collection_id: collection.id,
+                        add: !contains_sticker,
+                    },
+                ),
+                InlineKeyboardButton::switch_inline_query_current_chat(
+                    "🔍",
+                    InlineQueryData::collection(collection.name.clone()),
+                ),
+                InlineKeyboardButton::switch_inline_query_current_chat(
+                    "📤",
+                    InlineQueryData::export_collection(collection.id),
+                ),
+                InlineKeyboardButton::callback(
+                    if collection.is_public { "🌐" } else { "🔒" },
+                    CallbackData::SetCollectionPublic {
+                        sticker_id: sticker_id.clone(),
+                        collection_id: collection.id,
+                        public: !collection.is_public,
+                    },
+                ),
+            ];
+            if collection.is_public {
+                row.push(collection_share_button(collection.id, bot_username)?);
+            }
+            markup = markup.append_row(row);
+        }
+        Ok(markup
+            .append_row(vec![InlineKeyboardButton::switch_inline_query_current_chat(
+                "🆕 New collection",
+                InlineQueryData::create_collection(sticker_id.clone()),
+            )])
+            .append_row(vec![InlineKeyboardButton::callback(
+                "⬅️ Back",
+                CallbackData::StickerExplorePage {
+                    sticker_id: sticker_id.clone(),
+                },
+            )]))
+    }
+
+    pub fn public_collection(
+        collection_id: i64,
+        is_owner: bool,
+        is_following: bool,
+        bot_username: &str,
+        domain_name: &str,
+    ) -> Result<InlineKeyboardMarkup, InternalError> {
+        let mut markup = InlineKeyboardMarkup::new(vec![vec![
+            InlineKeyboardButton::switch_inline_query_current_chat(
+                "🔍 Browse",
+                InlineQueryData::public_collection(collection_id),
+            ),
+        ]]);
+        if !is_owner {
+            markup = markup.append_row(vec![InlineKeyboardButton::callback(
+                if is_following { "✅ Following" } else { "➕ Follow" },
+                CallbackData::FollowCollection {
+                    collection_id,
+                    follow: !is_following,
+                },
+            )]);
+        }
+        Ok(markup.append_row(vec![
+            InlineKeyboardButton::url(
+                "🌐 Website",
+                Url::parse(&format!("https://{domain_name}/collection/{collection_id}"))?,
+            ),
+            collection_share_button(collection_id, bot_username)?,
+        ]))
+    }
+
     #[must_use]
     pub fn sticker_explore_page(
         sticker_id: &StickerId,
@@ -1034,6 +1303,21 @@ impl Keyboard {
                             },
                         ),
                     ],
+                    vec![
+                        InlineKeyboardButton::callback(
+                            "🗃️ Collections",
+                            CallbackData::CollectionsPage {
+                                sticker_id: sticker_id.clone(),
+                            },
+                        ),
+                        InlineKeyboardButton::switch_inline_query_current_chat(
+                            "🚩 Report Sticker",
+                            InlineQueryData::ReportSticker {
+                                sticker_id: sticker_id.clone(),
+                                note: None,
+                            },
+                        ),
+                    ],
                 ],
                 if let Some(emoji) = emoji {
                     vec![vec![
@@ -1088,6 +1372,38 @@ impl Keyboard {
         InlineKeyboardMarkup::new(privacy_tabs(section))
     }
 
+    #[must_use]
+    pub fn tag_review(sticker_id: &StickerId, tags: &[StickerFileTag]) -> InlineKeyboardMarkup {
+        let review_buttons = tags.iter().map(|tag| {
+            vec![
+                InlineKeyboardButton::callback(
+                    format!("✅ {}", tag.tag),
+                    CallbackData::ReviewTag {
+                        sticker_id: sticker_id.clone(),
+                        tag: tag.tag.clone(),
+                        accept: true,
+                    },
+                ),
+                InlineKeyboardButton::callback(
+                    format!("❌ {}", tag.tag),
+                    CallbackData::ReviewTag {
+                        sticker_id: sticker_id.clone(),
+                        tag: tag.tag.clone(),
+                        accept: false,
+                    },
+                ),
+            ]
+        });
+        InlineKeyboardMarkup::new(review_buttons.chain(std::iter::once(vec![
+            InlineKeyboardButton::callback(
+                "Next ➡️",
+                CallbackData::NextTagReview {
+                    after: Some(sticker_id.clone()),
+                },
+            ),
+        ])))
+    }
+
     pub fn continuous_tag_confirm(sticker_id: &StickerId) -> InlineKeyboardMarkup {
         InlineKeyboardMarkup::new([[InlineKeyboardButton::callback(
             format!("Apply selected tags"),
@@ -1224,6 +1540,38 @@ fn user_button(user_id: UserId) -> InlineKeyboardButton {
     )
 }
 
+fn creator_profile_button(user_id: i64, domain_name: &str) -> Result<InlineKeyboardButton, InternalError> {
+    Ok(InlineKeyboardButton::url(
+        "👤 Creator profile",
+        Url::parse(&format!("https://{domain_name}/creator/{user_id}"))?,
+    ))
+}
+
+fn collection_share_button(
+    collection_id: i64,
+    bot_username: &str,
+) -> Result<InlineKeyboardButton, InternalError> {
+    let start_parameter = StartParameter::Collection(collection_id);
+    Ok(InlineKeyboardButton::url(
+        "🔗",
+        Url::parse_with_params(
+            "https://t.me/share/url",
+            &[(
+                "url",
+                format!("https://t.me/{bot_username}?start={start_parameter}"),
+            )],
+        )?,
+    ))
+}
+
+fn compare_sets_url(
+    set_id_a: &StickerSetId,
+    set_id_b: &StickerSetId,
+    domain_name: &str,
+) -> Result<Url, InternalError> {
+    Ok(Url::parse(&format!("https://{domain_name}/compare/{set_id_a}/{set_id_b}"))?)
+}
+
 fn set_button(set_id: &StickerSetId) -> Result<InlineKeyboardButton, InternalError> {
     Ok(InlineKeyboardButton::url(
         "Open Set",
@@ -1234,14 +1582,31 @@ fn set_button(set_id: &StickerSetId) -> Result<InlineKeyboardButton, InternalErr
 fn button_layout_to_keyboard_layout(
     button_layout: Vec<Vec<String>>,
     current_tags: &[String],
+    suggestions: &[SuggestedTag],
     sticker_unique_id: &StickerId,
     tag_manager: TagManagerService,
 ) -> Result<Vec<Vec<InlineKeyboardButton>>, InternalError> {
+    // the rank of a suggestion is its position among the suggestions that got a button, which is
+    // the order they are shown in; this way the acceptance of each displayed rank can be measured
+    let displayed_suggestions = suggestions
+        .iter()
+        .filter(|suggestion| !current_tags.contains(&suggestion.tag))
+        .filter(|suggestion| button_layout.iter().flatten().any(|tag| tag == &suggestion.tag))
+        .cloned()
+        .collect_vec();
     let keyboard = button_layout
         .iter()
         .map(|row| {
             row.iter()
-                .map(|tag| tag_to_button(tag, current_tags, sticker_unique_id, tag_manager.clone()))
+                .map(|tag| {
+                    tag_to_button(
+                        tag,
+                        current_tags,
+                        &displayed_suggestions,
+                        sticker_unique_id,
+                        tag_manager.clone(),
+                    )
+                })
                 .collect_vec()
         })
         .collect_vec();
@@ -1251,12 +1616,23 @@ fn button_layout_to_keyboard_layout(
 fn tag_to_button(
     tag: &str,
     current_tags: &[String],
+    suggestions: &[SuggestedTag],
     sticker_unique_id: &StickerId,
     tag_manager: TagManagerService,
 ) -> InlineKeyboardButton {
     let is_already_tagged = current_tags.contains(&tag.to_string());
+    let suggestion = suggestions
+        .iter()
+        .find_position(|suggestion| suggestion.tag == tag);
     let callback_data = if is_already_tagged {
         CallbackData::untag_sticker(sticker_unique_id.clone(), tag.to_string())
+    } else if let Some((position, suggestion)) = suggestion {
+        CallbackData::tag_sticker_from_suggestion(
+            sticker_unique_id.clone(),
+            tag.to_string(),
+            u8::try_from(position + 1).unwrap_or(u8::MAX),
+            suggestion.source,
+        )
     } else {
         CallbackData::tag_sticker(sticker_unique_id.clone(), tag.to_string())
     };
@@ -30,8 +30,8 @@ use crate::{
         report_internal_error_result,
     },
     callback::TagOperation,
-    database::DialogState,
-    sticker::fetch_sticker_file,
+    database::{DialogState, TagOrigin},
+    sticker::{fetch_sticker_file, find_with_image},
     tags::suggest_tags,
     text::{Markdown, Text},
     util::{Emoji, Required, parse_emoji},
@@ -41,7 +41,7 @@ use super::send_database_export_to_chat;
 use super::{
     Keyboard,
     command::{
-        AdminCommand, HiddenCommand, RegularCommand, fix_underline_command_separator_and_normalize,
+        AdminCommand, HiddenCommand, ModeratorCommand, RegularCommand, fix_underline_command_separator_and_normalize,
     },
     send_sticker_with_tag_input,
 };
@@ -71,7 +71,7 @@ fn get_all_urls_from_entities(entities: Vec<MessageEntityRef<'_>>) -> Vec<Url> {
         .collect_vec()
 }
 
-fn get_sticker_set_name_from_url(url: &Url) -> Option<StickerSetId> {
+pub fn get_sticker_set_name_from_url(url: &Url) -> Option<StickerSetId> {
     match url.host_str() {
         Some("t.me") => {
             let path = url.path();
@@ -128,7 +128,7 @@ async fn handle_sticker_sets(
         .bot
         .send_markdown(
             msg.chat.id,
-            Text::get_processed_sticker_sets_text(potential_sticker_set_names),
+            Text::get_processed_sticker_sets_text(potential_sticker_set_names, request_context.language),
         )
         .reply_to_message_id(msg.id)
         .await?;
@@ -201,14 +201,20 @@ async fn handle_text_message(
         Err(err) => err,
     };
 
-    if request_context.is_admin() {
-        if is_unknown_command(&err) {
-            match AdminCommand::parse(text, &request_context.config.telegram_bot_username) {
-                Ok(command) => command.execute(msg, request_context).await,
-                Err(err) => Err(UserError::CommandError(err).into()),
-            }
-        } else {
-            Err(UserError::CommandError(err).into())
+    if !request_context.is_moderator() || !is_unknown_command(&err) {
+        return Err(UserError::CommandError(err).into());
+    }
+    let err = match ModeratorCommand::parse(text, &request_context.config.telegram_bot_username) {
+        Ok(command) => {
+            return command.execute(msg, request_context).await;
+        }
+        Err(err) => err,
+    };
+
+    if request_context.is_admin() && is_unknown_command(&err) {
+        match AdminCommand::parse(text, &request_context.config.telegram_bot_username) {
+            Ok(command) => command.execute(msg, request_context).await,
+            Err(err) => Err(UserError::CommandError(err).into()),
         }
     } else {
         Err(UserError::CommandError(err).into())
@@ -241,7 +247,7 @@ pub async fn show_error(
     request_context: RequestContext,
     error: BotError,
 ) -> Result<(), BotError> {
-    let error = error.end_user_error();
+    let error = error.end_user_error(request_context.language);
     let icon = match error.1 {
         crate::bot::UserErrorSeverity::Error => "⚠️",
         crate::bot::UserErrorSeverity::Info => "ℹ️",
@@ -337,6 +343,26 @@ pub async fn message_handler(
         handle_text_message(text, request_context, msg.clone()).await
     } else if let Some(sticker) = msg.sticker() {
         handle_sticker_message(sticker, request_context, msg.clone()).await
+    } else if let Some(photo) = msg.photo().and_then(|sizes| {
+        sizes
+            .iter()
+            .max_by_key(|size| u64::from(size.width) * u64::from(size.height))
+    }) {
+        handle_image_message(photo.file.id.clone(), photo.file.size, request_context, msg.clone())
+            .await
+    } else if let Some(document) = msg.document().filter(|document| {
+        document
+            .mime_type
+            .as_ref()
+            .is_some_and(|mime| mime.type_().as_str() == "image")
+    }) {
+        handle_image_message(
+            document.file.id.clone(),
+            document.file.size,
+            request_context,
+            msg.clone(),
+        )
+        .await
     } else if let Some(shared_chat) = msg.shared_chat() {
         if let Some(username) = &shared_chat.username {
             request_context
@@ -521,6 +547,9 @@ async fn handle_sticker_message(
     match request_context.dialog_state() {
         DialogState::Normal => handle_sticker_1(msg, sticker, request_context, false).await?,
         DialogState::ContinuousTag(continuous_tag) => {
+            if !request_context.can_tag_stickers() {
+                return Err(UserError::NoPermissionForAction("tag sticker".to_string()).into());
+            }
             let file = request_context
                 .database
                 .get_sticker_file_by_sticker_id(&sticker_id)
@@ -532,6 +561,7 @@ async fn handle_sticker_message(
                     &file.id,
                     &continuous_tag.add_tags,
                     Some(request_context.user.id),
+                    TagOrigin::Manual,
                 )
                 .await?;
 
@@ -615,6 +645,73 @@ async fn handle_sticker_message(
     Ok(())
 }
 
+const MAX_IMAGE_SEARCH_FILE_SIZE: u32 = 10 * 1024 * 1024;
+const IMAGE_SEARCH_RESULT_COUNT: usize = 8;
+
+/// reverse image search for photos and image documents
+#[tracing::instrument(skip(request_context, msg))]
+async fn handle_image_message(
+    file_id: String,
+    file_size: u32,
+    request_context: RequestContext,
+    msg: Message,
+) -> Result<(), BotError> {
+    if file_size > MAX_IMAGE_SEARCH_FILE_SIZE {
+        return Err(UserError::ValidationError("the image is too large".to_string()).into());
+    }
+    let (buf, _) = fetch_sticker_file(file_id, request_context.bot.clone()).await?;
+    let matches = find_with_image(
+        request_context.database.clone(),
+        buf,
+        request_context.vector_db.clone(),
+        request_context.config.clone(),
+        IMAGE_SEARCH_RESULT_COUNT,
+    )
+    .await?;
+    let stickers = request_context
+        .services
+        .similarity
+        .matches_to_stickers(matches)
+        .await?;
+    let stickers = request_context
+        .services
+        .sticker
+        .filter_by_rating_profile(stickers, request_context.rating_profile(), |(sticker, _)| {
+            &sticker.sticker_file_id
+        })
+        .await?;
+    if stickers.is_empty() {
+        return Err(UserError::ListHasZeroResults("matching stickers".to_string()).into());
+    }
+
+    let mut sets = Vec::new();
+    for (set_id, score) in stickers
+        .iter()
+        .map(|(sticker, score)| (sticker.sticker_set_id.clone(), *score))
+        .unique_by(|(set_id, _)| set_id.clone())
+    {
+        if let Some(set) = request_context
+            .database
+            .get_sticker_set_by_id(&set_id)
+            .await?
+        {
+            sets.push((set, score));
+        }
+    }
+
+    request_context
+        .bot
+        .send_markdown(msg.chat.id, Text::reverse_image_search(sets, request_context.language))
+        .reply_markup(Keyboard::reverse_image_search(&stickers))
+        .link_preview_options(LinkPreviewOptions::new().is_disabled(true))
+        .allow_sending_without_reply(true)
+        .reply_to_message_id(msg.id)
+        .into_future()
+        .instrument(tracing::info_span!("telegram_bot_send_markdown"))
+        .await?;
+    Ok(())
+}
+
 #[tracing::instrument(skip(request_context, msg, sticker), fields(sticker_id = sticker.file.unique_id))]
 async fn handle_sticker_1(
     // TODO: rename
@@ -639,6 +736,8 @@ async fn handle_sticker_1(
         request_context.database.clone(),
         request_context.tfidf.clone(),
         request_context.vector_db.clone(),
+        request_context.services.tag_suggestion_rules.current(),
+        request_context.services.suggestion_combiner.current(),
         // request_context.tag_worker.clone(),
     )
     .await?;
@@ -656,13 +755,14 @@ async fn handle_sticker_1(
         .send_markdown(
             msg.chat.id,
             if is_continuous_tag {
-                Text::continuous_tag_success()
+                Text::continuous_tag_success(request_context.language)
             } else {
                 Text::get_sticker_text(
                     emojis,
                     set.as_ref().is_some_and(|set| set.last_fetched.is_none()),
                     request_context.is_admin(),
                     set.as_ref().map(|s| s.id.clone()),
+                    request_context.language,
                 )
             },
         )
@@ -546,6 +546,26 @@ impl VectorDatabase {
     ) -> Result<Vec<StickerMatch>, VectorDatabaseError> {
         self.find_stickers_given_vector_using_collection(
             clip_vector,
+            "clip",
+            limit,
+            offset,
+            STICKER_COLLECTION_NAME,
+            score_threshold,
+        )
+        .await
+    }
+
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn find_stickers_given_histogram(
+        &self,
+        histogram_vector: Vec<u8>,
+        limit: u64,
+        offset: u64,
+        score_threshold: Option<f32>,
+    ) -> Result<Vec<StickerMatch>, VectorDatabaseError> {
+        self.find_stickers_given_vector_using_collection(
+            vec_u8_to_f32(histogram_vector),
+            "histogram",
             limit,
             offset,
             STICKER_COLLECTION_NAME,
@@ -563,6 +583,7 @@ impl VectorDatabase {
     ) -> Result<Vec<StickerMatch>, VectorDatabaseError> {
         self.find_stickers_given_vector_using_collection(
             clip_vector,
+            "clip",
             limit,
             0,
             BANNED_STICKER_COLLECTION_NAME,
@@ -573,7 +594,8 @@ impl VectorDatabase {
 
     async fn find_stickers_given_vector_using_collection(
         &self,
-        clip_vector: Vec<f32>,
+        vector: Vec<f32>,
+        vector_name: &str,
         limit: u64,
         offset: u64,
         collection_name: &str,
@@ -583,8 +605,8 @@ impl VectorDatabase {
             .client
             .search_points(&SearchPoints {
                 collection_name: collection_name.into(),
-                vector: clip_vector.into(),
-                vector_name: Some("clip".to_string()),
+                vector,
+                vector_name: Some(vector_name.to_string()),
                 limit,
                 offset: Some(offset),
                 with_payload: Some(true.into()),
@@ -747,6 +769,7 @@ impl VectorDatabase {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
 pub struct StickerSimilarities {
     pub clip: f32,
     pub histogram: f32,
@@ -0,0 +1,119 @@
+use std::time::Instant;
+
+use rand::Rng;
+use ring::digest;
+
+use crate::{
+    bot::{BotError, InternalError, UserError},
+    database::{ApiKey, Database, TrustLevel, User},
+};
+
+use super::RateLimiter;
+
+const MAX_API_KEYS_PER_USER: usize = 5;
+const MAX_API_KEY_NAME_LENGTH: usize = 32;
+const DEFAULT_REQUESTS_PER_MINUTE: i64 = 60;
+/// `last_used_at` is only written once in a while so that every request does not cause a write
+const LAST_USED_UPDATE_INTERVAL: chrono::TimeDelta = chrono::TimeDelta::minutes(5);
+const API_KEY_PREFIX: &str = "fz_";
+
+/// keys for the public json api (`/api/v1`)
+///
+/// Keys are only shown once when they are created; the database only contains their hash.
+#[derive(Clone)]
+pub struct ApiKeyService {
+    database: Database,
+    rate_limiter: RateLimiter,
+}
+
+impl ApiKeyService {
+    pub fn new(database: Database) -> Self {
+        Self {
+            database,
+            rate_limiter: RateLimiter::default(),
+        }
+    }
+
+    /// returns the key and the secret that the user has to send with each request
+    #[tracing::instrument(skip(self))]
+    pub async fn create_key(&self, user_id: i64, name: &str) -> Result<(ApiKey, String), BotError> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > MAX_API_KEY_NAME_LENGTH {
+            return Err(UserError::ValidationError(format!(
+                "The name must have between 1 and {MAX_API_KEY_NAME_LENGTH} characters"
+            ))
+            .into());
+        }
+        let keys = self.database.get_api_keys_by_user_id(user_id).await?;
+        if keys.len() >= MAX_API_KEYS_PER_USER {
+            return Err(UserError::ValidationError(format!(
+                "You can have at most {MAX_API_KEYS_PER_USER} api keys, revoke one first"
+            ))
+            .into());
+        }
+        let secret = generate_secret();
+        let key = self
+            .database
+            .create_api_key(user_id, name, &hash_secret(&secret), DEFAULT_REQUESTS_PER_MINUTE)
+            .await?;
+        Ok((key, secret))
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn list_keys(&self, user_id: i64) -> Result<Vec<ApiKey>, InternalError> {
+        Ok(self.database.get_api_keys_by_user_id(user_id).await?)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn revoke_key(&self, user_id: i64, api_key_id: i64) -> Result<(), BotError> {
+        let revoked = self.database.revoke_api_key(user_id, api_key_id).await?;
+        if revoked {
+            self.rate_limiter.remove(api_key_id);
+            Ok(())
+        } else {
+            Err(UserError::ValidationError(format!("You have no api key with id {api_key_id}")).into())
+        }
+    }
+
+    /// the key and its owner, if the key is valid and the owner is not banned
+    #[tracing::instrument(skip(self, secret))]
+    pub async fn authenticate(&self, secret: &str) -> Result<Option<(ApiKey, User)>, InternalError> {
+        let Some(key) = self
+            .database
+            .get_active_api_key_by_hash(&hash_secret(secret))
+            .await?
+        else {
+            return Ok(None);
+        };
+        let Some(user) = self.database.get_user_by_id(key.user_id).await? else {
+            return Ok(None);
+        };
+        if user.trust_level == TrustLevel::Banned {
+            return Ok(None);
+        }
+        let now = chrono::Utc::now().naive_utc();
+        if key
+            .last_used_at
+            .is_none_or(|last_used_at| now - last_used_at > LAST_USED_UPDATE_INTERVAL)
+        {
+            self.database.update_api_key_last_used(key.id).await?;
+        }
+        Ok(Some((key, user)))
+    }
+
+    /// counts the request; returns the seconds until the next request is allowed if the key
+    /// exceeded its limit
+    pub fn check_rate_limit(&self, key: &ApiKey) -> Result<(), u64> {
+        self.rate_limiter
+            .check(key.id, key.requests_per_minute, Instant::now())
+    }
+}
+
+fn generate_secret() -> String {
+    let bytes: [u8; 24] = rand::rng().random();
+    format!("{API_KEY_PREFIX}{}", hex::encode(bytes))
+}
+
+fn hash_secret(secret: &str) -> String {
+    hex::encode(digest::digest(&digest::SHA256, secret.as_bytes()))
+}
@@ -0,0 +1,194 @@
+use itertools::Itertools;
+use url::Url;
+
+use crate::{
+    bot::{BotError, InternalError, UserError},
+    database::{CreatorProfile, Database, PopularTag, StickerSet, Tag, UsernameKind},
+};
+
+/// sets shown on the profile; creators with more sets are rare
+const MAX_SETS: i64 = 500;
+const TAG_DISTRIBUTION_SIZE: i64 = 30;
+const MAX_BIO_LENGTH: usize = 1000;
+const MAX_LINKS: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct Creator {
+    /// telegram id of the user that created the sets
+    pub user_id: i64,
+    pub username: Option<String>,
+    /// only present once the creator claimed the page
+    pub profile: Option<CreatorProfile>,
+    /// newest first
+    pub sets: Vec<StickerSet>,
+    pub set_count: i64,
+    /// artist tags that link to the creator
+    pub linked_tags: Vec<Tag>,
+    /// usernames of the channels of the linked tags
+    pub channels: Vec<(i64, String)>,
+    pub tag_distribution: Vec<PopularTag>,
+}
+
+impl Creator {
+    /// username, or else the first linked tag
+    #[must_use]
+    pub fn display_name(&self) -> String {
+        self.username.as_ref().map_or_else(
+            || {
+                self.linked_tags.first().map_or_else(
+                    || format!("Creator {}", self.user_id),
+                    |tag| tag.id.clone(),
+                )
+            },
+            |username| format!("@{username}"),
+        )
+    }
+}
+
+/// profile pages of sticker set creators
+///
+/// Creators are identified by `sticker_set.created_by_user_id`; they can claim their page by
+/// logging in on the website, which allows them to write a bio and add links.
+#[derive(Clone)]
+pub struct CreatorService {
+    database: Database,
+}
+
+impl CreatorService {
+    pub fn new(database: Database) -> Self {
+        Self { database }
+    }
+
+    /// none if the user did not create any known sets
+    #[tracing::instrument(skip(self))]
+    pub async fn creator(&self, user_id: i64) -> Result<Option<Creator>, InternalError> {
+        let set_count = self.database.get_owned_sticker_set_count(user_id).await?;
+        if set_count == 0 {
+            return Ok(None);
+        }
+        let linked_tags = self.database.get_all_tags_by_linked_user_id(user_id).await?;
+        let channels = self
+            .database
+            .get_usernames(
+                UsernameKind::Channel,
+                linked_tags
+                    .iter()
+                    .filter_map(|tag| tag.linked_channel_id)
+                    .unique()
+                    .collect_vec(),
+            )
+            .await?;
+        Ok(Some(Creator {
+            user_id,
+            username: self.database.get_username(UsernameKind::User, user_id).await?,
+            profile: self
+                .database
+                .get_creator_profile(user_id)
+                .await?
+                .filter(|profile| profile.claimed_at.is_some()),
+            sets: self.database.get_owned_sticker_sets(user_id, MAX_SETS, 0).await?,
+            set_count,
+            linked_tags,
+            channels,
+            tag_distribution: self
+                .database
+                .get_creator_tag_distribution(user_id, TAG_DISTRIBUTION_SIZE)
+                .await?,
+        }))
+    }
+
+    /// the logged in user claims the page with their own telegram id; the username from the login
+    /// is remembered so that the page can link to them
+    #[tracing::instrument(skip(self))]
+    pub async fn claim(&self, user_id: i64, username: Option<&str>) -> Result<(), BotError> {
+        if self.database.get_owned_sticker_set_count(user_id).await? == 0 {
+            return Err(UserError::ValidationError(
+                "Only creators of sticker sets that I know have a profile page".to_string(),
+            )
+            .into());
+        }
+        if let Some(username) = username {
+            self.database
+                .add_username_details(username, UsernameKind::User, user_id)
+                .await?;
+        }
+        self.database.claim_creator_profile(user_id).await?;
+        Ok(())
+    }
+
+    /// links are separated by whitespace; only http(s) links are allowed
+    #[tracing::instrument(skip(self, bio, links))]
+    pub async fn update_profile(&self, user_id: i64, bio: &str, links: &str) -> Result<(), BotError> {
+        let claimed = self
+            .database
+            .get_creator_profile(user_id)
+            .await?
+            .is_some_and(|profile| profile.claimed_at.is_some());
+        if !claimed {
+            return Err(UserError::ValidationError("Claim the page first".to_string()).into());
+        }
+        let bio = bio.trim();
+        if bio.chars().count() > MAX_BIO_LENGTH {
+            return Err(UserError::ValidationError(format!(
+                "The bio can be at most {MAX_BIO_LENGTH} characters long"
+            ))
+            .into());
+        }
+        let links = links.split_whitespace().map(str::to_string).unique().collect_vec();
+        if links.len() > MAX_LINKS {
+            return Err(UserError::ValidationError(format!("You can add at most {MAX_LINKS} links")).into());
+        }
+        if let Some(invalid) = links.iter().find(|link| !is_web_link(link)) {
+            return Err(UserError::ValidationError(format!("{invalid} is not a http(s) link")).into());
+        }
+        self.database.update_creator_profile(user_id, bio, links).await?;
+        Ok(())
+    }
+
+    /// removes the bio and links of a claimed page; the caller checks that the user is a moderator
+    #[tracing::instrument(skip(self))]
+    pub async fn reset_profile(&self, user_id: i64, moderator_id: i64) -> Result<(), BotError> {
+        let claimed = self
+            .database
+            .get_creator_profile(user_id)
+            .await?
+            .is_some_and(|profile| profile.claimed_at.is_some());
+        if !claimed {
+            return Err(UserError::ValidationError("The page is not claimed".to_string()).into());
+        }
+        self.database.reset_creator_profile(user_id, moderator_id).await?;
+        Ok(())
+    }
+}
+
+fn is_web_link(link: &str) -> bool {
+    Url::parse(link).is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host().is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn web_links() {
+        assert!(is_web_link("https://example.com"));
+        assert!(is_web_link("http://example.com/stickers?page=2"));
+    }
+
+    #[test]
+    fn links_with_other_schemes_are_rejected() {
+        assert!(!is_web_link("javascript:alert(1)"));
+        assert!(!is_web_link("JavaScript:alert(1)"));
+        assert!(!is_web_link("data:text/html,<script>alert(1)</script>"));
+        assert!(!is_web_link("ftp://example.com"));
+        assert!(!is_web_link("tg://resolve?domain=fuzzlebot"));
+    }
+
+    #[test]
+    fn links_without_host_are_rejected() {
+        assert!(!is_web_link("https:"));
+        assert!(!is_web_link("example.com"));
+        assert!(!is_web_link("/creator/1"));
+        assert!(!is_web_link(""));
+    }
+}
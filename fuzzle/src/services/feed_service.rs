@@ -0,0 +1,134 @@
+use std::sync::Arc;
+
+use itertools::Itertools;
+use ring::{digest, hmac};
+
+use crate::{
+    Config,
+    background_tasks::TagManagerService,
+    bot::{BotError, InternalError, UserError},
+    database::{Database, Order, Sticker, User},
+};
+
+const MAX_FOLLOWED_TAGS: usize = 20;
+
+/// personal feeds of followed tags
+///
+/// Feed readers can not log in, so the personal feed url contains a token that is derived from
+/// the user id and the bot token.
+#[derive(Clone)]
+pub struct FeedService {
+    config: Arc<Config>,
+    database: Database,
+}
+
+impl FeedService {
+    pub fn new(config: Arc<Config>, database: Database) -> Self {
+        Self { config, database }
+    }
+
+    fn token_key(&self) -> hmac::Key {
+        let secret = digest::digest(
+            &digest::SHA256,
+            format!("feed:{}", self.config.telegram_bot_token).as_bytes(),
+        );
+        hmac::Key::new(hmac::HMAC_SHA256, secret.as_ref())
+    }
+
+    #[must_use]
+    pub fn feed_token(&self, user_id: i64) -> String {
+        hex::encode(hmac::sign(&self.token_key(), user_id.to_string().as_bytes()))
+    }
+
+    #[must_use]
+    pub fn verify_feed_token(&self, user_id: i64, token: &str) -> bool {
+        let Ok(token) = hex::decode(token) else {
+            return false;
+        };
+        hmac::verify(&self.token_key(), user_id.to_string().as_bytes(), &token).is_ok()
+    }
+
+    /// path of the personal feed, without the format
+    #[must_use]
+    pub fn personal_feed_path(&self, user_id: i64) -> String {
+        format!("user/{user_id}/{}", self.feed_token(user_id))
+    }
+
+    /// returns the followed tags afterwards
+    #[tracing::instrument(skip(self, user, tag_manager))]
+    pub async fn follow_tags(
+        &self,
+        user: &User,
+        tags: &[String],
+        tag_manager: &TagManagerService,
+    ) -> Result<Vec<String>, BotError> {
+        let matched = tag_manager.closest_matching_tags(tags).await;
+        let unknown = matched
+            .iter()
+            .filter(|(_, tag)| tag.is_none())
+            .map(|(input, _)| input.clone())
+            .collect_vec();
+        if !unknown.is_empty() {
+            return Err(UserError::TagsNotFound(unknown).into());
+        }
+        let mut settings = user.settings.clone().unwrap_or_default();
+        settings.followed_tags = settings
+            .followed_tags
+            .into_iter()
+            .chain(matched.into_iter().filter_map(|(_, tag)| tag))
+            .unique()
+            .collect_vec();
+        if settings.followed_tags.len() > MAX_FOLLOWED_TAGS {
+            return Err(UserError::ValidationError(format!(
+                "You can follow at most {MAX_FOLLOWED_TAGS} tags"
+            ))
+            .into());
+        }
+        self.database.update_settings(user.id, &settings).await?;
+        Ok(settings.followed_tags)
+    }
+
+    /// returns the followed tags afterwards
+    #[tracing::instrument(skip(self, user))]
+    pub async fn unfollow_tag(&self, user: &User, tag: &str) -> Result<Vec<String>, InternalError> {
+        let mut settings = user.settings.clone().unwrap_or_default();
+        settings.followed_tags.retain(|followed| followed != tag);
+        self.database.update_settings(user.id, &settings).await?;
+        Ok(settings.followed_tags)
+    }
+
+    /// newest stickers with any of the followed tags, filtered by the settings of the user
+    #[tracing::instrument(skip(self, user))]
+    pub async fn followed_tag_stickers(
+        &self,
+        user: &User,
+        limit: i64,
+    ) -> Result<Vec<Sticker>, InternalError> {
+        let settings = user.settings.clone().unwrap_or_default();
+        let blacklist = settings.hidden_tags(&user.blacklist, false);
+        let always_hidden = settings.rating_profile().hidden_tags();
+        let mut stickers = Vec::new();
+        for tag in &settings.followed_tags {
+            stickers.extend(
+                self.database
+                    .get_stickers_for_tag_query(
+                        vec![tag.clone()],
+                        blacklist.clone(),
+                        settings.whitelisted_sets.clone(),
+                        always_hidden.clone(),
+                        vec![],
+                        limit,
+                        0,
+                        Order::LatestFirst,
+                    )
+                    .await?,
+            );
+        }
+        Ok(stickers
+            .into_iter()
+            .unique_by(|sticker| sticker.id.clone())
+            .sorted_by_key(|sticker| std::cmp::Reverse(sticker.created_at))
+            .take(limit as usize)
+            .collect_vec())
+    }
+}
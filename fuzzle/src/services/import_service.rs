@@ -9,7 +9,7 @@ use tracing::Instrument;
 use crate::{
     Config,
     bot::{Bot, BotError, InternalError, UserError, report_periodic_task_error},
-    database::{BanReason, Database, DatabaseError, StickerType},
+    database::{BanReason, Database, DatabaseError, ModerationAction, StickerType},
     fmetrics::TracedMessage,
     inference::image_to_clip_embedding,
     qdrant::VectorDatabase,
@@ -622,7 +622,7 @@ impl ImportService {
         if banned_sticker_count > 20 || banned_sticker_count as f32 > set_sticker_count as f32 * 0.3
         {
             // more than 10 stickers banned or set consists of more than 30% of banned stickers
-            self.ban_sticker_set(sticker_set_id).await?;
+            self.ban_sticker_set(sticker_set_id, None).await?;
             return Ok(true);
         }
         Ok(false)
@@ -697,7 +697,7 @@ impl ImportService {
         }
         if should_ban {
             tracing::info!(%sticker_id, "auto banning sticker");
-            self.ban_sticker(sticker_id, 0.95, crate::database::BanReason::Automatic)
+            self.ban_sticker(sticker_id, 0.95, crate::database::BanReason::Automatic, None)
                 .await?; // TODO: get rid of magic number
             return Ok(true);
         }
@@ -705,19 +705,39 @@ impl ImportService {
     }
 
     /// ban set and record who is to blame for adding it
+    ///
+    /// `moderator_id` is `None` for automatic bans
     #[tracing::instrument(skip(self), err(Debug))]
-    pub async fn ban_sticker_set(&self, set_id: &StickerSetId) -> Result<(), DatabaseError> {
+    pub async fn ban_sticker_set(
+        &self,
+        set_id: &StickerSetId,
+        moderator_id: Option<i64>,
+    ) -> Result<(), DatabaseError> {
         let set = self.database.get_sticker_set_by_id(set_id).await?;
+        let added_by_user_id = set.and_then(|set| set.added_by_user_id);
         self.database.delete_sticker_set(set_id).await?;
+        self.database.ban_set(set_id, added_by_user_id).await?;
         self.database
-            .ban_set(set_id, set.and_then(|set| set.added_by_user_id))
+            .add_moderation_log_entry(
+                moderator_id,
+                ModerationAction::BanSet,
+                set_id.as_ref(),
+                added_by_user_id.map(|user_id| format!("added by {user_id}")),
+            )
             .await?;
         Ok(())
     }
 
     #[tracing::instrument(skip(self), err(Debug))]
-    pub async fn unban_sticker_set(&self, set_id: &StickerSetId) -> Result<(), DatabaseError> {
+    pub async fn unban_sticker_set(
+        &self,
+        set_id: &StickerSetId,
+        moderator_id: Option<i64>,
+    ) -> Result<(), DatabaseError> {
         let original_adder = self.database.unban_set(&set_id).await?;
+        self.database
+            .add_moderation_log_entry(moderator_id, ModerationAction::UnbanSet, set_id.as_ref(), None)
+            .await?;
         self.database
             .upsert_sticker_set(&set_id, original_adder)
             .await?;
@@ -738,6 +758,7 @@ impl ImportService {
         sticker_id: &StickerId,
         clip_max_match_distance: f32,
         ban_reason: BanReason,
+        moderator_id: Option<i64>,
     ) -> Result<(), InternalError> {
         let Some(sticker_file) = self
             .database
@@ -789,11 +810,31 @@ impl ImportService {
                 ban_reason,
             )
             .await?;
+        self.database
+            .add_moderation_log_entry(
+                moderator_id,
+                ModerationAction::BanSticker,
+                sticker_id.as_ref(),
+                Some(format!("from set {}", sticker.sticker_set_id)),
+            )
+            .await?;
         Ok(())
     }
 
-    pub async fn unban_sticker(&self, sticker_id: &StickerId) -> Result<(), InternalError> {
+    pub async fn unban_sticker(
+        &self,
+        sticker_id: &StickerId,
+        moderator_id: Option<i64>,
+    ) -> Result<(), InternalError> {
         let (set_id, sticker_file_id) = self.database.unban_sticker(sticker_id).await?;
+        self.database
+            .add_moderation_log_entry(
+                moderator_id,
+                ModerationAction::UnbanSticker,
+                sticker_id.as_ref(),
+                Some(format!("from set {set_id}")),
+            )
+            .await?;
         self.vector_db
             .delete_banned_stickers(vec![sticker_file_id.clone()])
             .await?;
@@ -3,6 +3,20 @@ mod sticker_service;
 mod import_service;
 mod similarity_service;
 mod inference_service;
+mod tag_suggestion_rules_service;
+mod suggestion_combiner_service;
+mod tagging_queue_service;
+mod tag_review_service;
+mod trust_service;
+mod moderation_service;
+mod report_service;
+mod api_key_service;
+mod rate_limiter;
+mod tagging_service;
+mod feed_service;
+mod tag_wiki_service;
+mod creator_service;
+mod set_comparison_service;
 
 use std::sync::Arc;
 
@@ -11,6 +25,20 @@ pub use sticker_service::*;
 pub use import_service::*;
 pub use similarity_service::*;
 pub use inference_service::*;
+pub use tag_suggestion_rules_service::*;
+pub use suggestion_combiner_service::*;
+pub use tagging_queue_service::*;
+pub use tag_review_service::*;
+pub use trust_service::*;
+pub use moderation_service::*;
+pub use report_service::*;
+pub use api_key_service::*;
+pub use rate_limiter::*;
+pub use tagging_service::*;
+pub use feed_service::*;
+pub use tag_wiki_service::*;
+pub use creator_service::*;
+pub use set_comparison_service::*;
 
 use crate::{Config, bot::Bot, database::Database, qdrant::VectorDatabase};
 
@@ -20,16 +48,55 @@ pub struct Services {
     pub sticker: StickerService,
     pub import: ImportService,
     pub similarity: SimilarityService,
+    pub tag_suggestion_rules: TagSuggestionRulesService,
+    pub suggestion_combiner: SuggestionCombinerService,
+    pub tagging_queue: TaggingQueueService,
+    pub tag_review: TagReviewService,
+    pub trust: TrustService,
+    pub moderation: ModerationService,
+    pub report: ReportService,
+    pub api_key: ApiKeyService,
+    pub tagging: TaggingService,
+    pub feed: FeedService,
+    pub tag_wiki: TagWikiService,
+    pub creator: CreatorService,
+    pub set_comparison: SetComparisonService,
+    /// semantic searches on the website, per user; anonymous visitors share one limit
+    pub semantic_search_rate_limiter: RateLimiter,
 }
 
 impl Services {
     pub fn new(config: Arc<Config>, database: Database, vector_db: VectorDatabase, bot: Bot) -> Self {
         let telegram = ExternalTelegramService::new(&config.external_telegram_service_base_url);
-        let import = ImportService::new(database.clone(), config.clone(), bot, vector_db.clone(), telegram.clone());
+        let import = ImportService::new(database.clone(), config.clone(), bot.clone(), vector_db.clone(), telegram.clone());
+
+        let tag_suggestion_rules = TagSuggestionRulesService::new(database.clone());
+        let suggestion_combiner = SuggestionCombinerService::new(database.clone());
+        let tag_review = TagReviewService::new(database.clone());
+        let sticker = StickerService::new(database.clone());
 
         Self {
             // ban: BanService::new(database.clone(), import.clone(), vector_db.clone()),
-            sticker: StickerService::new(database.clone()),
+            feed: FeedService::new(config.clone(), database.clone()),
+            tag_wiki: TagWikiService::new(database.clone(), sticker.clone()),
+            creator: CreatorService::new(database.clone()),
+            set_comparison: SetComparisonService::new(database.clone(), vector_db.clone()),
+            semantic_search_rate_limiter: RateLimiter::default(),
+            sticker,
+            tag_review: tag_review.clone(),
+            tagging: TaggingService::new(database.clone(), tag_review),
+            trust: TrustService::new(database.clone()),
+            moderation: ModerationService::new(config.clone(), database.clone()),
+            report: ReportService::new(database.clone(), bot),
+            api_key: ApiKeyService::new(database.clone()),
+            tagging_queue: TaggingQueueService::new(
+                database.clone(),
+                vector_db.clone(),
+                tag_suggestion_rules.clone(),
+                suggestion_combiner.clone(),
+            ),
+            tag_suggestion_rules,
+            suggestion_combiner,
             similarity: SimilarityService::new(database, vector_db, import.clone()),
             import,
             telegram,
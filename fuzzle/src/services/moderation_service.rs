@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use itertools::Itertools;
+use teloxide::types::{ChatId, UserId};
+
+use crate::{
+    Config,
+    bot::{BotError, InternalError, UserError},
+    database::{Database, ModerationTask, TrustLevel},
+};
+
+/// tasks that a moderator did not decide on within this time can be taken by someone else
+const ASSIGNMENT_TIMEOUT_HOURS: i64 = 24;
+
+/// distributes moderation tasks between the admin and the users with the moderator trust level,
+/// or posts them to the moderator chat if one is configured
+#[derive(Clone)]
+pub struct ModerationService {
+    config: Arc<Config>,
+    database: Database,
+}
+
+impl ModerationService {
+    pub fn new(config: Arc<Config>, database: Database) -> Self {
+        Self { config, database }
+    }
+
+    /// the admin comes first
+    #[tracing::instrument(skip(self))]
+    pub async fn moderator_ids(&self) -> Result<Vec<UserId>, InternalError> {
+        let admin_id = self.config.get_admin_user_id();
+        let moderator_ids = self
+            .database
+            .get_user_ids_by_trust_level(TrustLevel::Moderator)
+            .await?;
+        Ok(std::iter::once(admin_id)
+            .chain(moderator_ids.into_iter().map(|id| UserId(id as u64)))
+            .unique()
+            .collect())
+    }
+
+    /// chats that receive the daily report
+    #[tracing::instrument(skip(self))]
+    pub async fn report_chat_ids(&self) -> Result<Vec<ChatId>, InternalError> {
+        if let Some(chat_id) = self.config.get_moderator_chat_id() {
+            return Ok(vec![chat_id]);
+        }
+        Ok(self
+            .moderator_ids()
+            .await?
+            .into_iter()
+            .map(ChatId::from)
+            .collect())
+    }
+
+    /// fails if another moderator is handling the task
+    #[tracing::instrument(skip(self))]
+    pub async fn claim_task(&self, task_id: i64, moderator_id: i64) -> Result<(), BotError> {
+        let claimed = self
+            .database
+            .claim_moderation_task(task_id, moderator_id, assignment_expiry())
+            .await?;
+        if claimed {
+            Ok(())
+        } else {
+            Err(UserError::ValidationError(
+                "another moderator is handling this task".to_string(),
+            )
+            .into())
+        }
+    }
+
+    /// the pending tasks of the moderator, topped up with unassigned ones
+    #[tracing::instrument(skip(self))]
+    pub async fn claim_tasks(
+        &self,
+        moderator_id: i64,
+        limit: i64,
+    ) -> Result<Vec<ModerationTask>, InternalError> {
+        Ok(self
+            .database
+            .claim_moderation_tasks(moderator_id, limit, assignment_expiry())
+            .await?)
+    }
+
+    /// pending tasks and the chat they should be sent to
+    ///
+    /// With a moderator chat every pending task is posted there and assigned to whoever decides
+    /// first; otherwise unassigned tasks are assigned to the moderators in turn.
+    #[tracing::instrument(skip(self))]
+    pub async fn distribute_tasks(&self) -> Result<Vec<(ChatId, ModerationTask)>, InternalError> {
+        if let Some(chat_id) = self.config.get_moderator_chat_id() {
+            let tasks = self.database.get_open_moderation_tasks().await?;
+            return Ok(tasks.into_iter().map(|task| (chat_id, task)).collect());
+        }
+        let moderator_ids = self.moderator_ids().await?;
+        let expired_before = assignment_expiry();
+        let tasks = self
+            .database
+            .get_unassigned_moderation_tasks(expired_before)
+            .await?;
+        let mut result = Vec::new();
+        for (mut task, moderator_id) in tasks.into_iter().zip(moderator_ids.iter().cycle()) {
+            let claimed = self
+                .database
+                .claim_moderation_task(task.id, moderator_id.0 as i64, expired_before)
+                .await?;
+            if claimed {
+                task.assigned_to_user_id = Some(moderator_id.0 as i64);
+                task.assigned_at = Some(chrono::Utc::now().naive_utc());
+                result.push(((*moderator_id).into(), task));
+            }
+        }
+        Ok(result)
+    }
+}
+
+fn assignment_expiry() -> chrono::NaiveDateTime {
+    chrono::Utc::now().naive_utc() - chrono::Duration::hours(ASSIGNMENT_TIMEOUT_HOURS)
+}
@@ -0,0 +1,104 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+struct RateLimitWindow {
+    started_at: Instant,
+    request_count: i64,
+}
+
+/// fixed one minute windows per id, like an api key or a user
+#[derive(Clone, Default)]
+pub struct RateLimiter {
+    windows: Arc<Mutex<HashMap<i64, RateLimitWindow>>>,
+}
+
+impl RateLimiter {
+    /// counts the request; returns the seconds until the next request is allowed if the id
+    /// exceeded its limit
+    pub fn check(&self, id: i64, requests_per_minute: i64, now: Instant) -> Result<(), u64> {
+        let mut windows = self
+            .windows
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let window = windows.entry(id).or_insert(RateLimitWindow {
+            started_at: now,
+            request_count: 0,
+        });
+        if now.duration_since(window.started_at) >= RATE_LIMIT_WINDOW {
+            window.started_at = now;
+            window.request_count = 0;
+        }
+        if window.request_count >= requests_per_minute {
+            let retry_after = RATE_LIMIT_WINDOW.saturating_sub(now.duration_since(window.started_at));
+            return Err(retry_after.as_secs().max(1));
+        }
+        window.request_count += 1;
+        Ok(())
+    }
+
+    pub fn remove(&self, id: i64) {
+        self.windows
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(&id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limit_allows_requests_up_to_the_limit() {
+        let limiter = RateLimiter::default();
+        let now = Instant::now();
+        assert_eq!(limiter.check(1, 3, now), Ok(()));
+        assert_eq!(limiter.check(1, 3, now), Ok(()));
+        assert_eq!(limiter.check(1, 3, now), Ok(()));
+        assert_eq!(limiter.check(1, 3, now), Err(60));
+        assert_eq!(limiter.check(1, 3, now + Duration::from_secs(45)), Err(15));
+    }
+
+    #[test]
+    fn rate_limit_resets_after_the_window() {
+        let limiter = RateLimiter::default();
+        let now = Instant::now();
+        assert_eq!(limiter.check(1, 1, now), Ok(()));
+        assert!(limiter.check(1, 1, now + Duration::from_secs(59)).is_err());
+        assert_eq!(limiter.check(1, 1, now + RATE_LIMIT_WINDOW), Ok(()));
+    }
+
+    #[test]
+    fn rate_limit_is_per_id() {
+        let limiter = RateLimiter::default();
+        let now = Instant::now();
+        assert_eq!(limiter.check(1, 1, now), Ok(()));
+        assert!(limiter.check(1, 1, now).is_err());
+        assert_eq!(limiter.check(2, 1, now), Ok(()));
+    }
+
+    #[test]
+    fn removed_ids_start_a_new_window() {
+        let limiter = RateLimiter::default();
+        let now = Instant::now();
+        assert_eq!(limiter.check(1, 1, now), Ok(()));
+        limiter.remove(1);
+        assert_eq!(limiter.check(1, 1, now), Ok(()));
+    }
+
+    #[test]
+    fn retry_after_is_at_least_one_second() {
+        let limiter = RateLimiter::default();
+        let now = Instant::now();
+        assert_eq!(limiter.check(1, 0, now + Duration::from_millis(59_500)), Err(60));
+        assert_eq!(
+            limiter.check(1, 0, now + Duration::from_millis(59_500) + Duration::from_millis(59_800)),
+            Err(1)
+        );
+    }
+}
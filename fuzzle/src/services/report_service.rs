@@ -0,0 +1,180 @@
+use crate::{
+    bot::{Bot, BotError, BotExt, InternalError, UserError},
+    database::{
+        AdditionalReport, Database, ModerationTask, ModerationTaskDetails, ModerationTaskStatus,
+        ReportInsertion, ReportReason, ReportTarget,
+    },
+    text::Text,
+};
+
+/// reports of a user within 24 hours, including reports that were added to a pending task
+const MAX_REPORTS_PER_DAY: usize = 10;
+/// a target can not be reported again for this long after a report was decided
+const REPORT_COOLDOWN_DAYS: i64 = 7;
+const MAX_NOTE_LENGTH: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportOutcome {
+    Created,
+    /// someone else already reported the target and the report is still pending
+    AddedToPendingReport,
+    AlreadyReported,
+}
+
+/// reports of sets and stickers by users
+#[derive(Clone)]
+pub struct ReportService {
+    database: Database,
+    bot: Bot,
+}
+
+impl ReportService {
+    pub fn new(database: Database, bot: Bot) -> Self {
+        Self { database, bot }
+    }
+
+    /// reports of the same target are collected in a single moderation task
+    #[tracing::instrument(skip(self))]
+    pub async fn report(
+        &self,
+        reporter_id: i64,
+        target: ReportTarget,
+        reason: ReportReason,
+        note: Option<String>,
+    ) -> Result<ReportOutcome, BotError> {
+        let note = note
+            .map(|note| note.trim().to_string())
+            .filter(|note| !note.is_empty());
+        if note
+            .as_ref()
+            .is_some_and(|note| note.chars().count() > MAX_NOTE_LENGTH)
+        {
+            return Err(UserError::ValidationError(format!(
+                "The note can be at most {MAX_NOTE_LENGTH} characters long"
+            ))
+            .into());
+        }
+        if matches!(target, ReportTarget::Sticker(_)) && reason == ReportReason::DuplicateSet {
+            return Err(
+                UserError::ValidationError("Only sets can be reported as duplicates".to_string())
+                    .into(),
+            );
+        }
+
+        let now = chrono::Utc::now().naive_utc();
+        if self
+            .report_count_since(reporter_id, now - chrono::Duration::days(1))
+            .await?
+            >= MAX_REPORTS_PER_DAY
+        {
+            return Err(UserError::ValidationError(
+                "You sent a lot of reports today, please try again tomorrow".to_string(),
+            )
+            .into());
+        }
+
+        let report = AdditionalReport {
+            user_id: reporter_id,
+            reason,
+            note: note.clone(),
+            created_at: Some(now),
+        };
+        let details = match target {
+            ReportTarget::Set(set_id) => ModerationTaskDetails::ReportStickerSet {
+                set_id,
+                reason,
+                note,
+                additional_reports: vec![],
+            },
+            ReportTarget::Sticker(sticker_id) => ModerationTaskDetails::ReportSticker {
+                sticker_id,
+                reason,
+                note,
+                additional_reports: vec![],
+            },
+        };
+        let insertion = self
+            .database
+            .insert_report(
+                &details,
+                report,
+                now - chrono::Duration::days(REPORT_COOLDOWN_DAYS),
+            )
+            .await?;
+        match insertion {
+            ReportInsertion::Created => Ok(ReportOutcome::Created),
+            ReportInsertion::AddedToPendingTask => Ok(ReportOutcome::AddedToPendingReport),
+            ReportInsertion::AlreadyReported => Ok(ReportOutcome::AlreadyReported),
+            ReportInsertion::RecentlyDecided => Err(UserError::ValidationError(
+                "This was reviewed recently, thank you anyway!".to_string(),
+            )
+            .into()),
+        }
+    }
+
+    /// reports that created a task and reports that were added to a task of someone else
+    async fn report_count_since(
+        &self,
+        reporter_id: i64,
+        since: chrono::NaiveDateTime,
+    ) -> Result<usize, InternalError> {
+        let created = self
+            .database
+            .get_moderation_tasks_created_by_user_since(reporter_id, since)
+            .await?
+            .into_iter()
+            .filter(|task| task.details.report_target().is_some())
+            .count();
+        // additional reports can only be in tasks that were still pending at `since`
+        let mut tasks = self.database.get_open_moderation_tasks().await?;
+        tasks.extend(self.database.get_moderation_tasks_decided_since(since).await?);
+        let added = tasks
+            .iter()
+            .flat_map(|task| task.details.additional_reports())
+            .filter(|report| {
+                report.user_id == reporter_id
+                    && report.created_at.is_some_and(|created_at| created_at >= since)
+            })
+            .count();
+        Ok(created + added)
+    }
+
+    /// tells everyone that reported the target that their report was handled
+    #[tracing::instrument(skip(self))]
+    pub async fn notify_reporters(&self, task: &ModerationTask) -> Result<(), InternalError> {
+        if task.completion_status == ModerationTaskStatus::Pending {
+            return Ok(());
+        }
+        let Some(target) = task.details.report_target() else {
+            return Ok(());
+        };
+        let reporter_ids = std::iter::once(task.created_by_user_id).chain(
+            task.details
+                .additional_reports()
+                .iter()
+                .map(|report| report.user_id),
+        );
+        for reporter_id in reporter_ids {
+            // the language of the telegram app is only known during an update
+            let language = self
+                .database
+                .get_user_by_id(reporter_id)
+                .await?
+                .and_then(|user| user.settings)
+                .unwrap_or_default()
+                .language(None);
+            let result = self
+                .bot
+                .send_markdown(
+                    teloxide::types::UserId(reporter_id as u64),
+                    Text::report_resolved(&target, task.completion_status, language),
+                )
+                .await;
+            // users that blocked the bot should not stop the others from being notified
+            if let Err(err) = result {
+                tracing::warn!("could not notify reporter {reporter_id}: {err:?}");
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,353 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use itertools::Itertools;
+
+use crate::{
+    bot::InternalError,
+    database::{Database, MergeStatus, ModerationAction, Sticker, StickerSet},
+    inline::SimilarityAspect,
+    qdrant::{StickerSimilarities, VectorDatabase},
+    sticker::determine_canonical_sticker_and_merge,
+    util::{Required, StickerId, StickerSetId},
+};
+
+/// unique stickers of the first set that are looked up in the vector database; each lookup is a
+/// request, so huge sets are only compared partially
+const MAX_NEAR_DUPLICATE_LOOKUPS: usize = 120;
+/// matches per lookup; the counterpart in the other set has to be among them
+const NEAR_DUPLICATE_CANDIDATES: u64 = 20;
+const NEAR_DUPLICATE_CANDIDATE_THRESHOLD: f32 = 0.9;
+/// both the embedding and the color histogram have to be this similar
+const NEAR_DUPLICATE_CLIP_THRESHOLD: f32 = 0.95;
+const NEAR_DUPLICATE_HISTOGRAM_THRESHOLD: f32 = 0.9;
+/// near duplicates of a set pair are looked up again after this time
+const NEAR_DUPLICATE_CACHE_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// two different files that probably show the same sticker
+#[derive(Debug, Clone)]
+pub struct NearDuplicate {
+    pub sticker_a: Sticker,
+    pub sticker_b: Sticker,
+    pub similarity: StickerSimilarities,
+}
+
+struct CachedNearDuplicates {
+    near_duplicates: Vec<NearDuplicate>,
+    found_at: Instant,
+}
+
+#[derive(Debug, Clone)]
+pub struct TagDifference {
+    pub tag: String,
+    /// number of stickers with the tag in the first set
+    pub count_a: i64,
+    pub count_b: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SetComparison {
+    pub set_a: StickerSet,
+    pub set_b: StickerSet,
+    pub sticker_count_a: usize,
+    pub sticker_count_b: usize,
+    /// stickers of the first set whose file is also in the second set
+    pub shared: Vec<Sticker>,
+    /// most similar first; empty if they were not looked for
+    pub near_duplicates: Vec<NearDuplicate>,
+    /// neither shared nor near duplicates
+    pub only_a: Vec<Sticker>,
+    pub only_b: Vec<Sticker>,
+    /// tags that are not used equally often in both sets, biggest difference first
+    pub tag_differences: Vec<TagDifference>,
+}
+
+impl SetComparison {
+    /// shared files relative to the smaller set
+    #[must_use]
+    pub fn overlap_percentage(&self) -> i64 {
+        let smaller = self.sticker_count_a.min(self.sticker_count_b).max(1);
+        (self.shared.len() * 100 / smaller) as i64
+    }
+}
+
+/// shows how two sticker sets relate to each other
+///
+/// Shared files come from the database, near duplicates from the vector database; moderators can
+/// merge near duplicates from the comparison. Near duplicates are cached per set pair so that
+/// repeated requests do not query the vector database again.
+#[derive(Clone)]
+pub struct SetComparisonService {
+    database: Database,
+    vector_db: VectorDatabase,
+    near_duplicate_cache: Arc<Mutex<HashMap<(StickerSetId, StickerSetId), CachedNearDuplicates>>>,
+}
+
+impl SetComparisonService {
+    pub fn new(database: Database, vector_db: VectorDatabase) -> Self {
+        Self {
+            database,
+            vector_db,
+            near_duplicate_cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// none if one of the sets is unknown; looking for near duplicates queries the vector
+    /// database for every sticker, without it they count as unique
+    #[tracing::instrument(skip(self))]
+    pub async fn compare(
+        &self,
+        set_id_a: &StickerSetId,
+        set_id_b: &StickerSetId,
+        find_near_duplicates: bool,
+    ) -> Result<Option<SetComparison>, InternalError> {
+        let Some(set_a) = self.database.get_sticker_set_by_id(set_id_a).await? else {
+            return Ok(None);
+        };
+        let Some(set_b) = self.database.get_sticker_set_by_id(set_id_b).await? else {
+            return Ok(None);
+        };
+        let stickers_a = self.database.get_all_stickers_in_set(set_id_a).await?;
+        let stickers_b = self.database.get_all_stickers_in_set(set_id_b).await?;
+        let sticker_count_a = stickers_a.len();
+        let sticker_count_b = stickers_b.len();
+
+        let files_a: HashSet<_> = stickers_a.iter().map(|sticker| sticker.sticker_file_id.clone()).collect();
+        let files_b: HashSet<_> = stickers_b.iter().map(|sticker| sticker.sticker_file_id.clone()).collect();
+        let (shared, only_a): (Vec<_>, Vec<_>) = stickers_a
+            .into_iter()
+            .partition(|sticker| files_b.contains(&sticker.sticker_file_id));
+        let only_b = stickers_b
+            .into_iter()
+            .filter(|sticker| !files_a.contains(&sticker.sticker_file_id))
+            .collect_vec();
+
+        let near_duplicates = if find_near_duplicates {
+            self.cached_near_duplicates(set_id_a, set_id_b, &only_a, &only_b)
+                .await?
+        } else {
+            vec![]
+        };
+        let near_duplicate_ids: HashSet<_> = near_duplicates
+            .iter()
+            .flat_map(|pair| [pair.sticker_a.id.clone(), pair.sticker_b.id.clone()])
+            .collect();
+        let only_a = only_a
+            .into_iter()
+            .filter(|sticker| !near_duplicate_ids.contains(&sticker.id))
+            .collect_vec();
+        let only_b = only_b
+            .into_iter()
+            .filter(|sticker| !near_duplicate_ids.contains(&sticker.id))
+            .collect_vec();
+
+        Ok(Some(SetComparison {
+            tag_differences: self.tag_differences(set_id_a, set_id_b).await?,
+            set_a,
+            set_b,
+            sticker_count_a,
+            sticker_count_b,
+            shared,
+            near_duplicates,
+            only_a,
+            only_b,
+        }))
+    }
+
+    /// cached pairs whose stickers were removed or merged since are left out
+    async fn cached_near_duplicates(
+        &self,
+        set_id_a: &StickerSetId,
+        set_id_b: &StickerSetId,
+        only_a: &[Sticker],
+        only_b: &[Sticker],
+    ) -> Result<Vec<NearDuplicate>, InternalError> {
+        let key = (set_id_a.clone(), set_id_b.clone());
+        let cached = {
+            let mut cache = self
+                .near_duplicate_cache
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            cache.retain(|_, cached| cached.found_at.elapsed() < NEAR_DUPLICATE_CACHE_DURATION);
+            cache.get(&key).map(|cached| cached.near_duplicates.clone())
+        };
+        if let Some(near_duplicates) = cached {
+            let ids_a: HashSet<_> = only_a.iter().map(|sticker| &sticker.id).collect();
+            let ids_b: HashSet<_> = only_b.iter().map(|sticker| &sticker.id).collect();
+            return Ok(near_duplicates
+                .into_iter()
+                .filter(|pair| ids_a.contains(&pair.sticker_a.id) && ids_b.contains(&pair.sticker_b.id))
+                .collect_vec());
+        }
+        let near_duplicates = self.near_duplicates(only_a, only_b).await?;
+        self.near_duplicate_cache
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(
+                key,
+                CachedNearDuplicates {
+                    near_duplicates: near_duplicates.clone(),
+                    found_at: Instant::now(),
+                },
+            );
+        Ok(near_duplicates)
+    }
+
+    /// candidates come from a similarity search and are confirmed by comparing both vectors of
+    /// the pair; every sticker is part of at most one pair
+    async fn near_duplicates(
+        &self,
+        only_a: &[Sticker],
+        only_b: &[Sticker],
+    ) -> Result<Vec<NearDuplicate>, InternalError> {
+        let stickers_b: HashMap<_, _> = only_b
+            .iter()
+            .map(|sticker| (sticker.sticker_file_id.clone(), sticker))
+            .collect();
+        let mut matched_b = HashSet::new();
+        let mut near_duplicates = Vec::new();
+        if stickers_b.is_empty() {
+            return Ok(near_duplicates);
+        }
+        for sticker_a in only_a.iter().take(MAX_NEAR_DUPLICATE_LOOKUPS) {
+            // the sticker might not be analyzed yet
+            let Some(matches) = self
+                .vector_db
+                .find_similar_stickers(
+                    &[sticker_a.sticker_file_id.clone()],
+                    &[],
+                    SimilarityAspect::Embedding,
+                    NEAR_DUPLICATE_CANDIDATE_THRESHOLD,
+                    NEAR_DUPLICATE_CANDIDATES,
+                    0,
+                )
+                .await?
+            else {
+                continue;
+            };
+            for candidate in matches {
+                let Some(sticker_b) = stickers_b.get(&candidate.file_hash) else {
+                    continue;
+                };
+                if matched_b.contains(&sticker_b.id) {
+                    continue;
+                }
+                let similarity = self
+                    .vector_db
+                    .compare_sticker_similarities(
+                        sticker_a.sticker_file_id.clone(),
+                        sticker_b.sticker_file_id.clone(),
+                    )
+                    .await?;
+                if similarity.clip >= NEAR_DUPLICATE_CLIP_THRESHOLD
+                    && similarity.histogram >= NEAR_DUPLICATE_HISTOGRAM_THRESHOLD
+                {
+                    matched_b.insert(sticker_b.id.clone());
+                    near_duplicates.push(NearDuplicate {
+                        sticker_a: sticker_a.clone(),
+                        sticker_b: (*sticker_b).clone(),
+                        similarity,
+                    });
+                    break;
+                }
+            }
+        }
+        Ok(near_duplicates
+            .into_iter()
+            .sorted_by(|a, b| b.similarity.clip.total_cmp(&a.similarity.clip))
+            .collect_vec())
+    }
+
+    async fn tag_differences(
+        &self,
+        set_id_a: &StickerSetId,
+        set_id_b: &StickerSetId,
+    ) -> Result<Vec<TagDifference>, InternalError> {
+        let tags_a: HashMap<_, _> = self
+            .database
+            .get_all_sticker_set_tag_counts(set_id_a)
+            .await?
+            .into_iter()
+            .collect();
+        let tags_b: HashMap<_, _> = self
+            .database
+            .get_all_sticker_set_tag_counts(set_id_b)
+            .await?
+            .into_iter()
+            .collect();
+        Ok(tags_a
+            .keys()
+            .chain(tags_b.keys())
+            .unique()
+            .map(|tag| TagDifference {
+                tag: tag.clone(),
+                count_a: tags_a.get(tag).copied().unwrap_or_default(),
+                count_b: tags_b.get(tag).copied().unwrap_or_default(),
+            })
+            .filter(|difference| difference.count_a != difference.count_b)
+            .sorted_by(|a, b| {
+                (b.count_a - b.count_b)
+                    .abs()
+                    .cmp(&(a.count_a - a.count_b).abs())
+                    .then_with(|| a.tag.cmp(&b.tag))
+            })
+            .collect_vec())
+    }
+
+    /// records the decision of a moderator on a pair of stickers; used by the merge queue and the
+    /// set comparison. The caller checks that the user is a moderator
+    #[tracing::instrument(skip(self))]
+    pub async fn decide_merge(
+        &self,
+        moderator_id: i64,
+        sticker_id_a: &StickerId,
+        sticker_id_b: &StickerId,
+        merge: bool,
+    ) -> Result<(), InternalError> {
+        let file_a = self
+            .database
+            .get_sticker_file_by_sticker_id(sticker_id_a)
+            .await?
+            .required()?;
+        let file_b = self
+            .database
+            .get_sticker_file_by_sticker_id(sticker_id_b)
+            .await?
+            .required()?;
+        if merge {
+            determine_canonical_sticker_and_merge(
+                sticker_id_a.clone(),
+                sticker_id_b.clone(),
+                self.database.clone(),
+            )
+            .await?;
+        }
+        self.database
+            .add_or_modify_potential_merge(
+                &file_a.id,
+                &file_b.id,
+                if merge {
+                    MergeStatus::Merged
+                } else {
+                    MergeStatus::NotMerged
+                },
+            )
+            .await?;
+        self.database
+            .add_moderation_log_entry(
+                Some(moderator_id),
+                if merge {
+                    ModerationAction::MergeStickers
+                } else {
+                    ModerationAction::KeepStickersSeparate
+                },
+                sticker_id_a,
+                Some(format!("with {sticker_id_b}")),
+            )
+            .await?;
+        Ok(())
+    }
+}
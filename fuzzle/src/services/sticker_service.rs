@@ -1,7 +1,9 @@
+use std::collections::HashSet;
+
 use chrono::NaiveDateTime;
 use itertools::Itertools;
 
-use crate::{bot::InternalError, database::{Database, Sticker, StickerSet}, util::{StickerSetId, format_relative_time}};
+use crate::{bot::InternalError, database::{Database, Sticker, StickerSet, UserSettings}, tags::Rating, util::{StickerFileId, StickerSetId, format_relative_time}};
 
 
 #[derive(Clone)]
@@ -14,11 +16,107 @@ impl StickerService {
         Self { database }
     }
 
+    /// removes all items whose sticker file is tagged with a tag hidden by the rating profile
+    pub async fn filter_by_rating_profile<T>(
+        &self,
+        items: Vec<T>,
+        rating_profile: Rating,
+        sticker_file_id: impl Fn(&T) -> &StickerFileId,
+    ) -> Result<Vec<T>, InternalError> {
+        self.filter_by_hidden_tags(items, rating_profile.hidden_tags(), sticker_file_id)
+            .await
+    }
+
+    /// removes all items whose sticker file is tagged with any of the tags; untagged files are
+    /// always kept
+    pub async fn filter_by_hidden_tags<T>(
+        &self,
+        items: Vec<T>,
+        hidden_tags: Vec<String>,
+        sticker_file_id: impl Fn(&T) -> &StickerFileId,
+    ) -> Result<Vec<T>, InternalError> {
+        if hidden_tags.is_empty() || items.is_empty() {
+            return Ok(items);
+        }
+        let file_ids = items
+            .iter()
+            .map(|item| sticker_file_id(item).clone())
+            .unique()
+            .collect_vec();
+        let hidden_files: HashSet<StickerFileId> = self
+            .database
+            .get_matching_sticker_file_tags(file_ids, hidden_tags)
+            .await?
+            .into_iter()
+            .map(|(file_id, _)| file_id)
+            .collect();
+        Ok(items
+            .into_iter()
+            .filter(|item| !hidden_files.contains(sticker_file_id(item)))
+            .collect())
+    }
+
+    pub async fn filter_stickers_by_hidden_tags(
+        &self,
+        stickers: Vec<Sticker>,
+        hidden_tags: Vec<String>,
+    ) -> Result<Vec<Sticker>, InternalError> {
+        self.filter_by_hidden_tags(stickers, hidden_tags, |sticker| &sticker.sticker_file_id)
+            .await
+    }
+
+    pub async fn filter_stickers_by_rating_profile(
+        &self,
+        stickers: Vec<Sticker>,
+        rating_profile: Rating,
+    ) -> Result<Vec<Sticker>, InternalError> {
+        self.filter_by_rating_profile(stickers, rating_profile, |sticker| &sticker.sticker_file_id)
+            .await
+    }
+
+    /// applies the rating profile and the blacklist of the settings; blacklisted tags are ignored
+    /// for stickers of whitelisted sets
+    pub async fn filter_stickers_by_settings(
+        &self,
+        stickers: Vec<Sticker>,
+        settings: &UserSettings,
+        blacklist: &[String],
+    ) -> Result<Vec<Sticker>, InternalError> {
+        let stickers = self
+            .filter_stickers_by_rating_profile(stickers, settings.rating_profile())
+            .await?;
+        let hidden_tags = settings.hidden_tags(blacklist, false);
+        if hidden_tags.is_empty() || stickers.is_empty() {
+            return Ok(stickers);
+        }
+        let file_ids = stickers
+            .iter()
+            .map(|sticker| sticker.sticker_file_id.clone())
+            .unique()
+            .collect_vec();
+        let hidden_files: HashSet<StickerFileId> = self
+            .database
+            .get_matching_sticker_file_tags(file_ids, hidden_tags)
+            .await?
+            .into_iter()
+            .map(|(file_id, _)| file_id)
+            .collect();
+        Ok(stickers
+            .into_iter()
+            .filter(|sticker| {
+                settings.whitelisted_sets.contains(&sticker.sticker_set_id)
+                    || !hidden_files.contains(&sticker.sticker_file_id)
+            })
+            .collect())
+    }
+
     pub async fn get_sticker_set_timeline(
         &self,
         set_id: &StickerSetId,
+        hidden_tags: Vec<String>,
     ) -> Result<Vec<(String, Vec<Sticker>)>, InternalError> {
         let stickers = self.database.get_all_stickers_in_set(set_id).await?;
+        let stickers = self.filter_stickers_by_hidden_tags(stickers, hidden_tags).await?;
 
     let groups = stickers
         .into_iter()
@@ -36,11 +134,14 @@ impl StickerService {
         &self,
         limit: i64,
         before: NaiveDateTime,
+        hidden_tags: Vec<String>,
     ) -> Result<Option<(NaiveDateTime, Vec<(String, Vec<Sticker>)>)>, InternalError> {
         let stickers = self.database.get_latest_stickers(limit, before).await?;
         let Some(after) = stickers.last().map(|sticker| sticker.created_at) else {
             return Ok(None);
         };
+        // paginate before filtering so that a page full of hidden stickers does not end the timeline
+        let stickers = self.filter_stickers_by_hidden_tags(stickers, hidden_tags).await?;
 
         let groups = stickers
             .into_iter()
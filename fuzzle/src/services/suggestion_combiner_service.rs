@@ -0,0 +1,96 @@
+use std::sync::{Arc, RwLock};
+
+use crate::{
+    bot::InternalError,
+    database::Database,
+    tags::{SuggestionCombiner, SuggestionSource},
+};
+
+/// keeps the trained source weights in memory
+#[derive(Clone)]
+pub struct SuggestionCombinerService {
+    database: Database,
+    combiner: Arc<RwLock<Arc<SuggestionCombiner>>>,
+    /// trained by the last evaluation, but not confirmed by an admin yet
+    proposed: Arc<RwLock<Option<SuggestionCombiner>>>,
+}
+
+impl SuggestionCombinerService {
+    pub fn new(database: Database) -> Self {
+        Self {
+            database,
+            combiner: Arc::new(RwLock::new(Arc::new(SuggestionCombiner::default()))),
+            proposed: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    #[must_use]
+    pub fn current(&self) -> Arc<SuggestionCombiner> {
+        self.combiner
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// loads the weights of the last training run; sources without a weight count as 1
+    #[tracing::instrument(skip(self))]
+    pub async fn reload(&self) -> Result<(), InternalError> {
+        let weights = self
+            .database
+            .get_tag_suggestion_source_weights()
+            .await?
+            .into_iter()
+            .filter_map(|(name, weight)| {
+                SuggestionSource::from_name(&name).map(|source| (source, weight))
+            })
+            .collect();
+        self.replace(SuggestionCombiner::new(weights));
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, combiner))]
+    pub async fn update(&self, combiner: SuggestionCombiner) -> Result<(), InternalError> {
+        let weights = combiner
+            .weights()
+            .iter()
+            .map(|(source, weight)| (source.name().to_string(), *weight))
+            .collect();
+        self.database
+            .replace_tag_suggestion_source_weights(weights)
+            .await?;
+        self.replace(combiner);
+        Ok(())
+    }
+
+    /// keeps the weights until they are applied with `apply_proposed`; replaces earlier proposals
+    pub fn propose(&self, combiner: SuggestionCombiner) {
+        let mut proposed = self
+            .proposed
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        *proposed = Some(combiner);
+    }
+
+    /// false if there was nothing to apply (eg after a restart)
+    #[tracing::instrument(skip(self))]
+    pub async fn apply_proposed(&self) -> Result<bool, InternalError> {
+        let proposed = self
+            .proposed
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take();
+        let Some(combiner) = proposed else {
+            return Ok(false);
+        };
+        self.update(combiner).await?;
+        Ok(true)
+    }
+
+    fn replace(&self, combiner: SuggestionCombiner) {
+        let mut current = self
+            .combiner
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        *current = Arc::new(combiner);
+    }
+}
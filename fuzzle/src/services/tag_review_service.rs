@@ -0,0 +1,180 @@
+use itertools::Itertools;
+
+use crate::{
+    background_tasks::TagManagerService,
+    bot::InternalError,
+    database::{Database, Sticker, StickerFileTag, TrustLevel},
+    tags::{TagConflict, exclusive_tag_groups, find_tag_conflicts},
+    util::{StickerFileId, StickerId},
+};
+
+/// tags of users with this trust level need a second opinion
+const REVIEWED_TRUST_LEVEL: TrustLevel = TrustLevel::New;
+/// older tags are not reviewed anymore
+const REVIEW_MAX_AGE_DAYS: i64 = 14;
+/// files that are checked for conflicts per group of exclusive tags
+const CONFLICT_CANDIDATE_COUNT: i64 = 200;
+
+#[derive(Debug, Clone)]
+pub struct TagReview {
+    pub sticker: Sticker,
+    /// unconfirmed tags of low trust users
+    pub tags: Vec<StickerFileTag>,
+    pub conflicts: Vec<TagConflict>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConflictingTags {
+    pub sticker: Sticker,
+    pub conflicts: Vec<TagConflict>,
+}
+
+/// second opinions on tags of new taggers, and tags that contradict each other
+#[derive(Clone)]
+pub struct TagReviewService {
+    database: Database,
+}
+
+impl TagReviewService {
+    pub fn new(database: Database) -> Self {
+        Self { database }
+    }
+
+    /// the review after the sticker that was shown last, so that skipping works
+    #[tracing::instrument(skip(self, tag_manager))]
+    pub async fn next_review(
+        &self,
+        reviewer_id: i64,
+        after: Option<&StickerId>,
+        tag_manager: &TagManagerService,
+    ) -> Result<Option<TagReview>, InternalError> {
+        let since = chrono::Utc::now().naive_utc() - chrono::Duration::days(REVIEW_MAX_AGE_DAYS);
+        let after = match after {
+            Some(sticker_id) => self
+                .database
+                .get_sticker_file_by_sticker_id(sticker_id)
+                .await?
+                .map(|file| file.id),
+            None => None,
+        };
+        let Some(sticker_file_id) = self
+            .database
+            .get_next_sticker_file_to_review(REVIEWED_TRUST_LEVEL, reviewer_id, since, after)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let Some(sticker) = self
+            .database
+            .get_some_sticker_by_file_id(&sticker_file_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let tags = self
+            .database
+            .get_unconfirmed_sticker_file_tags(&sticker_file_id, REVIEWED_TRUST_LEVEL, reviewer_id)
+            .await?;
+        let conflicts = self.get_conflicts(&sticker_file_id, tag_manager).await?;
+        Ok(Some(TagReview {
+            sticker,
+            tags,
+            conflicts,
+        }))
+    }
+
+    /// the tags of the file that the reviewer has not reviewed yet
+    #[tracing::instrument(skip(self))]
+    pub async fn pending_tags(
+        &self,
+        sticker_file_id: &StickerFileId,
+        reviewer_id: i64,
+    ) -> Result<Vec<StickerFileTag>, InternalError> {
+        Ok(self
+            .database
+            .get_unconfirmed_sticker_file_tags(sticker_file_id, REVIEWED_TRUST_LEVEL, reviewer_id)
+            .await?)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn confirm(
+        &self,
+        sticker_file_id: &StickerFileId,
+        tag: &str,
+        reviewer_id: i64,
+    ) -> Result<(), InternalError> {
+        Ok(self
+            .database
+            .confirm_sticker_file_tag(sticker_file_id, tag, reviewer_id)
+            .await?)
+    }
+
+    /// removes the tag; it stays in the tag history
+    #[tracing::instrument(skip(self))]
+    pub async fn reject(
+        &self,
+        sticker_file_id: &StickerFileId,
+        tag: &str,
+        reviewer_id: i64,
+    ) -> Result<(), InternalError> {
+        Ok(self
+            .database
+            .untag_file(sticker_file_id, &[tag.to_string()], reviewer_id)
+            .await?)
+    }
+
+    #[tracing::instrument(skip(self, tag_manager))]
+    pub async fn get_conflicts(
+        &self,
+        sticker_file_id: &StickerFileId,
+        tag_manager: &TagManagerService,
+    ) -> Result<Vec<TagConflict>, InternalError> {
+        let tags = self.database.get_sticker_tags_by_file_id(sticker_file_id).await?;
+        Ok(find_tag_conflicts(&tags, |tag| tag_manager.get_implications(tag)))
+    }
+
+    /// files whose tags contradict each other
+    #[tracing::instrument(skip(self, tag_manager))]
+    pub async fn find_conflicting_tags(
+        &self,
+        limit: usize,
+        tag_manager: &TagManagerService,
+    ) -> Result<Vec<ConflictingTags>, InternalError> {
+        // tags that imply a tag of the group can cause conflicts as well
+        let groups = exclusive_tag_groups()
+            .into_iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .flat_map(|tag| {
+                        std::iter::once(tag.clone())
+                            .chain(tag_manager.get_inverse_implications(tag).unwrap_or_default())
+                    })
+                    .unique()
+                    .collect_vec()
+            })
+            .collect_vec();
+        let candidates = self
+            .database
+            .get_sticker_file_ids_with_multiple_tags_of_group(groups, CONFLICT_CANDIDATE_COUNT)
+            .await?;
+        let mut result = Vec::new();
+        for sticker_file_id in candidates {
+            if result.len() >= limit {
+                break;
+            }
+            let conflicts = self.get_conflicts(&sticker_file_id, tag_manager).await?;
+            if conflicts.is_empty() {
+                continue;
+            }
+            if let Some(sticker) = self
+                .database
+                .get_some_sticker_by_file_id(&sticker_file_id)
+                .await?
+            {
+                result.push(ConflictingTags { sticker, conflicts });
+            }
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,103 @@
+use std::sync::{Arc, RwLock};
+
+use crate::{
+    bot::{BotError, InternalError, UserError},
+    database::Database,
+    tags::{DEFAULT_RULES, RuleSubject, RuleSuggestions, TagSuggestionRules, get_default_rules},
+    util::{Required, StickerId},
+};
+
+/// keeps the parsed rules in memory; saving new rules replaces them without a restart
+#[derive(Clone)]
+pub struct TagSuggestionRulesService {
+    database: Database,
+    rules: Arc<RwLock<Arc<TagSuggestionRules>>>,
+}
+
+impl TagSuggestionRulesService {
+    pub fn new(database: Database) -> Self {
+        Self {
+            database,
+            rules: Arc::new(RwLock::new(Arc::new(get_default_rules()))),
+        }
+    }
+
+    #[must_use]
+    pub fn current(&self) -> Arc<TagSuggestionRules> {
+        self.rules
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// the source of the active rules
+    #[tracing::instrument(skip(self))]
+    pub async fn get_source(&self) -> Result<String, InternalError> {
+        Ok(self
+            .database
+            .get_latest_tag_suggestion_rules()
+            .await?
+            .map_or_else(|| DEFAULT_RULES.to_string(), |revision| revision.rules))
+    }
+
+    /// loads the latest saved rules; invalid rules are logged and the current ones are kept
+    #[tracing::instrument(skip(self))]
+    pub async fn reload(&self) -> Result<(), InternalError> {
+        let source = self.get_source().await?;
+        match TagSuggestionRules::parse(&source) {
+            Ok(rules) => self.replace(rules),
+            Err(err) => tracing::error!("saved tag suggestion rules are invalid: {err}"),
+        }
+        Ok(())
+    }
+
+    /// validates and saves the rules, then makes them active
+    ///
+    /// returns the id of the new revision
+    #[tracing::instrument(skip(self, source))]
+    pub async fn update(&self, source: String, user_id: Option<i64>) -> Result<i64, BotError> {
+        let rules = Self::validate(&source)?;
+        let revision_id = self
+            .database
+            .insert_tag_suggestion_rules(source, user_id)
+            .await?;
+        self.replace(rules);
+        Ok(revision_id)
+    }
+
+    /// applies the rules to a sticker without saving them
+    #[tracing::instrument(skip(self, source))]
+    pub async fn dry_run(
+        &self,
+        source: &str,
+        sticker_id: &StickerId,
+    ) -> Result<RuleSuggestions, BotError> {
+        let rules = Self::validate(source)?;
+        let set = self
+            .database
+            .get_sticker_set_by_sticker_id(sticker_id)
+            .await?
+            .required()?;
+        let emojis = self.database.get_sticker_emojis(sticker_id).await?;
+        let file = self.database.get_sticker_file_by_sticker_id(sticker_id).await?;
+        Ok(rules.suggest_tags(&RuleSubject {
+            emojis,
+            set_title: set.title.unwrap_or_default(),
+            set_name: set.id,
+            sticker_type: file.map(|file| file.sticker_type),
+            owner_id: set.created_by_user_id,
+        }))
+    }
+
+    fn validate(source: &str) -> Result<TagSuggestionRules, UserError> {
+        TagSuggestionRules::parse(source).map_err(|err| UserError::ValidationError(err.to_string()))
+    }
+
+    fn replace(&self, rules: TagSuggestionRules) {
+        let mut current = self
+            .rules
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        *current = Arc::new(rules);
+    }
+}
@@ -0,0 +1,151 @@
+use itertools::Itertools;
+
+use crate::{
+    background_tasks::TagManagerService,
+    bot::{BotError, InternalError, UserError},
+    database::{Database, ModerationTaskDetails, Order, Sticker, TagDescription, TagUsage, User},
+    tags::Category,
+};
+
+use super::StickerService;
+
+/// stickers shown as examples on the wiki page of a tag
+const EXAMPLE_STICKER_COUNT: usize = 6;
+const MAX_DESCRIPTION_LENGTH: usize = 2000;
+
+#[derive(Debug, Clone)]
+pub struct TagWiki {
+    pub tag: String,
+    pub category: Category,
+    pub description: Option<TagDescription>,
+    /// names that resolve to the tag
+    pub aliases: Vec<String>,
+    /// tags that are added together with this tag
+    pub implications: Vec<String>,
+    /// tags that add this tag
+    pub inverse_implications: Vec<String>,
+    /// the examples of the person that proposed the tag, or else recently tagged stickers; only
+    /// those allowed by the rating profile
+    pub example_stickers: Vec<Sticker>,
+    /// oldest month first
+    pub usage: Vec<TagUsage>,
+}
+
+impl TagWiki {
+    #[must_use]
+    pub fn sticker_count(&self) -> i64 {
+        self.usage.iter().map(|usage| usage.count).sum()
+    }
+}
+
+/// descriptions, examples and relationships of tags
+#[derive(Clone)]
+pub struct TagWikiService {
+    database: Database,
+    sticker: StickerService,
+}
+
+impl TagWikiService {
+    pub fn new(database: Database, sticker: StickerService) -> Self {
+        Self { database, sticker }
+    }
+
+    /// none if the tag does not exist
+    #[tracing::instrument(skip(self, tag_manager))]
+    pub async fn wiki(
+        &self,
+        tag: &str,
+        tag_manager: &TagManagerService,
+        hidden_tags: Vec<String>,
+    ) -> Result<Option<TagWiki>, InternalError> {
+        let Some(category) = tag_manager.get_category(tag) else {
+            return Ok(None);
+        };
+        Ok(Some(TagWiki {
+            tag: tag.to_string(),
+            category,
+            description: self.database.get_tag_description(tag).await?,
+            aliases: tag_manager.get_aliases_of(tag),
+            implications: tag_manager
+                .get_implications(tag)
+                .unwrap_or_default()
+                .into_iter()
+                .sorted()
+                .collect_vec(),
+            inverse_implications: tag_manager
+                .get_inverse_implications(tag)
+                .unwrap_or_default()
+                .into_iter()
+                .sorted()
+                .collect_vec(),
+            example_stickers: self.example_stickers(tag, hidden_tags).await?,
+            usage: self.database.get_tag_usage_by_month(tag).await?,
+        }))
+    }
+
+    async fn example_stickers(
+        &self,
+        tag: &str,
+        hidden_tags: Vec<String>,
+    ) -> Result<Vec<Sticker>, InternalError> {
+        let mut stickers = Vec::new();
+        if let Some(task) = self.database.get_create_tag_task(tag).await? {
+            if let ModerationTaskDetails::CreateTag {
+                example_sticker_id, ..
+            } = task.details
+            {
+                for sticker_id in example_sticker_id {
+                    // the sticker might have been removed since the tag was created
+                    if let Some(sticker) = self.database.get_sticker_by_id(&sticker_id).await? {
+                        stickers.push(sticker);
+                    }
+                }
+            }
+        }
+        let mut stickers = self
+            .sticker
+            .filter_stickers_by_hidden_tags(stickers, hidden_tags.clone())
+            .await?;
+        if stickers.is_empty() {
+            stickers = self
+                .database
+                .get_stickers_for_tag_query(
+                    vec![tag.to_string()],
+                    vec![],
+                    vec![],
+                    hidden_tags,
+                    vec![],
+                    EXAMPLE_STICKER_COUNT as i64,
+                    0,
+                    Order::LatestFirst,
+                )
+                .await?;
+        }
+        Ok(stickers.into_iter().take(EXAMPLE_STICKER_COUNT).collect_vec())
+    }
+
+    /// an empty description removes it; the caller checks `Capability::EditTagDescriptions`
+    #[tracing::instrument(skip(self, user, description, tag_manager))]
+    pub async fn update_description(
+        &self,
+        user: &User,
+        tag: &str,
+        description: &str,
+        tag_manager: &TagManagerService,
+    ) -> Result<(), BotError> {
+        if tag_manager.get_category(tag).is_none() {
+            return Err(UserError::TagsNotFound(vec![tag.to_string()]).into());
+        }
+        let description = description.trim();
+        if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+            return Err(UserError::ValidationError(format!(
+                "Descriptions can be at most {MAX_DESCRIPTION_LENGTH} characters long"
+            ))
+            .into());
+        }
+        self.database
+            .set_tag_description(tag, description, user.id)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use itertools::Itertools;
+use tracing::warn;
+
+use crate::{
+    background_tasks::{TagManagerService, TfIdfService},
+    bot::BotError,
+    database::{Database, Sticker, TaggingCandidate, TaggingFocus},
+    qdrant::VectorDatabase,
+    tags::collect_suggestions,
+    util::StickerId,
+};
+
+use super::{SuggestionCombinerService, TagSuggestionRulesService};
+
+/// popular untagged stickers that are loaded per request
+const CANDIDATE_COUNT: i64 = 30;
+/// running all suggestion sources is expensive, so only some candidates are scored
+const SCORED_CANDIDATE_COUNT: usize = 5;
+/// a sticker that was served to a user is not served to anyone else within this time
+const RESERVATION_DURATION: Duration = Duration::from_secs(10 * 60);
+
+struct Reservation {
+    user_id: i64,
+    reserved_at: Instant,
+}
+
+/// decides which sticker /tagme serves next
+///
+/// Stickers are preferred if the suggestion sources disagree about them (so tagging them
+/// teaches the most), if they are used a lot, or if they are in popular sets.
+#[derive(Clone)]
+pub struct TaggingQueueService {
+    database: Database,
+    vector_db: VectorDatabase,
+    tag_suggestion_rules: TagSuggestionRulesService,
+    suggestion_combiner: SuggestionCombinerService,
+    reservations: Arc<Mutex<HashMap<StickerId, Reservation>>>,
+}
+
+impl TaggingQueueService {
+    pub fn new(
+        database: Database,
+        vector_db: VectorDatabase,
+        tag_suggestion_rules: TagSuggestionRulesService,
+        suggestion_combiner: SuggestionCombinerService,
+    ) -> Self {
+        Self {
+            database,
+            vector_db,
+            tag_suggestion_rules,
+            suggestion_combiner,
+            reservations: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    #[tracing::instrument(skip(self, tag_manager, tfidf))]
+    pub async fn next_sticker(
+        &self,
+        user_id: i64,
+        focus: Option<TaggingFocus>,
+        tag_manager: TagManagerService,
+        tfidf: TfIdfService,
+    ) -> Result<Option<Sticker>, BotError> {
+        let (set_id, set_contains_tag) = match focus {
+            None => (None, None),
+            Some(TaggingFocus::Set(set_id)) => (Some(set_id), None),
+            Some(TaggingFocus::Tag(tag)) => (None, Some(tag)),
+        };
+        let candidates = self
+            .database
+            .get_tagging_candidates(set_id, set_contains_tag, CANDIDATE_COUNT)
+            .await?
+            .into_iter()
+            .filter(|candidate| !self.is_reserved_by_other_user(&candidate.sticker_id, user_id))
+            .take(SCORED_CANDIDATE_COUNT)
+            .collect_vec();
+
+        let rules = self.tag_suggestion_rules.current();
+        let combiner = self.suggestion_combiner.current();
+        let mut scored = Vec::new();
+        for candidate in candidates {
+            let suggestions = collect_suggestions(
+                &candidate.sticker_id,
+                tag_manager.clone(),
+                self.database.clone(),
+                tfidf.clone(),
+                self.vector_db.clone(),
+                rules.clone(),
+                &[],
+                false,
+            )
+            .await;
+            match suggestions {
+                Ok(suggestions) => {
+                    let confidence = combiner.confidence(&suggestions, &tag_manager);
+                    scored.push((priority(&candidate, confidence), candidate.sticker_id));
+                }
+                Err(err) => {
+                    warn!("could not score tagging candidate {}: {err:?}", candidate.sticker_id);
+                }
+            }
+        }
+
+        let sticker_ids = scored
+            .into_iter()
+            .sorted_by(|a, b| b.0.total_cmp(&a.0))
+            .map(|(_, sticker_id)| sticker_id)
+            .collect_vec();
+        let Some(sticker_id) = self.reserve_first_available(&sticker_ids, user_id) else {
+            return Ok(None);
+        };
+        Ok(self.database.get_sticker_by_id(&sticker_id).await?)
+    }
+
+    fn is_reserved_by_other_user(&self, sticker_id: &StickerId, user_id: i64) -> bool {
+        let reservations = self
+            .reservations
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        reservations.get(sticker_id).is_some_and(|reservation| {
+            reservation.user_id != user_id && reservation.reserved_at.elapsed() < RESERVATION_DURATION
+        })
+    }
+
+    /// checks the reservations again since other users might have been served in the meantime
+    fn reserve_first_available(&self, sticker_ids: &[StickerId], user_id: i64) -> Option<StickerId> {
+        let mut reservations = self
+            .reservations
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        // users only keep the reservation of the sticker they were served last
+        reservations.retain(|_, reservation| {
+            reservation.user_id != user_id && reservation.reserved_at.elapsed() < RESERVATION_DURATION
+        });
+        let sticker_id = sticker_ids
+            .iter()
+            .find(|sticker_id| !reservations.contains_key(*sticker_id))?
+            .clone();
+        reservations.insert(
+            sticker_id.clone(),
+            Reservation {
+                user_id,
+                reserved_at: Instant::now(),
+            },
+        );
+        Some(sticker_id)
+    }
+}
+
+/// every part is between 0 and 1
+fn priority(candidate: &TaggingCandidate, confidence: f64) -> f64 {
+    (1.0 - confidence)
+        + saturate(candidate.usage_count, 3.0)
+        + saturate(candidate.set_user_count, 10.0)
+}
+
+/// 0 for no count, 0.5 if the count is `half`, approaches 1 for large counts
+fn saturate(count: i64, half: f64) -> f64 {
+    let count = count as f64;
+    count / (count + half)
+}
@@ -0,0 +1,182 @@
+use itertools::Itertools;
+
+use crate::{
+    background_tasks::{TagManagerService, TfIdfService},
+    bot::InternalError,
+    database::{Database, TagOrigin},
+    tags::{SuggestionSource, TagConflict},
+    util::{StickerFileId, StickerSetId},
+};
+
+use super::TagReviewService;
+
+#[derive(Debug, Clone)]
+pub struct TaggedFile {
+    /// tags that were added together with the tag
+    pub implications: Vec<String>,
+    /// conflicts of all tags of the file after tagging
+    pub conflicts: Vec<TagConflict>,
+}
+
+/// adding and removing tags of files and sets, shared by the bot and the website
+#[derive(Clone)]
+pub struct TaggingService {
+    database: Database,
+    tag_review: TagReviewService,
+}
+
+impl TaggingService {
+    pub fn new(database: Database, tag_review: TagReviewService) -> Self {
+        Self {
+            database,
+            tag_review,
+        }
+    }
+
+    /// adds the tag and its implications; `None` if the tag does not exist
+    #[tracing::instrument(skip(self, tag_manager, tfidf))]
+    pub async fn tag_file(
+        &self,
+        file_id: &StickerFileId,
+        tag: &str,
+        suggestion: Option<(u8, Option<SuggestionSource>)>,
+        user_id: i64,
+        tag_manager: &TagManagerService,
+        tfidf: &TfIdfService,
+    ) -> Result<Option<TaggedFile>, InternalError> {
+        let Some(implications) = tag_manager.get_implications(tag) else {
+            return Ok(None);
+        };
+        if let Some((rank, source)) = suggestion {
+            self.database
+                .tag_file_from_suggestion(file_id, tag, &implications, user_id, source, rank.into())
+                .await?;
+        } else {
+            self.database
+                .tag_file(file_id, &[tag.to_string()], Some(user_id), TagOrigin::Manual)
+                .await?;
+            self.database
+                .tag_file(file_id, &implications, Some(user_id), TagOrigin::Implication)
+                .await?;
+        }
+        tfidf.request_recompute().await;
+        let conflicts = self.tag_review.get_conflicts(file_id, tag_manager).await?;
+        Ok(Some(TaggedFile {
+            implications,
+            conflicts,
+        }))
+    }
+
+    /// adds the tags and their implications to all files at once; returns the added tags
+    /// including implications and the number of new taggings, or `None` if none of the tags exist
+    #[tracing::instrument(skip(self, tag_manager, tfidf))]
+    pub async fn tag_files(
+        &self,
+        file_ids: &[StickerFileId],
+        tags: &[String],
+        user_id: i64,
+        tag_manager: &TagManagerService,
+        tfidf: &TfIdfService,
+    ) -> Result<Option<(Vec<String>, usize)>, InternalError> {
+        let tags = tags
+            .iter()
+            .filter(|tag| tag_manager.get_category(tag).is_some())
+            .cloned()
+            .collect_vec();
+        if tags.is_empty() {
+            return Ok(None);
+        }
+        let implications = tags
+            .iter()
+            .filter_map(|tag| tag_manager.get_implications(tag))
+            .flatten()
+            .filter(|implication| !tags.contains(implication))
+            .sorted()
+            .dedup()
+            .collect_vec();
+        let tags_with_origin = tags
+            .iter()
+            .map(|tag| (tag.clone(), TagOrigin::Manual))
+            .chain(implications.iter().map(|tag| (tag.clone(), TagOrigin::Implication)))
+            .collect_vec();
+        let taggings_changed = self
+            .database
+            .tag_files(file_ids, &tags_with_origin, user_id)
+            .await?;
+        tfidf.request_recompute().await;
+        Ok(Some((tags.into_iter().chain(implications).collect_vec(), taggings_changed)))
+    }
+
+    /// removes the tag and every tag of the file that implies it; returns the removed tags
+    #[tracing::instrument(skip(self, tag_manager))]
+    pub async fn untag_file(
+        &self,
+        file_id: &StickerFileId,
+        tag: &str,
+        user_id: i64,
+        tag_manager: &TagManagerService,
+    ) -> Result<Vec<String>, InternalError> {
+        let current = self.database.get_sticker_tags_by_file_id(file_id).await?;
+        let tags = tags_that_should_be_removed(tag.to_string(), current, tag_manager.clone())?;
+        self.database.untag_file(file_id, &tags, user_id).await?;
+        Ok(tags)
+    }
+
+    /// tags all files of the set except locked ones; returns the tags including their
+    /// implications and the number of new taggings, or `None` if none of the tags exist
+    #[tracing::instrument(skip(self, tag_manager, tfidf))]
+    pub async fn tag_set(
+        &self,
+        set_id: &StickerSetId,
+        tags: &[String],
+        user_id: i64,
+        tag_manager: &TagManagerService,
+        tfidf: &TfIdfService,
+    ) -> Result<Option<(Vec<String>, usize)>, InternalError> {
+        let tags = tags
+            .iter()
+            .filter_map(|tag| tag_manager.get_implications_including_self(tag))
+            .flatten()
+            .sorted()
+            .dedup()
+            .collect_vec();
+        if tags.is_empty() {
+            return Ok(None);
+        }
+        let taggings_changed = self
+            .database
+            .tag_all_files_in_set(set_id, &tags, user_id)
+            .await?;
+        tfidf.request_recompute().await;
+        Ok(Some((tags, taggings_changed)))
+    }
+
+    /// untags all files of the set except locked ones; returns the number of removed taggings
+    #[tracing::instrument(skip(self))]
+    pub async fn untag_set(
+        &self,
+        set_id: &StickerSetId,
+        tags: &[String],
+        user_id: i64,
+    ) -> Result<usize, InternalError> {
+        Ok(self
+            .database
+            .untag_all_files_in_set(set_id, tags, user_id)
+            .await?)
+    }
+}
+
+/// the tag and all tags that imply it
+pub fn tags_that_should_be_removed(
+    tag: String,
+    current: Vec<String>,
+    tag_manager: TagManagerService,
+) -> Result<Vec<String>, InternalError> {
+    Ok(current
+        .into_iter()
+        .map(|t| (t.clone(), tag_manager.get_implications(&t)))
+        .filter(|(t, implications)| implications.clone().unwrap_or_default().contains(&tag))
+        .map(|(t, _)| t)
+        .chain(std::iter::once(tag.clone()))
+        .collect_vec())
+}
@@ -0,0 +1,107 @@
+use itertools::Itertools;
+
+use crate::{
+    bot::{BotError, InternalError, UserError},
+    database::{Capability, CapabilityGrants, Database, TrustLevel, User},
+};
+
+/// number of tags of a new user that must still be present before they become trusted
+const TRUSTED_MIN_ACCEPTED_TAGS: i64 = 200;
+/// fraction of their tags that other users may have removed
+const TRUSTED_MAX_REJECTION_RATE: f64 = 0.05;
+
+/// trust levels and capability grants of users
+#[derive(Clone)]
+pub struct TrustService {
+    database: Database,
+}
+
+impl TrustService {
+    pub fn new(database: Database) -> Self {
+        Self { database }
+    }
+
+    /// new users with enough accepted tags become trusted; users whose level was changed by an
+    /// admin are left alone. Promotions are logged without an actor
+    ///
+    /// returns the ids of the promoted users
+    #[tracing::instrument(skip(self))]
+    pub async fn promote_users(&self) -> Result<Vec<i64>, InternalError> {
+        let histories = self.database.get_tagging_histories(TrustLevel::New).await?;
+        let promoted = histories
+            .into_iter()
+            .filter(|history| {
+                let total = history.accepted_count + history.rejected_count;
+                history.accepted_count >= TRUSTED_MIN_ACCEPTED_TAGS
+                    && (history.rejected_count as f64) <= (total as f64) * TRUSTED_MAX_REJECTION_RATE
+            })
+            .map(|history| history.user_id)
+            .collect_vec();
+        for user_id in &promoted {
+            self.database
+                .update_trust_level(*user_id, TrustLevel::Trusted, None)
+                .await?;
+        }
+        Ok(promoted)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn set_trust_level(
+        &self,
+        user_id: i64,
+        trust_level: TrustLevel,
+        admin_id: i64,
+    ) -> Result<User, BotError> {
+        self.get_user(user_id).await?;
+        self.database
+            .update_trust_level(user_id, trust_level, Some(admin_id))
+            .await?;
+        self.get_user(user_id).await
+    }
+
+    /// `None` removes the grant so that the trust level decides again
+    #[tracing::instrument(skip(self))]
+    pub async fn set_capability_grant(
+        &self,
+        user_id: i64,
+        capability: Capability,
+        granted: Option<bool>,
+        admin_id: i64,
+    ) -> Result<User, BotError> {
+        let user = self.get_user(user_id).await?;
+        let mut grants = user
+            .capability_grants
+            .map(CapabilityGrants::into_inner)
+            .unwrap_or_default();
+        match granted {
+            Some(granted) => grants.insert(capability, granted),
+            None => grants.remove(&capability),
+        };
+        let grants = if grants.is_empty() {
+            None
+        } else {
+            Some(grants.into())
+        };
+        let grant = match granted {
+            Some(true) => "granted",
+            Some(false) => "revoked",
+            None => "default",
+        };
+        self.database
+            .update_capability_grants(
+                user_id,
+                grants,
+                admin_id,
+                format!("{} {grant}", capability.name()),
+            )
+            .await?;
+        self.get_user(user_id).await
+    }
+
+    async fn get_user(&self, user_id: i64) -> Result<User, BotError> {
+        self.database
+            .get_user_by_id(user_id)
+            .await?
+            .ok_or_else(|| UserError::ValidationError(format!("user {user_id} does not exist")).into())
+    }
+}
@@ -3,7 +3,7 @@ mod measures;
 mod util;
 
 pub use histogram::{calculate_color_histogram, create_historgram_image, Histogram};
-use itertools::Either;
+use itertools::{Either, Itertools};
 pub use measures::{Match, Measures};
 use qdrant_client::qdrant::Vector;
 
@@ -43,6 +43,54 @@ pub async fn find_with_text_embedding(
     ))
 }
 
+const IMAGE_SEARCH_EMBEDDING_WEIGHT: f32 = 0.85;
+const IMAGE_SEARCH_HISTOGRAM_WEIGHT: f32 = 0.15;
+
+/// reverse image search: combines the clip embedding (what is shown) with the color histogram (how it looks)
+#[tracing::instrument(skip(database, image, vector_db, config))]
+pub async fn find_with_image(
+    database: Database,
+    image: Vec<u8>,
+    vector_db: VectorDatabase,
+    config: Arc<Config>,
+    limit: usize,
+) -> Result<Vec<Match>, BotError> {
+    let image_2 = image.clone();
+    let histogram =
+        tokio::task::spawn_blocking(move || calculate_color_histogram(image_2)).await??;
+    let embedding = image_to_clip_embedding(image, config.inference_url.clone()).await?;
+
+    // fetch more candidates than needed; the two rankings are merged afterwards
+    let candidate_count = (limit * 4) as u64;
+    let embedding_matches = vector_db
+        .find_stickers_given_vector(embedding, candidate_count, 0, None)
+        .await?;
+    let histogram_matches = vector_db
+        .find_stickers_given_histogram(histogram.into(), candidate_count, 0, None)
+        .await?;
+
+    let mut scores: std::collections::HashMap<_, (f32, f32)> = std::collections::HashMap::new();
+    for m in embedding_matches {
+        scores.entry(m.file_hash).or_default().0 = m.score;
+    }
+    for m in histogram_matches {
+        scores.entry(m.file_hash).or_default().1 = m.score;
+    }
+    let file_hashes = scores
+        .into_iter()
+        .map(|(file_hash, (embedding_score, histogram_score))| StickerMatch {
+            file_hash,
+            score: embedding_score * IMAGE_SEARCH_EMBEDDING_WEIGHT
+                + histogram_score * IMAGE_SEARCH_HISTOGRAM_WEIGHT,
+        })
+        .sorted_by(|a, b| b.score.total_cmp(&a.score))
+        .take(limit)
+        .collect_vec();
+
+    resolve_file_hashes_to_sticker_ids_and_clean_up_unreferenced_files(database, vector_db, file_hashes)
+        .await
+}
+
 #[tracing::instrument(skip(database))]
 pub async fn resolve_file_hashes_to_sticker_ids_and_clean_up_unreferenced_files(
     database: Database,
@@ -0,0 +1,120 @@
+use std::fmt::{Display, Formatter};
+
+use itertools::Itertools;
+
+use super::{all_count_tags, all_rating_tags};
+
+/// tags that are mutually exclusive, present either directly or through an implication
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagConflict {
+    pub tags: Vec<String>,
+}
+
+impl Display for TagConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.tags.join(" + "))
+    }
+}
+
+/// groups of tags of which a sticker may only have one, eg `solo` and `duo`
+#[must_use]
+pub fn exclusive_tag_groups() -> Vec<Vec<String>> {
+    vec![all_count_tags(), all_rating_tags()]
+}
+
+/// `implications` returns all (transitive) implications of a tag
+///
+/// Tags that are implied by another tag of the same group do not conflict with it (eg `trio`
+/// implies `group`).
+#[must_use]
+pub fn find_tag_conflicts(
+    tags: &[String],
+    implications: impl Fn(&str) -> Option<Vec<String>>,
+) -> Vec<TagConflict> {
+    let effective_tags = tags
+        .iter()
+        .flat_map(|tag| {
+            std::iter::once(tag.clone()).chain(implications(tag).unwrap_or_default())
+        })
+        .unique()
+        .collect_vec();
+    exclusive_tag_groups()
+        .into_iter()
+        .filter_map(|group| {
+            let present = group
+                .iter()
+                .filter(|tag| effective_tags.contains(tag))
+                .collect_vec();
+            let conflicting = present
+                .iter()
+                .copied()
+                .filter(|tag| {
+                    !present.iter().any(|other| {
+                        other != tag
+                            && implications(other).is_some_and(|implied| implied.contains(tag))
+                    })
+                })
+                .cloned()
+                .collect_vec();
+            (conflicting.len() > 1).then_some(TagConflict { tags: conflicting })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(ToString::to_string).collect()
+    }
+
+    fn implications(implications: &[(&str, &[&str])]) -> impl Fn(&str) -> Option<Vec<String>> {
+        let implications: HashMap<String, Vec<String>> = implications
+            .iter()
+            .map(|(tag, implied)| ((*tag).to_string(), tags(implied)))
+            .collect();
+        move |tag| Some(implications.get(tag).cloned().unwrap_or_default())
+    }
+
+    #[test]
+    fn no_conflicts() {
+        let conflicts = find_tag_conflicts(&tags(&["solo", "safe", "fox"]), implications(&[]));
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn character_count_and_rating_conflicts() {
+        let conflicts = find_tag_conflicts(
+            &tags(&["duo", "solo", "safe", "explicit"]),
+            implications(&[]),
+        );
+        assert_eq!(
+            conflicts,
+            vec![
+                TagConflict { tags: tags(&["solo", "duo"]) },
+                TagConflict { tags: tags(&["safe", "explicit"]) },
+            ]
+        );
+    }
+
+    #[test]
+    fn implied_tags_of_the_same_group_do_not_conflict() {
+        let conflicts = find_tag_conflicts(
+            &tags(&["trio", "group"]),
+            implications(&[("trio", &["group"])]),
+        );
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn implications_can_cause_conflicts() {
+        let conflicts = find_tag_conflicts(
+            &tags(&["solo", "romantic_couple"]),
+            implications(&[("romantic_couple", &["duo"])]),
+        );
+        assert_eq!(conflicts, vec![TagConflict { tags: tags(&["solo", "duo"]) }]);
+    }
+}
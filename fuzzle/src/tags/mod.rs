@@ -5,10 +5,16 @@ mod tag_suggestions;
 mod e621_tags;
 mod util;
 mod category;
+mod consistency;
 
 pub use download::*;
 pub use tag_manager::*;
-pub use tag_suggestions::{suggest_tags, ScoredTagSuggestion, Tfidf};
+pub use tag_suggestions::{
+    collect_suggestions, get_default_rules, suggest_tags, train_and_evaluate_suggestions, RuleSubject, RuleSuggestions,
+    ScoredTagSuggestion, SourceSuggestions, SuggestedTag, SuggestionCombiner, SuggestionEvaluation,
+    SuggestionSource, TagSuggestionRules, Tfidf, DEFAULT_RULES, EVALUATION_TOP_K,
+};
 pub use util::*;
 pub use category::*;
 pub use e621_tags::*;
+pub use consistency::*;
@@ -281,6 +281,17 @@ impl TagManager2 {
             .map(std::borrow::ToOwned::to_owned)
     }
 
+    /// names that resolve to the tag, sorted
+    #[must_use]
+    pub fn get_aliases_of(&self, tag: &str) -> Vec<String> {
+        self.aliases
+            .iter()
+            .filter(|(_, aliased_tag)| *aliased_tag == tag)
+            .map(|(alias, _)| alias.clone())
+            .sorted()
+            .collect_vec()
+    }
+
     /// discards any tags where we couldnt find a match for
     #[must_use]
     #[tracing::instrument(skip(self))]
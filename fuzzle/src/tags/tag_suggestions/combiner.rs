@@ -0,0 +1,204 @@
+use std::collections::{HashMap, HashSet};
+
+use itertools::Itertools;
+
+use crate::{background_tasks::TagManagerService, tags::Category};
+
+use super::ScoredTagSuggestion;
+use super::suggest_tags::{SourceSuggestions, SuggestionSource};
+
+/// suggestions per source that are considered when combining
+const MAX_SUGGESTIONS_PER_SOURCE: usize = 30;
+
+/// suggestions per source that count as agreeing on a tag
+const CONFIDENCE_TOP_K: usize = 3;
+
+/// a combined suggestion, attributed to the source with the highest weight that suggested it
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuggestedTag {
+    pub tag: String,
+    /// `None` for tags that are always offered
+    pub source: Option<SuggestionSource>,
+}
+
+/// Combines the suggestions of all sources into a single ranking
+///
+/// Every tag gets the sum of the weights of the sources that suggested it. Without training,
+/// every source has a weight of 1, so tags are ranked by how many sources suggested them.
+#[derive(Debug, Clone, Default)]
+pub struct SuggestionCombiner {
+    weights: HashMap<SuggestionSource, f64>,
+}
+
+impl SuggestionCombiner {
+    #[must_use]
+    pub const fn new(weights: HashMap<SuggestionSource, f64>) -> Self {
+        Self { weights }
+    }
+
+    /// weights are the share of a source's top suggestions that were actually tagged (with add-one smoothing)
+    #[must_use]
+    pub fn train(
+        samples: &[(SourceSuggestions, HashSet<String>)],
+        tag_manager: &TagManagerService,
+        k: usize,
+    ) -> Self {
+        let weights = SuggestionSource::ALL
+            .into_iter()
+            .map(|source| {
+                let (hits, suggested) = samples
+                    .iter()
+                    .map(|(suggestions, actual_tags)| {
+                        let ranked = suggestions.ranked_source(source, tag_manager);
+                        let top = ranked.iter().take(k).collect_vec();
+                        let hits = top.iter().filter(|tag| actual_tags.contains(**tag)).count();
+                        (hits, top.len())
+                    })
+                    .fold((0, 0), |(hits, suggested), (h, s)| (hits + h, suggested + s));
+                let weight = (hits as f64 + 1.0) / (suggested as f64 + 2.0);
+                (source, weight)
+            })
+            .collect();
+        Self { weights }
+    }
+
+    #[must_use]
+    pub fn weight(&self, source: SuggestionSource) -> f64 {
+        self.weights.get(&source).copied().unwrap_or(1.0)
+    }
+
+    #[must_use]
+    pub const fn weights(&self) -> &HashMap<SuggestionSource, f64> {
+        &self.weights
+    }
+
+    /// how much the sources agree on the best tag: 0 if nothing was suggested, 1 if every source
+    /// that suggested something has the tag among its top suggestions
+    #[must_use]
+    pub fn confidence(
+        &self,
+        suggestions: &SourceSuggestions,
+        tag_manager: &TagManagerService,
+    ) -> f64 {
+        let mut total_weight = 0.0;
+        let mut tag_weights: HashMap<String, f64> = HashMap::new();
+        for (source, _) in &suggestions.by_source {
+            let ranked = suggestions.ranked_source(*source, tag_manager);
+            if ranked.is_empty() {
+                continue;
+            }
+            let weight = self.weight(*source);
+            total_weight += weight;
+            for tag in ranked.into_iter().take(CONFIDENCE_TOP_K) {
+                *tag_weights.entry(tag).or_default() += weight;
+            }
+        }
+        if total_weight == 0.0 {
+            return 0.0;
+        }
+        tag_weights.into_values().fold(0.0, f64::max) / total_weight
+    }
+
+    #[tracing::instrument(skip(self, suggestions, tag_manager))]
+    pub fn combine(
+        &self,
+        suggestions: &SourceSuggestions,
+        sticker_tags: &[String],
+        tag_manager: &TagManagerService,
+    ) -> Vec<SuggestedTag> {
+        let mut all_tags: HashMap<String, f64> = HashMap::new();
+        let mut best_sources: HashMap<String, SuggestionSource> = HashMap::new();
+        for (source, _) in &suggestions.by_source {
+            let weight = self.weight(*source);
+            for tag in suggestions.ranked_source(*source, tag_manager) {
+                *all_tags.entry(tag.clone()).or_default() += weight;
+                best_sources
+                    .entry(tag)
+                    .and_modify(|best| {
+                        if self.weight(*best) < weight {
+                            *best = *source;
+                        }
+                    })
+                    .or_insert(*source);
+            }
+        }
+        add_default_tags(&mut all_tags);
+
+        let mut limits = HashMap::new();
+        limits.insert(Category::General, 15);
+        limits.insert(Category::Species, 5);
+        limits.insert(Category::Meta, 5);
+
+        all_tags
+            .into_iter()
+            .sorted_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)))
+            .map(|it| it.0)
+            .filter(|suggestion| !sticker_tags.contains(suggestion))
+            .filter(|suggestion| !suggestions.suppressed.contains(suggestion))
+            .filter(|suggestion| {
+                let category = tag_manager.get_category(suggestion);
+                category
+                    .map(|category| {
+                        let entry = limits.entry(category).or_insert(2);
+                        *entry -= 1;
+                        *entry >= 0
+                    })
+                    .unwrap_or_default()
+            })
+            .take(20)
+            .map(|tag| SuggestedTag {
+                source: best_sources.get(&tag).copied(),
+                tag,
+            })
+            .collect_vec()
+    }
+}
+
+impl SourceSuggestions {
+    /// the tags of a single source (including implications), best first
+    #[must_use]
+    pub fn ranked_source(
+        &self,
+        source: SuggestionSource,
+        tag_manager: &TagManagerService,
+    ) -> Vec<String> {
+        self.by_source
+            .iter()
+            .filter(|(s, _)| *s == source)
+            .flat_map(|(_, suggestions)| {
+                ScoredTagSuggestion::merge(
+                    ScoredTagSuggestion::add_implications(suggestions.clone(), tag_manager.clone()),
+                    vec![],
+                )
+            })
+            .take(MAX_SUGGESTIONS_PER_SOURCE)
+            .map(|suggestion| suggestion.tag)
+            .filter(|tag| !self.suppressed.contains(tag))
+            .collect_vec()
+    }
+}
+
+/// tags that should always be offered, even if no source suggested them
+fn add_default_tags(all_tags: &mut HashMap<String, f64>) {
+    let default_tags = [
+        "ych_(character)",
+        "questionable",
+        "explicit",
+        "safe",
+        "solo",
+        "diaper",
+        "duo",
+        "watersports",
+        "young",
+        "vore",
+        "scat",
+        "gore",
+        "attribution",
+        "male",
+        "female",
+        "ambiguous_gender",
+    ];
+    for tag in default_tags {
+        all_tags.entry(tag.to_string()).or_default(); // add default tags with score 0
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use itertools::{Either, Itertools};
+
+use crate::background_tasks::{TagManagerService, TfIdfService};
+use crate::bot::BotError;
+use crate::database::Database;
+use crate::qdrant::VectorDatabase;
+
+use super::combiner::{SuggestedTag, SuggestionCombiner};
+use super::rules::TagSuggestionRules;
+use super::suggest_tags::{SourceSuggestions, SuggestionSource, collect_suggestions};
+
+/// the cutoffs that the precision is reported for
+pub const EVALUATION_TOP_K: [usize; 3] = [1, 5, 10];
+
+/// how many suggestions of each source count towards its weight
+const TRAINING_TOP_K: usize = 10;
+
+/// precision at k: the share of the top k suggestions that the sticker is actually tagged with
+#[derive(Debug, Clone)]
+pub struct SuggestionEvaluation {
+    pub training_samples: usize,
+    pub test_samples: usize,
+    /// one value per entry of `EVALUATION_TOP_K`
+    pub sources: Vec<(SuggestionSource, Vec<f64>)>,
+    pub untrained: Vec<f64>,
+    pub trained: Vec<f64>,
+    pub combiner: SuggestionCombiner,
+}
+
+/// Trains the combiner on half of a sample of tagged stickers and evaluates it on the other half
+///
+/// The sticker's own tags are hidden from all sources: its file and (except for the same set
+/// source) its set are excluded. The trained weights are only returned, applying them is up to
+/// the caller.
+#[tracing::instrument(skip(tag_manager, database, tfidf_service, vector_db, rules))]
+pub async fn train_and_evaluate_suggestions(
+    sample_size: i64,
+    tag_manager: TagManagerService,
+    database: Database,
+    tfidf_service: TfIdfService,
+    vector_db: VectorDatabase,
+    rules: Arc<TagSuggestionRules>,
+) -> Result<SuggestionEvaluation, BotError> {
+    let stickers = database.get_random_tagged_stickers(sample_size).await?;
+    let mut samples = Vec::new();
+    for sticker in stickers {
+        let actual_tags: HashSet<String> = database
+            .get_sticker_tags(&sticker.id)
+            .await?
+            .into_iter()
+            .collect();
+        let suggestions = collect_suggestions(
+            &sticker.id,
+            tag_manager.clone(),
+            database.clone(),
+            tfidf_service.clone(),
+            vector_db.clone(),
+            rules.clone(),
+            &[],
+            true,
+        )
+        .await?;
+        samples.push((suggestions, actual_tags));
+    }
+
+    let (training, test): (Vec<_>, Vec<_>) =
+        samples
+            .into_iter()
+            .enumerate()
+            .partition_map(|(index, sample)| {
+                if index % 2 == 0 {
+                    Either::Left(sample)
+                } else {
+                    Either::Right(sample)
+                }
+            });
+
+    let combiner = SuggestionCombiner::train(&training, &tag_manager, TRAINING_TOP_K);
+    let sources = SuggestionSource::ALL
+        .into_iter()
+        .map(|source| {
+            let precision = precision_at_k(&test, |suggestions| {
+                suggestions.ranked_source(source, &tag_manager)
+            });
+            (source, precision)
+        })
+        .collect_vec();
+    let untrained = precision_at_k(&test, |suggestions| {
+        suggested_tag_names(SuggestionCombiner::default().combine(suggestions, &[], &tag_manager))
+    });
+    let trained = precision_at_k(&test, |suggestions| {
+        suggested_tag_names(combiner.combine(suggestions, &[], &tag_manager))
+    });
+
+    Ok(SuggestionEvaluation {
+        training_samples: training.len(),
+        test_samples: test.len(),
+        sources,
+        untrained,
+        trained,
+        combiner,
+    })
+}
+
+fn suggested_tag_names(suggestions: Vec<SuggestedTag>) -> Vec<String> {
+    suggestions.into_iter().map(|suggestion| suggestion.tag).collect()
+}
+
+fn precision_at_k(
+    samples: &[(SourceSuggestions, HashSet<String>)],
+    rank: impl Fn(&SourceSuggestions) -> Vec<String>,
+) -> Vec<f64> {
+    let rankings = samples
+        .iter()
+        .map(|(suggestions, actual_tags)| (rank(suggestions), actual_tags))
+        .collect_vec();
+    EVALUATION_TOP_K
+        .into_iter()
+        .map(|k| {
+            let (hits, suggested) = rankings.iter().fold(
+                (0, 0),
+                |(hits, suggested), (ranked, actual_tags)| {
+                    let top = &ranked[..ranked.len().min(k)];
+                    let top_hits = top.iter().filter(|tag| actual_tags.contains(*tag)).count();
+                    (hits + top_hits, suggested + top.len())
+                },
+            );
+            if suggested == 0 {
+                0.0
+            } else {
+                hits as f64 / suggested as f64
+            }
+        })
+        .collect()
+}
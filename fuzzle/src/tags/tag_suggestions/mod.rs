@@ -1,3 +1,5 @@
+mod combiner;
+mod evaluation;
 mod implied;
 mod rules;
 mod same_set_tags;
@@ -9,6 +11,9 @@ mod tfidf;
 mod image_tag_similarity;
 mod owner_tags;
 
-pub use suggest_tags::suggest_tags;
+pub use rules::{DEFAULT_RULES, RuleSubject, RuleSuggestions, TagSuggestionRules, get_default_rules};
+pub use combiner::{SuggestedTag, SuggestionCombiner};
+pub use evaluation::{EVALUATION_TOP_K, SuggestionEvaluation, train_and_evaluate_suggestions};
+pub use suggest_tags::{SourceSuggestions, SuggestionSource, collect_suggestions, suggest_tags};
 pub use tag_suggestion::ScoredTagSuggestion;
 pub use tfidf::*;
@@ -1,183 +1,261 @@
 use super::tag_suggestion::ScoredTagSuggestion;
+use crate::database::StickerType;
 use crate::util::{Emoji, StickerSetId, parse_emoji, tag_literal};
 use itertools::Itertools;
 use nom::bytes::complete::{tag, take_till};
-use nom::character::complete::{alphanumeric1, multispace0, space0};
-use nom::combinator::{eof, fail, map, not, recognize, success};
-use nom::multi::{many0, many1};
+use nom::character::complete::{alphanumeric1, i64, multispace0};
+use nom::combinator::{eof, map, map_opt, recognize, success};
+use nom::multi::{many1, separated_list1};
 use nom::sequence::{delimited, preceded, terminated, tuple};
-use nom::{branch::alt, character::complete::u64, combinator::opt};
+use nom::branch::alt;
 use nom::{Finish, IResult, Parser};
+use regex::Regex;
 use std::collections::HashMap;
-use std::ops::Range;
 
 /// Anything that is more compicated than the e621 implications can be expressed as a rule
 ///
-/// The left hand side of the rule is a pattern that must match the sticker emoji, set title, or
-/// set name. The right hand side of the rule is a list of tags to apply to the sticker.
-/// If any of the left hand side patterns match, all of the right hand side tags are applied.
-/// If multiple rules match, scores are added together.
+/// The left hand side of the rule is a list of conditions on the sticker emoji, sticker type, set
+/// title, set name, or set owner. Conditions can be combined with `&` and negated with `!`.
+/// The right hand side of the rule is a list of tags to apply to the sticker, optionally weighted
+/// (`tag:0.3`), or tags to suppress (`-tag`).
+/// If any of the left hand side conditions match, the right hand side is applied.
+/// If multiple rules match, weights are added together.
 #[derive(Clone, Debug)]
 pub struct TagSuggestionRules {
-    emoji_rules: HashMap<Emoji, Vec<String>>,
-    string_rules: Vec<StringRule>,
+    rules: Vec<SuggestionRule>,
 }
 
 #[derive(Clone, Debug)]
-enum StringRule {
-    AnyStr(String, Vec<String>),
-    Title(String, Vec<String>),
-    TitleSuffix(String, Vec<String>),
-    Name(String, Vec<String>),
-    NameSuffix(String, Vec<String>),
+struct SuggestionRule {
+    conditions: Vec<Condition>,
+    effects: Vec<Effect>,
 }
 
+#[derive(Clone, Debug)]
+enum Condition {
+    Emoji(Emoji),
+    AnyStr(String),
+    Title(String),
+    TitleSuffix(String),
+    Name(String),
+    NameSuffix(String),
+    Regex(Regex),
+    Type(StickerType),
+    Owner(i64),
+    All(Vec<Condition>),
+    Not(Box<Condition>),
+}
+
+#[derive(Clone, Debug)]
+enum Effect {
+    Suggest(String, f64),
+    Suppress(String),
+}
+
+/// everything the rules can match against
+#[derive(Clone, Debug)]
+pub struct RuleSubject {
+    pub emojis: Vec<Emoji>,
+    pub set_title: String,
+    pub set_name: StickerSetId,
+    pub sticker_type: Option<StickerType>,
+    pub owner_id: Option<i64>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RuleSuggestions {
+    pub suggestions: Vec<ScoredTagSuggestion>,
+    /// tags that should not be suggested, regardless of other suggesters
+    pub suppressed: Vec<String>,
+}
+
+/// rules that are used until an admin saves their own
+pub const DEFAULT_RULES: &str = include_str!("./rules.uwu");
+
 impl TagSuggestionRules {
-    pub fn parse(input: &str) -> anyhow::Result<Self> {
-        let (input, rules) = parse_rules(input)
-            .finish()
-            .map_err(|err| anyhow::anyhow!("parse error: {:?}", err))?;
-        let mut emoji_rules = HashMap::new();
-        let mut string_rules = Vec::new();
-
-        for (lhs, rhs) in rules {
-            let mut tags = Vec::new();
-            for rhs in rhs {
-                match rhs {
-                    Rhs::Tag(tag) => tags.push(tag),
-                }
-            }
+    pub fn parse(source: &str) -> anyhow::Result<Self> {
+        let (_, rules) = parse_rules(source).finish().map_err(|err| {
+            let (line, column) = line_and_column(source, err.input);
+            let near = err.input.lines().next().unwrap_or_default().trim();
+            let near = near.chars().take(30).collect::<String>();
+            anyhow::anyhow!("syntax error at line {line}, column {column} near `{near}`")
+        })?;
 
-            for lhs in lhs {
-                match lhs {
-                    Lhs::Emoji(emoji) => {
-                        emoji_rules
-                            .entry(emoji)
-                            .or_insert_with(Vec::new)
-                            .extend(tags.clone());
+        let rules = rules
+            .into_iter()
+            .map(|(lhs, rhs)| {
+                let conditions = lhs
+                    .into_iter()
+                    .map(|lhs| Condition::from_lhs(lhs, source))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                let effects = rhs
+                    .into_iter()
+                    .map(|rhs| match rhs {
+                        Rhs::Tag(tag, weight) => Effect::Suggest(tag, weight),
+                        Rhs::Suppress(tag) => Effect::Suppress(tag),
+                    })
+                    .collect_vec();
+                Ok(SuggestionRule {
+                    conditions,
+                    effects,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self { rules })
+    }
+
+    #[must_use]
+    #[tracing::instrument(skip(self))]
+    pub fn suggest_tags(&self, subject: &RuleSubject) -> RuleSuggestions {
+        let combined = format!("{} {}", subject.set_name, subject.set_title).to_lowercase();
+        let mut weights: HashMap<String, f64> = HashMap::new();
+        let mut suppressed = Vec::new();
+        for rule in &self.rules {
+            let matches = rule
+                .conditions
+                .iter()
+                .filter(|condition| condition.matches(subject, &combined))
+                .count();
+            if matches == 0 {
+                continue;
+            }
+            for effect in &rule.effects {
+                match effect {
+                    Effect::Suggest(tag, weight) => {
+                        *weights.entry(tag.clone()).or_default() += weight * matches as f64;
                     }
-                    Lhs::Call(function, args) => match function.as_str() {
-                        "any_str" => {
-                            string_rules.push(StringRule::AnyStr(args, tags.clone()));
-                        }
-                        "title" => {
-                            string_rules.push(StringRule::Title(args, tags.clone()));
-                        }
-                        "title_suffix" => {
-                            string_rules.push(StringRule::TitleSuffix(args, tags.clone()));
-                        }
-                        "name" => {
-                            string_rules.push(StringRule::Name(args, tags.clone()));
-                        }
-                        "name_suffix" => {
-                            string_rules.push(StringRule::NameSuffix(args, tags.clone()));
-                        }
-                        _ => {
-                            return Err(anyhow::anyhow!("Unknown function {}", function));
-                        }
-                    },
+                    Effect::Suppress(tag) => suppressed.push(tag.clone()),
                 }
             }
         }
-        Ok(Self {
-            emoji_rules,
-            string_rules,
-        })
+
+        let suggestions = weights
+            .into_iter()
+            .filter(|(tag, _)| !suppressed.contains(tag))
+            .sorted_by(|a, b| a.0.cmp(&b.0))
+            .map(|(tag, weight)| ScoredTagSuggestion {
+                tag,
+                score: compute_score_for_weight(weight),
+            })
+            .collect_vec();
+        RuleSuggestions {
+            suggestions,
+            suppressed: suppressed.into_iter().unique().collect(),
+        }
     }
+}
 
-    fn apply_string_rules(&self, set_title: &str, set_name: &StickerSetId) -> Vec<String> {
-        let mut tags: Vec<String> = Vec::new();
-        let combined = format!("{set_name} {set_title}").to_lowercase();
-        for rule in &self.string_rules {
-            match rule {
-                StringRule::AnyStr(string, rule_tags) => {
-                    if combined.contains(string) {
-                        tags.extend(rule_tags.clone());
+impl Condition {
+    fn from_lhs(lhs: Lhs, source: &str) -> anyhow::Result<Self> {
+        Ok(match lhs {
+            Lhs::Emoji(emoji) => Self::Emoji(emoji),
+            Lhs::Owner(owner_id) => Self::Owner(owner_id),
+            Lhs::And(lhs) => Self::All(
+                lhs.into_iter()
+                    .map(|lhs| Self::from_lhs(lhs, source))
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            Lhs::Not(lhs) => Self::Not(Box::new(Self::from_lhs(*lhs, source)?)),
+            Lhs::Call(function, arg) => match function.as_str() {
+                "any_str" => Self::AnyStr(arg),
+                "title" => Self::Title(arg),
+                "title_suffix" => Self::TitleSuffix(arg),
+                "name" => Self::Name(arg),
+                "name_suffix" => Self::NameSuffix(arg),
+                "regex" => Self::Regex(Regex::new(&arg).map_err(|err| {
+                    let (line, column) = call_position(source, &function, &arg);
+                    anyhow::anyhow!("invalid regex at line {line}, column {column}: {err}")
+                })?),
+                "type" => Self::Type(match arg.as_str() {
+                    "animated" => StickerType::Animated,
+                    "video" => StickerType::Video,
+                    "static" => StickerType::Static,
+                    _ => {
+                        let (line, column) = call_position(source, &function, &arg);
+                        return Err(anyhow::anyhow!(
+                            "unknown sticker type `{arg}` at line {line}, column {column} (expected animated, video or static)"
+                        ));
                     }
+                }),
+                _ => {
+                    let (line, column) = call_position(source, &function, &arg);
+                    return Err(anyhow::anyhow!(
+                        "unknown function `{function}` at line {line}, column {column}"
+                    ));
                 }
-                StringRule::Title(string, rule_tags) => {
-                    if set_title.contains(string) {
-                        tags.extend(rule_tags.clone());
-                    }
-                }
-                StringRule::TitleSuffix(string, rule_tags) => {
-                    if set_title.ends_with(string) {
-                        tags.extend(rule_tags.clone());
-                    }
-                }
-                StringRule::Name(string, rule_tags) => {
-                    if set_name.contains(string) {
-                        tags.extend(rule_tags.clone());
-                    }
-                }
-                StringRule::NameSuffix(string, rule_tags) => {
-                    if set_name.ends_with(string) {
-                        tags.extend(rule_tags.clone());
-                    }
-                }
-            }
-        }
-
-        tags
+            },
+        })
     }
 
-    #[must_use]
-    #[tracing::instrument]
-    pub fn suggest_tags(
-        &self,
-        emojis: Vec<Emoji>,
-        set_title: &str,
-        set_name: &StickerSetId,
-    ) -> Vec<ScoredTagSuggestion> {
-        let mut tags: Vec<String> = Vec::new();
-        for emoji in emojis {
-            if let Some(emoji_tags) = self.emoji_rules.get(&emoji) {
-                // TODO: store
-                // Emojis in
-                // emoji_rules
-                // directly?
-                tags.extend(emoji_tags.clone());
+    fn matches(&self, subject: &RuleSubject, combined: &str) -> bool {
+        match self {
+            Self::Emoji(emoji) => subject.emojis.contains(emoji),
+            Self::AnyStr(string) => combined.contains(string.as_str()),
+            Self::Title(string) => subject.set_title.contains(string.as_str()),
+            Self::TitleSuffix(string) => subject.set_title.ends_with(string.as_str()),
+            Self::Name(string) => subject.set_name.contains(string.as_str()),
+            Self::NameSuffix(string) => subject.set_name.ends_with(string.as_str()),
+            Self::Regex(regex) => {
+                regex.is_match(&subject.set_name) || regex.is_match(&subject.set_title)
             }
+            Self::Type(sticker_type) => subject.sticker_type == Some(*sticker_type),
+            Self::Owner(owner_id) => subject.owner_id == Some(*owner_id),
+            Self::All(conditions) => conditions
+                .iter()
+                .all(|condition| condition.matches(subject, combined)),
+            Self::Not(condition) => !condition.matches(subject, combined),
         }
-
-        tags.into_iter()
-            .chain(self.apply_string_rules(set_title, set_name))
-            .sorted()
-            .chunk_by(std::clone::Clone::clone)
-            .into_iter()
-            .map(|(tag, group)| ScoredTagSuggestion {
-                tag,
-                score: compute_score_for_suggestion_count(group.count()),
-            })
-            .collect_vec()
     }
 }
 
 #[cached::proc_macro::once]
 pub fn get_default_rules() -> TagSuggestionRules {
     #[allow(clippy::expect_used)] // TODO: properly handle error?
-    TagSuggestionRules::parse(include_str!("./rules.uwu")).expect("default rules should parse")
+    TagSuggestionRules::parse(DEFAULT_RULES).expect("default rules should parse")
+}
+
+/// 1-based line and column (in characters) of `rest`, which must be a suffix of `source`
+fn line_and_column(source: &str, rest: &str) -> (usize, usize) {
+    let consumed = &source[..source.len() - rest.len()];
+    let line = consumed.matches('\n').count() + 1;
+    let column = consumed
+        .rsplit('\n')
+        .next()
+        .map_or(0, |line| line.chars().count())
+        + 1;
+    (line, column)
+}
+
+/// position of the first call with this function and argument
+fn call_position(source: &str, function: &str, arg: &str) -> (usize, usize) {
+    source
+        .find(&format!("{function}(\"{arg}\")"))
+        .map(|offset| line_and_column(source, &source[offset..]))
+        .unwrap_or_default()
 }
 
-const fn compute_score_for_suggestion_count(count: usize) -> f64 {
-    match count {
-        1 => 0.5,
-        2 => 0.6,
-        3 => 0.7,
-        4 => 0.8,
-        _ => 0.9,
+/// a single match scores 0.5, every additional match adds 0.1 (up to 0.9)
+fn compute_score_for_weight(weight: f64) -> f64 {
+    if weight < 1.0 {
+        0.5 * weight
+    } else {
+        (0.4 + 0.1 * weight).min(0.9)
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 enum Lhs {
     Call(String, String),
+    Owner(i64),
     Emoji(Emoji),
+    And(Vec<Lhs>),
+    Not(Box<Lhs>),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 enum Rhs {
-    Tag(String),
+    Tag(String, f64),
+    Suppress(String),
 }
 
 type Rule = (Vec<Lhs>, Vec<Rhs>);
@@ -190,21 +268,63 @@ fn call(input: &str) -> IResult<&str, Lhs> {
     success(Lhs::Call(func.to_string(), arg.to_string())).parse(input)
 }
 
+fn owner(input: &str) -> IResult<&str, Lhs> {
+    map(delimited(tag("owner("), i64, tag(")")), Lhs::Owner).parse(input)
+}
+
+fn negation(input: &str) -> IResult<&str, Lhs> {
+    alt((
+        map(preceded((tag("!"), multispace0), negation), |lhs| {
+            Lhs::Not(Box::new(lhs))
+        }),
+        owner,
+        call,
+        map(parse_emoji, Lhs::Emoji),
+    )).parse(input)
+}
+
+fn conjunction(input: &str) -> IResult<&str, Lhs> {
+    map(
+        separated_list1(delimited(multispace0, tag("&"), multispace0), negation),
+        |mut terms| {
+            if terms.len() == 1 {
+                terms.remove(0)
+            } else {
+                Lhs::And(terms)
+            }
+        },
+    ).parse(input)
+}
+
 fn parse_lhs(input: &str) -> IResult<&str, Vec<Lhs>> {
     many1(delimited(
         multispace0,
-        alt((
-            call,
-            map(parse_emoji, |emoji| Lhs::Emoji(emoji)),
-        )),
+        conjunction,
         multispace0, // TODO: also use space0 for the query input parser
     )).parse(input)
 }
 
+/// `tag` or `tag:0.3`; a suffix that is not a number is part of the tag
+fn weighted_tag(input: &str) -> IResult<&str, Rhs> {
+    map_opt(tag_literal, |literal: &str| match literal.rsplit_once(':') {
+        Some((tag, weight)) if !tag.is_empty() => match weight.parse::<f64>() {
+            Ok(weight) if weight.is_finite() && weight > 0.0 => {
+                Some(Rhs::Tag(tag.to_string(), weight))
+            }
+            Ok(_) => None,
+            Err(_) => Some(Rhs::Tag(literal.to_string(), 1.0)),
+        },
+        _ => Some(Rhs::Tag(literal.to_string(), 1.0)),
+    }).parse(input)
+}
+
 fn parse_rhs(input: &str) -> IResult<&str, Vec<Rhs>> {
     many1(delimited(
         multispace0,
-        map(tag_literal, |tag| Rhs::Tag(tag.to_string())),
+        alt((
+            map(preceded(tag("-"), tag_literal), |tag| Rhs::Suppress(tag.to_string())),
+            weighted_tag,
+        )),
         multispace0, // TODO: also use multispace0 for the query input parser
     )).parse(input)
 }
@@ -243,6 +363,29 @@ mod tests {
         assert_eq!(suggestions, tags);
     }
 
+    fn subject(emojis: &[&str], set_title: &str, set_name: &str) -> RuleSubject {
+        RuleSubject {
+            emojis: emojis
+                .iter()
+                .map(|emoji| Emoji::new_from_string_single(*emoji))
+                .collect(),
+            set_title: set_title.to_string(),
+            set_name: StickerSetId::from(set_name),
+            sticker_type: None,
+            owner_id: None,
+        }
+    }
+
+    fn suggested_tags(rules: &TagSuggestionRules, subject: &RuleSubject) -> Vec<String> {
+        rules
+            .suggest_tags(subject)
+            .suggestions
+            .into_iter()
+            .map(|suggestion| suggestion.tag)
+            .sorted()
+            .collect()
+    }
+
     #[test]
     fn test_parse_long() -> anyhow::Result<()> {
         let input = r#"
@@ -261,11 +404,9 @@ mod tests {
         "#;
         let rules = TagSuggestionRules::parse(input)?;
 
-        let suggestions = rules.suggest_tags(
-            vec![Emoji::new_from_string_single("⁉️"), Emoji::new_from_string_single("‼️")],
-            "Furry Paws Collection (NSFW)",
-            &StickerSetId::from("PawsNsfw"),
-        );
+        let suggestions = rules
+            .suggest_tags(&subject(&["⁉️", "‼️"], "Furry Paws Collection (NSFW)", "PawsNsfw"))
+            .suggestions;
         assert_suggested_tags_only_contain(
             suggestions,
             &[
@@ -277,7 +418,7 @@ mod tests {
             ],
         );
 
-        let suggestions = rules.suggest_tags(vec![Emoji::new_from_string_single("🔓"), Emoji::new_from_string_single("🧦")], "My Set", &StickerSetId::from("set385972"));
+        let suggestions = rules.suggest_tags(&subject(&["🔓", "🧦"], "My Set", "set385972")).suggestions;
         assert_suggested_tags_only_contain(
             suggestions,
             &[
@@ -289,18 +430,150 @@ mod tests {
             ],
         );
 
-        let suggestions = rules.suggest_tags(vec![Emoji::new_from_string_single("😍")], "Fox Pack", &StickerSetId::from("FoxByNaL"));
+        let suggestions = rules.suggest_tags(&subject(&["😍"], "Fox Pack", "FoxByNaL")).suggestions;
         assert_suggested_tags_only_contain(suggestions, &["nowandlater"]);
 
-        let suggestions = rules.suggest_tags(vec![Emoji::new_from_string_single("😍")], "Fox and Friends", &StickerSetId::from("foxfriends"));
+        let suggestions = rules.suggest_tags(&subject(&["😍"], "Fox and Friends", "foxfriends")).suggestions;
         assert_suggested_tags_only_contain(suggestions, &["duo"]);
 
-        let suggestions = rules.suggest_tags(vec![Emoji::new_from_string_single("😵‍💫")], "FoxNsfw", &StickerSetId::from("FoxNsfw"));
+        let suggestions = rules.suggest_tags(&subject(&["😵‍💫"], "FoxNsfw", "FoxNsfw")).suggestions;
         assert_suggested_tags_only_contain(suggestions, &["explicit", "questionable"]);
 
         Ok(())
     }
 
+    #[test]
+    fn test_default_rules_parse() {
+        assert!(TagSuggestionRules::parse(DEFAULT_RULES).is_ok());
+    }
+
+    #[test]
+    fn test_parse_error_position() {
+        let input = "🐾 => paws;\n❔ ❓ => question_mark\n🤗 => hug;";
+        let err = TagSuggestionRules::parse(input).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "syntax error at line 2, column 1 near `❔ ❓ => question_mark`"
+        );
+
+        let input = "🐾 => paws;\n  any_str(\"friends\") nam(\"NL\") => duo;";
+        let err = TagSuggestionRules::parse(input).unwrap_err();
+        assert_eq!(err.to_string(), "unknown function `nam` at line 2, column 22");
+    }
+
+    #[test]
+    fn test_parse_regex() -> anyhow::Result<()> {
+        let rules = TagSuggestionRules::parse(r#"regex("(?i)^fox(y|es)?\b") => fox;"#)?;
+        assert_eq!(suggested_tags(&rules, &subject(&[], "Foxes Pack", "set1")), ["fox"]);
+        assert_eq!(suggested_tags(&rules, &subject(&[], "My Set", "fox")), ["fox"]);
+        assert!(suggested_tags(&rules, &subject(&[], "Firefox", "set1")).is_empty());
+
+        let err = TagSuggestionRules::parse(r#"🐾 => paws;
+regex("(unclosed") => fox;"#)
+        .unwrap_err();
+        assert!(err.to_string().starts_with("invalid regex at line 2, column 1"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_sticker_type() -> anyhow::Result<()> {
+        let rules = TagSuggestionRules::parse(r#"type("video") type("animated") => animated;"#)?;
+        let mut video = subject(&[], "Set", "set1");
+        video.sticker_type = Some(StickerType::Video);
+        assert_eq!(suggested_tags(&rules, &video), ["animated"]);
+        let mut still = subject(&[], "Set", "set1");
+        still.sticker_type = Some(StickerType::Static);
+        assert!(suggested_tags(&rules, &still).is_empty());
+
+        let err = TagSuggestionRules::parse(r#"type("gif") => animated;"#).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown sticker type `gif` at line 1, column 1 (expected animated, video or static)"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_owner() -> anyhow::Result<()> {
+        let rules = TagSuggestionRules::parse("owner(123456) owner(-42) => yuniwusky;")?;
+        let mut owned = subject(&[], "Set", "set1");
+        owned.owner_id = Some(123_456);
+        assert_eq!(suggested_tags(&rules, &owned), ["yuniwusky"]);
+        owned.owner_id = Some(-42);
+        assert_eq!(suggested_tags(&rules, &owned), ["yuniwusky"]);
+        owned.owner_id = Some(1);
+        assert!(suggested_tags(&rules, &owned).is_empty());
+        assert!(suggested_tags(&rules, &subject(&[], "Set", "set1")).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_and_not() -> anyhow::Result<()> {
+        let rules = TagSuggestionRules::parse(
+            r#"
+            🍕 & any_str("nsfw") => pizza_lewd;
+            🍕 & !any_str("nsfw") & ! type("video") => pizza_safe;
+            !🍕 => no_pizza;
+            "#,
+        )?;
+        assert_eq!(
+            suggested_tags(&rules, &subject(&["🍕"], "Pizza NSFW", "pizza")),
+            ["pizza_lewd"]
+        );
+        assert_eq!(suggested_tags(&rules, &subject(&["🍕"], "Pizza", "pizza")), ["pizza_safe"]);
+        let mut video = subject(&["🍕"], "Pizza", "pizza");
+        video.sticker_type = Some(StickerType::Video);
+        assert!(suggested_tags(&rules, &video).is_empty());
+        assert_eq!(suggested_tags(&rules, &subject(&["🍔"], "Food", "food")), ["no_pizza"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_weights() -> anyhow::Result<()> {
+        let rules = TagSuggestionRules::parse(
+            r#"
+            🐾 => paws:0.3 foot_focus;
+            any_str("paw") => paws:2;
+            "#,
+        )?;
+        let suggestions = rules.suggest_tags(&subject(&["🐾"], "Paws", "set")).suggestions;
+        let scores: HashMap<_, _> = suggestions
+            .into_iter()
+            .map(|suggestion| (suggestion.tag, suggestion.score))
+            .collect();
+        assert!((scores["paws"] - 0.63).abs() < 1e-9); // weight 2.3
+        assert!((scores["foot_focus"] - 0.5).abs() < 1e-9);
+
+        let suggestions = rules.suggest_tags(&subject(&["🐾"], "Set", "set")).suggestions;
+        let paws = suggestions.iter().find(|suggestion| suggestion.tag == "paws");
+        assert!(paws.is_some_and(|paws| (paws.score - 0.15).abs() < 1e-9));
+
+        let rules = TagSuggestionRules::parse("🐾 => meta:tag;")?;
+        assert_eq!(suggested_tags(&rules, &subject(&["🐾"], "Set", "set")), ["meta:tag"]);
+
+        assert!(TagSuggestionRules::parse("🐾 => paws:0;").is_err());
+        assert!(TagSuggestionRules::parse("🐾 => paws:-1;").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_suppressed() -> anyhow::Result<()> {
+        let rules = TagSuggestionRules::parse(
+            r#"
+            🍼 => young diaper;
+            any_str("adult") => -young -diaper;
+            "#,
+        )?;
+        let result = rules.suggest_tags(&subject(&["🍼"], "Adult Babyfur", "set"));
+        assert!(result.suggestions.is_empty());
+        assert_eq!(result.suppressed, ["young", "diaper"]);
+
+        let result = rules.suggest_tags(&subject(&["🍼"], "Babyfur", "set"));
+        assert_eq!(result.suggestions.len(), 2);
+        assert!(result.suppressed.is_empty());
+        Ok(())
+    }
+
 /*     #[tokio::test]
     async fn test_default_rules_parse() -> anyhow::Result<()> {
         let rules = get_default_rules();
@@ -4,12 +4,25 @@ use crate::{bot::InternalError, database::Database, util::{StickerFileId, Sticke
 
 use super::ScoredTagSuggestion;
 
+/// the file's own tags are subtracted if `excluded_file_id` is set
 #[tracing::instrument(skip(database))]
 pub async fn suggest_tags_from_same_set(
     database: &Database,
     set_id: &StickerSetId,
+    excluded_file_id: Option<&StickerFileId>,
 ) -> Result<Vec<ScoredTagSuggestion>, InternalError> {
-    let suggested_tags = database.get_all_sticker_set_tag_counts(set_id).await?;
+    let mut suggested_tags = database.get_all_sticker_set_tag_counts(set_id).await?;
+    if let Some(excluded_file_id) = excluded_file_id {
+        let file_tags = database.get_sticker_tags_by_file_id(excluded_file_id).await?;
+        suggested_tags = suggested_tags
+            .into_iter()
+            .map(|(tag, count)| {
+                let own = i64::from(file_tags.contains(&tag));
+                (tag, count - own)
+            })
+            .filter(|(_, count)| *count > 0)
+            .collect_vec();
+    }
     let max_count = suggested_tags.iter().map(|tag| tag.1).max().unwrap_or(1);
     Ok(suggested_tags
         .into_iter()
@@ -21,9 +34,15 @@ pub async fn suggest_tags_from_same_set(
 pub async fn suggest_tags_from_sets_with_same_sticker_file(
     database: &Database,
     sticker_file_id: &StickerFileId,
+    excluded_set_id: Option<&StickerSetId>,
+    excluded_file_id: Option<&StickerFileId>,
 ) -> Result<Vec<ScoredTagSuggestion>, InternalError> {
     let suggested_tags = database
-        .get_all_sticker_set_tag_counts_by_sticker_file_id(sticker_file_id)
+        .get_all_sticker_set_tag_counts_by_sticker_file_id(
+            sticker_file_id,
+            excluded_set_id,
+            excluded_file_id,
+        )
         .await?;
     let max_count = suggested_tags.iter().map(|tag| tag.1).max().unwrap_or(1);
     Ok(suggested_tags
@@ -36,10 +55,16 @@ pub async fn suggest_tags_from_sets_with_same_sticker_file(
 pub async fn suggest_tags_from_sets_with_same_owner(
     database: &Database,
     owner_user_id: Option<i64>,
+    excluded_set_id: Option<&StickerSetId>,
+    excluded_file_id: Option<&StickerFileId>,
 ) -> Result<Vec<ScoredTagSuggestion>, InternalError> {
     if let Some(owner_user_id) = owner_user_id {
         let suggested_tags = database
-            .get_all_sticker_set_tag_counts_by_owner_id(owner_user_id)
+            .get_all_sticker_set_tag_counts_by_owner_id(
+                owner_user_id,
+                excluded_set_id,
+                excluded_file_id,
+            )
             .await?;
         let max_count = suggested_tags.iter().map(|tag| tag.1).max().unwrap_or(1);
         Ok(suggested_tags
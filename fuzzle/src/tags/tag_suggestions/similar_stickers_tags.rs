@@ -4,11 +4,13 @@ use crate::{bot::InternalError, database::Database, qdrant::VectorDatabase, util
 
 use super::ScoredTagSuggestion;
 
-#[tracing::instrument(skip(database))]
+/// matches among `excluded_file_hashes` are ignored (the file always matches itself)
+#[tracing::instrument(skip(database, excluded_file_hashes))]
 pub async fn suggest_tags_from_similar_stickers(
     database: &Database,
     vector_db: &VectorDatabase,
     file_hash: &StickerFileId,
+    excluded_file_hashes: &[StickerFileId],
     score_threshold: f32,
     limit: u64,
 ) -> Result<Vec<ScoredTagSuggestion>, InternalError> {
@@ -20,7 +22,11 @@ pub async fn suggest_tags_from_similar_stickers(
             return Ok(vec![]);
         }
     }) else {return Ok(vec![])};
-    let result = result.into_iter().map(|r| r.file_hash).collect_vec();
+    let result = result
+        .into_iter()
+        .map(|r| r.file_hash)
+        .filter(|file_hash| !excluded_file_hashes.contains(file_hash))
+        .collect_vec();
     let result = database.get_some_sticker_ids_for_sticker_file_ids(result).await?;
     let result = result.into_iter().map(|a| a.sticker_id).collect_vec(); // TODO: take into account the order of the matches?
     get_all_tags_from_stickers(result, database.clone()).await
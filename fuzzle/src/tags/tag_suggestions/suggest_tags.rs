@@ -6,6 +6,7 @@ use crate::qdrant::VectorDatabase;
 use crate::tags::Category;
 use crate::util::{Emoji, Required, StickerId};
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use teloxide::requests::Requester;
 use tracing::Instrument;
 
@@ -15,7 +16,8 @@ use std::sync::Arc;
 use super::image_tag_similarity::suggest_closest_tags;
 use super::implied::suggest_tags_by_reverse_implication;
 use super::owner_tags::suggest_owners_tags;
-use super::rules::get_default_rules;
+use super::combiner::{SuggestedTag, SuggestionCombiner};
+use super::rules::{RuleSubject, TagSuggestionRules};
 use super::same_set_tags::{
     suggest_tags_from_same_set, suggest_tags_from_sets_with_same_owner,
     suggest_tags_from_sets_with_same_sticker_file,
@@ -24,11 +26,92 @@ use super::similar_stickers_tags::suggest_tags_from_similar_stickers;
 use super::similar_tags::suggest_similar_tags;
 use super::ScoredTagSuggestion;
 
-// TODO: refactor the whole module
-// - `TagSuggestions` should be a map
-// - each `Vec<ScoredTagSuggestion>` should also be a map
+/// where a suggestion came from; the combiner learns a weight for each source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SuggestionSource {
+    OwnerTags,
+    SameSet,
+    SameFile,
+    SameOwnerSets,
+    Tfidf,
+    SimilarStickers,
+    VerySimilarStickers,
+    SimilarTags,
+    ReverseImplications,
+    ClosestTags,
+    Rules,
+}
+
+impl SuggestionSource {
+    pub const ALL: [Self; 11] = [
+        Self::OwnerTags,
+        Self::SameSet,
+        Self::SameFile,
+        Self::SameOwnerSets,
+        Self::Tfidf,
+        Self::SimilarStickers,
+        Self::VerySimilarStickers,
+        Self::SimilarTags,
+        Self::ReverseImplications,
+        Self::ClosestTags,
+        Self::Rules,
+    ];
 
-#[tracing::instrument(skip(bot, tag_manager, database, tfidf_service, vector_db))]
+    /// stored in the database, do not change
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::OwnerTags => "owner_tags",
+            Self::SameSet => "same_set",
+            Self::SameFile => "same_file",
+            Self::SameOwnerSets => "same_owner_sets",
+            Self::Tfidf => "tfidf",
+            Self::SimilarStickers => "similar_stickers",
+            Self::VerySimilarStickers => "very_similar_stickers",
+            Self::SimilarTags => "similar_tags",
+            Self::ReverseImplications => "reverse_implications",
+            Self::ClosestTags => "closest_tags",
+            Self::Rules => "rules",
+        }
+    }
+
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|source| source.name() == name)
+    }
+
+    /// used in callback data, which is limited to 64 bytes
+    #[must_use]
+    pub const fn short_name(self) -> &'static str {
+        match self {
+            Self::OwnerTags => "ot",
+            Self::SameSet => "ss",
+            Self::SameFile => "sf",
+            Self::SameOwnerSets => "so",
+            Self::Tfidf => "tf",
+            Self::SimilarStickers => "si",
+            Self::VerySimilarStickers => "vs",
+            Self::SimilarTags => "st",
+            Self::ReverseImplications => "ri",
+            Self::ClosestTags => "ct",
+            Self::Rules => "ru",
+        }
+    }
+
+    #[must_use]
+    pub fn from_short_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|source| source.short_name() == name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceSuggestions {
+    pub by_source: Vec<(SuggestionSource, Vec<ScoredTagSuggestion>)>,
+    /// tags that the rules do not want to be suggested
+    pub suppressed: Vec<String>,
+}
+
+#[tracing::instrument(skip(bot, tag_manager, database, tfidf_service, vector_db, rules, combiner))]
 pub async fn suggest_tags(
     sticker_id: &StickerId,
     bot: Bot,
@@ -36,28 +119,90 @@ pub async fn suggest_tags(
     database: Database,
     tfidf_service: TfIdfService,
     vector_db: VectorDatabase,
-) -> Result<Vec<String>, BotError> {
+    rules: Arc<TagSuggestionRules>,
+    combiner: Arc<SuggestionCombiner>,
+) -> Result<Vec<SuggestedTag>, BotError> {
+    let sticker_tags = database.get_sticker_tags(sticker_id).await?;
+    let suggestions = collect_suggestions(
+        sticker_id,
+        tag_manager.clone(),
+        database,
+        tfidf_service,
+        vector_db,
+        rules,
+        &sticker_tags,
+        false,
+    )
+    .await?;
+    Ok(combiner.combine(&suggestions, &sticker_tags, &tag_manager))
+}
+
+/// runs all suggestion sources; `sticker_tags` are the tags the sticker is assumed to have
+///
+/// With `held_out`, the sources ignore the sticker's own file and the other stickers of its set
+/// (except for the same set source, which only ignores the file), so that the evaluation does
+/// not find the sticker's actual tags through them.
+#[tracing::instrument(skip(tag_manager, database, tfidf_service, vector_db, rules))]
+pub async fn collect_suggestions(
+    sticker_id: &StickerId,
+    tag_manager: TagManagerService,
+    database: Database,
+    tfidf_service: TfIdfService,
+    vector_db: VectorDatabase,
+    rules: Arc<TagSuggestionRules>,
+    sticker_tags: &[String],
+    held_out: bool,
+) -> Result<SourceSuggestions, BotError> {
     let sticker = database.get_sticker_by_id(sticker_id).await?.required()?;
     let set = database
         .get_sticker_set_by_sticker_id(sticker_id)
         .await?
         .required()?;
-    let sticker_tags = database.get_sticker_tags(sticker_id).await?;
     let emojis = database.get_sticker_emojis(sticker_id).await?;
+    let file = database.get_sticker_file_by_sticker_id(sticker_id).await?;
+    let rule_suggestions = rules.suggest_tags(&RuleSubject {
+        emojis,
+        set_title: set.title.clone().unwrap_or_default(),
+        set_name: set.id.clone(),
+        sticker_type: file.map(|file| file.sticker_type),
+        owner_id: set.created_by_user_id,
+    });
+
+    let excluded_set_id = held_out.then_some(&set.id);
+    let excluded_file_id = held_out.then_some(&sticker.sticker_file_id);
+    let excluded_file_hashes = if held_out {
+        database
+            .get_all_stickers_in_set(&set.id)
+            .await?
+            .into_iter()
+            .map(|sticker| sticker.sticker_file_id)
+            .chain([sticker.sticker_file_id.clone()])
+            .collect_vec()
+    } else {
+        vec![]
+    };
 
     let suggestions = tokio::try_join!(
         suggest_owners_tags(&database, set.created_by_user_id),
-        // db_based_sticker_tags_from_same_set:
-        suggest_tags_from_same_set(&database, &set.id),
-        suggest_tags_from_sets_with_same_sticker_file(&database, &sticker.sticker_file_id),
-        suggest_tags_from_sets_with_same_owner(&database, set.created_by_user_id),
-        // worker_based_tf_idf:
+        suggest_tags_from_same_set(&database, &set.id, excluded_file_id),
+        suggest_tags_from_sets_with_same_sticker_file(
+            &database,
+            &sticker.sticker_file_id,
+            excluded_set_id,
+            excluded_file_id,
+        ),
+        suggest_tags_from_sets_with_same_owner(
+            &database,
+            set.created_by_user_id,
+            excluded_set_id,
+            excluded_file_id,
+        ),
         tfidf_service.suggest_tags_for_sticker(&sticker.id),
-        // clip_and_db_based_tags_from_similar_stickers:
         suggest_tags_from_similar_stickers(
             &database,
             &vector_db,
             &sticker.sticker_file_id,
+            &excluded_file_hashes,
             0.7,
             200,
         ),
@@ -65,45 +210,41 @@ pub async fn suggest_tags(
             &database,
             &vector_db,
             &sticker.sticker_file_id,
+            &excluded_file_hashes,
             0.9,
             30,
         ),
-        // clip_text_embedding_based_on_existing_tags:
         suggest_similar_tags(
             &database,
             &vector_db,
             tag_manager.clone(),
-            sticker_tags.as_slice(),
+            sticker_tags,
         ),
-        // tag_manager_based_reverse_implications:
-        suggest_tags_by_reverse_implication(&sticker_tags, tag_manager.clone()),
-        // image_to_tag_similarity_based:
+        suggest_tags_by_reverse_implication(sticker_tags, tag_manager.clone()),
         suggest_closest_tags(
             &database,
             &vector_db,
             tag_manager.clone(),
             &sticker.sticker_file_id,
         ),
-        // static_rule_based_emoji_and_set_name:
-        async {
-            Ok(get_default_rules() // TODO: those are re-parsed every time!
-                .suggest_tags(emojis, &set.title.unwrap_or_default(), &set.id))
-        },
     )?;
-    let suggestions = vec![
-        suggestions.0,
-        suggestions.1,
-        suggestions.2,
-        suggestions.3,
-        suggestions.4,
-        suggestions.5,
-        suggestions.6,
-        suggestions.7,
-        suggestions.8,
-        suggestions.9,
-        suggestions.10,
+    let by_source = vec![
+        (SuggestionSource::OwnerTags, suggestions.0),
+        (SuggestionSource::SameSet, suggestions.1),
+        (SuggestionSource::SameFile, suggestions.2),
+        (SuggestionSource::SameOwnerSets, suggestions.3),
+        (SuggestionSource::Tfidf, suggestions.4),
+        (SuggestionSource::SimilarStickers, suggestions.5),
+        (SuggestionSource::VerySimilarStickers, suggestions.6),
+        (SuggestionSource::SimilarTags, suggestions.7),
+        (SuggestionSource::ReverseImplications, suggestions.8),
+        (SuggestionSource::ClosestTags, suggestions.9),
+        (SuggestionSource::Rules, rule_suggestions.suggestions),
     ];
-    Ok(combine_suggestions_alt_1(suggestions, sticker_tags, tag_manager)?)
+    Ok(SourceSuggestions {
+        by_source,
+        suppressed: rule_suggestions.suppressed,
+    })
 }
 
 // #[tracing::instrument(skip(tag_manager))]
@@ -209,87 +350,3 @@ pub async fn suggest_tags(
 //         .collect_vec();
 //     result
 // }
-
-#[tracing::instrument(skip(tag_manager))]
-fn combine_suggestions_alt_1(
-    suggestions: Vec<Vec<ScoredTagSuggestion>>,
-    sticker_tags: Vec<String>,
-    tag_manager: TagManagerService,
-) -> Result<Vec<String>, InternalError> {
-    let suggestion_vec = suggestions
-        .into_iter()
-        .map(|s| {
-            ScoredTagSuggestion::merge(
-                ScoredTagSuggestion::add_implications(s, tag_manager.clone()),
-                vec![],
-            )
-            .into_iter()
-            .take(30)
-            .collect_vec()
-        })
-        .filter(|s| s.len() > 0)
-        .collect_vec();
-
-    let mut all_tags: HashMap<_, _> = suggestion_vec
-        .iter()
-        .flatten()
-        .map(|suggestion| (suggestion.tag.clone(), 0))
-        .collect();
-    for tags in suggestion_vec {
-        for ScoredTagSuggestion { tag, .. } in tags {
-            *all_tags.entry(tag).or_default() += 1;
-        }
-    }
-
-    let all_tags = filter(all_tags, sticker_tags.clone());
-
-    let mut limits = HashMap::new();
-    limits.insert(Category::General, 15);
-    limits.insert(Category::Species, 5);
-    limits.insert(Category::Meta, 5);
-
-    let result = all_tags
-        .into_iter()
-        .sorted_unstable_by_key(|it| -it.1)
-        .map(|it| it.0)
-        .filter(|suggestion| !sticker_tags.contains(&suggestion))
-        .filter(|suggestion| {
-            let category = tag_manager.get_category(&suggestion);
-            category
-                .map(|category| {
-                    let entry = limits.entry(category).or_insert(2);
-                    *entry -= 1;
-                    *entry >= 0
-                })
-                .unwrap_or_default()
-        })
-        .take(20)
-        .collect_vec();
-    Ok(result)
-}
-
-fn filter(mut all_tags: HashMap<String, i32>, sticker_tags: Vec<String>) -> HashMap<String, i32> {
-    let default_tags = [
-        "ych_(character)",
-        "questionable",
-        "explicit",
-        "safe",
-        "solo",
-        "diaper",
-        "duo",
-        "watersports",
-        "young",
-        "vore",
-        "scat",
-        "gore",
-        "attribution",
-        "male",
-        "female",
-        "ambiguous_gender",
-    ];
-    for tag in default_tags {
-        all_tags.entry(tag.to_string()).or_default(); // add default tags with score 0
-    }
-
-    all_tags
-}
@@ -1,5 +1,8 @@
 use std::fmt::{Display, Formatter};
 
+use enum_primitive_derive::Primitive;
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
 const ZERO_CHARACTERS: &str = "zero_pictured";
 const ONE_CHARACTER: &str = "solo";
 const TWO_CHARACTERS: &str = "duo";
@@ -10,6 +13,9 @@ const RATING_SAFE: &str = "safe";
 const RATING_QUESTIONABLE: &str = "questionable";
 const RATING_EXPLICIT: &str = "explicit";
 
+/// content that is hidden for the safe and questionable rating profiles, regardless of the rating tag
+const MATURE_CONTENT_TAGS: [&str; 2] = ["gore", "scat"];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Characters {
     Zero = 0,
@@ -19,11 +25,41 @@ pub enum Characters {
     Multiple = 4,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// also used as rating profile: stickers rated above the profile are hidden
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Primitive, Serialize_repr, Deserialize_repr,
+)]
+#[repr(u8)]
 pub enum Rating {
-    Safe,
-    Questionable,
-    Explicit,
+    #[default]
+    Safe = 0,
+    Questionable = 1,
+    Explicit = 2,
+}
+
+impl Rating {
+    /// tags that hide a sticker for this rating profile
+    #[must_use]
+    pub fn hidden_tags(self) -> Vec<String> {
+        let ratings = [Self::Questionable, Self::Explicit]
+            .into_iter()
+            .filter(|rating| *rating > self)
+            .map(|rating| rating.to_string());
+        let mature_content = MATURE_CONTENT_TAGS
+            .into_iter()
+            .filter(|_| self < Self::Explicit)
+            .map(ToString::to_string);
+        ratings.chain(mature_content).collect()
+    }
+
+    #[must_use]
+    pub const fn to_emoji(self) -> &'static str {
+        match self {
+            Self::Safe => "🟢",
+            Self::Questionable => "🟡",
+            Self::Explicit => "🔴",
+        }
+    }
 }
 
 #[must_use]
@@ -89,3 +125,21 @@ impl Display for Rating {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rating_profile_hidden_tags() {
+        assert_eq!(
+            Rating::Safe.hidden_tags(),
+            vec!["questionable", "explicit", "gore", "scat"]
+        );
+        assert_eq!(
+            Rating::Questionable.hidden_tags(),
+            vec!["explicit", "gore", "scat"]
+        );
+        assert!(Rating::Explicit.hidden_tags().is_empty());
+    }
+}
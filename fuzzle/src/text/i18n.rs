@@ -0,0 +1,512 @@
+use std::{collections::HashMap, fmt::Display};
+
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use teloxide::utils::markdown::{escape, escape_code};
+use tracing::warn;
+
+use super::Markdown;
+
+/*
+ * Catalogs contain plain text with `key = value` lines; `\n` in a value is a line break.
+ *
+ * Placeholders are written as `{name}`. For bot messages the text is escaped for markdown v2,
+ * except for `*bold*` and `` `code` `` spans, which are kept as markup.
+ *
+ * Keys of countable texts end with a plural category (`.one`, `.few`, `.many`, `.other`) and are
+ * looked up with `Language::plural_markdown`/`Language::plural_text`; `{count}` is filled in.
+ */
+
+const EN_CATALOG: &str = include_str!("./locales/en.txt");
+const DE_CATALOG: &str = include_str!("./locales/de.txt");
+const RU_CATALOG: &str = include_str!("./locales/ru.txt");
+
+static CATALOGS: Lazy<HashMap<Language, Catalog>> = Lazy::new(|| {
+    Language::ALL
+        .into_iter()
+        .map(|language| (language, parse_catalog(language.catalog_source())))
+        .collect()
+});
+
+type Catalog = HashMap<&'static str, String>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "de")]
+    German,
+    #[serde(rename = "ru")]
+    Russian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PluralCategory {
+    One,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::One => "one",
+            Self::Few => "few",
+            Self::Many => "many",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// value for a placeholder
+pub enum Arg {
+    /// escaped when rendered as markdown
+    Text(String),
+    /// already formatted, eg a link
+    Markdown(Markdown),
+}
+
+impl From<&str> for Arg {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+
+impl From<String> for Arg {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<i64> for Arg {
+    fn from(value: i64) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+
+impl From<usize> for Arg {
+    fn from(value: usize) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+
+impl From<Markdown> for Arg {
+    fn from(value: Markdown) -> Self {
+        Self::Markdown(value)
+    }
+}
+
+impl Display for Arg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text(text) => write!(f, "{text}"),
+            Self::Markdown(markdown) => write!(f, "{markdown}"),
+        }
+    }
+}
+
+impl Language {
+    pub const ALL: [Self; 3] = [Self::English, Self::German, Self::Russian];
+
+    /// ISO 639-1 code, as used by telegram and in html
+    #[must_use]
+    pub const fn code(self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::German => "de",
+            Self::Russian => "ru",
+        }
+    }
+
+    /// name of the language in the language itself
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::German => "Deutsch",
+            Self::Russian => "Русский",
+        }
+    }
+
+    /// accepts IETF language tags like `de-AT`; `None` for languages without a catalog
+    #[must_use]
+    pub fn from_language_code(code: &str) -> Option<Self> {
+        let primary = code.split(['-', '_']).next()?.trim().to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|language| language.code() == primary)
+    }
+
+    /// the supported language with the highest weight in an `Accept-Language` header
+    #[must_use]
+    pub fn from_accept_language(header: &str) -> Option<Self> {
+        header
+            .split(',')
+            .filter_map(|entry| {
+                let mut parts = entry.split(';');
+                let language = Self::from_language_code(parts.next()?)?;
+                let weight = parts
+                    .find_map(|part| part.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |weight| weight.trim().parse::<f32>().ok())?;
+                Some((language, weight))
+            })
+            .filter(|(_, weight)| *weight > 0.0)
+            // stable, so the first entry wins for equal weights
+            .sorted_by(|(_, a), (_, b)| b.total_cmp(a))
+            .map(|(language, _)| language)
+            .next()
+    }
+
+    const fn catalog_source(self) -> &'static str {
+        match self {
+            Self::English => EN_CATALOG,
+            Self::German => DE_CATALOG,
+            Self::Russian => RU_CATALOG,
+        }
+    }
+
+    /// CLDR plural rules for integers
+    fn plural_category(self, count: i64) -> PluralCategory {
+        let count = count.unsigned_abs();
+        match self {
+            Self::English | Self::German => {
+                if count == 1 {
+                    PluralCategory::One
+                } else {
+                    PluralCategory::Other
+                }
+            }
+            Self::Russian => match (count % 10, count % 100) {
+                (1, rest) if rest != 11 => PluralCategory::One,
+                (2..=4, rest) if !(12..=14).contains(&rest) => PluralCategory::Few,
+                _ => PluralCategory::Many,
+            },
+        }
+    }
+
+    /// categories that countable keys need in the catalog of this language
+    const fn plural_categories(self) -> &'static [PluralCategory] {
+        match self {
+            Self::English | Self::German => &[PluralCategory::One, PluralCategory::Other],
+            Self::Russian => &[PluralCategory::One, PluralCategory::Few, PluralCategory::Many],
+        }
+    }
+
+    /// falls back to english; empty (and logged) if the key is missing there as well
+    fn template(self, key: &str) -> &'static str {
+        CATALOGS
+            .get(&self)
+            .and_then(|catalog| catalog.get(key))
+            .or_else(|| {
+                CATALOGS
+                    .get(&Self::English)
+                    .and_then(|catalog| catalog.get(key))
+            })
+            .map_or_else(
+                || {
+                    warn!("missing translation for {key}");
+                    ""
+                },
+                String::as_str,
+            )
+    }
+
+    fn plural_key(self, key: &str, count: i64) -> String {
+        let plural_key = format!("{key}.{}", self.plural_category(count).as_str());
+        if self.has_key(&plural_key) {
+            plural_key
+        } else {
+            // the english catalog only has `one` and `other`
+            format!("{key}.{}", Self::English.plural_category(count).as_str())
+        }
+    }
+
+    fn has_key(self, key: &str) -> bool {
+        CATALOGS
+            .get(&self)
+            .is_some_and(|catalog| catalog.contains_key(key))
+    }
+
+    /// plain text, eg for the website (which escapes on its own)
+    #[must_use]
+    pub fn text(self, key: &str, args: &[(&str, Arg)]) -> String {
+        render(self.template(key), &borrowed(args), str::to_string, Arg::to_string)
+    }
+
+    #[must_use]
+    pub fn plural_text(self, key: &str, count: i64, args: &[(&str, Arg)]) -> String {
+        let count_arg = Arg::from(count);
+        let template = self.template(&self.plural_key(key, count));
+        render(template, &with_count(args, &count_arg), str::to_string, Arg::to_string)
+    }
+
+    #[must_use]
+    pub fn markdown(self, key: &str, args: &[(&str, Arg)]) -> Markdown {
+        Markdown::new(render(
+            self.template(key),
+            &borrowed(args),
+            escape_literal,
+            arg_to_markdown,
+        ))
+    }
+
+    #[must_use]
+    pub fn plural_markdown(self, key: &str, count: i64, args: &[(&str, Arg)]) -> Markdown {
+        let count_arg = Arg::from(count);
+        let template = self.template(&self.plural_key(key, count));
+        Markdown::new(render(
+            template,
+            &with_count(args, &count_arg),
+            escape_literal,
+            arg_to_markdown,
+        ))
+    }
+}
+
+fn borrowed<'a>(args: &'a [(&'a str, Arg)]) -> Vec<(&'a str, &'a Arg)> {
+    args.iter().map(|(name, arg)| (*name, arg)).collect_vec()
+}
+
+fn with_count<'a>(args: &'a [(&'a str, Arg)], count: &'a Arg) -> Vec<(&'a str, &'a Arg)> {
+    let mut args = borrowed(args);
+    args.push(("count", count));
+    args
+}
+
+fn arg_to_markdown(arg: &Arg) -> String {
+    match arg {
+        Arg::Text(text) => escape(text),
+        Arg::Markdown(markdown) => markdown.to_string(),
+    }
+}
+
+/// escapes everything except `*bold*` markers and `` `code` `` spans
+fn escape_literal(literal: &str) -> String {
+    literal
+        .split('`')
+        .enumerate()
+        .map(|(index, part)| {
+            if index % 2 == 1 {
+                format!("`{}`", escape_code(part))
+            } else {
+                part.split('*').map(escape).join("*")
+            }
+        })
+        .collect()
+}
+
+/// placeholders without an argument are kept as they are
+fn render(
+    template: &str,
+    args: &[(&str, &Arg)],
+    literal: impl Fn(&str) -> String,
+    arg: impl Fn(&Arg) -> String,
+) -> String {
+    let mut result = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 1..start + length];
+        result.push_str(&literal(&rest[..start]));
+        match args.iter().find(|(arg_name, _)| *arg_name == name) {
+            Some((_, value)) => result.push_str(&arg(value)),
+            None => result.push_str(&literal(&rest[start..=start + length])),
+        }
+        rest = &rest[start + length + 1..];
+    }
+    result.push_str(&literal(rest));
+    result
+}
+
+fn parse_catalog(source: &'static str) -> Catalog {
+    source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let Some((key, value)) = line.split_once('=') else {
+                warn!("invalid catalog line: {line}");
+                return None;
+            };
+            Some((key.trim(), value.trim().replace("\\n", "\n")))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use teloxide::utils::command::BotCommands;
+
+    use super::*;
+
+    /// keys without the plural category, and the categories of countable keys
+    fn normalized_keys(language: Language) -> HashMap<String, HashSet<&'static str>> {
+        let mut keys: HashMap<String, HashSet<&'static str>> = HashMap::new();
+        for key in CATALOGS[&language].keys() {
+            let plural = ["one", "few", "many", "other"]
+                .into_iter()
+                .find_map(|category| {
+                    key.strip_suffix(&format!(".{category}"))
+                        .map(|base| (base.to_string(), category))
+                });
+            match plural {
+                Some((base, category)) => {
+                    keys.entry(base).or_default().insert(category);
+                }
+                None => {
+                    keys.entry((*key).to_string()).or_default();
+                }
+            }
+        }
+        keys
+    }
+
+    fn placeholders(template: &str) -> Vec<&str> {
+        template
+            .split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+            .sorted()
+            .dedup()
+            .collect_vec()
+    }
+
+    #[test]
+    fn every_key_exists_in_every_catalog() {
+        let english = normalized_keys(Language::English);
+        for language in Language::ALL {
+            let keys = normalized_keys(language);
+            let missing = english
+                .keys()
+                .filter(|key| !keys.contains_key(*key))
+                .collect_vec();
+            let unknown = keys
+                .keys()
+                .filter(|key| !english.contains_key(*key))
+                .collect_vec();
+            assert!(missing.is_empty(), "{language:?} is missing {missing:?}");
+            assert!(unknown.is_empty(), "{language:?} has unknown keys {unknown:?}");
+            for (key, categories) in &keys {
+                if english[key].is_empty() {
+                    assert!(categories.is_empty(), "{language:?}: {key} is not countable");
+                    continue;
+                }
+                for category in language.plural_categories() {
+                    assert!(
+                        categories.contains(category.as_str()),
+                        "{language:?}: {key} is missing the plural form {}",
+                        category.as_str()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn translations_use_the_same_placeholders() {
+        let english = &CATALOGS[&Language::English];
+        for language in Language::ALL {
+            for (key, template) in &CATALOGS[&language] {
+                // plural forms that english does not have are compared with `other`
+                let english_template = english.get(key).or_else(|| {
+                    let (base, _) = key.rsplit_once('.')?;
+                    english.get(format!("{base}.other").as_str())
+                });
+                let Some(english_template) = english_template else {
+                    continue;
+                };
+                assert_eq!(
+                    placeholders(template),
+                    placeholders(english_template),
+                    "{language:?}: placeholders of {key}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn every_user_command_has_a_description() {
+        for command in crate::message::RegularCommand::bot_commands() {
+            let key = format!("command.{}", command.command.trim_start_matches('/'));
+            assert!(Language::English.has_key(&key), "missing {key}");
+        }
+    }
+
+    #[test]
+    fn markup_is_balanced() {
+        for language in Language::ALL {
+            for (key, template) in &CATALOGS[&language] {
+                assert_eq!(template.matches('*').count() % 2, 0, "{language:?}: {key}");
+                assert_eq!(template.matches('`').count() % 2, 0, "{language:?}: {key}");
+            }
+        }
+    }
+
+    #[test]
+    fn russian_plural_rules() {
+        let categories = [1, 2, 5, 11, 12, 21, 22, 25, 111, 0]
+            .map(|count| Language::Russian.plural_category(count));
+        assert_eq!(
+            categories,
+            [
+                PluralCategory::One,
+                PluralCategory::Few,
+                PluralCategory::Many,
+                PluralCategory::Many,
+                PluralCategory::Many,
+                PluralCategory::One,
+                PluralCategory::Few,
+                PluralCategory::Many,
+                PluralCategory::Many,
+                PluralCategory::Many,
+            ]
+        );
+        assert_eq!(Language::German.plural_category(1), PluralCategory::One);
+        assert_eq!(Language::German.plural_category(0), PluralCategory::Other);
+    }
+
+    #[test]
+    fn markdown_escapes_literals_and_text_args() {
+        let rendered = render(
+            "*Hi* {name}! Use `/tagme all` or {link}.",
+            &[
+                ("name", &Arg::Text("a.b".to_string())),
+                ("link", &Arg::Markdown(Markdown::new("[x](https://x.y)"))),
+            ],
+            escape_literal,
+            arg_to_markdown,
+        );
+        assert_eq!(
+            rendered,
+            "*Hi* a\\.b\\! Use `/tagme all` or [x](https://x.y)\\."
+        );
+    }
+
+    #[test]
+    fn plural_markdown_fills_in_the_count() {
+        let rendered = Language::Russian.plural_markdown(
+            "stats.popular_tag",
+            3,
+            &[("emoji", "🦊".into()), ("tag", "fox".into())],
+        );
+        assert_eq!(rendered.to_string(), "🦊 fox: 3 стикера");
+    }
+
+    #[test]
+    fn language_selection() {
+        assert_eq!(Language::from_language_code("de-AT"), Some(Language::German));
+        assert_eq!(Language::from_language_code("fr"), None);
+        assert_eq!(
+            Language::from_accept_language("fr-CH, fr;q=0.9, ru;q=0.7, de;q=0.8"),
+            Some(Language::German)
+        );
+        assert_eq!(Language::from_accept_language("fr, *;q=0.5"), None);
+    }
+}
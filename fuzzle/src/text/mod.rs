@@ -1,5 +1,7 @@
+mod i18n;
 mod markdown;
 mod texts;
 
+pub use i18n::*;
 pub use markdown::*;
 pub use texts::*;
@@ -2,24 +2,30 @@ use std::collections::HashMap;
 
 use crate::{
     callback::TagOperation,
+    inline::InlineQueryData,
     database::{
         AddedRemoved, AdminStats, AggregatedUserStats, FullUserStats, PersonalStats, PopularTag,
-        Stats, StickerChange, StickerSet, Tag, UserSettings, UserStats, UserStickerStat,
+        Stats, StickerChange, StickerCollection, StickerSet, Tag, TaggingFocus, User, UserSettings, UserStats, UserStickerStat,
+        Capability, ModerationTask, ModerationTaskStatus, AdditionalReport, ReportReason, ReportTarget,
+        ApiKey,
     },
     message::{
-        PrivacyPolicy, admin_command_description, escape_sticker_unique_id_for_command, user_command_description
+        PrivacyPolicy, admin_command_description, escape_sticker_unique_id_for_command, list_visible_user_commands,
+        moderator_command_description,
     },
-    tags::Category,
-    util::{Emoji, StickerSetId, format_relative_time},
+    services::{Creator, SetComparison, TagReview, TagWiki},
+    tags::{Category, EVALUATION_TOP_K, SuggestionCombiner, SuggestionEvaluation, TagConflict},
+    util::{Emoji, StickerId, StickerSetId, format_relative_time},
 };
 use itertools::Itertools;
 use teloxide::{
     types::{Message, UserId},
-    utils::markdown::escape,
+    utils::markdown::{escape, escape_code},
 };
 use tracing::warn;
+use url::Url;
 
-use super::Markdown;
+use super::{Arg, Language, Markdown};
 
 /*
  * All text here uses markdown v2 syntax
@@ -30,35 +36,52 @@ pub struct Text;
 
 impl Text {
     #[must_use]
-    pub fn get_help_text(admin: bool) -> Markdown {
+    pub fn get_help_text(moderator: bool, admin: bool, language: Language) -> Markdown {
+        let commands = list_visible_user_commands(language)
+            .into_iter()
+            .map(|command| format!("/{} — {}", command.command.trim_start_matches('/'), command.description))
+            .join("\n");
+        let user_commands = language.markdown("help.commands", &[("commands", commands.into())]);
+        // moderators and admins get the english descriptions of their commands
         Markdown::new(if admin {
             format!(
-                "{}\n\n{}",
-                escape(&user_command_description()),
+                "{user_commands}\n\n{}\n\n{}",
+                escape(&moderator_command_description()),
                 escape(&admin_command_description())
             )
+        } else if moderator {
+            format!(
+                "{user_commands}\n\n{}",
+                escape(&moderator_command_description())
+            )
         } else {
-            user_command_description()
+            user_commands.to_string()
         })
     }
 
     #[must_use]
-    pub fn removed_set() -> Markdown {
-        Markdown::new("I can't add this sticker set\\.")
+    pub fn removed_set(language: Language) -> Markdown {
+        language.markdown("sets.removed", &[])
     }
 
     #[must_use]
-    pub fn get_settings_text(settings: &UserSettings) -> Markdown {
+    pub fn get_settings_text(settings: &UserSettings, language: Language) -> Markdown {
         let order = match settings.order() {
-            crate::database::StickerOrder::LatestFirst => "🆕 Latest First",
-            crate::database::StickerOrder::Random => "🔀 Random",
+            crate::database::StickerOrder::LatestFirst => "settings.order.latest_first",
+            crate::database::StickerOrder::Random => "settings.order.random",
         };
 
-        Markdown::new(format!(
-            "*Settings:*
-Current Order: {order}
-"
-        ))
+        let rating = settings.rating_profile();
+
+        language.markdown(
+            "settings.text",
+            &[
+                ("order", language.text(order, &[]).into()),
+                ("rating_emoji", rating.to_emoji().into()),
+                ("rating", rating.to_string().into()),
+                ("language", language.name().into()),
+            ],
+        )
     }
 
     #[must_use]
@@ -67,40 +90,55 @@ Current Order: {order}
     }
 
     #[must_use]
-    pub fn popular_tags(tags: Vec<(PopularTag, Category)>) -> Markdown {
+    pub fn popular_tags(tags: Vec<(PopularTag, Category)>, language: Language) -> Markdown {
         let tags_str = tags
             .into_iter()
             .map(|(tag, category)| {
-                format!(
-                    "{} {}: {} Stickers",
-                    category.to_emoji(),
-                    escape(&tag.name),
-                    tag.count
-                )
+                language
+                    .plural_markdown(
+                        "stats.popular_tag",
+                        tag.count,
+                        &[("emoji", category.to_emoji().into()), ("tag", tag.name.into())],
+                    )
+                    .to_string()
             })
             .collect_vec()
             .join("\n");
-        Markdown::new(format!("🏷 *Most Used Tags*\n\n{tags_str}"))
+        language.markdown(
+            "stats.popular_tags",
+            &[("tags", Markdown::new(tags_str).into())],
+        )
     }
 
     #[must_use]
-    pub fn general_stats(stats: Stats) -> Markdown {
-        Markdown::new(format!(
-            "🌐 *General Stats*\n\nSets: {}\nStickers: {}\nTagged Stickers: {}\nTaggings: {}",
-            stats.sets, stats.stickers, stats.tagged_stickers, stats.taggings
-        ))
+    pub fn general_stats(stats: Stats, language: Language) -> Markdown {
+        language.markdown(
+            "stats.general",
+            &[
+                ("sets", stats.sets.into()),
+                ("stickers", stats.stickers.into()),
+                ("tagged_stickers", stats.tagged_stickers.into()),
+                ("taggings", stats.taggings.into()),
+            ],
+        )
     }
 
     #[must_use]
-    pub fn personal_stats(stats: PersonalStats, set_count: i64) -> Markdown {
-        Markdown::new(format!(
-            "👤 *Personal Stats*\n\nFavorites: {}\nOwned Sets: {set_count}",
-            stats.favorites
-        ))
+    pub fn personal_stats(stats: PersonalStats, set_count: i64, language: Language) -> Markdown {
+        language.markdown(
+            "stats.personal",
+            &[
+                ("favorites", stats.favorites.into()),
+                ("owned_sets", set_count.into()),
+                ("public_collections", stats.public_collections.into()),
+                ("collection_followers", stats.collection_followers.into()),
+                ("followed_collections", stats.followed_collections.into()),
+            ],
+        )
     }
 
     #[must_use]
-    pub fn latest_sets(sets: Vec<StickerSet>) -> Markdown {
+    pub fn latest_sets(sets: Vec<StickerSet>, language: Language) -> Markdown {
         let sets_str = sets
             .into_iter()
             .map(|set| {
@@ -114,7 +152,127 @@ Current Order: {order}
             })
             .collect_vec()
             .join("\n");
-        Markdown::new(format!("🗂️ *New Sets*\n\n{sets_str}"))
+        language.markdown("sets.latest", &[("sets", Markdown::new(sets_str).into())])
+    }
+
+    #[must_use]
+    pub fn reverse_image_search(sets: Vec<(StickerSet, f32)>, language: Language) -> Markdown {
+        let sets_str = sets
+            .into_iter()
+            .map(|(set, score)| {
+                let link = format_set_as_markdown_link(&set.id, &set.title_or_id());
+                language
+                    .markdown(
+                        "sets.closest.set",
+                        &[
+                            ("set", Markdown::new(link).into()),
+                            ("score", format!("{:.0}%", score * 100.0).into()),
+                        ],
+                    )
+                    .to_string()
+            })
+            .collect_vec()
+            .join("\n");
+        language.markdown("sets.closest", &[("sets", Markdown::new(sets_str).into())])
+    }
+
+    #[must_use]
+    pub fn suggestion_evaluation(
+        evaluation: &SuggestionEvaluation,
+        current: &SuggestionCombiner,
+    ) -> Markdown {
+        let format_precision = |precision: &[f64]| {
+            escape(
+                &precision
+                    .iter()
+                    .map(|precision| format!("{:.0}%", precision * 100.0))
+                    .join(" / "),
+            )
+        };
+        let cutoffs = EVALUATION_TOP_K.iter().join(" / ");
+        let sources = evaluation
+            .sources
+            .iter()
+            .map(|(source, precision)| {
+                format!(
+                    "`{}`: {} \\(weight {}\\)",
+                    source.name(),
+                    format_precision(precision),
+                    escape(&format!(
+                        "{:.2} → {:.2}",
+                        current.weight(*source),
+                        evaluation.combiner.weight(*source)
+                    ))
+                )
+            })
+            .join("\n");
+        Markdown::new(format!(
+            "📊 *Tag Suggestion Evaluation*
+
+{} training stickers, {} test stickers
+Precision at k \\= {}:
+
+{sources}
+
+Combined \\(untrained\\): {}
+Combined \\(trained\\): {}
+
+The current weights stay in use until the trained ones are applied\\.",
+            evaluation.training_samples,
+            evaluation.test_samples,
+            escape(&cutoffs),
+            format_precision(&evaluation.untrained),
+            format_precision(&evaluation.trained),
+        ))
+    }
+
+    #[must_use]
+    pub fn public_collection(
+        collection: &StickerCollection,
+        sticker_count: i64,
+        follower_count: i64,
+        language: Language,
+    ) -> Markdown {
+        let query = format!(
+            "`{}`",
+            escape_code(InlineQueryData::public_collection(collection.id).to_string().trim_end())
+        );
+        language.markdown(
+            "collection.public",
+            &[
+                ("name", collection.name.clone().into()),
+                ("stickers", language.plural_markdown("collection.stickers", sticker_count, &[]).into()),
+                ("followers", language.plural_markdown("collection.followers", follower_count, &[]).into()),
+                ("query", Markdown::new(query).into()),
+            ],
+        )
+    }
+
+    #[must_use]
+    pub fn own_collections(collections: &[StickerCollection], language: Language) -> Markdown {
+        let collections = if collections.is_empty() {
+            language.markdown("collections.empty", &[]).to_string()
+        } else {
+            collections
+                .iter()
+                .map(|collection| {
+                    format!(
+                        "\\- `{}`: {}{}",
+                        collection.id,
+                        escape(&collection.name),
+                        if collection.is_public {
+                            format!(" {}", language.markdown("collections.public", &[]))
+                        } else {
+                            String::new()
+                        }
+                    )
+                })
+                .join("\n")
+        };
+        language.markdown(
+            "collections.list",
+            &[("collections", Markdown::new(collections).into())],
+        )
     }
 
     #[must_use]
@@ -127,111 +285,141 @@ Current Order: {order}
     }
 
     #[must_use]
-    pub fn latest_stickers(changes: Vec<StickerChange>) -> Markdown {
+    pub fn latest_stickers(changes: Vec<StickerChange>, language: Language) -> Markdown {
         let sets_str = changes
             .into_iter()
             .map(|change| {
                 let link =
                     format_set_as_markdown_link(&change.sticker_set_id, &change.sticker_set_id);
-                format!(
-                    "{link}: {} added today, {} added this week",
-                    change.today, change.this_week
-                )
+                language
+                    .markdown(
+                        "sets.latest_stickers.set",
+                        &[
+                            ("set", Markdown::new(link).into()),
+                            ("today", change.today.into()),
+                            ("week", change.this_week.into()),
+                        ],
+                    )
+                    .to_string()
             })
             .collect_vec()
             .join("\n");
-        Markdown::new(format!("✨️ *New Stickers*\n\n{sets_str}"))
+        language.markdown("sets.latest_stickers", &[("sets", Markdown::new(sets_str).into())])
     }
 
     #[must_use]
-    pub fn infos() -> Markdown {
-        Markdown::new(
-        "*Tagging:*
-Tag what you see\\. This is the same policy as e621\\. Tags are saved immediately\\.
-Infos \\(e621 wiki\\): [twys](https://e621.net/wiki_pages/1684), [genders](https://e621.net/wiki_pages/3294), [checklist](https://e621.net/wiki_pages/310)
-
-*Tag Locking:*
-Tag locking prevents adding or removing tags via set operations\\. This is useful for adding e\\.g\\. species or fur color tags to a whole set witout messing up the tags on attribution stickers\\.
-
-*Problems or Suggestions:*
-Create an issue on [GitHub](https://github.com/avoonix/fuzzle-bot/issues)\\.",
-    )
+    pub fn infos(language: Language) -> Markdown {
+        let wiki_links = [
+            ("twys", "https://e621.net/wiki_pages/1684"),
+            ("genders", "https://e621.net/wiki_pages/3294"),
+            ("checklist", "https://e621.net/wiki_pages/310"),
+        ]
+        .into_iter()
+        .map(|(title, url)| format!("[{title}]({url})"))
+        .join(", ");
+        language.markdown(
+            "infos.text",
+            &[
+                ("wiki_links", Markdown::new(wiki_links).into()),
+                ("github", Markdown::new("[GitHub](https://github.com/avoonix/fuzzle-bot/issues)").into()),
+            ],
+        )
     }
 
     #[must_use]
-    pub fn blacklist() -> Markdown {
-        Markdown::new(
-        "*Blacklist Info:*
-The blacklist is not very useful as of now because the majority of stickers are not properly tagged yet\\.
-
-*Emoji Search:*
-If you search stickers by emojis instead of tags, the blacklist is not in effect\\.",
-    )
+    pub fn blacklist(whitelisted_sets: &[StickerSetId], language: Language) -> Markdown {
+        let whitelisted_sets = if whitelisted_sets.is_empty() {
+            language.markdown("blacklist.no_sets", &[]).to_string()
+        } else {
+            whitelisted_sets
+                .iter()
+                .map(|set_id| format_set_as_markdown_link(set_id, set_id))
+                .join(", ")
+        };
+        language.markdown(
+            "blacklist.text",
+            &[("sets", Markdown::new(whitelisted_sets).into())],
+        )
     }
 
     #[must_use]
-    pub fn get_set_operations_text(set_id: &StickerSetId, set_title: &str) -> Markdown {
+    pub fn spoiler_sticker(warned_tags: &[String], sticker_url: &Url, language: Language) -> Markdown {
         Markdown::new(format!(
-            "Tag or untag all stickers in the set {}",
-            format_set_as_markdown_link(set_id, set_title)
+            "{}\n||{}||",
+            language.markdown("blacklist.spoiler", &[("tags", warned_tags.join(", ").into())]),
+            escape(sticker_url.as_str())
         ))
     }
 
     #[must_use]
-    pub fn get_processed_sticker_sets_text(queued_set_names: Vec<StickerSetId>) -> Markdown {
-        let mut message = String::new();
-        message.push_str("Queued sticker sets:\n");
-        for (i, set_name) in queued_set_names.iter().enumerate() {
-            message.push_str(&format_set_as_markdown_link(set_name, set_name));
-            message.push('\n');
-        }
+    pub fn get_set_operations_text(set_id: &StickerSetId, set_title: &str, language: Language) -> Markdown {
+        language.markdown(
+            "sets.operations",
+            &[("set", Markdown::new(format_set_as_markdown_link(set_id, set_title)).into())],
+        )
+    }
 
-        Markdown::new(message)
+    #[must_use]
+    pub fn get_processed_sticker_sets_text(queued_set_names: Vec<StickerSetId>, language: Language) -> Markdown {
+        let sets = queued_set_names
+            .iter()
+            .map(|set_name| format_set_as_markdown_link(set_name, set_name))
+            .join("\n");
+        language.markdown("sets.queued", &[("sets", Markdown::new(sets).into())])
     }
 
     #[must_use]
-    pub fn get_sticker_text(emoji: Option<Emoji>, set_is_new: bool, is_admin: bool, set_id: Option<StickerSetId>) -> Markdown {
-        let value = if let Some(emoji) = emoji {
-            if let Some(name) = emoji.name() {
-                format!(" {} {} ", name, emoji.to_string_with_variant())
+    pub fn get_sticker_text(emoji: Option<Emoji>, set_is_new: bool, is_admin: bool, set_id: Option<StickerSetId>, language: Language) -> Markdown {
+        let sent_text = if let Some(emoji) = emoji {
+            let emoji = if let Some(name) = emoji.name() {
+                format!("{} {}", name, emoji.to_string_with_variant())
             } else {
-                format!(" {} ", emoji.to_string_with_variant())
-            }
+                emoji.to_string_with_variant()
+            };
+            language.markdown("sticker.sent_with_emoji", &[("emoji", emoji.into())])
         } else {
-            " ".to_string()
+            language.markdown("sticker.sent", &[])
         };
         let new_set_text = if set_is_new {
-            "\n\n✨ New Set ✨\nIt can take a few minutes until I'm done processing all stickers"
+            format!("\n\n{}", language.markdown("sticker.new_set", &[]))
         } else {
-            ""
+            String::new()
         };
         let admin_text = if is_admin && let Some(set_id) = set_id {
             format!("\n\n/banset\\_{} /unbanset\\_{}", escape(&set_id), escape(&set_id))
         } else {"".to_string()};
 
-        Markdown::new(format!(
-            "UwU you sent a{}sticker :3{}{}",
-            escape(&value),
-            new_set_text,
-            admin_text
-        ))
+        Markdown::new(format!("{sent_text}{new_set_text}{admin_text}"))
     }
 
     #[must_use]
-    pub fn get_start_text() -> Markdown {
-        Markdown::new(
-    "I'm a bot that can help you find stickers\\. I am using many of the e621 tags, but with telegram stickers\\. To use me, press the \"Use me in a chat\" button below\\.
+    pub fn get_start_text(language: Language) -> Markdown {
+        language.markdown("start.text", &[])
+    }
 
-If you send me some stickers in this chat, I will add them to the database\\. Help with tagging is appreciated :3".to_string()
-    )
+    #[must_use]
+    pub fn get_main_text(language: Language) -> Markdown {
+        language.markdown("main.text", &[])
     }
 
     #[must_use]
-    pub fn get_main_text() -> Markdown {
-        Markdown::new(
-    "Below are some things you can explore\\. You will also find some commands in the bot menu\\."
-        .to_string()
-    )
+    pub fn language_changed(chosen: Option<Language>, language: Language) -> Markdown {
+        match chosen {
+            Some(chosen) => language.markdown("language.changed", &[("language", chosen.name().into())]),
+            None => language.markdown("language.auto", &[]),
+        }
+    }
+
+    #[must_use]
+    pub fn language_usage(language: Language) -> Markdown {
+        let languages = Language::ALL
+            .into_iter()
+            .map(|language| format!("`{}` \\({}\\)", language.code(), escape(language.name())))
+            .join(", ");
+        language.markdown(
+            "language.usage",
+            &[("languages", Markdown::new(languages).into())],
+        )
     }
 
     #[must_use]
@@ -260,8 +448,48 @@ DB: {}
     }
 
     #[must_use]
-    pub fn report_sticker_set_task() -> Markdown {
-        Markdown::new("*Sticker Set Reported*\n".to_string())
+    pub fn report_sticker_set_task(
+        set_id: &StickerSetId,
+        reason: ReportReason,
+        note: Option<&str>,
+        additional_reports: &[AdditionalReport],
+    ) -> Markdown {
+        Markdown::new(format!(
+            "*Sticker Set Reported*\nSet: {}\n{}",
+            escape(set_id),
+            report_details(reason, note, additional_reports)
+        ))
+    }
+
+    #[must_use]
+    pub fn report_sticker_task(
+        sticker_id: &StickerId,
+        reason: ReportReason,
+        note: Option<&str>,
+        additional_reports: &[AdditionalReport],
+    ) -> Markdown {
+        Markdown::new(format!(
+            "*Sticker Reported*\nSticker: `{}`\n{}",
+            escape_code(sticker_id),
+            report_details(reason, note, additional_reports)
+        ))
+    }
+
+    #[must_use]
+    pub fn report_resolved(target: &ReportTarget, status: ModerationTaskStatus, language: Language) -> Markdown {
+        let target = match target {
+            ReportTarget::Set(set_id) => {
+                language.markdown("report.target.set", &[("set", Markdown::new(escape(set_id)).into())])
+            }
+            ReportTarget::Sticker(_) => language.markdown("report.target.sticker", &[]),
+        };
+        let key = match status {
+            ModerationTaskStatus::Completed => "report.resolved.action",
+            ModerationTaskStatus::Cancelled | ModerationTaskStatus::Pending => {
+                "report.resolved.no_action"
+            }
+        };
+        language.markdown(key, &[("target", target.into())])
     }
 
     #[must_use]
@@ -269,6 +497,16 @@ DB: {}
         Markdown::new("*New Sets*\n".to_string())
     }
 
+    /// appends who is handling the task
+    #[must_use]
+    pub fn with_moderation_task_moderator(text: Markdown, task: &ModerationTask) -> Markdown {
+        match (task.decided_by_user_id, task.assigned_to_user_id) {
+            (Some(moderator_id), _) => Markdown::new(format!("{text}\nDecided by `{moderator_id}`")),
+            (None, Some(moderator_id)) => Markdown::new(format!("{text}\nAssigned to `{moderator_id}`")),
+            (None, None) => text,
+        }
+    }
+
     #[must_use]
     pub fn daily_report(
         counts: Stats,
@@ -280,12 +518,24 @@ DB: {}
             .map_or("never".to_string(), |age| {
                 format!("{} hours", age.num_hours())
             });
+        let acceptance = &stats.suggestion_acceptance;
+        let suggested_share = acceptance
+            .suggested_share()
+            .map_or("-".to_string(), |rate| format!("{:.0}%", rate * 100.0));
+        let acceptance_sources = acceptance
+            .sources
+            .iter()
+            .map(|(source, accepted, average_rank)| {
+                format!("\n  - {source}: {accepted} (average rank {average_rank:.1})")
+            })
+            .join("");
         let text = escape(&format!(
             "Daily Report:
 - {} stickers ({} tagged, {} sets) with {} taggings
 - {} sets fetched within 24 hours
 - least recently fetched set age: {}
 - {} pending sets
+- suggested share of chosen tags (24 hours): {} ({} suggested, {} manual, {} implied){}
 - merge queue: /mergequeue
 
 user taggings (24 hours):",
@@ -296,6 +546,11 @@ user taggings (24 hours):",
             stats.number_of_sets_fetched_in_24_hours,
             age,
             stats.pending_set_count,
+            suggested_share,
+            acceptance.suggested,
+            acceptance.manual,
+            acceptance.implied,
+            acceptance_sources,
         ));
 
         let user_taggings = taggings
@@ -343,16 +598,428 @@ Taggings per set \\(24 hours\\):
     }
 
     #[must_use]
-    pub fn continuous_tag_success() -> Markdown {
-        Markdown::new("Successfully tagged")
+    pub fn user_permissions(user: &User) -> Markdown {
+        let updated_by = user
+            .trust_level_updated_by_user_id
+            .map_or_else(|| "automatically".to_string(), |admin_id| format!("by `{admin_id}`"));
+        let capabilities = Capability::ALL
+            .into_iter()
+            .filter(|capability| user.has_capability(*capability))
+            .map(|capability| format!("`{}`", capability.name()))
+            .join(", ");
+        let grants = user
+            .capability_grants
+            .as_ref()
+            .map(|grants| {
+                grants
+                    .iter()
+                    .map(|(capability, granted)| {
+                        format!("`{}` {}", capability.name(), if *granted { "✅" } else { "❌" })
+                    })
+                    .sorted()
+                    .join(", ")
+            })
+            .filter(|grants| !grants.is_empty())
+            .unwrap_or_else(|| "none".to_string());
+        Markdown::new(format!(
+            "User: `{}`\nTrust level: {} \\(set {updated_by}\\)\nCapabilities: {}\nGrants: {grants}",
+            user.id,
+            user.trust_level.name(),
+            if capabilities.is_empty() { "none".to_string() } else { capabilities },
+        ))
+    }
+
+    #[must_use]
+    pub fn api_keys(keys: &[ApiKey], domain_name: &str, language: Language) -> Markdown {
+        let keys = if keys.is_empty() {
+            language.markdown("api_keys.empty", &[]).to_string()
+        } else {
+            keys.iter()
+                .map(|key| {
+                    let last_used = key.last_used_at.map_or_else(
+                        || language.text("api_keys.never_used", &[]),
+                        format_relative_time,
+                    );
+                    language
+                        .markdown(
+                            "api_keys.key",
+                            &[
+                                ("id", Markdown::new(format!("`{}`", key.id)).into()),
+                                ("name", key.name.clone().into()),
+                                ("requests", key.requests_per_minute.into()),
+                                ("last_used", last_used.into()),
+                            ],
+                        )
+                        .to_string()
+                })
+                .join("\n")
+        };
+        language.markdown(
+            "api_keys.list",
+            &[
+                ("keys", Markdown::new(keys).into()),
+                ("openapi_url", format!("https://{domain_name}/api/v1/openapi.json").into()),
+            ],
+        )
+    }
+
+    /// the feed urls are `None` outside of private chats
+    #[must_use]
+    pub fn followed_tags(
+        tags: &[String],
+        feed_urls: Option<(&str, &str)>,
+        language: Language,
+    ) -> Markdown {
+        if tags.is_empty() {
+            return language.markdown("follow.none", &[]);
+        }
+        let (atom_url, json_url) = feed_urls.map_or_else(
+            || {
+                let hint = language.text("follow.private_chat", &[]);
+                (hint.clone(), hint)
+            },
+            |(atom_url, json_url)| (atom_url.to_string(), json_url.to_string()),
+        );
+        let tags = tags.iter().map(|tag| format!("`{}`", escape_code(tag))).join(", ");
+        language.markdown(
+            "follow.list",
+            &[
+                ("tags", Markdown::new(tags).into()),
+                ("atom_url", atom_url.into()),
+                ("json_url", json_url.into()),
+            ],
+        )
     }
 
     #[must_use]
-    pub fn sticker_recommender_text(similar: usize, dissimilar: usize) -> Markdown {
+    pub fn tag_wiki(wiki: &TagWiki, language: Language) -> Markdown {
+        // months shown in the bot; the website shows all of them
+        const USAGE_MONTHS: usize = 6;
+
+        let code_list = |tags: &[String]| -> Arg {
+            Markdown::new(tags.iter().map(|tag| format!("`{}`", escape_code(tag))).join(", ")).into()
+        };
+        let mut sections = vec![language
+            .plural_markdown(
+                "wiki.header",
+                wiki.sticker_count(),
+                &[
+                    ("tag", wiki.tag.as_str().into()),
+                    ("category", wiki.category.to_human_name().into()),
+                ],
+            )
+            .to_string()];
+        sections.push(wiki.description.as_ref().map_or_else(
+            || language.markdown("wiki.no_description", &[]).to_string(),
+            |description| escape(&description.description),
+        ));
+        let relationships = [
+            ("wiki.aliases", &wiki.aliases),
+            ("wiki.implies", &wiki.implications),
+            ("wiki.implied_by", &wiki.inverse_implications),
+        ]
+        .into_iter()
+        .filter(|(_, tags)| !tags.is_empty())
+        .map(|(key, tags)| language.markdown(key, &[("tags", code_list(tags))]).to_string())
+        .join("\n");
+        if !relationships.is_empty() {
+            sections.push(relationships);
+        }
+        if !wiki.usage.is_empty() {
+            let months = wiki
+                .usage
+                .iter()
+                .rev()
+                .take(USAGE_MONTHS)
+                .map(|usage| format!("{}: {}", escape(&usage.month), usage.count))
+                .join("\n");
+            sections.push(
+                language
+                    .markdown("wiki.usage", &[("months", Markdown::new(months).into())])
+                    .to_string(),
+            );
+        }
+        sections.push(language.markdown("wiki.edit_hint", &[]).to_string());
+        Markdown::new(sections.join("\n\n"))
+    }
+
+    #[must_use]
+    pub fn creator_profile(creator: &Creator, language: Language) -> Markdown {
+        // sets and tags shown in the bot; the website shows all of them
+        const LATEST_SETS: usize = 5;
+        const TOP_TAGS: usize = 10;
+
+        let code_list = |tags: Vec<&str>| -> Arg {
+            Markdown::new(tags.into_iter().map(|tag| format!("`{}`", escape_code(tag))).join(", ")).into()
+        };
+        let mut sections = vec![language
+            .plural_markdown(
+                "creator.sets",
+                creator.set_count,
+                &[("name", creator.display_name().into())],
+            )
+            .to_string()];
+        match &creator.profile {
+            Some(profile) => {
+                let mut profile_section = vec![language.markdown("creator.claimed", &[]).to_string()];
+                if !profile.bio.is_empty() {
+                    profile_section.push(escape(&profile.bio));
+                }
+                if !profile.links.is_empty() {
+                    profile_section.push(
+                        language
+                            .markdown("creator.links", &[("links", profile.links.join(" ").into())])
+                            .to_string(),
+                    );
+                }
+                sections.push(profile_section.join("\n"));
+            }
+            None => sections.push(language.markdown("creator.unclaimed", &[]).to_string()),
+        }
+        let mut related = Vec::new();
+        if !creator.linked_tags.is_empty() {
+            related.push(language.markdown(
+                "creator.tags",
+                &[(
+                    "tags",
+                    code_list(creator.linked_tags.iter().map(|tag| tag.id.as_str()).collect_vec()),
+                )],
+            ));
+        }
+        if !creator.channels.is_empty() {
+            let channels = creator
+                .channels
+                .iter()
+                .map(|(_, channel)| format!("@{channel}"))
+                .join(", ");
+            related.push(language.markdown("creator.channels", &[("channels", channels.into())]));
+        }
+        if !creator.tag_distribution.is_empty() {
+            related.push(language.markdown(
+                "creator.top_tags",
+                &[(
+                    "tags",
+                    code_list(
+                        creator
+                            .tag_distribution
+                            .iter()
+                            .take(TOP_TAGS)
+                            .map(|tag| tag.name.as_str())
+                            .collect_vec(),
+                    ),
+                )],
+            ));
+        }
+        if !related.is_empty() {
+            sections.push(related.iter().map(ToString::to_string).join("\n"));
+        }
+        let sets = creator
+            .sets
+            .iter()
+            .take(LATEST_SETS)
+            .map(|set| {
+                format!(
+                    "{} \\({}\\)",
+                    escape(&set.title_or_id()),
+                    escape(&set.created_at.format("%Y-%m-%d").to_string())
+                )
+            })
+            .join("\n");
+        sections.push(
+            language
+                .markdown("creator.latest_sets", &[("sets", Markdown::new(sets).into())])
+                .to_string(),
+        );
+        Markdown::new(sections.join("\n\n"))
+    }
+
+    #[must_use]
+    pub fn set_comparison(comparison: &SetComparison, is_moderator: bool, language: Language) -> Markdown {
+        // the website lists all of them
+        const TAGS_PER_SET: usize = 10;
+
+        let title_a = comparison.set_a.title_or_id();
+        let title_b = comparison.set_b.title_or_id();
+        let mut sections = vec![
+            language
+                .markdown("compare.sets", &[("set_a", title_a.clone().into()), ("set_b", title_b.clone().into())])
+                .to_string(),
+            [
+                language.markdown(
+                    "compare.stickers",
+                    &[
+                        ("count_a", comparison.sticker_count_a.into()),
+                        ("count_b", comparison.sticker_count_b.into()),
+                    ],
+                ),
+                language.markdown(
+                    "compare.shared",
+                    &[
+                        ("count", comparison.shared.len().into()),
+                        ("percentage", comparison.overlap_percentage().into()),
+                    ],
+                ),
+                language.markdown(
+                    "compare.near_duplicates",
+                    &[("count", comparison.near_duplicates.len().into())],
+                ),
+                language.markdown(
+                    "compare.unique",
+                    &[
+                        ("count_a", comparison.only_a.len().into()),
+                        ("count_b", comparison.only_b.len().into()),
+                    ],
+                ),
+            ]
+            .iter()
+            .map(ToString::to_string)
+            .join("\n"),
+        ];
+        let tags_only_in = |title: String, tags: Vec<&str>| -> Option<String> {
+            if tags.is_empty() {
+                return None;
+            }
+            let tags = tags.into_iter().map(|tag| format!("`{}`", escape_code(tag))).join(", ");
+            Some(
+                language
+                    .markdown("compare.tags_only", &[("set", title.into()), ("tags", Markdown::new(tags).into())])
+                    .to_string(),
+            )
+        };
+        let tag_sections = [
+            tags_only_in(
+                title_a,
+                comparison
+                    .tag_differences
+                    .iter()
+                    .filter(|difference| difference.count_b == 0)
+                    .take(TAGS_PER_SET)
+                    .map(|difference| difference.tag.as_str())
+                    .collect_vec(),
+            ),
+            tags_only_in(
+                title_b,
+                comparison
+                    .tag_differences
+                    .iter()
+                    .filter(|difference| difference.count_a == 0)
+                    .take(TAGS_PER_SET)
+                    .map(|difference| difference.tag.as_str())
+                    .collect_vec(),
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .collect_vec();
+        if !tag_sections.is_empty() {
+            sections.push(tag_sections.join("\n"));
+        }
+        if is_moderator && !comparison.near_duplicates.is_empty() {
+            sections.push(language.markdown("compare.merge_hint", &[]).to_string());
+        }
+        Markdown::new(sections.join("\n\n"))
+    }
+
+    #[must_use]
+    pub fn api_key_created(key: &ApiKey, secret: &str, language: Language) -> Markdown {
+        language.markdown(
+            "api_keys.created",
+            &[
+                ("name", key.name.clone().into()),
+                ("secret", Markdown::new(format!("`{}`", escape_code(secret))).into()),
+                ("revoke", Markdown::new(format!("`/apikeys revoke {}`", key.id)).into()),
+            ],
+        )
+    }
+
+    #[must_use]
+    pub fn tagging_focus(focus: Option<&TaggingFocus>, language: Language) -> Markdown {
+        match focus {
+            None => language.markdown("tagme.focus.all", &[]),
+            Some(TaggingFocus::Tag(tag)) => language.markdown(
+                "tagme.focus.tag",
+                &[("tag", Markdown::new(format!("`{}`", escape_code(tag))).into())],
+            ),
+            Some(TaggingFocus::Set(set_id)) => language.markdown(
+                "tagme.focus.set",
+                &[("set", Markdown::new(format_set_as_markdown_link(set_id, set_id)).into())],
+            ),
+        }
+    }
+
+    #[must_use]
+    pub fn no_stickers_to_tag(focus: Option<&TaggingFocus>, language: Language) -> Markdown {
+        if focus.is_some() {
+            language.markdown("tagme.empty_with_focus", &[])
+        } else {
+            language.markdown("tagme.empty", &[])
+        }
+    }
+
+    #[must_use]
+    pub fn tag_review(review: &TagReview, language: Language) -> Markdown {
+        let tags = review
+            .tags
+            .iter()
+            .map(|tag| {
+                let added_by = tag.added_by_user_id.map_or_else(
+                    || language.markdown("review.unknown_user", &[]),
+                    |user_id| Markdown::new(format!("`{user_id}`")),
+                );
+                language
+                    .markdown(
+                        "review.tag",
+                        &[
+                            ("tag", Markdown::new(format!("`{}`", escape_code(&tag.tag))).into()),
+                            ("user", added_by.into()),
+                        ],
+                    )
+                    .to_string()
+            })
+            .join("\n");
+        let conflicts = if review.conflicts.is_empty() {
+            String::new()
+        } else {
+            format!("\n\n{}", Self::tag_conflicts(&review.conflicts, language))
+        };
+        Markdown::new(format!(
+            "{}{conflicts}",
+            language.markdown("review.text", &[("tags", Markdown::new(tags).into())])
+        ))
+    }
+
+    #[must_use]
+    pub fn no_tags_to_review(language: Language) -> Markdown {
+        language.markdown("review.none", &[])
+    }
+
+    #[must_use]
+    pub fn tag_conflicts(conflicts: &[TagConflict], language: Language) -> Markdown {
+        let conflicts = conflicts
+            .iter()
+            .map(|conflict| conflict
+                .tags
+                .iter()
+                .map(|tag| format!("`{}`", escape_code(tag)))
+                .join(" \\+ "))
+            .join(", ");
+        language.markdown("tags.conflicts", &[("conflicts", Markdown::new(conflicts).into())])
+    }
+
+    #[must_use]
+    pub fn continuous_tag_success(language: Language) -> Markdown {
+        language.markdown("continuous_tag.success", &[])
+    }
+
+    #[must_use]
+    pub fn sticker_recommender_text(similar: usize, dissimilar: usize, language: Language) -> Markdown {
         if similar == 0 && dissimilar == 0 {
-            Markdown::escaped("Send stickers to get recommendations!")
+            language.markdown("recommender.empty", &[])
         } else {
-            Markdown::escaped(format!("Send more stickers to improve the recommendations!\nPositive Examples: {similar}\nNegative Examples: {dissimilar}"))
+            language.markdown(
+                "recommender.examples",
+                &[("positive", similar.into()), ("negative", dissimilar.into())],
+            )
         }
     }
 
@@ -362,31 +1029,21 @@ Taggings per set \\(24 hours\\):
     // }
 
     #[must_use]
-    pub fn get_continuous_tag_mode_text(add_tags: &[String], remove_tags: &[String]) -> Markdown {
-        let add_tags = if add_tags.len() > 0 {
-            add_tags
-                .iter()
-                .map(|tag| format!("`{}`", escape(tag)))
-                .join(", ")
-        } else {
-            "none".to_string()
-        }; // TODO: better join with ` and `
-        let remove_tags = if remove_tags.len() > 0 {
-            remove_tags
-                .iter()
-                .map(|tag| format!("`{}`", escape(tag)))
-                .join(", ")
-        } else {
-            "none".to_string()
+    pub fn get_continuous_tag_mode_text(add_tags: &[String], remove_tags: &[String], language: Language) -> Markdown {
+        let format_tags = |tags: &[String]| {
+            if tags.is_empty() {
+                language.markdown("continuous_tag.none", &[])
+            } else {
+                Markdown::new(tags.iter().map(|tag| format!("`{}`", escape_code(tag))).join(", "))
+            }
         }; // TODO: better join with ` and `
-        Markdown::new(format!(
-            "You are in Continuous Tag Mode\\.
-Tags that will be added:
-{add_tags}
-Tags that will be removed: 
-{remove_tags}
-Send stickers to apply these changes to them\\."
-        ))
+        language.markdown(
+            "continuous_tag.mode",
+            &[
+                ("add_tags", format_tags(add_tags).into()),
+                ("remove_tags", format_tags(remove_tags).into()),
+            ],
+        )
     }
 
     #[must_use]
@@ -401,33 +1058,32 @@ Send stickers to apply these changes to them\\."
     }
 
     #[must_use]
-    pub fn untagged_set(set_name: &StickerSetId, tags: &[String], count: usize) -> Markdown {
-        let tags = tags.into_iter().map(|t| escape(t)).join(", ");
-        let set = format_set_as_markdown_link(set_name, set_name);
-        Markdown::new(format!(
-            "Removed {tags} from set {set} \\({count} taggings changed\\)"
-        ))
+    pub fn untagged_set(set_name: &StickerSetId, tags: &[String], count: usize, language: Language) -> Markdown {
+        Self::set_tags_changed("set_tags.removed", set_name, tags, count, language)
     }
 
     #[must_use]
-    pub fn tagged_set(set_name: &StickerSetId, tags: &[String], count: usize) -> Markdown {
+    pub fn tagged_set(set_name: &StickerSetId, tags: &[String], count: usize, language: Language) -> Markdown {
+        Self::set_tags_changed("set_tags.added", set_name, tags, count, language)
+    }
+
+    fn set_tags_changed(
+        key: &str,
+        set_name: &StickerSetId,
+        tags: &[String],
+        count: usize,
+        language: Language,
+    ) -> Markdown {
         let set = format_set_as_markdown_link(set_name, set_name);
-        let tags = tags
-            .iter()
-            .with_position()
-            .map(|(position, tag)| {
-                let tag = format!("\"{}\"", escape(tag));
-                match position {
-                    itertools::Position::Only => format!("tag {tag}"),
-                    itertools::Position::First => format!("tags {tag}"),
-                    itertools::Position::Middle => tag,
-                    itertools::Position::Last => format!("and {tag}"),
-                }
-            })
-            .join(", ");
-        Markdown::new(format!(
-            "Added {tags} for set {set} \\({count} taggings changed\\)"
-        ))
+        let tags = tags.iter().map(|tag| format!("`{}`", escape_code(tag))).join(", ");
+        language.plural_markdown(
+            key,
+            i64::try_from(count).unwrap_or(i64::MAX),
+            &[
+                ("tags", Markdown::new(tags).into()),
+                ("set", Markdown::new(set).into()),
+            ],
+        )
     }
 
     #[must_use]
@@ -436,8 +1092,25 @@ Send stickers to apply these changes to them\\."
     }
 
     #[must_use]
-    pub fn privacy(section: PrivacyPolicy) -> Markdown {
-        Markdown::new(section.text())
+    pub fn privacy(section: PrivacyPolicy, language: Language) -> Markdown {
+        match section {
+            PrivacyPolicy::Introduction => language.markdown("privacy.introduction", &[]),
+            PrivacyPolicy::License => language.markdown(
+                "privacy.license",
+                &[("licenses", Markdown::new("[gnu\\.org/licenses](https://www.gnu.org/licenses/)").into())],
+            ),
+            PrivacyPolicy::DataCollection => {
+                let source_code = format!(
+                    "[{}](https://github.com/avoonix/fuzzle-bot/blob/213692c7ec070f372175bd7bd8352ec884606171/fuzzle/src/database/schema.rs#L92)",
+                    escape(&language.text("privacy.source_code", &[]))
+                );
+                language.markdown(
+                    "privacy.data_collection",
+                    &[("source_code", Markdown::new(source_code).into())],
+                )
+            }
+            PrivacyPolicy::DataUsage => language.markdown("privacy.data_usage", &[]),
+        }
     }
 }
 
@@ -462,3 +1135,34 @@ fn db_tag_text(tag: Option<Tag>) -> String {
         escape(&chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string())
     ) // TODO: more values
 }
+
+fn report_details(
+    reason: ReportReason,
+    note: Option<&str>,
+    additional_reports: &[AdditionalReport],
+) -> String {
+    let note = note.map_or_else(String::new, |note| format!("\nNote: {}", escape(note)));
+    let additional_reports = additional_reports
+        .iter()
+        .map(|report| {
+            format!(
+                "\n  \\- `{}`: {}{}",
+                report.user_id,
+                escape(report.reason.get_title()),
+                report
+                    .note
+                    .as_deref()
+                    .map_or_else(String::new, |note| format!(" \\({}\\)", escape(note)))
+            )
+        })
+        .join("");
+    let additional_reports = if additional_reports.is_empty() {
+        String::new()
+    } else {
+        format!("\nAlso reported by:{additional_reports}")
+    };
+    format!(
+        "Reason: {}{note}{additional_reports}",
+        escape(reason.get_title())
+    )
+}
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use actix_web::{
     HttpRequest, HttpResponse, Responder,
@@ -17,7 +17,7 @@ use serde::{Deserialize, Serialize};
 use tracing::Instrument;
 
 use crate::{
-    bot::{BotError, InternalError}, database::{BanReason, BannedSticker, Sticker, StickerSet}, services::Services, sticker::{
+    bot::{BotError, InternalError, UserError}, database::{BanReason, BannedSticker, Capability, ModerationAction, ModerationLogEntry, Sticker, StickerSet, TrustLevel, User}, message::update_moderator_command_menu, services::Services, sticker::{
         create_historgram_image, create_sticker_thumbnail, fetch_sticker_file, generate_merge_image, resolve_file_hashes_to_sticker_ids_and_clean_up_unreferenced_files,
     }, tags::Category, util::{Required, StickerId, StickerSetId}, web::shared::{AppState, thumbnail_cache_control_header}
 };
@@ -153,7 +153,11 @@ async fn ban_set(
     Path(set_id): Path<StickerSetId>,
     data: Data<AppState>,
 ) -> actix_web::Result<impl Responder> {
-    data.services.import.ban_sticker_set(&set_id).await?;
+    let admin_id = data.config.get_admin_user_id().0 as i64;
+    data.services
+        .import
+        .ban_sticker_set(&set_id, Some(admin_id))
+        .await?;
     Ok(HttpResponse::Ok().finish())
 }
 
@@ -163,7 +167,11 @@ async fn unban_set(
     Path(set_id): Path<StickerSetId>,
     data: Data<AppState>,
 ) -> actix_web::Result<impl Responder> {
-    data.services.import.unban_sticker_set(&set_id).await?;
+    let admin_id = data.config.get_admin_user_id().0 as i64;
+    data.services
+        .import
+        .unban_sticker_set(&set_id, Some(admin_id))
+        .await?;
     Ok(HttpResponse::Ok().finish())
 }
 
@@ -211,9 +219,15 @@ async fn ban_sticker(
     body: Json<StickerBanBody>,
     data: Data<AppState>,
 ) -> actix_web::Result<impl Responder> {
+    let admin_id = data.config.get_admin_user_id().0 as i64;
     data.services
         .import
-        .ban_sticker(&sticker_id, body.clip_max_match_distance, BanReason::Manual)
+        .ban_sticker(
+            &sticker_id,
+            body.clip_max_match_distance,
+            BanReason::Manual,
+            Some(admin_id),
+        )
         .await?;
     Ok(HttpResponse::Ok().finish())
 }
@@ -224,7 +238,11 @@ async fn unban_sticker(
     Path(sticker_id): Path<StickerId>,
     data: Data<AppState>,
 ) -> actix_web::Result<impl Responder> {
-    data.services.import.unban_sticker(&sticker_id).await?;
+    let admin_id = data.config.get_admin_user_id().0 as i64;
+    data.services
+        .import
+        .unban_sticker(&sticker_id, Some(admin_id))
+        .await?;
     Ok(HttpResponse::Ok().finish())
 }
 
@@ -259,6 +277,10 @@ async fn approve_set(
     data.database
         .set_sticker_set_pending(&set_id, false)
         .await?;
+    let admin_id = data.config.get_admin_user_id().0 as i64;
+    data.database
+        .add_moderation_log_entry(Some(admin_id), ModerationAction::ApproveSet, &set_id, None)
+        .await?;
     Ok(HttpResponse::Ok().finish())
 }
 
@@ -405,3 +427,281 @@ async fn get_banned_sticker_thumbnail(
         .insert_header(header::ContentType::png())
         .body(buf))
 }
+
+#[derive(Serialize)]
+struct TagSuggestionRulesPub {
+    rules: String,
+}
+
+#[derive(Deserialize)]
+struct TagSuggestionRulesBody {
+    rules: String,
+}
+
+#[derive(Deserialize)]
+struct TagSuggestionRulesDryRunBody {
+    rules: String,
+    #[serde(rename = "stickerId")]
+    sticker_id: StickerId,
+}
+
+#[derive(Serialize)]
+struct ScoredTagPub {
+    tag: String,
+    score: f64,
+}
+
+#[derive(Serialize)]
+struct TagSuggestionRulesDryRunPub {
+    suggestions: Vec<ScoredTagPub>,
+    suppressed: Vec<String>,
+}
+
+#[actix_web::get("/api/tag-suggestion-rules")]
+#[tracing::instrument(skip(data))]
+async fn get_tag_suggestion_rules(data: Data<AppState>) -> actix_web::Result<impl Responder> {
+    let rules = data.services.tag_suggestion_rules.get_source().await?;
+    Ok(actix_web::web::Json(TagSuggestionRulesPub { rules }))
+}
+
+/// invalid rules are rejected with a 400 containing the line and column of the error
+#[actix_web::post("/api/tag-suggestion-rules")]
+#[tracing::instrument(skip(data, body))]
+async fn update_tag_suggestion_rules(
+    body: Json<TagSuggestionRulesBody>,
+    data: Data<AppState>,
+) -> actix_web::Result<impl Responder> {
+    let admin_id = data.config.get_admin_user_id().0 as i64;
+    let revision_id = data
+        .services
+        .tag_suggestion_rules
+        .update(body.into_inner().rules, Some(admin_id))
+        .await?;
+    data.database
+        .add_moderation_log_entry(
+            Some(admin_id),
+            ModerationAction::UpdateTagSuggestionRules,
+            &revision_id.to_string(),
+            None,
+        )
+        .await?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[actix_web::post("/api/tag-suggestion-rules/dry-run")]
+#[tracing::instrument(skip(data, body))]
+async fn dry_run_tag_suggestion_rules(
+    body: Json<TagSuggestionRulesDryRunBody>,
+    data: Data<AppState>,
+) -> actix_web::Result<impl Responder> {
+    let result = data
+        .services
+        .tag_suggestion_rules
+        .dry_run(&body.rules, &body.sticker_id)
+        .await?;
+    Ok(actix_web::web::Json(TagSuggestionRulesDryRunPub {
+        suggestions: result
+            .suggestions
+            .into_iter()
+            .map(|suggestion| ScoredTagPub {
+                tag: suggestion.tag,
+                score: suggestion.score,
+            })
+            .collect_vec(),
+        suppressed: result.suppressed,
+    }))
+}
+
+#[derive(Serialize)]
+struct UserPub {
+    id: i64,
+    #[serde(rename = "trustLevel")]
+    trust_level: &'static str,
+    #[serde(rename = "trustLevelUpdatedByUserId")]
+    trust_level_updated_by_user_id: Option<i64>,
+    /// `true` grants, `false` revokes
+    grants: HashMap<&'static str, bool>,
+    /// effective capabilities from the trust level and the grants
+    capabilities: Vec<&'static str>,
+    #[serde(rename = "createdAt")]
+    created_at: chrono::NaiveDateTime,
+}
+
+impl From<User> for UserPub {
+    fn from(value: User) -> Self {
+        Self {
+            id: value.id,
+            trust_level: value.trust_level.name(),
+            trust_level_updated_by_user_id: value.trust_level_updated_by_user_id,
+            grants: value
+                .capability_grants
+                .clone()
+                .map(|grants| {
+                    grants
+                        .into_inner()
+                        .into_iter()
+                        .map(|(capability, granted)| (capability.name(), granted))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            capabilities: Capability::ALL
+                .into_iter()
+                .filter(|capability| value.has_capability(*capability))
+                .map(Capability::name)
+                .collect_vec(),
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct UsersQuery {
+    #[serde(rename = "trustLevel")]
+    trust_level: String,
+    offset: i64,
+}
+
+#[derive(Deserialize)]
+struct TrustLevelBody {
+    #[serde(rename = "trustLevel")]
+    trust_level: String,
+}
+
+/// `null` resets the capability to the default of the trust level
+#[derive(Deserialize)]
+struct CapabilityGrantBody {
+    granted: Option<bool>,
+}
+
+fn parse_trust_level(trust_level: &str) -> Result<TrustLevel, BotError> {
+    TrustLevel::from_name(trust_level).ok_or_else(|| {
+        UserError::ValidationError(format!("unknown trust level {trust_level}")).into()
+    })
+}
+
+#[actix_web::get("/api/users")]
+#[tracing::instrument(skip(data, query))]
+async fn get_users(
+    data: Data<AppState>,
+    Query(query): Query<UsersQuery>,
+) -> actix_web::Result<impl Responder> {
+    let trust_level = parse_trust_level(&query.trust_level)?;
+    let users = data
+        .database
+        .get_users_by_trust_level(trust_level, 100, query.offset)
+        .await?;
+    Ok(actix_web::web::Json(
+        users.into_iter().map(UserPub::from).collect_vec(),
+    ))
+}
+
+#[actix_web::get("/api/users/{user_id}")]
+#[tracing::instrument(skip(data))]
+async fn get_user(
+    Path(user_id): Path<i64>,
+    data: Data<AppState>,
+) -> actix_web::Result<impl Responder> {
+    let user = data.database.get_user_by_id(user_id).await?;
+    let user = user.ok_or_else(|| ErrorNotFound("user does not exist"))?;
+    Ok(actix_web::web::Json(UserPub::from(user)))
+}
+
+#[actix_web::post("/api/users/{user_id}/trust-level")]
+#[tracing::instrument(skip(data, body))]
+async fn update_user_trust_level(
+    Path(user_id): Path<i64>,
+    body: Json<TrustLevelBody>,
+    data: Data<AppState>,
+) -> actix_web::Result<impl Responder> {
+    let trust_level = parse_trust_level(&body.trust_level)?;
+    let admin_id = data.config.get_admin_user_id().0 as i64;
+    let user = data
+        .services
+        .trust
+        .set_trust_level(user_id, trust_level, admin_id)
+        .await?;
+    update_moderator_command_menu(&data.bot, &data.config, &user).await?;
+    Ok(actix_web::web::Json(UserPub::from(user)))
+}
+
+#[actix_web::post("/api/users/{user_id}/capabilities/{capability}")]
+#[tracing::instrument(skip(data, body))]
+async fn update_user_capability_grant(
+    Path((user_id, capability)): Path<(i64, String)>,
+    body: Json<CapabilityGrantBody>,
+    data: Data<AppState>,
+) -> actix_web::Result<impl Responder> {
+    let capability = Capability::from_name(&capability).ok_or_else(|| {
+        BotError::from(UserError::ValidationError(format!("unknown capability {capability}")))
+    })?;
+    let admin_id = data.config.get_admin_user_id().0 as i64;
+    let user = data
+        .services
+        .trust
+        .set_capability_grant(user_id, capability, body.granted, admin_id)
+        .await?;
+    Ok(actix_web::web::Json(UserPub::from(user)))
+}
+
+#[derive(Serialize)]
+struct ModerationLogEntryPub {
+    id: i64,
+    #[serde(rename = "createdAt")]
+    created_at: chrono::NaiveDateTime,
+    #[serde(rename = "actorUserId")]
+    actor_user_id: Option<i64>,
+    action: &'static str,
+    target: String,
+    details: Option<String>,
+}
+
+impl From<ModerationLogEntry> for ModerationLogEntryPub {
+    fn from(value: ModerationLogEntry) -> Self {
+        Self {
+            id: value.id,
+            created_at: value.created_at,
+            actor_user_id: value.actor_user_id,
+            action: value.action.name(),
+            target: value.target,
+            details: value.details,
+        }
+    }
+}
+
+/// empty filters match everything
+#[derive(Deserialize)]
+struct ModerationLogQuery {
+    #[serde(rename = "actorUserId")]
+    actor_user_id: Option<i64>,
+    action: Option<String>,
+    target: Option<String>,
+    offset: i64,
+}
+
+#[actix_web::get("/api/moderation-log")]
+#[tracing::instrument(skip(data, query))]
+async fn get_moderation_log(
+    data: Data<AppState>,
+    Query(query): Query<ModerationLogQuery>,
+) -> actix_web::Result<impl Responder> {
+    let action = query
+        .action
+        .filter(|action| !action.is_empty())
+        .map(|action| {
+            ModerationAction::from_name(&action).ok_or_else(|| {
+                BotError::from(UserError::ValidationError(format!("unknown action {action}")))
+            })
+        })
+        .transpose()?;
+    let target = query.target.filter(|target| !target.is_empty());
+    let entries = data
+        .database
+        .get_moderation_log(query.actor_user_id, action, target, 100, query.offset)
+        .await?;
+    Ok(actix_web::web::Json(
+        entries
+            .into_iter()
+            .map(ModerationLogEntryPub::from)
+            .collect_vec(),
+    ))
+}
@@ -108,6 +108,14 @@ pub fn setup_admin_server(
                 .service(service::get_banned_sticker_thumbnail)
                 .service(service::get_tags)
                 .service(service::recommend_stickers_for_ban)
+                .service(service::get_tag_suggestion_rules)
+                .service(service::update_tag_suggestion_rules)
+                .service(service::dry_run_tag_suggestion_rules)
+                .service(service::get_users)
+                .service(service::get_user)
+                .service(service::update_user_trust_level)
+                .service(service::update_user_capability_grant)
+                .service(service::get_moderation_log)
                 .wrap(middleware::Compress::default())
         })
         .bind(addr)
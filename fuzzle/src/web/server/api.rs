@@ -0,0 +1,342 @@
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use actix_web::{
+    FromRequest, HttpRequest, HttpResponse, Responder,
+    error::{ErrorNotFound, ErrorUnauthorized},
+    http::header,
+    web::{self, Data, Json},
+};
+use actix_web_lab::extract::{Path, Query};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bot::{BotError, InternalError, UserError},
+    database::{ApiKey, Sticker, User},
+    inline::{InlineQueryData, SimilarityAspect, query_stickers},
+    util::{StickerId, StickerSetId},
+    web::shared::AppState,
+};
+
+const MAX_PAGE_SIZE: usize = 100;
+const DEFAULT_PAGE_SIZE: usize = 50;
+const OPENAPI_DESCRIPTION: &str = include_str!("openapi.json");
+
+/// owner of the api key that was sent with the request
+///
+/// Every extraction counts towards the rate limit of the key.
+#[derive(Debug)]
+pub struct ApiUser {
+    pub key: ApiKey,
+    pub user: Arc<User>,
+}
+
+impl ApiUser {
+    /// applies the rating profile and the blacklist of the key's owner
+    async fn filter_stickers(
+        &self,
+        data: &AppState,
+        stickers: Vec<Sticker>,
+    ) -> Result<Vec<Sticker>, InternalError> {
+        let settings = self.user.settings.clone().unwrap_or_default();
+        data.services
+            .sticker
+            .filter_stickers_by_settings(stickers, &settings, &self.user.blacklist)
+            .await
+    }
+}
+
+impl FromRequest for ApiUser {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut actix_web::dev::Payload) -> Self::Future {
+        let data = req
+            .app_data::<web::Data<AppState>>()
+            .expect("data to be present")
+            .clone();
+        let secret = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|secret| secret.trim().to_string());
+        Box::pin(async move {
+            let Some(secret) = secret else {
+                return Err(ErrorUnauthorized("missing api key"));
+            };
+            let Some((key, user)) = data.services.api_key.authenticate(&secret).await? else {
+                return Err(ErrorUnauthorized("invalid api key"));
+            };
+            if let Err(retry_after) = data.services.api_key.check_rate_limit(&key) {
+                let response = HttpResponse::TooManyRequests()
+                    .insert_header((header::RETRY_AFTER, retry_after.to_string()))
+                    .body("rate limit exceeded");
+                return Err(actix_web::error::InternalError::from_response(
+                    "rate limit exceeded",
+                    response,
+                )
+                .into());
+            }
+            Ok(Self {
+                key,
+                user: Arc::new(user),
+            })
+        })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StickerV1 {
+    id: StickerId,
+    set_id: StickerSetId,
+    emoji: Option<String>,
+    thumbnail_url: String,
+}
+
+impl StickerV1 {
+    fn new(sticker: Sticker, domain_name: &str) -> Self {
+        Self {
+            thumbnail_url: format!(
+                "https://{domain_name}/files/stickers/{}/thumbnail.png",
+                sticker.id
+            ),
+            id: sticker.id,
+            set_id: sticker.sticker_set_id,
+            emoji: sticker.emoji,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StickerDetailsV1 {
+    #[serde(flatten)]
+    sticker: StickerV1,
+    tags: Vec<String>,
+    created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StickerSetV1 {
+    id: StickerSetId,
+    title: Option<String>,
+    created_at: chrono::NaiveDateTime,
+    stickers: Vec<StickerV1>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TagV1 {
+    id: String,
+    category: Option<&'static str>,
+    implications: Vec<String>,
+}
+
+fn page_size(limit: Option<usize>) -> usize {
+    limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE)
+}
+
+#[derive(Deserialize, Debug)]
+struct SearchQuery {
+    /// same syntax as inline queries: tags and emojis separated by spaces, `-tag` excludes a tag
+    q: String,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+enum SimilarityAspectV1 {
+    Color,
+    Embedding,
+}
+
+#[derive(Deserialize, Debug)]
+struct SimilarQuery {
+    aspect: Option<SimilarityAspectV1>,
+    limit: Option<usize>,
+}
+
+#[actix_web::get("/api/v1/openapi.json")]
+async fn openapi_description() -> impl Responder {
+    HttpResponse::Ok()
+        .content_type(header::ContentType::json())
+        .body(OPENAPI_DESCRIPTION)
+}
+
+#[actix_web::get("/api/v1/stickers")]
+#[tracing::instrument(skip(data, user))]
+async fn search_stickers(
+    Query(query): Query<SearchQuery>,
+    data: Data<AppState>,
+    user: ApiUser,
+) -> actix_web::Result<impl Responder> {
+    let (tags, emoji) = match InlineQueryData::try_from(query.q.clone()).map_err(BotError::from)? {
+        InlineQueryData::SearchStickers { tags, emoji } => (tags, emoji),
+        _ => {
+            return Err(BotError::from(UserError::ValidationError(
+                "q must only contain tags and emojis".to_string(),
+            ))
+            .into());
+        }
+    };
+    if tags.is_empty() && emoji.is_empty() {
+        return Err(
+            BotError::from(UserError::ValidationError("q must not be empty".to_string())).into(),
+        );
+    }
+    let stickers = query_stickers(
+        tags,
+        data.database.clone(),
+        emoji,
+        user.user.clone(),
+        data.tag_manager.clone(),
+        page_size(query.limit),
+        query.offset.unwrap_or_default(),
+        0,
+    )
+    .await?;
+    Ok(Json(
+        stickers
+            .into_iter()
+            .map(|sticker| StickerV1::new(sticker, &data.config.domain_name))
+            .collect_vec(),
+    ))
+}
+
+#[actix_web::get("/api/v1/stickers/{stickerId}")]
+#[tracing::instrument(skip(data, user))]
+async fn get_sticker(
+    Path(sticker_id): Path<StickerId>,
+    data: Data<AppState>,
+    user: ApiUser,
+) -> actix_web::Result<impl Responder> {
+    let sticker = data
+        .database
+        .get_sticker_by_id(&sticker_id)
+        .await?
+        .ok_or_else(|| ErrorNotFound("sticker not found"))?;
+    let sticker = user
+        .filter_stickers(&data, vec![sticker])
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| ErrorNotFound("sticker not found"))?;
+    let tags = data
+        .database
+        .get_sticker_tags_by_file_id(&sticker.sticker_file_id)
+        .await?;
+    Ok(Json(StickerDetailsV1 {
+        created_at: sticker.created_at,
+        sticker: StickerV1::new(sticker, &data.config.domain_name),
+        tags,
+    }))
+}
+
+#[actix_web::get("/api/v1/stickers/{stickerId}/similar")]
+#[tracing::instrument(skip(data, user))]
+async fn get_similar_stickers(
+    Path(sticker_id): Path<StickerId>,
+    Query(query): Query<SimilarQuery>,
+    data: Data<AppState>,
+    user: ApiUser,
+) -> actix_web::Result<impl Responder> {
+    let aspect = match query.aspect {
+        Some(SimilarityAspectV1::Color) => SimilarityAspect::Color,
+        Some(SimilarityAspectV1::Embedding) | None => SimilarityAspect::Embedding,
+    };
+    let (matches, _) = data
+        .services
+        .similarity
+        .find_similar_stickers(sticker_id, aspect, page_size(query.limit) as u64, 0)
+        .await?;
+    let stickers = data
+        .services
+        .similarity
+        .matches_to_stickers(matches)
+        .await?
+        .into_iter()
+        .map(|(sticker, _)| sticker)
+        .collect_vec();
+    let stickers = user.filter_stickers(&data, stickers).await?;
+    Ok(Json(
+        stickers
+            .into_iter()
+            .map(|sticker| StickerV1::new(sticker, &data.config.domain_name))
+            .collect_vec(),
+    ))
+}
+
+#[actix_web::get("/api/v1/sets/{setId}")]
+#[tracing::instrument(skip(data, user))]
+async fn get_sticker_set(
+    Path(set_id): Path<StickerSetId>,
+    data: Data<AppState>,
+    user: ApiUser,
+) -> actix_web::Result<impl Responder> {
+    let set = data
+        .database
+        .get_public_sticker_set_by_id(&set_id)
+        .await?
+        .ok_or_else(|| ErrorNotFound("set not found"))?;
+    let stickers = data.database.get_all_stickers_in_set(&set.id).await?;
+    let stickers = user.filter_stickers(&data, stickers).await?;
+    Ok(Json(StickerSetV1 {
+        id: set.id,
+        title: set.title,
+        created_at: set.created_at,
+        stickers: stickers
+            .into_iter()
+            .map(|sticker| StickerV1::new(sticker, &data.config.domain_name))
+            .collect_vec(),
+    }))
+}
+
+#[actix_web::get("/api/v1/tags/{tagId}")]
+#[tracing::instrument(skip(data, _user))]
+async fn get_tag(
+    Path(tag_id): Path<String>,
+    data: Data<AppState>,
+    _user: ApiUser,
+) -> actix_web::Result<impl Responder> {
+    let Some(category) = data.tag_manager.get_category(&tag_id) else {
+        return Err(ErrorNotFound("tag not found"));
+    };
+    Ok(Json(TagV1 {
+        category: Some(category.to_human_name()),
+        implications: data.tag_manager.get_implications(&tag_id).unwrap_or_default(),
+        id: tag_id,
+    }))
+}
+
+#[derive(Deserialize, Debug)]
+struct TagSearchQuery {
+    q: String,
+}
+
+/// tags that match the beginning of the query, for autocompletion
+#[actix_web::get("/api/v1/tags")]
+#[tracing::instrument(skip(data, _user))]
+async fn search_tags(
+    Query(query): Query<TagSearchQuery>,
+    data: Data<AppState>,
+    _user: ApiUser,
+) -> actix_web::Result<impl Responder> {
+    let query = query.q.split_whitespace().map(str::to_string).collect_vec();
+    let tags = data
+        .tag_manager
+        .find_tags(&query)
+        .await
+        .into_iter()
+        .take(MAX_PAGE_SIZE)
+        .map(|tag| TagV1 {
+            category: data.tag_manager.get_category(&tag).map(|category| category.to_human_name()),
+            implications: data.tag_manager.get_implications(&tag).unwrap_or_default(),
+            id: tag,
+        })
+        .collect_vec();
+    Ok(Json(tags))
+}
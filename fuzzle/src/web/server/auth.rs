@@ -17,6 +17,8 @@ use std::{
 use teloxide::types::UserId;
 
 pub const AUTH_COOKIE_NAME: &str = "fuzzlebot_login_data";
+/// init data is only accepted shortly after telegram created it, so that leaked links expire
+const MAX_WEB_APP_INIT_DATA_AGE: TimeDelta = TimeDelta::days(1);
 
 // adapted from https://docs.rs/telegram-login/latest/src/telegram_login/lib.rs.html
 #[derive(Serialize, Deserialize, Debug)]
@@ -147,6 +149,8 @@ impl FromRequest for AuthenticatedUser {
 #[derive(Debug, Deserialize, Clone)]
 pub struct WebAppInitData {
     hash: String,
+    /// page of the mini app to continue with; added by the login script and not signed
+    pub redirect: Option<String>,
     #[serde(flatten)]
     other: HashMap<String, String>,
 }
@@ -154,6 +158,16 @@ pub struct WebAppInitData {
 impl WebAppInitData {
     #[tracing::instrument(skip(self, bot_token))]
     pub fn check(&self, bot_token: String) -> bool {
+        let auth_date = self
+            .other
+            .get("auth_date")
+            .and_then(|auth_date| auth_date.parse().ok())
+            .and_then(|auth_date| chrono::DateTime::from_timestamp(auth_date, 0));
+        let is_recent = auth_date
+            .is_some_and(|auth_date| chrono::Utc::now() - auth_date < MAX_WEB_APP_INIT_DATA_AGE);
+        if !is_recent {
+            return false;
+        }
         match hex::decode(&self.hash) {
             Ok(hash) => {
                 let data_check_string = self.data_check_string();
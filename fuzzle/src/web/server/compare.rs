@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+
+use actix_web::{
+    error::ErrorNotFound,
+    get, post,
+    web::{Data, Form},
+    Responder, Result as ActixResult,
+};
+use actix_web_lab::extract::Path;
+use itertools::Itertools;
+use maud::{html, Markup};
+use serde::Deserialize;
+
+use crate::{
+    services::{NearDuplicate, SetComparison},
+    util::{StickerId, StickerSetId},
+    web::shared::AppState,
+};
+
+use super::{
+    editor::{is_moderator, moderator_user},
+    page, service::safe_set_thumbnail, sticker_list_item, tag_list_item, visitor_hidden_tags,
+    AuthenticatedUser, OptionalAuthenticatedUser, PageMeta,
+};
+
+/// both stickers side by side, with buttons to decide on the merge for moderators
+fn near_duplicate_pair(pair: &NearDuplicate, moderator: bool) -> Markup {
+    html! {
+        div class="compare-pair" {
+            (sticker_list_item(&pair.sticker_a.id))
+            (sticker_list_item(&pair.sticker_b.id))
+            p {
+                "embedding " ((pair.similarity.clip * 100.0).round() as i64) "% · colors "
+                ((pair.similarity.histogram * 100.0).round() as i64) "%"
+            }
+            @if moderator {
+                @for (merge, label) in [(true, "merge"), (false, "keep separate")] {
+                    button
+                        hx-post="/compare/merge"
+                        hx-vals=(serde_json::json!({
+                            "sticker_id_a": pair.sticker_a.id,
+                            "sticker_id_b": pair.sticker_b.id,
+                            "merge": merge,
+                        }).to_string())
+                        hx-target="closest .compare-pair"
+                        hx-swap="outerHTML" {
+                        (label)
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn tag_differences(data: &AppState, comparison: &SetComparison) -> Markup {
+    html! {
+        table class="compare-tags" {
+            tr {
+                th { "Tag" }
+                th { (comparison.set_a.title_or_id()) }
+                th { (comparison.set_b.title_or_id()) }
+            }
+            @for difference in &comparison.tag_differences {
+                tr {
+                    td { (tag_list_item(&data.tag_manager, &difference.tag, None)) }
+                    td { (difference.count_a) }
+                    td { (difference.count_b) }
+                }
+            }
+        }
+    }
+}
+
+#[get("/compare/{setIdA}/{setIdB}")]
+#[tracing::instrument(skip(data, auth_data))]
+async fn set_comparison_page(
+    Path((set_id_a, set_id_b)): Path<(StickerSetId, StickerSetId)>,
+    data: Data<AppState>,
+    OptionalAuthenticatedUser { auth_data }: OptionalAuthenticatedUser,
+) -> ActixResult<impl Responder> {
+    let hidden_tags = visitor_hidden_tags(&data, auth_data.as_deref()).await?;
+    // near duplicates are expensive to find, so they are only loaded on request
+    let comparison = data
+        .services
+        .set_comparison
+        .compare(&set_id_a, &set_id_b, false)
+        .await?
+        .ok_or_else(|| ErrorNotFound("sticker set not found"))?;
+
+    // the counts include all stickers, but only the visible ones are shown
+    let sticker_service = &data.services.sticker;
+    let shared = sticker_service
+        .filter_stickers_by_hidden_tags(comparison.shared.clone(), hidden_tags.clone())
+        .await?;
+    let only_a = sticker_service
+        .filter_stickers_by_hidden_tags(comparison.only_a.clone(), hidden_tags.clone())
+        .await?;
+    let only_b = sticker_service
+        .filter_stickers_by_hidden_tags(comparison.only_b.clone(), hidden_tags)
+        .await?;
+
+    let title_a = comparison.set_a.title_or_id();
+    let title_b = comparison.set_b.title_or_id();
+    let meta = PageMeta::new(
+        format!("{title_a} vs {title_b}"),
+        format!(
+            "The sticker sets {title_a} and {title_b} share {} stickers",
+            comparison.shared.len()
+        ),
+        format!("/compare/{set_id_a}/{set_id_b}"),
+    )
+    .image(safe_set_thumbnail(&set_id_a));
+
+    let content = html! {
+        #content {
+            h1 {
+                a href={ "/set/" (set_id_a) } { (title_a) }
+                " vs "
+                a href={ "/set/" (set_id_b) } { (title_b) }
+            }
+            p {
+                (comparison.sticker_count_a) " and " (comparison.sticker_count_b) " stickers · "
+                (comparison.shared.len()) " shared (" (comparison.overlap_percentage()) "% of the smaller set)"
+            }
+
+            h2 { "Shared stickers" }
+            div class="grid" {
+                @for sticker in &shared {
+                    (sticker_list_item(&sticker.id))
+                }
+            }
+
+            h2 { "Near duplicates" }
+            div id="near-duplicates" {
+                p { "Different files that look almost the same. They are also listed below." }
+                @if auth_data.is_some() {
+                    button hx-get={ "/fragment/compare/" (set_id_a) "/" (set_id_b) "/near-duplicates" }
+                        hx-target="#near-duplicates" hx-swap="innerHTML" {
+                        "find near duplicates"
+                    }
+                } @else {
+                    p { "Log in to look for near duplicates." }
+                }
+            }
+
+            h2 { "Only in " (title_a) }
+            div class="grid" {
+                @for sticker in &only_a {
+                    (sticker_list_item(&sticker.id))
+                }
+            }
+
+            h2 { "Only in " (title_b) }
+            div class="grid" {
+                @for sticker in &only_b {
+                    (sticker_list_item(&sticker.id))
+                }
+            }
+
+            @if !comparison.tag_differences.is_empty() {
+                h2 { "Tag differences" }
+                (tag_differences(&data, &comparison))
+            }
+        }
+    };
+
+    Ok(page(&data, &meta, content))
+}
+
+/// only for logged in users since it queries the vector database for every sticker
+#[get("/fragment/compare/{setIdA}/{setIdB}/near-duplicates")]
+#[tracing::instrument(skip(data, auth_data))]
+async fn near_duplicates_fragment(
+    Path((set_id_a, set_id_b)): Path<(StickerSetId, StickerSetId)>,
+    data: Data<AppState>,
+    AuthenticatedUser { auth_data }: AuthenticatedUser,
+) -> ActixResult<Markup> {
+    let hidden_tags = visitor_hidden_tags(&data, Some(&*auth_data)).await?;
+    let comparison = data
+        .services
+        .set_comparison
+        .compare(&set_id_a, &set_id_b, true)
+        .await?
+        .ok_or_else(|| ErrorNotFound("sticker set not found"))?;
+    let moderator = is_moderator(&data, Some(&*auth_data)).await;
+
+    let visible_pair_stickers: HashSet<_> = data
+        .services
+        .sticker
+        .filter_stickers_by_hidden_tags(
+            comparison
+                .near_duplicates
+                .iter()
+                .flat_map(|pair| [pair.sticker_a.clone(), pair.sticker_b.clone()])
+                .collect_vec(),
+            hidden_tags,
+        )
+        .await?
+        .into_iter()
+        .map(|sticker| sticker.id)
+        .collect();
+    let near_duplicates = comparison
+        .near_duplicates
+        .iter()
+        .filter(|pair| {
+            visible_pair_stickers.contains(&pair.sticker_a.id)
+                && visible_pair_stickers.contains(&pair.sticker_b.id)
+        })
+        .collect_vec();
+
+    Ok(html! {
+        @if near_duplicates.is_empty() {
+            p { "No near duplicates found." }
+        } @else {
+            p { "Different files that look almost the same." }
+            div class="compare-pairs" {
+                @for pair in &near_duplicates {
+                    (near_duplicate_pair(pair, moderator))
+                }
+            }
+        }
+    })
+}
+
+#[derive(Deserialize)]
+struct MergeForm {
+    sticker_id_a: StickerId,
+    sticker_id_b: StickerId,
+    merge: bool,
+}
+
+#[post("/compare/merge")]
+#[tracing::instrument(skip(data, form))]
+async fn merge_near_duplicates(
+    form: Form<MergeForm>,
+    data: Data<AppState>,
+    AuthenticatedUser { auth_data }: AuthenticatedUser,
+) -> ActixResult<Markup> {
+    let user = moderator_user(&data, &auth_data).await?;
+    data.services
+        .set_comparison
+        .decide_merge(user.id, &form.sticker_id_a, &form.sticker_id_b, form.merge)
+        .await?;
+    Ok(html! {
+        div class="compare-pair" {
+            p class="notification" {
+                @if form.merge { "Merged" } @else { "Kept separate" }
+            }
+        }
+    })
+}
@@ -0,0 +1,252 @@
+use actix_web::{
+    error::{ErrorForbidden, ErrorNotFound},
+    get, post,
+    web::{Data, Form},
+    Responder, Result as ActixResult,
+};
+use actix_web_lab::extract::Path;
+use itertools::Itertools;
+use maud::{html, Markup};
+use serde::Deserialize;
+
+use crate::{services::Creator, web::shared::AppState};
+
+use super::{
+    editor::{is_moderator, moderator_user},
+    page, service::safe_set_thumbnail, sticker_set_list_item, tag_list_item, AuthData,
+    AuthenticatedUser, OptionalAuthenticatedUser, PageMeta,
+};
+
+/// bio and links, with the claim button or the form to edit them for the creator themselves and
+/// a reset button for moderators
+fn profile_editor(
+    creator: &Creator,
+    is_creator: bool,
+    is_moderator: bool,
+    notification: Option<String>,
+) -> Markup {
+    html! {
+        div id="creator-profile" {
+            @match &creator.profile {
+                Some(profile) => {
+                    p class="creator-claimed" { "✔️ This page is maintained by the creator" }
+                    @if !profile.bio.is_empty() {
+                        p class="creator-bio" { (profile.bio) }
+                    }
+                    @if !profile.links.is_empty() {
+                        ul {
+                            @for link in profile.links.iter() {
+                                li { a href=(link) rel="nofollow ugc noopener" { (link) } }
+                            }
+                        }
+                    }
+                    @if is_creator {
+                        form hx-post={ "/creator/" (creator.user_id) } hx-target="#creator-profile" hx-swap="outerHTML" {
+                            textarea name="bio" rows="4" placeholder="Tell people about yourself and your stickers ..." {
+                                (profile.bio)
+                            }
+                            textarea name="links" rows="3" placeholder="Links (one per line) ..." {
+                                (profile.links.join("\n"))
+                            }
+                            button type="submit" { "save" }
+                        }
+                    }
+                    @if is_moderator && (!profile.bio.is_empty() || !profile.links.is_empty()) {
+                        button hx-post={ "/creator/" (creator.user_id) "/reset" } hx-target="#creator-profile" hx-swap="outerHTML"
+                            hx-confirm="Remove the bio and links of this page?" {
+                            "remove bio and links"
+                        }
+                    }
+                },
+                None => {
+                    @if is_creator {
+                        p { "These are your sticker sets. Claim this page to add a bio and links." }
+                        button hx-post={ "/creator/" (creator.user_id) "/claim" } hx-target="#creator-profile" hx-swap="outerHTML" {
+                            "claim page"
+                        }
+                    }
+                },
+            }
+            @if let Some(notification) = notification {
+                p class="notification" { (notification) }
+            }
+        }
+    }
+}
+
+/// the logged in user is the creator of the page
+fn is_creator(auth_data: Option<&AuthData>, user_id: i64) -> bool {
+    auth_data.is_some_and(|auth_data| auth_data.id as i64 == user_id)
+}
+
+#[get("/creator/{userId}")]
+#[tracing::instrument(skip(data, auth_data))]
+async fn creator_page(
+    Path(user_id): Path<i64>,
+    data: Data<AppState>,
+    OptionalAuthenticatedUser { auth_data }: OptionalAuthenticatedUser,
+) -> ActixResult<impl Responder> {
+    let creator = data
+        .services
+        .creator
+        .creator(user_id)
+        .await?
+        .ok_or_else(|| ErrorNotFound("creator not found"))?;
+    let name = creator.display_name();
+    let mut meta = PageMeta::new(
+        name.clone(),
+        creator
+            .profile
+            .as_ref()
+            .filter(|profile| !profile.bio.is_empty())
+            .map_or_else(
+                || format!("{} furry Telegram sticker sets by {name}", creator.set_count),
+                |profile| profile.bio.clone(),
+            ),
+        format!("/creator/{user_id}"),
+    );
+    if let Some(set) = creator.sets.first() {
+        meta = meta.image(safe_set_thumbnail(&set.id));
+    }
+    let moderator = is_moderator(&data, auth_data.as_deref()).await;
+    // the sets are sorted newest first, so the months are as well
+    let timeline = creator
+        .sets
+        .iter()
+        .chunk_by(|set| set.created_at.format("%Y-%m").to_string())
+        .into_iter()
+        .map(|(month, sets)| (month, sets.collect_vec()))
+        .collect_vec();
+
+    let content = html! {
+        #content {
+            h1 { (name) }
+            p {
+                (creator.set_count) " sticker sets"
+                @if let Some(username) = &creator.username {
+                    " · "
+                    a href={ "https://t.me/" (username) } { "@" (username) }
+                }
+            }
+
+            (profile_editor(&creator, is_creator(auth_data.as_deref(), user_id), moderator, None))
+
+            @if !creator.linked_tags.is_empty() {
+                h2 { "Artist tags" }
+                div class="tag-container" {
+                    @for tag in &creator.linked_tags {
+                        (tag_list_item(&data.tag_manager, &tag.id, None))
+                    }
+                }
+            }
+
+            @if !creator.channels.is_empty() {
+                h2 { "Channels" }
+                ul {
+                    @for (_, channel) in &creator.channels {
+                        li { a href={ "https://t.me/" (channel) } { "@" (channel) } }
+                    }
+                }
+            }
+
+            @if !creator.tag_distribution.is_empty() {
+                h2 { "Most used tags" }
+                div class="tag-container" {
+                    @for tag in &creator.tag_distribution {
+                        (tag_list_item(&data.tag_manager, &tag.name, Some(tag.count.to_string())))
+                    }
+                }
+            }
+
+            h2 { "Timeline" }
+            @for (month, sets) in &timeline {
+                h3 { (month) }
+                div class="set-grid" {
+                    @for set in sets {
+                        (sticker_set_list_item(&set.id))
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(page(&data, &meta, content))
+}
+
+/// only the creator can claim or edit their page
+fn check_creator(data: &AppState, auth_data: &AuthData, user_id: i64) -> ActixResult<()> {
+    if data.config.is_readonly {
+        return Err(ErrorForbidden("editing is disabled at the moment"));
+    }
+    if !is_creator(Some(auth_data), user_id) {
+        return Err(ErrorForbidden("only the creator can change this page"));
+    }
+    Ok(())
+}
+
+async fn render_profile_editor(
+    data: &AppState,
+    user_id: i64,
+    is_creator: bool,
+    notification: &str,
+) -> ActixResult<Markup> {
+    let creator = data
+        .services
+        .creator
+        .creator(user_id)
+        .await?
+        .ok_or_else(|| ErrorNotFound("creator not found"))?;
+    Ok(profile_editor(&creator, is_creator, !is_creator, Some(notification.to_string())))
+}
+
+#[post("/creator/{userId}/claim")]
+#[tracing::instrument(skip(data))]
+async fn claim_creator_page(
+    Path(user_id): Path<i64>,
+    data: Data<AppState>,
+    AuthenticatedUser { auth_data }: AuthenticatedUser,
+) -> ActixResult<Markup> {
+    check_creator(&data, &auth_data, user_id)?;
+    data.services
+        .creator
+        .claim(user_id, auth_data.username.as_deref())
+        .await?;
+    render_profile_editor(&data, user_id, true, "Claimed").await
+}
+
+#[derive(Deserialize)]
+struct ProfileForm {
+    bio: String,
+    links: String,
+}
+
+#[post("/creator/{userId}")]
+#[tracing::instrument(skip(data, form))]
+async fn update_creator_page(
+    Path(user_id): Path<i64>,
+    form: Form<ProfileForm>,
+    data: Data<AppState>,
+    AuthenticatedUser { auth_data }: AuthenticatedUser,
+) -> ActixResult<Markup> {
+    check_creator(&data, &auth_data, user_id)?;
+    data.services
+        .creator
+        .update_profile(user_id, &form.bio, &form.links)
+        .await?;
+    render_profile_editor(&data, user_id, true, "Saved").await
+}
+
+#[post("/creator/{userId}/reset")]
+#[tracing::instrument(skip(data))]
+async fn reset_creator_page(
+    Path(user_id): Path<i64>,
+    data: Data<AppState>,
+    AuthenticatedUser { auth_data }: AuthenticatedUser,
+) -> ActixResult<Markup> {
+    let moderator = moderator_user(&data, &auth_data).await?;
+    data.services
+        .creator
+        .reset_profile(user_id, moderator.id)
+        .await?;
+    render_profile_editor(&data, user_id, false, "Removed the bio and links").await
+}
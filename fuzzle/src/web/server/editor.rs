@@ -0,0 +1,326 @@
+use actix_web::{
+    error::ErrorForbidden,
+    post,
+    web::{Data, Form},
+    Result as ActixResult,
+};
+use actix_web_lab::extract::Path;
+use itertools::Itertools;
+use maud::{html, Markup};
+use serde::Deserialize;
+
+use crate::{
+    database::{Capability, StickerFile, TrustLevel, User},
+    services::TaggedFile,
+    util::{Required, StickerId, StickerSetId},
+    web::shared::AppState,
+};
+
+use super::{AuthData, AuthenticatedUser, tag_list_item};
+
+/// the logged in user, if they exist and have the capability; the admin can do everything
+pub async fn editor_user(
+    data: &AppState,
+    auth_data: &AuthData,
+    capability: Capability,
+) -> ActixResult<User> {
+    if data.config.is_readonly {
+        return Err(ErrorForbidden("editing is disabled at the moment"));
+    }
+    let Some(user) = data.database.get_user_by_id(auth_data.id as i64).await? else {
+        return Err(ErrorForbidden("start the bot on telegram before editing"));
+    };
+    let is_admin = data.config.get_admin_user_id().0 == auth_data.id;
+    if !is_admin && !user.has_capability(capability) {
+        return Err(ErrorForbidden("you are not allowed to do this yet"));
+    }
+    Ok(user)
+}
+
+/// whether the visitor may use the capability; used to decide whether editing controls are shown
+pub async fn can_edit(data: &AppState, auth_data: Option<&AuthData>, capability: Capability) -> bool {
+    match auth_data {
+        Some(auth_data) => editor_user(data, auth_data, capability).await.is_ok(),
+        None => false,
+    }
+}
+
+/// the logged in user, if they are a moderator; the admin is always a moderator
+pub async fn moderator_user(data: &AppState, auth_data: &AuthData) -> ActixResult<User> {
+    if data.config.is_readonly {
+        return Err(ErrorForbidden("editing is disabled at the moment"));
+    }
+    let Some(user) = data.database.get_user_by_id(auth_data.id as i64).await? else {
+        return Err(ErrorForbidden("start the bot on telegram before editing"));
+    };
+    let is_admin = data.config.get_admin_user_id().0 == auth_data.id;
+    if !is_admin && user.trust_level != TrustLevel::Moderator {
+        return Err(ErrorForbidden("only moderators can do this"));
+    }
+    Ok(user)
+}
+
+/// whether moderation controls are shown to the visitor
+pub async fn is_moderator(data: &AppState, auth_data: Option<&AuthData>) -> bool {
+    match auth_data {
+        Some(auth_data) => moderator_user(data, auth_data).await.is_ok(),
+        None => false,
+    }
+}
+
+/// whether the visitor may change the tags of the file; like in the bot, files with locked tags
+/// can only be edited by moderators
+pub async fn can_edit_file_tags(
+    data: &AppState,
+    auth_data: Option<&AuthData>,
+    file: &StickerFile,
+) -> bool {
+    if file.tags_locked_by_user_id.is_some() {
+        is_moderator(data, auth_data).await
+    } else {
+        can_edit(data, auth_data, Capability::TagStickers).await
+    }
+}
+
+async fn check_file_tags_unlocked(
+    data: &AppState,
+    auth_data: &AuthData,
+    file: &StickerFile,
+) -> ActixResult<()> {
+    if file.tags_locked_by_user_id.is_some() && !is_moderator(data, Some(auth_data)).await {
+        return Err(ErrorForbidden("the tags of this sticker are locked"));
+    }
+    Ok(())
+}
+
+/// the tags of a sticker with buttons to remove them and an input to add tags
+pub fn sticker_tag_editor(
+    data: &AppState,
+    sticker_id: &StickerId,
+    tags: &[String],
+    editable: bool,
+    notification: Option<String>,
+) -> Markup {
+    html! {
+        div id="tag-editor" {
+            "Tags: "
+            div class="tag-container" {
+                @for tag in tags {
+                    (tag_list_item(&data.tag_manager, tag, None))
+                    @if editable {
+                        button class="tag-remove"
+                            hx-post={ "/sticker/" (sticker_id) "/untag" }
+                            hx-vals=(serde_json::json!({ "tag": tag }).to_string())
+                            hx-target="#tag-editor"
+                            hx-swap="outerHTML"
+                            title={ "remove " (tag) } {
+                            "×"
+                        }
+                    }
+                }
+            }
+            @if editable {
+                form hx-post={ "/sticker/" (sticker_id) "/tag" } hx-target="#tag-editor" hx-swap="outerHTML" {
+                    (tag_input("tag", "Add tag ..."))
+                    button type="submit" { "add" }
+                }
+            }
+            @if let Some(notification) = notification {
+                p class="notification" { (notification) }
+            }
+        }
+    }
+}
+
+/// tag counts of a set and a form to add or remove tags of all stickers in the set
+pub fn set_tag_editor(
+    data: &AppState,
+    set_id: &StickerSetId,
+    tags: &[(String, i64)],
+    editable: bool,
+    notification: Option<String>,
+) -> Markup {
+    html! {
+        div id="set-tag-editor" {
+            "Tags:"
+            div class="tag-container" {
+                @for tag in tags {
+                    (tag_list_item(&data.tag_manager, &tag.0, Some(format!("{}", tag.1))))
+                }
+            }
+            @if editable {
+                form hx-post={ "/set/" (set_id) "/tags" } hx-target="#set-tag-editor" hx-swap="outerHTML" {
+                    (tag_input("tags", "Tags for all stickers (comma separated) ..."))
+                    button type="submit" name="operation" value="tag" { "tag set" }
+                    button type="submit" name="operation" value="untag" { "untag set" }
+                }
+            }
+            @if let Some(notification) = notification {
+                p class="notification" { (notification) }
+            }
+        }
+    }
+}
+
+/// suggestions are loaded from `/search-tags` into the datalist while typing
+pub fn tag_input(name: &str, placeholder: &str) -> Markup {
+    html! {
+        input type="text" name=(name) placeholder=(placeholder) autocomplete="off" list="tag-suggestions"
+            hx-post="/search-tags"
+            hx-vals="{\"autocomplete\": true}"
+            hx-trigger="input changed delay:300ms"
+            hx-target="#tag-suggestions"
+            hx-swap="innerHTML";
+        datalist id="tag-suggestions" {}
+    }
+}
+
+#[derive(Deserialize)]
+struct StickerTagForm {
+    tag: String,
+}
+
+#[post("/sticker/{stickerId}/tag")]
+#[tracing::instrument(skip(data, form))]
+async fn tag_sticker(
+    Path(sticker_id): Path<StickerId>,
+    form: Form<StickerTagForm>,
+    data: Data<AppState>,
+    AuthenticatedUser { auth_data }: AuthenticatedUser,
+) -> ActixResult<Markup> {
+    let user = editor_user(&data, &auth_data, Capability::TagStickers).await?;
+    let file = data
+        .database
+        .get_sticker_file_by_sticker_id(&sticker_id)
+        .await?
+        .required()?;
+    check_file_tags_unlocked(&data, &auth_data, &file).await?;
+    let tag = data.tag_manager.closest_matching_tag(form.tag.trim()).await;
+    let tagged = match &tag {
+        Some(tag) => {
+            data.services
+                .tagging
+                .tag_file(&file.id, tag, None, user.id, &data.tag_manager, &data.tfidf_service)
+                .await?
+        }
+        None => None,
+    };
+    let notification = match (tag, tagged) {
+        (Some(tag), Some(TaggedFile { implications, conflicts })) => {
+            let mut notification = if implications.is_empty() {
+                format!("Added {tag}")
+            } else {
+                format!("Added {tag} (implies {})", implications.join(", "))
+            };
+            if !conflicts.is_empty() {
+                notification = format!("{notification} ⚠️ Conflicting tags: {}", conflicts.iter().join(", "));
+            }
+            notification
+        }
+        _ => format!("Unknown tag {}", form.tag.trim()),
+    };
+    let tags = data.database.get_sticker_tags_by_file_id(&file.id).await?;
+    Ok(sticker_tag_editor(&data, &sticker_id, &tags, true, Some(notification)))
+}
+
+#[post("/sticker/{stickerId}/untag")]
+#[tracing::instrument(skip(data, form))]
+async fn untag_sticker(
+    Path(sticker_id): Path<StickerId>,
+    form: Form<StickerTagForm>,
+    data: Data<AppState>,
+    AuthenticatedUser { auth_data }: AuthenticatedUser,
+) -> ActixResult<Markup> {
+    let user = editor_user(&data, &auth_data, Capability::TagStickers).await?;
+    let file = data
+        .database
+        .get_sticker_file_by_sticker_id(&sticker_id)
+        .await?
+        .required()?;
+    check_file_tags_unlocked(&data, &auth_data, &file).await?;
+    let previous_tags = data.database.get_sticker_tags_by_file_id(&file.id).await?;
+    data.services
+        .tagging
+        .untag_file(&file.id, form.tag.trim(), user.id, &data.tag_manager)
+        .await?;
+    let tags = data.database.get_sticker_tags_by_file_id(&file.id).await?;
+    // the service also returns tags that the sticker did not have
+    let removed = previous_tags
+        .iter()
+        .filter(|tag| !tags.contains(tag))
+        .join(", ");
+    let notification = if removed.is_empty() {
+        format!("The sticker is not tagged {}", form.tag.trim())
+    } else {
+        format!("Removed {removed}")
+    };
+    Ok(sticker_tag_editor(&data, &sticker_id, &tags, true, Some(notification)))
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum SetTagOperation {
+    Tag,
+    Untag,
+}
+
+#[derive(Deserialize)]
+struct SetTagForm {
+    tags: String,
+    operation: SetTagOperation,
+}
+
+#[post("/set/{setId}/tags")]
+#[tracing::instrument(skip(data, form))]
+async fn tag_sticker_set(
+    Path(set_id): Path<StickerSetId>,
+    form: Form<SetTagForm>,
+    data: Data<AppState>,
+    AuthenticatedUser { auth_data }: AuthenticatedUser,
+) -> ActixResult<Markup> {
+    let user = editor_user(&data, &auth_data, Capability::TagSets).await?;
+    data.database
+        .get_sticker_set_by_id(&set_id)
+        .await?
+        .required()?;
+    let input = form
+        .tags
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect_vec();
+    let matched = data.tag_manager.closest_matching_tags(&input).await;
+    let unknown = matched
+        .iter()
+        .filter(|(_, tag)| tag.is_none())
+        .map(|(input, _)| input.clone())
+        .collect_vec();
+    let tags = matched.into_iter().filter_map(|(_, tag)| tag).collect_vec();
+    let mut notification = match form.operation {
+        SetTagOperation::Tag => match data
+            .services
+            .tagging
+            .tag_set(&set_id, &tags, user.id, &data.tag_manager, &data.tfidf_service)
+            .await?
+        {
+            Some((tags, taggings_changed)) => {
+                format!("Added {} to {taggings_changed} stickers", tags.join(", "))
+            }
+            None => "No tags changed".to_string(),
+        },
+        SetTagOperation::Untag => {
+            let taggings_changed = data
+                .services
+                .tagging
+                .untag_set(&set_id, &tags, user.id)
+                .await?;
+            format!("Removed {} from {taggings_changed} stickers", tags.join(", "))
+        }
+    };
+    if !unknown.is_empty() {
+        notification = format!("{notification} (unknown tags: {})", unknown.join(", "));
+    }
+    let tag_counts = data.database.get_all_sticker_set_tag_counts(&set_id).await?;
+    Ok(set_tag_editor(&data, &set_id, &tag_counts, true, Some(notification)))
+}
@@ -0,0 +1,389 @@
+use actix_web::{
+    error::ErrorNotFound,
+    get,
+    http::header,
+    web::Data,
+    HttpResponse, Responder, Result as ActixResult,
+};
+use actix_web_lab::extract::Path;
+use chrono::NaiveDateTime;
+use itertools::Itertools;
+use maud::{html, PreEscaped};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{Order, Sticker},
+    tags::Rating,
+    util::StickerSetId,
+    web::shared::AppState,
+};
+
+use super::canonical_url;
+use super::service::safe_set_thumbnail;
+
+const FEED_SIZE: i64 = 50;
+const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum FeedFormat {
+    Atom,
+    Json,
+}
+
+struct FeedItem {
+    title: String,
+    /// path of the page of the item, also used as its id
+    path: String,
+    image: Option<String>,
+    summary: String,
+    published: NaiveDateTime,
+}
+
+impl FeedItem {
+    fn sticker(sticker: &Sticker, summary: String) -> Self {
+        Self {
+            title: format!(
+                "New sticker {}in {}",
+                sticker.emoji.as_ref().map(|emoji| format!("{emoji} ")).unwrap_or_default(),
+                sticker.sticker_set_id
+            ),
+            path: format!("/sticker/{}", sticker.id),
+            image: Some(format!("/files/stickers/{}/thumbnail.png", sticker.id)),
+            summary,
+            published: sticker.created_at,
+        }
+    }
+}
+
+struct Feed {
+    title: String,
+    /// path of the html page the feed belongs to
+    home_path: String,
+    /// path of the feed, without the format
+    feed_path: String,
+    items: Vec<FeedItem>,
+}
+
+#[derive(Serialize)]
+struct JsonFeed {
+    version: &'static str,
+    title: String,
+    home_page_url: String,
+    feed_url: String,
+    items: Vec<JsonFeedItem>,
+}
+
+#[derive(Serialize)]
+struct JsonFeedItem {
+    id: String,
+    url: String,
+    title: String,
+    content_text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+    date_published: String,
+}
+
+/// renders the feed as atom or json feed 1.1
+fn feed_response(data: &AppState, format: FeedFormat, feed: Feed) -> ActixResult<HttpResponse> {
+    let domain_name = &data.config.domain_name;
+    let url = |path: &str| canonical_url(domain_name, path);
+    let format_name = match format {
+        FeedFormat::Atom => "atom",
+        FeedFormat::Json => "json",
+    };
+    let feed_url = url(&format!("/feeds/{format_name}/{}", feed.feed_path));
+    let updated = feed
+        .items
+        .iter()
+        .map(|item| item.published)
+        .max()
+        .unwrap_or_default()
+        .and_utc()
+        .to_rfc3339();
+    match format {
+        FeedFormat::Atom => {
+            // links are written as `link {}` because maud would not close them otherwise
+            let markup = html! {
+                (PreEscaped(XML_DECLARATION))
+                feed xmlns="http://www.w3.org/2005/Atom" {
+                    title { (feed.title) }
+                    id { (feed_url) }
+                    updated { (updated) }
+                    link rel="self" href=(feed_url) {}
+                    link rel="alternate" href=(url(&feed.home_path)) {}
+                    @for item in &feed.items {
+                        entry {
+                            title { (item.title) }
+                            id { (url(&item.path)) }
+                            link rel="alternate" href=(url(&item.path)) {}
+                            published { (item.published.and_utc().to_rfc3339()) }
+                            updated { (item.published.and_utc().to_rfc3339()) }
+                            summary { (item.summary) }
+                            @if let Some(image) = &item.image {
+                                content type="html" {
+                                    (html! { img src=(url(image)) alt=(item.title); }.into_string())
+                                }
+                            }
+                        }
+                    }
+                }
+            };
+            Ok(HttpResponse::Ok()
+                .insert_header((header::CONTENT_TYPE, "application/atom+xml; charset=utf-8"))
+                .body(markup.into_string()))
+        }
+        FeedFormat::Json => {
+            let json = JsonFeed {
+                version: "https://jsonfeed.org/version/1.1",
+                home_page_url: url(&feed.home_path),
+                feed_url,
+                title: feed.title,
+                items: feed
+                    .items
+                    .into_iter()
+                    .map(|item| JsonFeedItem {
+                        id: url(&item.path),
+                        url: url(&item.path),
+                        title: item.title,
+                        content_text: item.summary,
+                        image: item.image.as_deref().map(url),
+                        date_published: item.published.and_utc().to_rfc3339(),
+                    })
+                    .collect_vec(),
+            };
+            Ok(HttpResponse::Ok()
+                .insert_header((header::CONTENT_TYPE, "application/feed+json; charset=utf-8"))
+                .body(serde_json::to_string(&json)?))
+        }
+    }
+}
+
+/// feeds are public, so they only contain safe stickers
+async fn safe_stickers(data: &AppState, stickers: Vec<Sticker>) -> ActixResult<Vec<Sticker>> {
+    Ok(data
+        .services
+        .sticker
+        .filter_stickers_by_rating_profile(stickers, Rating::Safe)
+        .await?)
+}
+
+#[get("/feeds/{format}/sets")]
+#[tracing::instrument(skip(data))]
+async fn new_sets_feed(
+    Path(format): Path<FeedFormat>,
+    data: Data<AppState>,
+) -> ActixResult<impl Responder> {
+    let sets = data
+        .database
+        .get_latest_sticker_sets(FEED_SIZE, chrono::Utc::now().naive_utc())
+        .await?;
+    let mut items = Vec::new();
+    for set in sets {
+        // sets without safe stickers would not have a preview image
+        let stickers = data.database.get_all_stickers_in_set(&set.id).await?;
+        if safe_stickers(&data, stickers).await?.is_empty() {
+            continue;
+        }
+        items.push(FeedItem {
+            title: format!("New sticker set {}", set.title_or_id()),
+            path: format!("/set/{}", set.id),
+            image: Some(safe_set_thumbnail(&set.id)),
+            summary: format!("{} was added to FuzzleBot", set.title_or_id()),
+            published: set.created_at,
+        });
+    }
+    feed_response(
+        &data,
+        format,
+        Feed {
+            title: "FuzzleBot: new sticker sets".to_string(),
+            home_path: "/timeline/sets".to_string(),
+            feed_path: "sets".to_string(),
+            items,
+        },
+    )
+}
+
+#[get("/feeds/{format}/stickers")]
+#[tracing::instrument(skip(data))]
+async fn new_stickers_feed(
+    Path(format): Path<FeedFormat>,
+    data: Data<AppState>,
+) -> ActixResult<impl Responder> {
+    let stickers = data
+        .database
+        .get_latest_stickers(FEED_SIZE, chrono::Utc::now().naive_utc())
+        .await?;
+    let items = safe_stickers(&data, stickers)
+        .await?
+        .iter()
+        .map(|sticker| FeedItem::sticker(sticker, format!("Added to {}", sticker.sticker_set_id)))
+        .collect_vec();
+    feed_response(
+        &data,
+        format,
+        Feed {
+            title: "FuzzleBot: new stickers".to_string(),
+            home_path: "/timeline/stickers".to_string(),
+            feed_path: "stickers".to_string(),
+            items,
+        },
+    )
+}
+
+/// sets that received new stickers within the last week
+#[get("/feeds/{format}/changes")]
+#[tracing::instrument(skip(data))]
+async fn set_changes_feed(
+    Path(format): Path<FeedFormat>,
+    data: Data<AppState>,
+) -> ActixResult<impl Responder> {
+    let changes = data.database.get_n_latest_sticker_changes(FEED_SIZE).await?;
+    let mut items = Vec::new();
+    for change in changes {
+        let Some(sticker) = data.database.get_sticker_by_id(&change.sticker_id).await? else {
+            continue;
+        };
+        let Some(sticker) = safe_stickers(&data, vec![sticker]).await?.pop() else {
+            continue;
+        };
+        items.push(FeedItem {
+            title: format!("{} new stickers in {}", change.this_week, change.sticker_set_id),
+            path: format!("/set/{}", change.sticker_set_id),
+            image: Some(safe_set_thumbnail(&change.sticker_set_id)),
+            summary: format!(
+                "{} stickers were added today and {} this week",
+                change.today, change.this_week
+            ),
+            published: sticker.created_at,
+        });
+    }
+    feed_response(
+        &data,
+        format,
+        Feed {
+            title: "FuzzleBot: updated sticker sets".to_string(),
+            home_path: "/timeline/sets".to_string(),
+            feed_path: "changes".to_string(),
+            items,
+        },
+    )
+}
+
+#[get("/feeds/{format}/tag/{tagId}")]
+#[tracing::instrument(skip(data))]
+async fn tag_feed(
+    Path((format, tag_id)): Path<(FeedFormat, String)>,
+    data: Data<AppState>,
+) -> ActixResult<impl Responder> {
+    if data.tag_manager.get_category(&tag_id).is_none() {
+        return Err(ErrorNotFound("tag not found"));
+    }
+    let stickers = data
+        .database
+        .get_stickers_for_tag_query(
+            vec![tag_id.clone()],
+            vec![],
+            vec![],
+            Rating::Safe.hidden_tags(),
+            vec![],
+            FEED_SIZE,
+            0,
+            Order::LatestFirst,
+        )
+        .await?;
+    let items = stickers
+        .iter()
+        .map(|sticker| FeedItem::sticker(sticker, format!("Tagged {tag_id}")))
+        .collect_vec();
+    feed_response(
+        &data,
+        format,
+        Feed {
+            title: format!("FuzzleBot: stickers tagged {tag_id}"),
+            home_path: format!("/tag/{tag_id}"),
+            feed_path: format!("tag/{tag_id}"),
+            items,
+        },
+    )
+}
+
+#[get("/feeds/{format}/set/{setId}")]
+#[tracing::instrument(skip(data))]
+async fn set_feed(
+    Path((format, set_id)): Path<(FeedFormat, StickerSetId)>,
+    data: Data<AppState>,
+) -> ActixResult<impl Responder> {
+    let set = data
+        .database
+        .get_sticker_set_by_id(&set_id)
+        .await?
+        .ok_or_else(|| ErrorNotFound("sticker set not found"))?;
+    let stickers = data
+        .database
+        .get_all_stickers_in_set(&set.id)
+        .await?
+        .into_iter()
+        .sorted_by_key(|sticker| std::cmp::Reverse(sticker.created_at))
+        .take(FEED_SIZE as usize)
+        .collect_vec();
+    let items = safe_stickers(&data, stickers)
+        .await?
+        .iter()
+        .map(|sticker| FeedItem::sticker(sticker, format!("Added to {}", set.title_or_id())))
+        .collect_vec();
+    feed_response(
+        &data,
+        format,
+        Feed {
+            title: format!("FuzzleBot: new stickers in {}", set.title_or_id()),
+            home_path: format!("/set/{}", set.id),
+            feed_path: format!("set/{}", set.id),
+            items,
+        },
+    )
+}
+
+/// stickers of the tags the user follows (`/follow`); the token is part of the url that the bot
+/// sends, since feed readers can not log in
+#[get("/feeds/{format}/user/{userId}/{token}")]
+#[tracing::instrument(skip(data, token))]
+async fn followed_tags_feed(
+    Path((format, user_id, token)): Path<(FeedFormat, i64, String)>,
+    data: Data<AppState>,
+) -> ActixResult<impl Responder> {
+    if !data.services.feed.verify_feed_token(user_id, &token) {
+        return Err(ErrorNotFound("feed not found"));
+    }
+    let user = data
+        .database
+        .get_user_by_id(user_id)
+        .await?
+        .ok_or_else(|| ErrorNotFound("feed not found"))?;
+    let stickers = data
+        .services
+        .feed
+        .followed_tag_stickers(&user, FEED_SIZE)
+        .await?;
+    let followed_tags = user
+        .settings
+        .as_ref()
+        .map(|settings| settings.followed_tags.join(", "))
+        .unwrap_or_default();
+    let items = stickers
+        .iter()
+        .map(|sticker| FeedItem::sticker(sticker, format!("New sticker for {followed_tags}")))
+        .collect_vec();
+    feed_response(
+        &data,
+        format,
+        Feed {
+            title: "FuzzleBot: your followed tags".to_string(),
+            home_path: "/".to_string(),
+            feed_path: data.services.feed.personal_feed_path(user_id),
+            items,
+        },
+    )
+}
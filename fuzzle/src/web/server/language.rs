@@ -0,0 +1,58 @@
+use std::{future::Future, pin::Pin};
+
+use actix_web::{
+    http::header::{ACCEPT_LANGUAGE, CACHE_CONTROL, VARY},
+    web, CustomizeResponder, FromRequest, HttpRequest, Responder,
+};
+
+use crate::{text::Language, web::shared::AppState};
+
+use super::{AuthData, OptionalAuthenticatedUser};
+
+/// language of the website for the visitor: the one chosen with `/language` for logged in users,
+/// otherwise the preferred one of the browser
+#[derive(Debug, Clone, Copy)]
+pub struct VisitorLanguage(pub Language);
+
+impl FromRequest for VisitorLanguage {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut actix_web::dev::Payload) -> Self::Future {
+        let data = req
+            .app_data::<web::Data<AppState>>()
+            .expect("data to be present")
+            .clone();
+        let browser_language = req
+            .headers()
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|header| header.to_str().ok())
+            .and_then(Language::from_accept_language);
+        let auth = OptionalAuthenticatedUser::from_request(req, payload);
+        Box::pin(async move {
+            let chosen_language = match auth.await?.auth_data {
+                Some(auth_data) => data
+                    .database
+                    .get_user_by_id(auth_data.id as i64)
+                    .await?
+                    .and_then(|user| user.settings)
+                    .and_then(|settings| settings.language),
+                None => None,
+            };
+            Ok(Self(
+                chosen_language.or(browser_language).unwrap_or_default(),
+            ))
+        })
+    }
+}
+
+/// pages that use `VisitorLanguage` differ by the browser language for anonymous visitors; pages
+/// of logged in visitors depend on their settings and must not be stored by shared caches
+pub fn vary_language<R: Responder>(responder: R, auth_data: Option<&AuthData>) -> CustomizeResponder<R> {
+    let responder = responder.customize();
+    if auth_data.is_some() {
+        responder.insert_header((CACHE_CONTROL, "private"))
+    } else {
+        responder.insert_header((VARY, "Accept-Language"))
+    }
+}
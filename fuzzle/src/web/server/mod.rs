@@ -1,9 +1,20 @@
+mod api;
 mod auth;
+mod compare;
+mod creator;
+mod editor;
+mod feed;
+mod language;
 pub(super) mod service;
 mod setup;
 mod page;
+mod search;
+mod sitemap;
+mod webapp;
+mod wiki;
 
 pub use auth::*;
+pub use language::*;
 pub use setup::*;
 
 pub use page::*; // TODO: dont expose everything
@@ -1,7 +1,7 @@
 use actix_web::web::{route, Form};
 use actix_web::web::{Data, Query};
 use actix_web::{
-    get, post, App, HttpRequest, HttpResponse, HttpServer, Responder, Result as ActixResult,
+    get, post, App, HttpResponse, HttpServer, Responder, Result as ActixResult,
 };
 use actix_web_lab::extract::Path;
 use chrono::{DateTime, NaiveDateTime};
@@ -14,29 +14,64 @@ use serde::Deserialize;
 
 use crate::background_tasks::TagManagerService;
 use crate::bot::{InternalError, UserError};
-use crate::database::{Database, Order, Sticker, StickerSet};
+use crate::database::{Capability, Database, Order, Sticker, StickerSet};
 use crate::inline::{
     get_last_input_match_list_and_other_input_closest_matches, parse_comma_separated_tags,
 };
+use crate::message::StartParameter;
 use crate::services::Services;
 use crate::sticker::resolve_file_hashes_to_sticker_ids_and_clean_up_unreferenced_files;
+use crate::tags::Rating;
+use crate::text::Language;
 use crate::util::{Emoji, Required, StickerId, StickerSetId, format_relative_time, parse_first_emoji};
 use crate::web::shared::AppState;
 
-use super::OptionalAuthenticatedUser;
+use super::editor::{can_edit, can_edit_file_tags, set_tag_editor, sticker_tag_editor};
+use super::service::safe_set_thumbnail;
+use super::{vary_language, AuthData, OptionalAuthenticatedUser, VisitorLanguage};
+
+/// tags that hide stickers from the visitor: anonymous visitors get the safe profile combined
+/// with the default blacklist, logged in users their own rating profile (their blacklist is not
+/// applied on pages because sets can be whitelisted)
+///
+/// rating profiles can only hide tagged stickers, untagged stickers are shown to everyone
+pub(super) async fn visitor_hidden_tags(
+    data: &AppState,
+    auth_data: Option<&AuthData>,
+) -> Result<Vec<String>, InternalError> {
+    let Some(auth_data) = auth_data else {
+        return Ok(Rating::Safe
+            .hidden_tags()
+            .into_iter()
+            .chain(data.config.default_blacklist.iter().cloned())
+            .unique()
+            .collect_vec());
+    };
+    let user = data.database.get_user_by_id(auth_data.id as i64).await?;
+    Ok(user
+        .and_then(|user| user.settings)
+        .unwrap_or_default()
+        .rating_profile()
+        .hidden_tags())
+}
 
 #[get("/")]
 pub async fn index(
-    req: HttpRequest,
     data: Data<AppState>,
 
     OptionalAuthenticatedUser { auth_data }: OptionalAuthenticatedUser,
-) -> ActixResult<Markup> {
-    let host = format!("{}", req.uri());
-    let title = "fuzzle bot";
-    let desc = "Hi there";
-    let lang = "en";
+    VisitorLanguage(language): VisitorLanguage,
+) -> ActixResult<impl Responder> {
     let stats = data.database.get_stats().await?;
+    let meta = PageMeta::new(
+        language.text("web.index.title", &[]),
+        language.text(
+            "web.index.description",
+            &[("sets", stats.sets.into()), ("stickers", stats.stickers.into())],
+        ),
+        "/",
+    )
+    .language(language);
     let tags = data.database.get_popular_tags(20, 0).await?;
     let emojis = data.database.get_most_used_emojis(20, 0).await?;
 
@@ -46,7 +81,7 @@ pub async fn index(
                 "FuzzleBot"
             }
             p {
-                "Furry Telegram Sticker Collector"
+                (language.text("web.index.subtitle", &[]))
             }
             // div {
             //     @match auth_data {
@@ -59,10 +94,17 @@ pub async fn index(
             //     }
             // }
             p {
-                "I organize " (stats.sets) " furry sticker sets • " (stats.taggings) " taggings • " (stats.stickers) " stickers"
+                (language.text(
+                    "web.index.stats",
+                    &[
+                        ("sets", stats.sets.into()),
+                        ("taggings", stats.taggings.into()),
+                        ("stickers", stats.stickers.into()),
+                    ],
+                ))
             }
             input type="search"
-                name="name" placeholder="Search Tags ..."
+                name="name" placeholder=(language.text("web.index.search_tags", &[]))
                 hx-post="/search-tags"
                 hx-trigger="input changed delay:300ms, search"
                 hx-target="#content"
@@ -74,12 +116,12 @@ pub async fn index(
 
             p {
                 span class="htmx-indicator" {
-                    "Searching ..."
+                    (language.text("web.index.searching", &[]))
                 }
             }
 
 
-            "Most Used Tags:"
+            (language.text("web.index.most_used_tags", &[]))
                 div class="tag-container" {
                     @for tag in &tags {
                         (tag_list_item(&data.tag_manager, &tag.name, Some(format!("{}", tag.count))))
@@ -87,19 +129,20 @@ pub async fn index(
                 }
 
 
-            "Most Used Emojis:"
+            (language.text("web.index.most_used_emojis", &[]))
                 div class="tag-container" {
                     @for emoji in &emojis {
                         (emoji_list_item(&emoji.0, Some(format!("{}", emoji.1))))
                     }
         }
             
-            (link_btn("/tags".to_string(), "All Tags".to_string()))
-            (link_btn("/emojis".to_string(), "All Emojis".to_string()))
+            (link_btn("/search".to_string(), language.text("web.search.title", &[])))
+            (link_btn("/tags".to_string(), language.text("web.index.all_tags", &[])))
+            (link_btn("/emojis".to_string(), language.text("web.index.all_emojis", &[])))
 
         }
     };
-    Ok(page(&host, title, desc, lang, content))
+    Ok(vary_language(page(&data, &meta, content), auth_data.as_deref()))
 }
 
 pub fn tag_list_item(
@@ -150,11 +193,15 @@ pub async fn search_tags(
     user_input: Form<SearchTagsForm>,
     data: Data<AppState>,
 ) -> ActixResult<Markup> {
+    // only the last tag of a comma separated list is completed
+    let (previous, current) = match user_input.name.rsplit_once(',') {
+        Some((previous, current)) if user_input.autocomplete => (Some(previous.trim()), current),
+        _ => (None, user_input.name.as_str()),
+    };
     let suggested_tags = data
         .tag_manager
         .find_tags(
-            &user_input
-                .name
+            &current
                 .split(" ")
                 .map(|s| s.to_string())
                 .collect_vec(),
@@ -164,6 +211,18 @@ pub async fn search_tags(
         .take(20)
         .collect_vec();
 
+    if user_input.autocomplete {
+        return Ok(html! {
+            @for tag in &suggested_tags {
+                @if let Some(previous) = previous {
+                    option value={ (previous) ", " (tag) } {}
+                } @else {
+                    option value=(tag) {}
+                }
+            }
+        });
+    }
+
     Ok(html! {
         #content {
 
@@ -208,8 +267,9 @@ pub fn sticker_set_list_item(set_id: &StickerSetId) -> Markup {
 async fn sticker_set(
     Path(set_id): Path<StickerSetId>,
     data: Data<AppState>,
-    req: HttpRequest,
+    OptionalAuthenticatedUser { auth_data }: OptionalAuthenticatedUser,
 ) -> actix_web::Result<impl Responder> {
+    let hidden_tags = visitor_hidden_tags(&data, auth_data.as_deref()).await?;
     let set = data
         .database
         .get_sticker_set_by_id(&set_id)
@@ -217,6 +277,11 @@ async fn sticker_set(
         .required()?;
     set.last_fetched.required()?; // TODO: better error
     let stickers = data.database.get_all_stickers_in_set(&set_id).await?;
+    let stickers = data
+        .services
+        .sticker
+        .filter_stickers_by_hidden_tags(stickers, hidden_tags.clone())
+        .await?;
     let overlapping_sets = data
         .database
         .get_overlapping_sets(&set_id)
@@ -232,11 +297,20 @@ async fn sticker_set(
         .database
         .get_all_sticker_set_tag_counts(&set_id)
         .await?;
-
-    let host = format!("{}", req.uri());
-    let title = "fuzzle bot";
-    let desc = "Hi there";
-    let lang = "en";
+    let editable = can_edit(&data, auth_data.as_deref(), Capability::TagSets).await;
+
+    let meta = PageMeta::new(
+        set.title_or_id(),
+        format!(
+            "{}: furry Telegram sticker set with {} stickers{}",
+            set.title_or_id(),
+            stickers.len(),
+            tagged_with(tags.iter().map(|(tag, _)| tag)),
+        ),
+        format!("/set/{}", set.id),
+    )
+    .image(safe_set_thumbnail(&set.id))
+    .feed(format!("set/{}", set.id));
     let set_title = set.title_or_id();
 
     let content = html! {
@@ -245,12 +319,7 @@ async fn sticker_set(
                 (set_title)
             }
 
-            "Tags:"
-                div class="tag-container" {
-                    @for tag in &tags {
-                        (tag_list_item(&data.tag_manager, &tag.0, Some(format!("{}", tag.1))))
-                    }
-                }
+            (set_tag_editor(&data, &set.id, &tags, editable, None))
 
                 div {
 
@@ -280,6 +349,10 @@ async fn sticker_set(
             div class="set-grid" {
                 @for set in &overlapping_sets {
                     "Overlap: " (set.1)
+                    @if set.0 != set_id {
+                        " · "
+                        a href={ "/compare/" (set_id) "/" (set.0) } { "compare" }
+                    }
                     (sticker_set_list_item(&set.0))
                 }
             }
@@ -287,6 +360,9 @@ async fn sticker_set(
                 h2 {
             "Same Owner"
                 }
+            @if let Some(creator_id) = set.created_by_user_id {
+                a href={ "/creator/" (creator_id) } { "show the profile of the creator" }
+            }
             div class="set-grid" {
                 @for set in &owner_sets {
                     (sticker_set_list_item(&set.id))
@@ -296,15 +372,85 @@ async fn sticker_set(
         }
     };
 
-    Ok(page(&host, title, desc, lang, content))
+    Ok(page(&data, &meta, content))
+}
+
+/// only public collections are visible; private ones are treated as non-existent
+#[get("/collection/{collectionId}")]
+async fn collection_page(
+    Path(collection_id): Path<i64>,
+    data: Data<AppState>,
+    OptionalAuthenticatedUser { auth_data }: OptionalAuthenticatedUser,
+) -> actix_web::Result<impl Responder> {
+    let hidden_tags = visitor_hidden_tags(&data, auth_data.as_deref()).await?;
+    let collection = data
+        .database
+        .get_public_sticker_collection(collection_id)
+        .await?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("collection not found"))?;
+    let stickers = data
+        .database
+        .get_collection_stickers(collection.id, 500, 0)
+        .await?;
+    let stickers = data
+        .services
+        .sticker
+        .filter_stickers_by_hidden_tags(stickers, hidden_tags.clone())
+        .await?;
+    let sticker_count = data
+        .database
+        .get_collection_sticker_count(collection.id)
+        .await?;
+    let follower_count = data
+        .database
+        .get_sticker_collection_follower_count(collection.id)
+        .await?;
+
+    let meta = PageMeta::new(
+        collection.name.clone(),
+        format!(
+            "Collection of {sticker_count} furry Telegram stickers with {follower_count} followers"
+        ),
+        format!("/collection/{}", collection.id),
+    );
+    let start_parameter = StartParameter::Collection(collection.id);
+
+    let content = html! {
+        #content {
+            h1 {
+                (collection.name)
+            }
+
+            p {
+                (sticker_count) " stickers • " (follower_count) " followers"
+            }
+
+                div {
+
+                            a href={ "https://t.me/" (data.config.telegram_bot_username) "?start=" (start_parameter) } {
+                                "follow this collection on telegram"
+                            }
+
+                }
+
+            div class="grid" {
+                @for sticker in &stickers {
+                    (sticker_list_item(&sticker.id))
+                }
+            }
+        }
+    };
+
+    Ok(page(&data, &meta, content))
 }
 
 #[get("/sticker/{stickerId}")]
 async fn sticker_page(
     Path(sticker_id): Path<StickerId>,
     data: Data<AppState>,
-    req: HttpRequest,
+    OptionalAuthenticatedUser { auth_data }: OptionalAuthenticatedUser,
 ) -> actix_web::Result<impl Responder> {
+    let hidden_tags = visitor_hidden_tags(&data, auth_data.as_deref()).await?;
     let set = data
         .database
         .get_sticker_set_by_sticker_id(&sticker_id)
@@ -327,8 +473,17 @@ async fn sticker_page(
         .get_sticker_file_by_sticker_id(&sticker_id)
         .await?
         .required()?;
-    let set_stickers = data.database.get_all_stickers_in_set(&set.id).await?;
     let tags = data.database.get_sticker_tags_by_file_id(&file.id).await?;
+    let editable = can_edit_file_tags(&data, auth_data.as_deref(), &file).await;
+    if tags.iter().any(|tag| hidden_tags.contains(tag)) {
+        return Err(actix_web::error::ErrorNotFound("sticker not found"));
+    }
+    let set_stickers = data.database.get_all_stickers_in_set(&set.id).await?;
+    let set_stickers = data
+        .services
+        .sticker
+        .filter_stickers_by_hidden_tags(set_stickers, hidden_tags.clone())
+        .await?;
     let sticker_type = match file.sticker_type {
         crate::database::StickerType::Animated => "yes (vector/tgs)",
         crate::database::StickerType::Video => "yes (video)",
@@ -338,19 +493,27 @@ async fn sticker_page(
     let similar_color = {
         let (matches, _) = data.services.similarity.find_similar_stickers(sticker_id.clone(), crate::inline::SimilarityAspect::Color, 20, 0).await?;
         let stickers = data.services.similarity.matches_to_stickers(matches).await?;
-        stickers.into_iter().map(|(s, _)| s).collect_vec()
+        let stickers = stickers.into_iter().map(|(s, _)| s).collect_vec();
+        data.services.sticker.filter_stickers_by_hidden_tags(stickers, hidden_tags.clone()).await?
     };
     let similar_embedding = {
         let (matches, _) = data.services.similarity.find_similar_stickers(sticker_id.clone(), crate::inline::SimilarityAspect::Embedding, 20, 0).await?;
         let stickers = data.services.similarity.matches_to_stickers(matches).await?;
-        stickers.into_iter().map(|(s, _)| s).collect_vec()
+        let stickers = stickers.into_iter().map(|(s, _)| s).collect_vec();
+        data.services.sticker.filter_stickers_by_hidden_tags(stickers, hidden_tags).await?
     };
     let emoji = Emoji::new_from_string_single(sticker.emoji.required()?);
 
-    let host = format!("{}", req.uri());
-    let title = "fuzzle bot";
-    let desc = "Hi there";
-    let lang = "en";
+    let meta = PageMeta::new(
+        format!("{} Sticker from {}", emoji.to_string_with_variant(), set.title_or_id()),
+        format!(
+            "Furry Telegram sticker from the set {}{}",
+            set.title_or_id(),
+            tagged_with(tags.iter()),
+        ),
+        format!("/sticker/{}", sticker.id),
+    )
+    .image(format!("/files/stickers/{}/thumbnail.png", sticker.id));
     let set_title = set.title_or_id();
 
     let content = html! {
@@ -361,12 +524,7 @@ async fn sticker_page(
                 img class="big-sticker" src={ "/files/stickers/" (sticker.id) "/thumbnail.png" };
                 div class="sticker-information" {
 
-                "Tags: "
-                div class="tag-container" {
-                    @for tag in &tags {
-                        (tag_list_item(&data.tag_manager, &tag, None))
-                    }
-                }
+                (sticker_tag_editor(&data, &sticker.id, &tags, editable, None))
                 h1 {
                     "Sticker from set " (set_title)
                 }
@@ -433,57 +591,35 @@ async fn sticker_page(
         }
     };
 
-    Ok(page(&host, title, desc, lang, content))
+    Ok(page(&data, &meta, content))
 }
 
-pub async fn not_found() -> impl Responder {
-    (
+pub async fn not_found(
+    OptionalAuthenticatedUser { auth_data }: OptionalAuthenticatedUser,
+    VisitorLanguage(language): VisitorLanguage,
+) -> impl Responder {
+    let title = language.text("web.not_found.title", &[]);
+    let response = (
         html! {
-            html lang="en" {
+            html lang=(language.code()) {
                 head {
                     meta charset=(strings::UTF8);
-                    title { (strings::NOT_FOUND_TITLE) }
+                    title { (title) }
                     meta name=(strings::VIEWPORT) content=(strings::VIEWPORT_CONTENT);
                     style { (strings::NOT_FOUND_STYLE) }
                 }
                 body {
-                    h1 { (strings::NOT_FOUND_TITLE) }
-                    p { (strings::NOT_FOUND_MESSAGE) }
+                    h1 { (title) }
+                    p { (language.text("web.not_found.message", &[])) }
                 }
                 (PreEscaped(strings::NOT_FOUND_COMMENT))
             }
         },
         actix_web::http::StatusCode::NOT_FOUND,
-    )
+    );
+    vary_language(response, auth_data.as_deref())
 }
 
-// #[get("/webapp")]
-// pub async fn webapp_entrypoint(start_form: Query<WebAppStart>) -> impl Responder {
-//     dbg!(&start_form.start_param);
-//     (
-//         html! {
-//             html lang="en" {
-//                 head {
-//                     meta charset=(strings::UTF8);
-//                     title { "Loading" }
-//                     script { (PreEscaped(strings::WEBAPP_LOGIN_SCRIPT)) }
-//                 }
-//                 body {
-//                     h1 { "Loading" }
-//                     p { "Loading" }
-//                 }
-//             }
-//         },
-//         actix_web::http::StatusCode::OK,
-//     )
-// }
-
-// #[derive(Deserialize)]
-// struct WebAppStart {
-//     #[serde(rename = "tgWebAppStartParam")]
-//     start_param: Option<WebAppStartParameter>,
-// }
-
 #[derive(Deserialize)]
 struct TagForm {
     query: String,
@@ -492,7 +628,12 @@ struct TagForm {
 
 #[derive(Deserialize)]
 struct SearchTagsForm {
+    /// the tag editor inputs are named after what they contain
+    #[serde(alias = "tag", alias = "tags")]
     name: String,
+    /// render options for a datalist instead of tag links
+    #[serde(default)]
+    autocomplete: bool,
 }
 
 fn body(content: Markup) -> Markup {
@@ -506,33 +647,144 @@ fn body(content: Markup) -> Markup {
     }
 }
 
-fn head(title: &str, desc: &str, url: &str) -> Markup {
+/// title, description and preview image of a page for search engines and link previews
+#[derive(Debug, Clone)]
+pub struct PageMeta {
+    pub title: String,
+    pub description: String,
+    /// path of the canonical url, without query parameters
+    pub path: String,
+    /// path of the preview image
+    pub image: Option<String>,
+    /// feed path without the format, e.g. `tag/fox` for `/feeds/atom/tag/fox`
+    pub feed: Option<String>,
+    pub indexable: bool,
+    /// language of the page content
+    pub language: Language,
+}
+
+/// search engines and link previews cut longer descriptions anyway
+const MAX_META_DESCRIPTION_LENGTH: usize = 160;
+
+impl PageMeta {
+    /// long descriptions (eg user written ones) are shortened
+    pub fn new(
+        title: impl Into<String>,
+        description: impl Into<String>,
+        path: impl Into<String>,
+    ) -> Self {
+        let description: String = description.into();
+        let description = if description.chars().count() > MAX_META_DESCRIPTION_LENGTH {
+            let shortened: String = description
+                .chars()
+                .take(MAX_META_DESCRIPTION_LENGTH - 1)
+                .collect();
+            format!("{}…", shortened.trim_end())
+        } else {
+            description
+        };
+        Self {
+            title: title.into(),
+            description,
+            path: path.into(),
+            image: None,
+            feed: None,
+            indexable: true,
+            language: Language::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn image(mut self, path: impl Into<String>) -> Self {
+        self.image = Some(path.into());
+        self
+    }
+
+    #[must_use]
+    pub fn feed(mut self, path: impl Into<String>) -> Self {
+        self.feed = Some(path.into());
+        self
+    }
+
+    /// for translated pages; everything else is english
+    #[must_use]
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// for pages that only make sense with query parameters or for the current visitor
+    #[must_use]
+    pub fn noindex(mut self) -> Self {
+        self.indexable = false;
+        self
+    }
+}
+
+/// `", tagged a, b, c"` with the first few tags for descriptions; empty without tags
+fn tagged_with<'a>(tags: impl Iterator<Item = &'a String>) -> String {
+    let tags = tags.take(8).join(", ");
+    if tags.is_empty() {
+        String::new()
+    } else {
+        format!(", tagged {tags}")
+    }
+}
+
+/// absolute url of a path on the website; non-ascii characters (emojis) are percent-encoded
+pub fn canonical_url(domain_name: &str, path: &str) -> String {
+    let url = format!("https://{domain_name}{path}");
+    url::Url::parse(&url).map_or(url, |url| url.to_string())
+}
+
+fn head(domain_name: &str, meta: &PageMeta) -> Markup {
+    let title = format!("{} · {}", meta.title, strings::SITE_NAME);
+    let url = canonical_url(domain_name, &meta.path);
+    let image = meta
+        .image
+        .as_ref()
+        .map(|image| canonical_url(domain_name, image));
     html! {
         head {
             meta charset=(strings::UTF8);
             title { (title) }
-            meta name=(strings::DESCRIPTION) content=(desc);
+            meta name=(strings::DESCRIPTION) content=(meta.description);
             meta name=(strings::VIEWPORT) content=(strings::VIEWPORT_CONTENT);
-            meta property="og:title" content=(title);
+            link rel="canonical" href=(url);
+            meta property="og:site_name" content=(strings::SITE_NAME);
+            meta property="og:title" content=(meta.title);
+            meta property="og:description" content=(meta.description);
             meta property="og:type" content=(strings::WEBSITE);
             meta property="og:url" content=(url);
-            meta property="og:image" content="";
+            meta name="twitter:card" content="summary";
+            meta name="twitter:title" content=(meta.title);
+            meta name="twitter:description" content=(meta.description);
+            @if let Some(image) = &image {
+                meta property="og:image" content=(image);
+                meta name="twitter:image" content=(image);
+            }
+            @if let Some(feed) = &meta.feed {
+                link rel="alternate" type="application/atom+xml" title=(meta.title) href={ "/feeds/atom/" (feed) };
+                link rel="alternate" type="application/feed+json" title=(meta.title) href={ "/feeds/json/" (feed) };
+            }
             link rel="manifest" href="site.webmanifest";
             link rel="apple-touch-icon" href="icon.png";
             link rel="stylesheet" href="/assets/css/normalize.css";
             link rel="stylesheet" href="/assets/css/main.css";
             meta name="theme-color" content="#fafafa";
-            meta name="robots" content="noindex"; // TODO: remove
+            @if !meta.indexable {
+                meta name="robots" content="noindex";
+            }
             meta name="rating" content="adult";
         }
     }
 }
 
-pub fn page(host: &str, title: &str, desc: &str, lang: &str, content: Markup) -> Markup {
+pub fn page(data: &AppState, meta: &PageMeta, content: Markup) -> Markup {
     html! {
         (DOCTYPE)
-        html class="no-js" lang=(lang) {
-            (head(title, desc, host))
+        html class="no-js" lang=(meta.language.code()) {
+            (head(&data.config.domain_name, meta))
             (body(content))
         }
     }
@@ -542,8 +794,6 @@ mod strings {
 
     pub static DESCRIPTION: &str = "description";
     pub static NOT_FOUND_COMMENT: &str = "<!-- IE needs 512+ bytes: https://docs.microsoft.com/archive/blogs/ieinternals/friendly-http-error-pages -->";
-    pub static NOT_FOUND_MESSAGE: &str =
-        "Sorry, but the page you were trying to view does not exist.";
     pub static NOT_FOUND_STYLE: &str = "
             * {
                 line-height: 1.2;
@@ -589,111 +839,33 @@ mod strings {
             }
 
             }";
-    pub static NOT_FOUND_TITLE: &str = "Page Not Found";
     pub static UTF8: &str = "utf-8";
     pub static VIEWPORT: &str = "viewport";
     pub static VIEWPORT_CONTENT: &str = "width=device-width, initial-scale=1.0, maximum-scale=1.0, user-scalable=0";
     pub static WEBSITE: &str = "website";
-    pub static WEBAPP_LOGIN_SCRIPT: &str = r#"
-var locationHash = '';
-try {
-    locationHash = location.hash.toString();
-} catch (e) {}
-
-var initParams = urlParseHashParams(locationHash);
-sessionStorageSet('initParams', initParams);
-
-submitTelegramLogin(initParams.tgWebAppData);
-
- function submitTelegramLogin(initData) {
-            
-            if (!initData) {
-                console.error('No initData available - are you running this outside of a Telegram Web App?');
-                return;
-            }
-
-            // Create the URL with the initData as a query parameter
-            const loginUrl = `/login-webapp?${initData}`;
-            
-            // Redirect to the login endpoint
-            window.location.href = loginUrl;
-        }
-
-function urlParseHashParams(locationHash) {
-    locationHash = locationHash.replace(/^#/, '');
-    var params = {};
-    if (!locationHash.length) {
-      return params;
-    }
-    if (locationHash.indexOf('=') < 0 && locationHash.indexOf('?') < 0) {
-      params._path = urlSafeDecode(locationHash);
-      return params;
-    }
-    var qIndex = locationHash.indexOf('?');
-    if (qIndex >= 0) {
-      var pathParam = locationHash.substr(0, qIndex);
-      params._path = urlSafeDecode(pathParam);
-      locationHash = locationHash.substr(qIndex + 1);
-    }
-    var query_params = urlParseQueryString(locationHash);
-    for (var k in query_params) {
-      params[k] = query_params[k];
-    }
-    return params;
-  }
-
-  function urlParseQueryString(queryString) {
-    var params = {};
-    if (!queryString.length) {
-      return params;
-    }
-    var queryStringParams = queryString.split('&');
-    var i, param, paramName, paramValue;
-    for (i = 0; i < queryStringParams.length; i++) {
-      param = queryStringParams[i].split('=');
-      paramName = urlSafeDecode(param[0]);
-      paramValue = param[1] == null ? null : urlSafeDecode(param[1]);
-      params[paramName] = paramValue;
-    }
-    return params;
-  }
-
-function urlSafeDecode(urlencoded) {
-    try {
-      urlencoded = urlencoded.replace(/\+/g, '%20');
-      return decodeURIComponent(urlencoded);
-    } catch (e) {
-      return urlencoded;
-    }
-  }
-    function sessionStorageSet(key, value) {
-    try {
-      window.sessionStorage.setItem('__uwu__' + key, JSON.stringify(value));
-      window.localStorage.setItem('__uwu__' + key, JSON.stringify(value));
-      return true;
-    } catch(e) {}
-    return false;
-  }
-    "#;
+    pub static SITE_NAME: &str = "FuzzleBot";
 }
 
 #[get("/tag/{tagId}")]
 async fn tag_page(
     Path(tag_id): Path<String>,
     data: Data<AppState>,
-    req: HttpRequest,
+    OptionalAuthenticatedUser { auth_data }: OptionalAuthenticatedUser,
 ) -> actix_web::Result<impl Responder> {
     // TODO: show the tag type
+    let hidden_tags = visitor_hidden_tags(&data, auth_data.as_deref()).await?;
     let sets = data
         .database
-        .get_sticker_sets_for_tag_query(vec![tag_id.clone()], vec![], 100, 0)
-        .await?; // TODO: use default blacklist
+        .get_sticker_sets_for_tag_query(vec![tag_id.clone()], hidden_tags.clone(), 100, 0)
+        .await?;
     let stickers = data
         .database
         .get_stickers_for_tag_query(
             vec![tag_id.clone()],
             vec![],
             vec![],
+            hidden_tags,
+            vec![],
             100,
             0,
             Order::LatestFirst,
@@ -707,10 +879,21 @@ async fn tag_page(
         .take(10)
         .collect_vec();
 
-    let host = format!("{}", req.uri());
-    let title = "fuzzle bot";
-    let desc = "Hi there";
-    let lang = "en";
+    let mut meta = PageMeta::new(
+        format!("Tag {tag_id}"),
+        format!(
+            "Furry Telegram stickers and sticker sets tagged {tag_id}{}",
+            data.tag_manager
+                .get_category(&tag_id)
+                .map(|category| format!(" ({})", category.to_human_name()))
+                .unwrap_or_default()
+        ),
+        format!("/tag/{tag_id}"),
+    )
+    .feed(format!("tag/{tag_id}"));
+    if let Some(sticker) = stickers.first() {
+        meta = meta.image(format!("/files/stickers/{}/thumbnail.png", sticker.id));
+    }
 
     let content = html! {
         #content {
@@ -718,6 +901,9 @@ async fn tag_page(
                 "Tag "
                 (tag_id)
             }
+            p {
+                a href={ "/wiki/" (tag_id) } { "What does this tag mean?" }
+            }
 
             "Recommended Emojis:"
                 div class="tag-container" {
@@ -747,15 +933,16 @@ async fn tag_page(
         }
     };
 
-    Ok(page(&host, title, desc, lang, content))
+    Ok(page(&data, &meta, content))
 }
 
 #[get("/emoji/{emoji}")]
 async fn emoji_page(
     Path(emoji): Path<String>,
     data: Data<AppState>,
-    req: HttpRequest,
+    OptionalAuthenticatedUser { auth_data }: OptionalAuthenticatedUser,
 ) -> actix_web::Result<impl Responder> {
+    let hidden_tags = visitor_hidden_tags(&data, auth_data.as_deref()).await?;
     let emoji = parse_first_emoji(&emoji).0.required()?;
     // let sets = data
     //     .database
@@ -763,7 +950,7 @@ async fn emoji_page(
     //     .await?; // TODO: use default blacklist
     let stickers = data
         .database
-        .get_stickers_by_emoji(&emoji.to_string_without_variant(), 100, 0)
+        .get_stickers_by_emoji(&emoji.to_string_without_variant(), vec![], vec![], hidden_tags, 100, 0)
         .await?;
     let tags = data
         .tfidf_service
@@ -773,10 +960,22 @@ async fn emoji_page(
         .take(10)
         .collect_vec();
 
-    let host = format!("{}", req.uri());
-    let title = "fuzzle bot";
-    let desc = "Hi there";
-    let lang = "en";
+    let emoji_name = emoji
+        .name()
+        .map(|name| format!(" ({name})"))
+        .unwrap_or_default();
+    let mut meta = PageMeta::new(
+        format!("Emoji {}{emoji_name}", emoji.to_string_with_variant()),
+        format!(
+            "Furry Telegram stickers for {}{emoji_name}{}",
+            emoji.to_string_with_variant(),
+            tagged_with(tags.iter().map(|tag| &tag.tag)),
+        ),
+        format!("/emoji/{}", emoji.to_string_without_variant()),
+    );
+    if let Some(sticker) = stickers.first() {
+        meta = meta.image(format!("/files/stickers/{}/thumbnail.png", sticker.id));
+    }
 
     let content = html! {
         #content {
@@ -811,22 +1010,23 @@ async fn emoji_page(
         }
     };
 
-    Ok(page(&host, title, desc, lang, content))
+    Ok(page(&data, &meta, content))
 }
 
 #[get("/set/{setId}/timeline")]
 async fn sticker_set_timeline_page(
     Path(set_id): Path<StickerSetId>,
     data: Data<AppState>,
-    req: HttpRequest,
+    OptionalAuthenticatedUser { auth_data }: OptionalAuthenticatedUser,
 ) -> actix_web::Result<impl Responder> {
+    let hidden_tags = visitor_hidden_tags(&data, auth_data.as_deref()).await?;
         let set = data // TODO: set is not required
             .database
             .get_sticker_set_by_id(&set_id)
             .await?
             .required()?;
     let groups = data.services.sticker
-        .get_sticker_set_timeline(&set_id)
+        .get_sticker_set_timeline(&set_id, hidden_tags)
         .await?;
     let r = groups
         .into_iter()
@@ -843,10 +1043,12 @@ async fn sticker_set_timeline_page(
         })
         .collect_vec();
 
-    let host = format!("{}", req.uri());
-    let title = "fuzzle bot";
-    let desc = "Hi there";
-    let lang = "en";
+    let meta = PageMeta::new(
+        format!("Timeline of {}", set.title_or_id()),
+        format!("Stickers of the set {} by the date they were added", set.title_or_id()),
+        format!("/set/{}/timeline", set.id),
+    )
+    .image(safe_set_thumbnail(&set.id));
     let set_title = set.title_or_id();
 
     let content = html! {
@@ -859,7 +1061,7 @@ async fn sticker_set_timeline_page(
         }
     };
 
-    Ok(page(&host, title, desc, lang, content))
+    Ok(page(&data, &meta, content))
 }
 
 pub struct TimelineItem {
@@ -945,17 +1147,20 @@ pub fn infinite_scroll_trigger(url: impl Into<String>, swap_target: impl Into<St
 #[get("/timeline/stickers")]
 async fn sticker_timeline_page(
     data: Data<AppState>,
-    req: HttpRequest,
+    OptionalAuthenticatedUser { auth_data }: OptionalAuthenticatedUser,
 ) -> actix_web::Result<impl Responder> {
-    let host = format!("{}", req.uri());
-    let title = "fuzzle bot";
-    let desc = "Hi there";
-    let lang = "en";
+    let hidden_tags = visitor_hidden_tags(&data, auth_data.as_deref()).await?;
+    let meta = PageMeta::new(
+        "Sticker Timeline",
+        "The newest furry Telegram stickers",
+        "/timeline/stickers",
+    )
+    .feed("stickers");
 
     let Some((after, groups)) = data.services.sticker
-        .get_all_sticker_timeline(100, chrono::Utc::now().naive_utc())
+        .get_all_sticker_timeline(100, chrono::Utc::now().naive_utc(), hidden_tags)
         .await? else {
-            return Ok(page(&host, title, desc, lang, html! {"End"}))
+            return Ok(page(&data, &meta, html! {"End"}))
         };
     let r = groups
         .into_iter()
@@ -974,19 +1179,20 @@ async fn sticker_timeline_page(
         })))
     };
 
-    Ok(page(&host, title, desc, lang, content))
+    Ok(page(&data, &meta, content))
 }
 
 #[get("/fragment/timeline/stickers/{after}")]
 async fn sticker_timeline_page_fragment(
     Path(after): Path<i64>,
     data: Data<AppState>,
-    req: HttpRequest,
+    OptionalAuthenticatedUser { auth_data }: OptionalAuthenticatedUser,
 ) -> actix_web::Result<impl Responder> {
+    let hidden_tags = visitor_hidden_tags(&data, auth_data.as_deref()).await?;
     let after = DateTime::from_timestamp(after, 0).required()?.naive_utc();
     // TODO: fragments should be served with noindex header
     let Some((after, groups)) = data.services.sticker
-        .get_all_sticker_timeline(100, after)
+        .get_all_sticker_timeline(100, after, hidden_tags)
         .await? else {
             return Ok(html! {"End"})
         };
@@ -1009,16 +1215,17 @@ async fn sticker_timeline_page_fragment(
 }
 
 #[get("/emojis")]
-async fn emojis_page(data: Data<AppState>, req: HttpRequest) -> actix_web::Result<impl Responder> {
+async fn emojis_page(data: Data<AppState>) -> actix_web::Result<impl Responder> {
     let emojis = data.database.get_most_used_emojis(300, 0).await?;
     if emojis.is_empty() {
         return Ok(html! {""});
     }
 
-    let host = format!("{}", req.uri());
-    let title = "fuzzle bot";
-    let desc = "Hi there";
-    let lang = "en";
+    let meta = PageMeta::new(
+        "All Emojis",
+        "All emojis of furry Telegram stickers, most used first",
+        "/emojis",
+    );
 
     let content = html! {
             h1 {
@@ -1035,14 +1242,13 @@ async fn emojis_page(data: Data<AppState>, req: HttpRequest) -> actix_web::Resul
 
     };
 
-    Ok(page(&host, title, desc, lang, content))
+    Ok(page(&data, &meta, content))
 }
 
 #[get("/fragment/emojis/{page}")]
 async fn emojis_page_fragment(
     Path(page): Path<i64>,
     data: Data<AppState>,
-    req: HttpRequest,
 ) -> actix_web::Result<impl Responder> {
     let offset = page * 300; // TODO: higher limit, constant
     let emojis = data.database.get_most_used_emojis(300, offset).await?;
@@ -1064,7 +1270,6 @@ async fn emojis_page_fragment(
 #[get("/timeline/sets")]
 async fn set_timeline_page(
     data: Data<AppState>,
-    req: HttpRequest,
 ) -> actix_web::Result<impl Responder> {
     let Some((after, groups)) = data.services.sticker
         .get_all_sticker_set_timeline(100, chrono::Utc::now().naive_utc())
@@ -1077,10 +1282,12 @@ async fn set_timeline_page(
         })
         .collect_vec();
 
-    let host = format!("{}", req.uri());
-    let title = "fuzzle bot";
-    let desc = "Hi there";
-    let lang = "en";
+    let meta = PageMeta::new(
+        "Sticker Set Timeline",
+        "The newest furry Telegram sticker sets",
+        "/timeline/sets",
+    )
+    .feed("sets");
 
     let content = html! {
             h1 { " Timeline" }
@@ -1091,14 +1298,13 @@ async fn set_timeline_page(
         })))
     };
 
-    Ok(page(&host, title, desc, lang, content))
+    Ok(page(&data, &meta, content))
 }
 
 #[get("/fragment/timeline/sets/{after}")]
 async fn set_timeline_page_fragment(
     Path(after): Path<i64>,
     data: Data<AppState>,
-    req: HttpRequest,
 ) -> actix_web::Result<impl Responder> {
     let after = DateTime::from_timestamp(after, 0).required()?.naive_utc();
     // TODO: fragments should be served with noindex header
@@ -1124,16 +1330,17 @@ async fn set_timeline_page_fragment(
 }
 
 #[get("/tags")]
-async fn tags_page(data: Data<AppState>, req: HttpRequest) -> actix_web::Result<impl Responder> {
+async fn tags_page(data: Data<AppState>) -> actix_web::Result<impl Responder> {
     let tags = data.database.get_popular_tags(300, 0).await?;
     if tags.is_empty() {
         return Ok(html! {""});
     }
 
-    let host = format!("{}", req.uri());
-    let title = "fuzzle bot";
-    let desc = "Hi there";
-    let lang = "en";
+    let meta = PageMeta::new(
+        "All Tags",
+        "All tags of furry Telegram stickers, most used first",
+        "/tags",
+    );
 
     let content = html! {
             h1 {
@@ -1150,14 +1357,13 @@ async fn tags_page(data: Data<AppState>, req: HttpRequest) -> actix_web::Result<
 
     };
 
-    Ok(page(&host, title, desc, lang, content))
+    Ok(page(&data, &meta, content))
 }
 
 #[get("/fragment/tags/{page}")]
 async fn tags_page_fragment(
     Path(page): Path<i64>,
     data: Data<AppState>,
-    req: HttpRequest,
 ) -> actix_web::Result<impl Responder> {
     let offset = page * 100; // TODO: higher limit, constant
     let tags = data.database.get_popular_tags(300, offset).await?;
@@ -1176,17 +1382,3 @@ async fn tags_page_fragment(
     Ok(content)
 }
 
-// #[derive(Debug)]
-// pub enum WebAppStartParameter {
-//     Home,
-//     StickerSetTimeline { set_id: String },
-//     AllStickerTimeline,
-//     AllStickerSetTimeline,
-//     EmojiList,
-//     TagList,
-// }
-
-// impl WebAppStartParameter {
-//     // fn validate(db: Database)
-//     // impl parse + tostring/display
-// }
@@ -0,0 +1,287 @@
+use std::time::Instant;
+
+use actix_web::{
+    error::ErrorBadRequest,
+    get,
+    web::{Data, Query},
+    Responder, Result as ActixResult,
+};
+use itertools::Itertools;
+use maud::{html, Markup};
+use serde::Deserialize;
+
+use crate::{
+    bot::{BotError, InternalError, UserError},
+    database::{Sticker, UserSettings},
+    inline::{search_stickers, InlineQueryData},
+    sticker::find_with_text_embedding,
+    tags::Rating,
+    text::Language,
+    web::shared::AppState,
+};
+
+use super::{
+    AuthData, OptionalAuthenticatedUser, PageMeta, VisitorLanguage, infinite_scroll_trigger, page,
+    sticker_list_item, vary_language,
+};
+
+const SEARCH_PAGE_SIZE: usize = 60;
+/// every semantic search computes a text embedding; anonymous visitors share their limit
+const SEMANTIC_SEARCHES_PER_MINUTE: i64 = 20;
+const ANONYMOUS_SEMANTIC_SEARCHES_PER_MINUTE: i64 = 60;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+enum SearchMode {
+    /// tags and emojis, like `@FuzzleBot fox -gore`
+    #[default]
+    Tags,
+    /// describe the sticker in words
+    Semantic,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct SearchQuery {
+    #[serde(default)]
+    q: String,
+    #[serde(default)]
+    mode: SearchMode,
+    #[serde(default)]
+    page: usize,
+}
+
+impl SearchQuery {
+    /// `None` if the page is too large for the database
+    fn offset(&self) -> Option<usize> {
+        self.page
+            .checked_mul(SEARCH_PAGE_SIZE)
+            .filter(|offset| i64::try_from(*offset).is_ok())
+    }
+
+    fn next_page_url(&self) -> String {
+        let query = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("q", &self.q)
+            .append_pair(
+                "mode",
+                match self.mode {
+                    SearchMode::Tags => "tags",
+                    SearchMode::Semantic => "semantic",
+                },
+            )
+            .append_pair("page", &(self.page + 1).to_string())
+            .finish();
+        format!("/fragment/search?{query}")
+    }
+}
+
+/// settings and blacklist that apply to the visitor's searches; anonymous visitors get the
+/// default blacklist and only see safe stickers
+async fn visitor_search_settings(
+    data: &AppState,
+    auth_data: Option<&AuthData>,
+) -> Result<(UserSettings, Vec<String>), InternalError> {
+    let user = match auth_data {
+        Some(auth_data) => data.database.get_user_by_id(auth_data.id as i64).await?,
+        None => None,
+    };
+    let Some(user) = user else {
+        let settings = UserSettings {
+            rating_profile: Some(Rating::Safe),
+            ..Default::default()
+        };
+        return Ok((settings, data.config.default_blacklist.clone()));
+    };
+    let settings = user.settings.unwrap_or_default();
+    Ok((settings, user.blacklist.to_vec()))
+}
+
+/// `None` if there are no more results
+async fn find_stickers(
+    data: &AppState,
+    query: &SearchQuery,
+    offset: usize,
+    settings: &UserSettings,
+    blacklist: &[String],
+) -> Result<Option<Vec<Sticker>>, BotError> {
+    match query.mode {
+        SearchMode::Tags => {
+            let (tags, emoji) = match InlineQueryData::try_from(query.q.clone())? {
+                InlineQueryData::SearchStickers { tags, emoji } => (tags, emoji),
+                _ => {
+                    return Err(UserError::ValidationError(
+                        "the search must only contain tags and emojis".to_string(),
+                    )
+                    .into());
+                }
+            };
+            let stickers = search_stickers(
+                tags,
+                emoji,
+                settings,
+                blacklist,
+                data.database.clone(),
+                data.tag_manager.clone(),
+                SEARCH_PAGE_SIZE,
+                offset,
+                0,
+            )
+            .await?;
+            Ok((!stickers.is_empty()).then_some(stickers))
+        }
+        SearchMode::Semantic => {
+            let (matches, original_result_len) = find_with_text_embedding(
+                data.database.clone(),
+                query.q.clone(),
+                data.vector_db.clone(),
+                data.config.clone(),
+                SEARCH_PAGE_SIZE,
+                offset,
+            )
+            .await?;
+            if original_result_len == 0 {
+                return Ok(None);
+            }
+            let stickers = data
+                .services
+                .similarity
+                .matches_to_stickers(matches)
+                .await?
+                .into_iter()
+                .map(|(sticker, _)| sticker)
+                .collect_vec();
+            // hidden stickers can empty a page that is followed by more results
+            Ok(Some(
+                data.services
+                    .sticker
+                    .filter_stickers_by_settings(stickers, settings, blacklist)
+                    .await?,
+            ))
+        }
+    }
+}
+
+/// sticker items followed by the trigger that loads the next page
+async fn search_results(
+    data: &AppState,
+    query: &SearchQuery,
+    offset: usize,
+    auth_data: Option<&AuthData>,
+    language: Language,
+) -> Result<Markup, BotError> {
+    if query.mode == SearchMode::Semantic {
+        let (id, requests_per_minute) = auth_data.map_or(
+            (0, ANONYMOUS_SEMANTIC_SEARCHES_PER_MINUTE),
+            |auth_data| (auth_data.id as i64, SEMANTIC_SEARCHES_PER_MINUTE),
+        );
+        if let Err(retry_after) = data.services.semantic_search_rate_limiter.check(
+            id,
+            requests_per_minute,
+            Instant::now(),
+        ) {
+            return Ok(html! {
+                p class="notification" {
+                    (language.text("web.search.rate_limited", &[("seconds", (retry_after as i64).into())]))
+                }
+            });
+        }
+    }
+    let (settings, blacklist) = visitor_search_settings(data, auth_data).await?;
+    let Some(stickers) = find_stickers(data, query, offset, &settings, &blacklist).await? else {
+        return Ok(html! {
+            @if query.page == 0 {
+                p { (language.text("web.search.no_results", &[])) }
+            }
+        });
+    };
+    Ok(html! {
+        @for sticker in &stickers {
+            (sticker_list_item(&sticker.id))
+        }
+        (infinite_scroll_trigger(query.next_page_url(), "this"))
+    })
+}
+
+/// user errors (unknown tags, invalid syntax) are shown next to the search box
+fn error_message(err: BotError, language: Language) -> ActixResult<Markup> {
+    match err {
+        BotError::UserError(err) => {
+            let (message, _) = err.end_user_error(language);
+            Ok(html! { p class="notification" { (message) } })
+        }
+        BotError::InternalError(err) => Err(err.into()),
+    }
+}
+
+#[get("/search")]
+#[tracing::instrument(skip(data, auth_data))]
+async fn search_page(
+    Query(query): Query<SearchQuery>,
+    data: Data<AppState>,
+    OptionalAuthenticatedUser { auth_data }: OptionalAuthenticatedUser,
+    VisitorLanguage(language): VisitorLanguage,
+) -> actix_web::Result<impl Responder> {
+    let meta = PageMeta::new(
+        language.text("web.search.title", &[]),
+        language.text("web.search.description", &[]),
+        "/search",
+    )
+    .language(language)
+    .noindex();
+
+    let query = SearchQuery { page: 0, ..query };
+    let results = if query.q.trim().is_empty() {
+        html! {}
+    } else {
+        match search_results(&data, &query, 0, auth_data.as_deref(), language).await {
+            Ok(results) => html! {
+                div class="grid" {
+                    (results)
+                }
+            },
+            Err(err) => error_message(err, language)?,
+        }
+    };
+
+    let content = html! {
+        #content {
+            h1 { (meta.title) }
+
+            form action="/search" method="get" {
+                input type="search" name="q" value=(query.q) placeholder="fox hug -gore ..." autofocus;
+                select name="mode" {
+                    option value="tags" selected[query.mode == SearchMode::Tags] {
+                        (language.text("web.search.mode.tags", &[]))
+                    }
+                    option value="semantic" selected[query.mode == SearchMode::Semantic] {
+                        (language.text("web.search.mode.semantic", &[]))
+                    }
+                }
+                button type="submit" { (language.text("web.search.submit", &[])) }
+            }
+            p {
+                small { (language.text("web.search.help", &[])) }
+            }
+
+            (results)
+        }
+    };
+
+    Ok(vary_language(page(&data, &meta, content), auth_data.as_deref()))
+}
+
+#[get("/fragment/search")]
+#[tracing::instrument(skip(data, auth_data))]
+async fn search_page_fragment(
+    Query(query): Query<SearchQuery>,
+    data: Data<AppState>,
+    OptionalAuthenticatedUser { auth_data }: OptionalAuthenticatedUser,
+    VisitorLanguage(language): VisitorLanguage,
+) -> actix_web::Result<impl Responder> {
+    // TODO: fragments should be served with noindex header
+    let offset = query.offset().ok_or_else(|| ErrorBadRequest("page is too large"))?;
+    let results = match search_results(&data, &query, offset, auth_data.as_deref(), language).await {
+        Ok(results) => results,
+        Err(err) => error_message(err, language)?,
+    };
+    Ok(vary_language(results, auth_data.as_deref()))
+}
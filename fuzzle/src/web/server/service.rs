@@ -17,6 +17,7 @@ use crate::{
     sticker::{
         create_historgram_image, create_sticker_thumbnail, fetch_sticker_file, generate_merge_image,
     },
+    tags::Rating,
     util::{Required, StickerId, StickerSetId}, web::{server::WebAppInitData, shared::{AppState, HOUR, thumbnail_cache_control_header}},
 };
 use web::Data;
@@ -71,11 +72,44 @@ async fn sticker_set_thumbnail(
     Path(set_id): Path<StickerSetId>,
     data: Data<AppState>,
 ) -> actix_web::Result<impl Responder> {
-    let set = data.database.get_sticker_set_by_id(&set_id).await?.required()?;
+    set_thumbnail(&set_id, &data, None).await
+}
+
+/// only contains safe stickers; used for link previews because they are shown to everyone
+#[actix_web::get("/thumbnails/sticker-set/{setId}/safe.png")]
+#[tracing::instrument(skip(data))]
+async fn safe_sticker_set_thumbnail(
+    Path(set_id): Path<StickerSetId>,
+    data: Data<AppState>,
+) -> actix_web::Result<impl Responder> {
+    set_thumbnail(&set_id, &data, Some(Rating::Safe)).await
+}
+
+/// path of the preview image of a set for `PageMeta::image` and feeds
+pub fn safe_set_thumbnail(set_id: &StickerSetId) -> String {
+    format!("/thumbnails/sticker-set/{set_id}/safe.png")
+}
+
+async fn set_thumbnail(
+    set_id: &StickerSetId,
+    data: &AppState,
+    rating_profile: Option<Rating>,
+) -> actix_web::Result<HttpResponse> {
+    let set = data.database.get_sticker_set_by_id(set_id).await?.required()?;
     if set.last_fetched.is_none() {
         return Ok(HttpResponse::InternalServerError().finish());
     }
-    let stickers = data.database.get_all_stickers_in_set(&set_id).await?;
+    let mut stickers = data.database.get_all_stickers_in_set(set_id).await?;
+    if let Some(rating_profile) = rating_profile {
+        stickers = data
+            .services
+            .sticker
+            .filter_stickers_by_rating_profile(stickers, rating_profile)
+            .await?;
+        if stickers.is_empty() {
+            return Err(ErrorNotFound("no stickers with this rating"));
+        }
+    }
     let files = data
         .database
         .get_sticker_files_by_ids(
@@ -170,6 +204,12 @@ async fn login_webapp(
     if !init_data.check(data.config.telegram_bot_token.clone()) {
         return Err(ErrorUnauthorized("invalid web app data"));
     }
+    // only pages of the mini app are allowed, anything else could redirect to other sites
+    let redirect = init_data
+        .redirect
+        .clone()
+        .filter(|redirect| redirect.starts_with("/app") && !redirect.contains("//"))
+        .unwrap_or_else(|| "/app".to_string());
     let auth_data = init_data.0.clone().into_auth_data(data.config.telegram_bot_token.clone())?;
     let cookie = Cookie::build(AUTH_COOKIE_NAME, serde_json::to_string(&auth_data)?)
         .max_age(Duration::DAY * 30)
@@ -180,7 +220,7 @@ async fn login_webapp(
 
     Ok(HttpResponse::Ok()
         .cookie(cookie)
-        .insert_header((header::LOCATION, "/"))
+        .insert_header((header::LOCATION, redirect))
         .status(actix_web::http::StatusCode::TEMPORARY_REDIRECT)
         .finish())
 }
@@ -9,7 +9,7 @@ use crate::{
     background_tasks::{TagManagerService, TfIdfService}, bot::Bot, database::Database, qdrant::VectorDatabase, web::server::page, Config
 };
 
-use super::service;
+use super::{api, creator, editor, feed, search, service, sitemap, webapp, wiki};
 
 pub fn setup_public_server(
     config: Arc<Config>,
@@ -44,24 +44,61 @@ pub fn setup_public_server(
                 .service(service::login_webapp)
                 .service(service::logout)
                 .service(service::favicon)
+                .service((sitemap::robots_txt, sitemap::sitemap_index, sitemap::sitemap))
+                .service((
+                    feed::new_sets_feed,
+                    feed::new_stickers_feed,
+                    feed::set_changes_feed,
+                    feed::tag_feed,
+                    feed::set_feed,
+                    feed::followed_tags_feed,
+                ))
                 .service(service::asset_folder)
                 .service(service::sticker_files)
                 // .service(service::merge_files)
                 .service(service::sticker_set_thumbnail)
+                .service(service::safe_sticker_set_thumbnail)
                 .service(service::sticker_comparison_thumbnail)
+                .service(api::openapi_description)
+                .service((
+                    api::search_stickers,
+                    api::get_sticker,
+                    api::get_similar_stickers,
+                    api::get_sticker_set,
+                    api::search_tags,
+                    api::get_tag,
+                ))
+                .service((editor::tag_sticker, editor::untag_sticker, editor::tag_sticker_set))
                 .service(page::index)
                 .service(page::search_tags)
                 .service(page::sticker_set)
+                .service(page::collection_page)
                 .service(page::sticker_page)
                 .service(page::tag_page)
+                .service((wiki::tag_wiki_page, wiki::update_tag_description))
+                .service((
+                    creator::creator_page,
+                    creator::claim_creator_page,
+                    creator::update_creator_page,
+                    creator::reset_creator_page,
+                ))
+                .service((
+                    compare::set_comparison_page,
+                    compare::near_duplicates_fragment,
+                    compare::merge_near_duplicates,
+                ))
                 .service(page::emoji_page)
-                // .service(page::webapp_entrypoint)
+                .service(webapp::webapp_entrypoint)
+                .service((webapp::app_home, webapp::app_sticker_set, webapp::app_tag_stickers))
+                .service(webapp::app_similar_stickers)
+                .service((webapp::app_settings, webapp::app_update_settings))
                 .service(page::sticker_set_timeline_page)
                 .service(page::sticker_timeline_page)
                 .service(page::sticker_timeline_page_fragment)
                 .service((page::emojis_page, page::emojis_page_fragment))
                 .service((page::set_timeline_page, page::set_timeline_page_fragment))
                 .service((page::tags_page, page::tags_page_fragment))
+                .service((search::search_page, search::search_page_fragment))
                 // TODO: assets should be the default route
                 .default_service(route().to(page::not_found))
                 .wrap(middleware::Compress::default())
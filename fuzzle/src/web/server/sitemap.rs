@@ -0,0 +1,161 @@
+use actix_web::{
+    error::ErrorNotFound,
+    get,
+    http::header::ContentType,
+    web::Data,
+    HttpResponse, Responder, Result as ActixResult,
+};
+use actix_web_lab::extract::Path;
+use chrono::NaiveDateTime;
+use itertools::Itertools;
+use maud::{html, Markup, PreEscaped};
+use serde::Deserialize;
+
+use crate::web::shared::AppState;
+
+use super::canonical_url;
+
+/// search engines accept up to 50000 urls per sitemap
+const SITEMAP_PAGE_SIZE: i64 = 10_000;
+const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+const SITEMAP_NAMESPACE: &str = "http://www.sitemaps.org/schemas/sitemap/0.9";
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum SitemapKind {
+    Pages,
+    Sets,
+    Tags,
+    Emojis,
+}
+
+impl SitemapKind {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pages => "pages",
+            Self::Sets => "sets",
+            Self::Tags => "tags",
+            Self::Emojis => "emojis",
+        }
+    }
+}
+
+fn xml_response(markup: Markup) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(ContentType::xml())
+        .body(markup.into_string())
+}
+
+fn page_count(count: i64) -> i64 {
+    (count + SITEMAP_PAGE_SIZE - 1) / SITEMAP_PAGE_SIZE
+}
+
+#[get("/robots.txt")]
+async fn robots_txt(data: Data<AppState>) -> impl Responder {
+    let sitemap = canonical_url(&data.config.domain_name, "/sitemap.xml");
+    HttpResponse::Ok()
+        .content_type(ContentType::plaintext())
+        .body(format!(
+            "User-agent: *\n\
+             Disallow: /api/\n\
+             Disallow: /app\n\
+             Disallow: /webapp\n\
+             Disallow: /fragment/\n\
+             Disallow: /login\n\
+             Disallow: /search\n\
+             Disallow: /compare/\n\
+             \n\
+             Sitemap: {sitemap}\n"
+        ))
+}
+
+/// index of all sitemaps; sets, tags and emojis are split into pages
+#[get("/sitemap.xml")]
+#[tracing::instrument(skip(data))]
+async fn sitemap_index(data: Data<AppState>) -> ActixResult<impl Responder> {
+    let stats = data.database.get_sitemap_stats().await?;
+    let sitemaps = std::iter::once((SitemapKind::Pages, 0))
+        .chain((0..page_count(stats.sets)).map(|page| (SitemapKind::Sets, page)))
+        .chain((0..page_count(stats.tags)).map(|page| (SitemapKind::Tags, page)))
+        .chain((0..page_count(stats.emojis)).map(|page| (SitemapKind::Emojis, page)))
+        .map(|(kind, page)| {
+            canonical_url(
+                &data.config.domain_name,
+                &format!("/sitemaps/{}/{page}", kind.as_str()),
+            )
+        })
+        .collect_vec();
+    Ok(xml_response(html! {
+        (PreEscaped(XML_DECLARATION))
+        sitemapindex xmlns=(SITEMAP_NAMESPACE) {
+            @for sitemap in &sitemaps {
+                sitemap { loc { (sitemap) } }
+            }
+        }
+    }))
+}
+
+#[get("/sitemaps/{kind}/{page}")]
+#[tracing::instrument(skip(data))]
+async fn sitemap(
+    Path((kind, page)): Path<(SitemapKind, i64)>,
+    data: Data<AppState>,
+) -> ActixResult<impl Responder> {
+    // only the pages that are listed in the index exist
+    let stats = data.database.get_sitemap_stats().await?;
+    let count = match kind {
+        SitemapKind::Pages => 1,
+        SitemapKind::Sets => page_count(stats.sets),
+        SitemapKind::Tags => page_count(stats.tags),
+        SitemapKind::Emojis => page_count(stats.emojis),
+    };
+    if !(0..count).contains(&page) {
+        return Err(ErrorNotFound("sitemap not found"));
+    }
+    let offset = page * SITEMAP_PAGE_SIZE;
+    let urls: Vec<(String, Option<NaiveDateTime>)> = match kind {
+        SitemapKind::Pages => ["/", "/tags", "/emojis", "/timeline/stickers", "/timeline/sets"]
+            .into_iter()
+            .map(|path| (path.to_string(), None))
+            .collect_vec(),
+        SitemapKind::Sets => data
+            .database
+            .get_public_sticker_set_ids(SITEMAP_PAGE_SIZE, offset)
+            .await?
+            .into_iter()
+            .map(|(set_id, last_fetched)| (format!("/set/{set_id}"), last_fetched))
+            .collect_vec(),
+        SitemapKind::Tags => data
+            .database
+            .get_popular_tags(SITEMAP_PAGE_SIZE, offset)
+            .await?
+            .into_iter()
+            .flat_map(|tag| {
+                [
+                    (format!("/tag/{}", tag.name), None),
+                    (format!("/wiki/{}", tag.name), None),
+                ]
+            })
+            .collect_vec(),
+        SitemapKind::Emojis => data
+            .database
+            .get_most_used_emojis(SITEMAP_PAGE_SIZE, offset)
+            .await?
+            .into_iter()
+            .map(|(emoji, _)| (format!("/emoji/{}", emoji.to_string_without_variant()), None))
+            .collect_vec(),
+    };
+    Ok(xml_response(html! {
+        (PreEscaped(XML_DECLARATION))
+        urlset xmlns=(SITEMAP_NAMESPACE) {
+            @for (path, last_modified) in &urls {
+                url {
+                    loc { (canonical_url(&data.config.domain_name, path)) }
+                    @if let Some(last_modified) = last_modified {
+                        lastmod { (last_modified.format("%Y-%m-%d")) }
+                    }
+                }
+            }
+        }
+    }))
+}
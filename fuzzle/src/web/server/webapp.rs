@@ -0,0 +1,504 @@
+use actix_web::{
+    error::ErrorBadRequest,
+    get, post,
+    web::{Data, Form, Query},
+    Responder, Result as ActixResult,
+};
+use actix_web_lab::extract::Path;
+use itertools::Itertools;
+use maud::{html, Markup, PreEscaped, DOCTYPE};
+use serde::Deserialize;
+
+use crate::{
+    database::{Capability, StickerOrder, User},
+    inline::SimilarityAspect,
+    tags::Rating,
+    util::{Required, StickerId, StickerSetId},
+    web::shared::AppState,
+};
+
+use super::{
+    editor::{editor_user, tag_input},
+    AuthenticatedUser,
+};
+
+/// opened by telegram; logs the user in with the init data from the location hash and
+/// continues with the page from the `redirect` query parameter
+#[get("/webapp")]
+async fn webapp_entrypoint() -> impl Responder {
+    html! {
+        (DOCTYPE)
+        html lang="en" {
+            head {
+                meta charset="utf-8";
+                title { "Loading" }
+                script { (PreEscaped(WEBAPP_LOGIN_SCRIPT)) }
+            }
+            body {
+                p { "Loading ..." }
+            }
+        }
+    }
+}
+
+/// pages of the mini app only make sense in the telegram client; they use the telegram theme
+/// and are kept narrow
+fn app_page(title: &str, content: Markup) -> Markup {
+    html! {
+        (DOCTYPE)
+        html lang="en" {
+            head {
+                meta charset="utf-8";
+                title { (title) }
+                meta name="viewport" content="width=device-width, initial-scale=1.0, maximum-scale=1.0, user-scalable=0";
+                meta name="robots" content="noindex";
+                link rel="stylesheet" href="/assets/css/normalize.css";
+                link rel="stylesheet" href="/assets/css/main.css";
+                script src="https://telegram.org/js/telegram-web-app.js" {}
+            }
+            body class="webapp" hx-boost="true" {
+                nav class="webapp-nav" {
+                    a href="/app" { "🏠 Home" }
+                    a href="/app/settings" { "⚙️ Settings" }
+                }
+                div #loading {}
+                #content {
+                    h1 { (title) }
+                    (content)
+                }
+                script src="/assets/js/vendor/htmx.min.js" {}
+                script { (PreEscaped(WEBAPP_THEME_SCRIPT)) }
+            }
+        }
+    }
+}
+
+async fn app_user(data: &AppState, auth_data: &super::AuthData) -> ActixResult<User> {
+    Ok(data
+        .database
+        .get_user_by_id(auth_data.id as i64)
+        .await?
+        .required()?)
+}
+
+fn rating_profile(user: &User) -> Rating {
+    user.settings.clone().unwrap_or_default().rating_profile()
+}
+
+#[get("/app")]
+#[tracing::instrument(skip(data))]
+async fn app_home(
+    data: Data<AppState>,
+    AuthenticatedUser { auth_data }: AuthenticatedUser,
+) -> ActixResult<Markup> {
+    let user = app_user(&data, &auth_data).await?;
+    let sets = data.database.get_owned_sticker_sets(user.id, 50, 0).await?;
+    Ok(app_page(
+        "FuzzleBot",
+        html! {
+            p { "Open a sticker set from the bot to browse and tag it here." }
+            @if !sets.is_empty() {
+                h2 { "Your sets" }
+                ul {
+                    @for set in &sets {
+                        li { a href={ "/app/set/" (set.id) } { (set.title_or_id()) } }
+                    }
+                }
+            }
+        },
+    ))
+}
+
+#[get("/app/set/{setId}")]
+#[tracing::instrument(skip(data))]
+async fn app_sticker_set(
+    Path(set_id): Path<StickerSetId>,
+    data: Data<AppState>,
+    AuthenticatedUser { auth_data }: AuthenticatedUser,
+) -> ActixResult<Markup> {
+    let user = app_user(&data, &auth_data).await?;
+    let set = data
+        .database
+        .get_sticker_set_by_id(&set_id)
+        .await?
+        .required()?;
+    let stickers = data.database.get_all_stickers_in_set(&set.id).await?;
+    let stickers = data
+        .services
+        .sticker
+        .filter_stickers_by_rating_profile(stickers, rating_profile(&user))
+        .await?;
+    let can_tag = editor_user(&data, &auth_data, Capability::TagStickers)
+        .await
+        .is_ok();
+
+    Ok(app_page(
+        &set.title_or_id(),
+        html! {
+            form class="webapp-set" hx-post={ "/app/set/" (set.id) "/tags" } hx-target="#app-notification" {
+                div class="grid" {
+                    @for sticker in &stickers {
+                        label class="webapp-sticker" {
+                            @if can_tag {
+                                input type="checkbox" name="sticker" value=(sticker.id);
+                            }
+                            img loading="lazy" class="sticker-thumbnail" src={ "/files/stickers/" (sticker.id) "/thumbnail.png" };
+                            a href={ "/app/sticker/" (sticker.id) "/similar" } { "similar" }
+                        }
+                    }
+                }
+                @if can_tag {
+                    div class="webapp-toolbar" {
+                        button type="button" onclick="toggleAllStickers(this.form)" { "select all" }
+                        (tag_input("tags", "Tags for the selected stickers (comma separated) ..."))
+                        button type="submit" name="operation" value="tag" { "tag" }
+                        button type="submit" name="operation" value="untag" { "untag" }
+                    }
+                }
+                p #app-notification {}
+            }
+        },
+    ))
+}
+
+/// `sticker` is repeated for every selected sticker, which the regular form extractor can not
+/// handle
+fn parse_set_tag_form(body: &str) -> (Vec<StickerId>, Vec<String>, Option<String>) {
+    let mut sticker_ids = Vec::new();
+    let mut tags = Vec::new();
+    let mut operation = None;
+    for (key, value) in url::form_urlencoded::parse(body.as_bytes()) {
+        match key.as_ref() {
+            "sticker" => sticker_ids.push(StickerId::new(value)),
+            "tags" => {
+                tags = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|tag| !tag.is_empty())
+                    .map(str::to_string)
+                    .collect_vec();
+            }
+            "operation" => operation = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    (sticker_ids, tags, operation)
+}
+
+#[post("/app/set/{setId}/tags")]
+#[tracing::instrument(skip(data, body))]
+async fn app_tag_stickers(
+    Path(set_id): Path<StickerSetId>,
+    body: String,
+    data: Data<AppState>,
+    AuthenticatedUser { auth_data }: AuthenticatedUser,
+) -> ActixResult<Markup> {
+    let (sticker_ids, tags, operation) = parse_set_tag_form(&body);
+    if sticker_ids.is_empty() {
+        return Ok(html! { "Select some stickers first" });
+    }
+    let matched = data.tag_manager.closest_matching_tags(&tags).await;
+    let unknown = matched
+        .iter()
+        .filter(|(_, tag)| tag.is_none())
+        .map(|(input, _)| input.clone())
+        .collect_vec();
+    if !unknown.is_empty() {
+        return Ok(html! { "Unknown tags: " (unknown.join(", ")) });
+    }
+    let tags = matched.into_iter().filter_map(|(_, tag)| tag).collect_vec();
+    if tags.is_empty() {
+        return Ok(html! { "Enter some tags first" });
+    }
+
+    let stickers = data.database.get_all_stickers_in_set(&set_id).await?;
+    let set_file_count = stickers
+        .iter()
+        .map(|sticker| &sticker.sticker_file_id)
+        .unique()
+        .count();
+    let file_ids = stickers
+        .into_iter()
+        .filter(|sticker| sticker_ids.contains(&sticker.id))
+        .map(|sticker| sticker.sticker_file_id)
+        .unique()
+        .collect_vec();
+    // changing several stickers at once is the same as tagging the set
+    let capability = if file_ids.len() > 1 || file_ids.len() == set_file_count {
+        Capability::TagSets
+    } else {
+        Capability::TagStickers
+    };
+    let user = editor_user(&data, &auth_data, capability).await?;
+    match operation.as_deref() {
+        Some("tag") => {
+            let added = data
+                .services
+                .tagging
+                .tag_files(&file_ids, &tags, user.id, &data.tag_manager, &data.tfidf_service)
+                .await?
+                .map(|(added, _)| added)
+                .unwrap_or_default();
+            Ok(html! { "Added " (added.join(", ")) " to " (file_ids.len()) " stickers" })
+        }
+        Some("untag") => {
+            for file_id in &file_ids {
+                for tag in &tags {
+                    data.services
+                        .tagging
+                        .untag_file(file_id, tag, user.id, &data.tag_manager)
+                        .await?;
+                }
+            }
+            Ok(html! { "Removed " (tags.join(", ")) " from " (file_ids.len()) " stickers" })
+        }
+        _ => Err(ErrorBadRequest("unknown operation")),
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+enum ExploreAspect {
+    #[default]
+    Embedding,
+    Color,
+}
+
+impl ExploreAspect {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Embedding => "embedding",
+            Self::Color => "color",
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct ExploreQuery {
+    #[serde(default)]
+    aspect: ExploreAspect,
+}
+
+#[get("/app/sticker/{stickerId}/similar")]
+#[tracing::instrument(skip(data))]
+async fn app_similar_stickers(
+    Path(sticker_id): Path<StickerId>,
+    Query(query): Query<ExploreQuery>,
+    data: Data<AppState>,
+    AuthenticatedUser { auth_data }: AuthenticatedUser,
+) -> ActixResult<Markup> {
+    let user = app_user(&data, &auth_data).await?;
+    let sticker = data
+        .database
+        .get_sticker_by_id(&sticker_id)
+        .await?
+        .required()?;
+    let aspect = match query.aspect {
+        ExploreAspect::Embedding => SimilarityAspect::Embedding,
+        ExploreAspect::Color => SimilarityAspect::Color,
+    };
+    let (matches, _) = data
+        .services
+        .similarity
+        .find_similar_stickers(sticker_id.clone(), aspect, 60, 0)
+        .await?;
+    let similar = data
+        .services
+        .similarity
+        .matches_to_stickers(matches)
+        .await?
+        .into_iter()
+        .map(|(sticker, _)| sticker)
+        .filter(|similar| similar.id != sticker_id)
+        .collect_vec();
+    let similar = data
+        .services
+        .sticker
+        .filter_stickers_by_rating_profile(similar, rating_profile(&user))
+        .await?;
+
+    Ok(app_page(
+        "Similar stickers",
+        html! {
+            div class="webapp-explore" {
+                img class="sticker-thumbnail" src={ "/files/stickers/" (sticker.id) "/thumbnail.png" };
+                a href={ "/app/set/" (sticker.sticker_set_id) } { "open set" }
+            }
+            nav class="webapp-tabs" {
+                a href={ "/app/sticker/" (sticker.id) "/similar?aspect=embedding" }
+                    class=[(query.aspect == ExploreAspect::Embedding).then_some("selected")] { "Content" }
+                a href={ "/app/sticker/" (sticker.id) "/similar?aspect=color" }
+                    class=[(query.aspect == ExploreAspect::Color).then_some("selected")] { "Color" }
+            }
+            div class="grid" {
+                @for similar in &similar {
+                    a href={ "/app/sticker/" (similar.id) "/similar?aspect=" (query.aspect.as_str()) } {
+                        img loading="lazy" class="sticker-thumbnail" src={ "/files/stickers/" (similar.id) "/thumbnail.png" };
+                    }
+                }
+            }
+        },
+    ))
+}
+
+fn settings_form(user: &User, notification: Option<&str>) -> Markup {
+    let settings = user.settings.clone().unwrap_or_default();
+    let rating = rating_profile(user);
+    html! {
+        form #settings-form hx-post="/app/settings" hx-target="this" hx-swap="outerHTML" {
+            label {
+                "Content rating "
+                select name="rating" {
+                    @for profile in [Rating::Safe, Rating::Questionable, Rating::Explicit] {
+                        option value=(profile as u8) selected[profile == rating] {
+                            (profile.to_emoji()) " " (profile)
+                        }
+                    }
+                }
+            }
+            label {
+                "Sticker order "
+                select name="order" {
+                    option value=(StickerOrder::LatestFirst as u8) selected[settings.order() == StickerOrder::LatestFirst] { "Latest first" }
+                    option value=(StickerOrder::Random as u8) selected[settings.order() == StickerOrder::Random] { "Random" }
+                }
+            }
+            p { "Blacklist: " (user.blacklist.join(", ")) }
+            button type="submit" { "save" }
+            @if let Some(notification) = notification {
+                p class="notification" { (notification) }
+            }
+        }
+    }
+}
+
+#[get("/app/settings")]
+#[tracing::instrument(skip(data))]
+async fn app_settings(
+    data: Data<AppState>,
+    AuthenticatedUser { auth_data }: AuthenticatedUser,
+) -> ActixResult<Markup> {
+    let user = app_user(&data, &auth_data).await?;
+    Ok(app_page("Settings", settings_form(&user, None)))
+}
+
+#[derive(Deserialize, Debug)]
+struct SettingsForm {
+    rating: Rating,
+    order: StickerOrder,
+}
+
+#[post("/app/settings")]
+#[tracing::instrument(skip(data))]
+async fn app_update_settings(
+    form: Form<SettingsForm>,
+    data: Data<AppState>,
+    AuthenticatedUser { auth_data }: AuthenticatedUser,
+) -> ActixResult<Markup> {
+    let mut user = app_user(&data, &auth_data).await?;
+    let mut settings = user.settings.clone().unwrap_or_default();
+    settings.rating_profile = Some(form.rating);
+    settings.order = Some(form.order);
+    data.database.update_settings(user.id, &settings).await?;
+    user.settings = Some(settings);
+    Ok(settings_form(&user, Some("Saved")))
+}
+
+const WEBAPP_THEME_SCRIPT: &str = r#"
+if (window.Telegram && Telegram.WebApp) {
+    Telegram.WebApp.ready();
+    Telegram.WebApp.expand();
+    const theme = Telegram.WebApp.themeParams;
+    if (theme.bg_color) document.documentElement.style.setProperty('--background', theme.bg_color);
+    if (theme.text_color) document.documentElement.style.setProperty('--foreground', theme.text_color);
+}
+
+function toggleAllStickers(form) {
+    const checkboxes = form.querySelectorAll('input[name="sticker"]');
+    const check = Array.from(checkboxes).some((checkbox) => !checkbox.checked);
+    checkboxes.forEach((checkbox) => checkbox.checked = check);
+}
+"#;
+
+const WEBAPP_LOGIN_SCRIPT: &str = r#"
+var locationHash = '';
+try {
+    locationHash = location.hash.toString();
+} catch (e) {}
+
+var initParams = urlParseHashParams(locationHash);
+sessionStorageSet('initParams', initParams);
+
+submitTelegramLogin(initParams.tgWebAppData);
+
+ function submitTelegramLogin(initData) {
+
+            if (!initData) {
+                console.error('No initData available - are you running this outside of a Telegram Web App?');
+                return;
+            }
+
+            const redirect = new URLSearchParams(window.location.search).get('redirect') || '/app';
+            // Create the URL with the initData as a query parameter
+            const loginUrl = `/login-webapp?${initData}&redirect=${encodeURIComponent(redirect)}`;
+
+            // Redirect to the login endpoint
+            window.location.href = loginUrl;
+        }
+
+function urlParseHashParams(locationHash) {
+    locationHash = locationHash.replace(/^#/, '');
+    var params = {};
+    if (!locationHash.length) {
+      return params;
+    }
+    if (locationHash.indexOf('=') < 0 && locationHash.indexOf('?') < 0) {
+      params._path = urlSafeDecode(locationHash);
+      return params;
+    }
+    var qIndex = locationHash.indexOf('?');
+    if (qIndex >= 0) {
+      var pathParam = locationHash.substr(0, qIndex);
+      params._path = urlSafeDecode(pathParam);
+      locationHash = locationHash.substr(qIndex + 1);
+    }
+    var query_params = urlParseQueryString(locationHash);
+    for (var k in query_params) {
+      params[k] = query_params[k];
+    }
+    return params;
+  }
+
+  function urlParseQueryString(queryString) {
+    var params = {};
+    if (!queryString.length) {
+      return params;
+    }
+    var queryStringParams = queryString.split('&');
+    var i, param, paramName, paramValue;
+    for (i = 0; i < queryStringParams.length; i++) {
+      param = queryStringParams[i].split('=');
+      paramName = urlSafeDecode(param[0]);
+      paramValue = param[1] == null ? null : urlSafeDecode(param[1]);
+      params[paramName] = paramValue;
+    }
+    return params;
+  }
+
+function urlSafeDecode(urlencoded) {
+    try {
+      urlencoded = urlencoded.replace(/\+/g, '%20');
+      return decodeURIComponent(urlencoded);
+    } catch (e) {
+      return urlencoded;
+    }
+  }
+    function sessionStorageSet(key, value) {
+    try {
+      window.sessionStorage.setItem('__uwu__' + key, JSON.stringify(value));
+      window.localStorage.setItem('__uwu__' + key, JSON.stringify(value));
+      return true;
+    } catch(e) {}
+    return false;
+    };
+"#;
@@ -0,0 +1,190 @@
+use actix_web::{
+    error::ErrorNotFound,
+    get, post,
+    web::{Data, Form},
+    Responder, Result as ActixResult,
+};
+use actix_web_lab::extract::Path;
+use maud::{html, Markup};
+use serde::Deserialize;
+
+use crate::{database::Capability, services::TagWiki, tags::Rating, web::shared::AppState};
+
+use super::{
+    editor::{can_edit, editor_user},
+    page, page::visitor_hidden_tags, sticker_list_item, AuthenticatedUser,
+    OptionalAuthenticatedUser, PageMeta,
+};
+
+/// the description with a form to change it for users that may edit descriptions
+fn description_editor(wiki: &TagWiki, editable: bool, notification: Option<String>) -> Markup {
+    html! {
+        div id="wiki-description" {
+            @match &wiki.description {
+                Some(description) => {
+                    p class="wiki-description" { (description.description) }
+                    p class="wiki-updated" {
+                        "Last edited on " (description.updated_at.format("%Y-%m-%d"))
+                    }
+                },
+                None => p { "This tag has no description yet." },
+            }
+            @if editable {
+                form hx-post={ "/wiki/" (wiki.tag) } hx-target="#wiki-description" hx-swap="outerHTML" {
+                    textarea name="description" rows="6" placeholder="What does this tag mean, and when should it be used?" {
+                        (wiki.description.as_ref().map(|description| description.description.as_str()).unwrap_or_default())
+                    }
+                    button type="submit" { "save" }
+                }
+            }
+            @if let Some(notification) = notification {
+                p class="notification" { (notification) }
+            }
+        }
+    }
+}
+
+/// like `tag_list_item`, but links to the wiki page of the tag instead of its stickers
+fn wiki_tag_link(data: &AppState, tag: &str) -> Markup {
+    html! {
+        a class="tag" style={"--foreground: "(data.tag_manager.get_category(tag).unwrap_or_default().to_color_name())";"} href={ "/wiki/" (tag) } {
+            (tag)
+        }
+    }
+}
+
+/// stickers per month as bars, relative to the busiest month
+fn usage_chart(wiki: &TagWiki) -> Markup {
+    let max = wiki.usage.iter().map(|usage| usage.count).max().unwrap_or(1).max(1);
+    html! {
+        table class="wiki-usage" {
+            @for usage in &wiki.usage {
+                tr {
+                    td { (usage.month) }
+                    td class="wiki-usage-bar" {
+                        div style={ "width: " (usage.count * 100 / max) "%;" } {}
+                    }
+                    td { (usage.count) }
+                }
+            }
+        }
+    }
+}
+
+#[get("/wiki/{tagId}")]
+#[tracing::instrument(skip(data, auth_data))]
+async fn tag_wiki_page(
+    Path(tag_id): Path<String>,
+    data: Data<AppState>,
+    OptionalAuthenticatedUser { auth_data }: OptionalAuthenticatedUser,
+) -> ActixResult<impl Responder> {
+    let wiki = data
+        .services
+        .tag_wiki
+        .wiki(&tag_id, &data.tag_manager, visitor_hidden_tags(&data, auth_data.as_deref()).await?)
+        .await?
+        .ok_or_else(|| ErrorNotFound("tag not found"))?;
+    let editable = can_edit(&data, auth_data.as_deref(), Capability::EditTagDescriptions).await;
+
+    let mut meta = PageMeta::new(
+        format!("Tag wiki: {tag_id}"),
+        wiki.description.as_ref().map_or_else(
+            || format!("What the {} tag {tag_id} means on FuzzleBot", wiki.category.to_human_name()),
+            |description| description.description.clone(),
+        ),
+        format!("/wiki/{tag_id}"),
+    );
+    // link previews are shown to everyone
+    let safe_examples = data
+        .services
+        .sticker
+        .filter_stickers_by_rating_profile(wiki.example_stickers.clone(), Rating::Safe)
+        .await?;
+    if let Some(sticker) = safe_examples.first() {
+        meta = meta.image(format!("/files/stickers/{}/thumbnail.png", sticker.id));
+    }
+
+    let content = html! {
+        #content {
+            h1 {
+                "Tag wiki: " (wiki.tag)
+            }
+            p {
+                (wiki.category.to_human_name()) " · used for " (wiki.sticker_count()) " stickers · "
+                a href={ "/tag/" (wiki.tag) } { "show all stickers" }
+            }
+
+            (description_editor(&wiki, editable, None))
+
+            @if !wiki.aliases.is_empty() {
+                h2 { "Aliases" }
+                p { (wiki.aliases.join(", ")) }
+            }
+
+            @if !wiki.implications.is_empty() {
+                h2 { "Implies" }
+                div class="tag-container" {
+                    @for tag in &wiki.implications {
+                        (wiki_tag_link(&data, tag))
+                    }
+                }
+            }
+
+            @if !wiki.inverse_implications.is_empty() {
+                h2 { "Implied by" }
+                div class="tag-container" {
+                    @for tag in &wiki.inverse_implications {
+                        (wiki_tag_link(&data, tag))
+                    }
+                }
+            }
+
+            @if !wiki.example_stickers.is_empty() {
+                h2 { "Examples" }
+                div class="grid" {
+                    @for sticker in &wiki.example_stickers {
+                        (sticker_list_item(&sticker.id))
+                    }
+                }
+            }
+
+            @if !wiki.usage.is_empty() {
+                h2 { "Usage over time" }
+                (usage_chart(&wiki))
+            }
+        }
+    };
+
+    Ok(page(&data, &meta, content))
+}
+
+#[derive(Deserialize)]
+struct DescriptionForm {
+    description: String,
+}
+
+#[post("/wiki/{tagId}")]
+#[tracing::instrument(skip(data, form))]
+async fn update_tag_description(
+    Path(tag_id): Path<String>,
+    form: Form<DescriptionForm>,
+    data: Data<AppState>,
+    AuthenticatedUser { auth_data }: AuthenticatedUser,
+) -> ActixResult<Markup> {
+    let user = editor_user(&data, &auth_data, Capability::EditTagDescriptions).await?;
+    data.services
+        .tag_wiki
+        .update_description(&user, &tag_id, &form.description, &data.tag_manager)
+        .await?;
+    let wiki = data
+        .services
+        .tag_wiki
+        .wiki(
+            &tag_id,
+            &data.tag_manager,
+            user.settings.clone().unwrap_or_default().rating_profile().hidden_tags(),
+        )
+        .await?
+        .ok_or_else(|| ErrorNotFound("tag not found"))?;
+    Ok(description_editor(&wiki, true, Some("Saved".to_string())))
+}
@@ -1,10 +1,18 @@
 use actix_web::{body::BoxBody, HttpResponse, ResponseError};
 
-use crate::{bot::{BotError, InternalError}, database::DatabaseError, qdrant::VectorDatabaseError};
+use crate::{bot::{BotError, InternalError, UserError}, database::DatabaseError, qdrant::VectorDatabaseError, text::Language};
 
 impl ResponseError for BotError {
     fn error_response(&self) -> HttpResponse<BoxBody> {
-        HttpResponse::InternalServerError().finish() // TODO: better error for common user-facing errors
+        match self {
+            Self::UserError(UserError::ValidationError(description)) => {
+                HttpResponse::BadRequest().body(description.clone())
+            }
+            Self::UserError(error @ (UserError::TagsNotFound(_) | UserError::ParseError(_, _))) => {
+                HttpResponse::BadRequest().body(error.end_user_error(Language::English).0)
+            }
+            _ => HttpResponse::InternalServerError().finish(), // TODO: better error for common user-facing errors
+        }
     }
 }
 impl ResponseError for InternalError {